@@ -0,0 +1,81 @@
+//! asciicast v2 recordings of worker terminals.
+//!
+//! Format: a JSON header line followed by one `[time, code, data]` event per
+//! line, where `code` is `"o"` for output and `"r"` for a resize (`"COLSxROWS"`).
+//! See <https://docs.asciinema.org/manual/asciicast/v2/>.
+
+use std::fs;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+pub struct CastWriter {
+    out: BufWriter<fs::File>,
+    started: Instant,
+    /// Seconds already elapsed when this writer was opened (non-zero when resuming).
+    offset: f64,
+}
+
+impl CastWriter {
+    /// Create a recording, or resume an existing one so the timeline continues
+    /// from its original header timestamp (e.g. after a session reattach).
+    pub fn open(path: &Path, cols: u16, rows: u16, title: &str) -> std::io::Result<Self> {
+        let now = chrono::Utc::now().timestamp();
+        let existing_start = fs::File::open(path).ok().and_then(|f| {
+            let mut header = String::new();
+            BufReader::new(f).read_line(&mut header).ok()?;
+            serde_json::from_str::<serde_json::Value>(&header)
+                .ok()?
+                .get("timestamp")?
+                .as_i64()
+        });
+
+        let file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+        let mut out = BufWriter::new(file);
+        let offset = match existing_start {
+            Some(start) => (now - start).max(0) as f64,
+            None => {
+                let header = serde_json::json!({
+                    "version": 2,
+                    "width": cols,
+                    "height": rows,
+                    "timestamp": now,
+                    "title": title,
+                    "env": { "TERM": "xterm-256color" },
+                });
+                writeln!(out, "{}", header)?;
+                0.0
+            }
+        };
+        let mut writer = Self {
+            out,
+            started: Instant::now(),
+            offset,
+        };
+        if existing_start.is_some() {
+            writer.resize(cols, rows);
+        }
+        Ok(writer)
+    }
+
+    fn elapsed(&self) -> f64 {
+        self.offset + self.started.elapsed().as_secs_f64()
+    }
+
+    fn event(&mut self, code: &str, data: &str) {
+        let line = serde_json::json!([(self.elapsed() * 1e6).round() / 1e6, code, data]);
+        writeln!(self.out, "{}", line).ok();
+    }
+
+    pub fn output(&mut self, data: &str) {
+        self.event("o", data);
+    }
+
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        self.event("r", &format!("{}x{}", cols, rows));
+    }
+
+    pub fn flush(&mut self) {
+        self.out.flush().ok();
+    }
+}
@@ -0,0 +1,577 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::workers::{create_polecat_crew, launch_run, remove_polecat_crew};
+use crate::models::audit::{AuditEvent, AuditEventType};
+use crate::models::best_of::{BestOfComparison, BestOfEntry, BestOfStatus};
+use crate::models::lifecycle::LifecycleEvent;
+use crate::models::task::TaskStatus;
+use crate::models::verification::checks_passed;
+use crate::models::worker::{Run, RunStatus, WorkerStatusEnum, WorkerType};
+use crate::state::AppState;
+
+/// One candidate in a comparison report, with everything needed to pick a winner.
+#[derive(Debug, Clone, Serialize)]
+pub struct BestOfCandidate {
+    pub run_id: String,
+    pub worker_id: String,
+    pub branch: String,
+    pub agent_type: String,
+    pub model_tag: Option<String>,
+    pub status: RunStatus,
+    pub diff_stats: Option<String>,
+    pub files_changed: u32,
+    pub insertions: u32,
+    pub deletions: u32,
+    /// `None` when the rig has no verification checks (or the run has not finished).
+    pub checks_passed: Option<bool>,
+    pub failed_checks: Vec<String>,
+    pub quality_signal: Option<f32>,
+    pub cost_usd: Option<f64>,
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    pub total_tokens: Option<u64>,
+    pub duration_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BestOfReport {
+    pub comparison: BestOfComparison,
+    /// Best first: completed, passing checks, higher quality, cheaper, faster.
+    pub candidates: Vec<BestOfCandidate>,
+    pub all_finished: bool,
+    pub recommended_run_id: Option<String>,
+}
+
+/// `"agent@model_tag"` → (agent, Some(model_tag)).
+fn parse_agent_spec(spec: &str) -> (String, Option<String>) {
+    match spec.split_once('@') {
+        Some((agent, tag)) if !tag.trim().is_empty() => (agent.trim().to_string(), Some(tag.trim().to_string())),
+        _ => (spec.trim().trim_end_matches('@').to_string(), None),
+    }
+}
+
+/// Files/insertions/deletions from the summary line of `git diff --stat`.
+fn parse_diff_summary(stat: &str) -> (u32, u32, u32) {
+    let summary = stat.lines().last().unwrap_or_default();
+    let mut counts = (0, 0, 0);
+    for part in summary.split(',') {
+        let part = part.trim();
+        let value = part
+            .split_whitespace()
+            .next()
+            .and_then(|n| n.parse::<u32>().ok())
+            .unwrap_or(0);
+        if part.contains("file") {
+            counts.0 = value;
+        } else if part.contains("insertion") {
+            counts.1 = value;
+        } else if part.contains("deletion") {
+            counts.2 = value;
+        }
+    }
+    counts
+}
+
+fn candidate_from_run(entry: &BestOfEntry, run: &Run) -> BestOfCandidate {
+    let (files_changed, insertions, deletions) = run
+        .diff_stats
+        .as_deref()
+        .map(parse_diff_summary)
+        .unwrap_or((0, 0, 0));
+    let duration_secs = run.finished_at.as_deref().and_then(|end| {
+        let start = chrono::DateTime::parse_from_rfc3339(&run.started_at).ok()?;
+        let end = chrono::DateTime::parse_from_rfc3339(end).ok()?;
+        Some((end - start).num_seconds())
+    });
+    BestOfCandidate {
+        run_id: run.id.clone(),
+        worker_id: run.worker_id.clone(),
+        branch: entry.branch.clone(),
+        agent_type: run.agent_type.clone(),
+        model_tag: run.model_tag.clone(),
+        status: run.status.clone(),
+        diff_stats: run.diff_stats.clone(),
+        files_changed,
+        insertions,
+        deletions,
+        checks_passed: if run.checks.is_empty() { None } else { Some(checks_passed(&run.checks)) },
+        failed_checks: run
+            .checks
+            .iter()
+            .filter(|c| !c.passed)
+            .map(|c| c.name.clone())
+            .collect(),
+        quality_signal: run.quality_signal,
+        cost_usd: run.cost_usd,
+        input_tokens: run.input_tokens,
+        output_tokens: run.output_tokens,
+        total_tokens: run.total_tokens,
+        duration_secs,
+    }
+}
+
+fn build_report(state: &AppState, comparison: BestOfComparison) -> BestOfReport {
+    let mut candidates: Vec<BestOfCandidate> = {
+        let runs = state.runs.lock().unwrap();
+        comparison
+            .entries
+            .iter()
+            .filter_map(|e| runs.iter().find(|r| r.id == e.run_id).map(|r| candidate_from_run(e, r)))
+            .collect()
+    };
+    let all_finished = candidates.iter().all(|c| c.status != RunStatus::Running);
+
+    candidates.sort_by(|a, b| {
+        let completed = |c: &BestOfCandidate| c.status == RunStatus::Completed;
+        // Passing checks beat no checks, which beat failing checks.
+        let checks = |c: &BestOfCandidate| match c.checks_passed {
+            Some(true) => 2,
+            None => 1,
+            Some(false) => 0,
+        };
+        completed(b)
+            .cmp(&completed(a))
+            .then(checks(b).cmp(&checks(a)))
+            .then(
+                b.quality_signal
+                    .unwrap_or(0.0)
+                    .partial_cmp(&a.quality_signal.unwrap_or(0.0))
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            )
+            .then(
+                a.cost_usd
+                    .unwrap_or(f64::MAX)
+                    .partial_cmp(&b.cost_usd.unwrap_or(f64::MAX))
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            )
+            .then(a.duration_secs.unwrap_or(i64::MAX).cmp(&b.duration_secs.unwrap_or(i64::MAX)))
+    });
+    let recommended_run_id = candidates
+        .first()
+        .filter(|c| all_finished && c.status == RunStatus::Completed && c.checks_passed != Some(false))
+        .map(|c| c.run_id.clone());
+
+    BestOfReport {
+        comparison,
+        candidates,
+        all_finished,
+        recommended_run_id,
+    }
+}
+
+/// Worker of a candidate; read from its run, since a queued candidate only
+/// gets one when the scheduler starts it.
+fn entry_worker_id(state: &AppState, entry: &BestOfEntry) -> String {
+    let runs = state.runs.lock().unwrap();
+    runs.iter()
+        .find(|r| r.id == entry.run_id)
+        .map(|r| r.worker_id.clone())
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| entry.worker_id.clone())
+}
+
+/// Stop candidates that are still running (or drop them from the scheduler
+/// queue) and remove every candidate worktree.
+fn clean_up_candidates(state: &AppState, app: &AppHandle, comparison: &BestOfComparison) {
+    for entry in &comparison.entries {
+        crate::scheduler::cancel_run(state, &entry.run_id);
+    }
+    let running: Vec<String> = {
+        let workers = state.workers.lock().unwrap();
+        comparison
+            .entries
+            .iter()
+            .map(|e| entry_worker_id(state, e))
+            .filter(|id| workers.iter().any(|w| w.id == *id && w.status == WorkerStatusEnum::Running))
+            .collect()
+    };
+    for worker_id in running {
+        let _ = crate::commands::workers::stop_worker_inner(state, app, &worker_id);
+    }
+    for entry in &comparison.entries {
+        remove_polecat_crew(state, &entry.crew_id);
+    }
+}
+
+fn find_open_comparison(state: &AppState, id: &str) -> Result<BestOfComparison, String> {
+    let comparisons = state.best_of.lock().unwrap();
+    let comparison = comparisons
+        .iter()
+        .find(|c| c.id == id)
+        .cloned()
+        .ok_or_else(|| "Comparison not found".to_string())?;
+    if comparison.status != BestOfStatus::Open {
+        return Err("Comparison is already resolved".to_string());
+    }
+    Ok(comparison)
+}
+
+fn resolve_comparison(state: &AppState, updated: &BestOfComparison) {
+    let mut comparisons = state.best_of.lock().unwrap();
+    if let Some(c) = comparisons.iter_mut().find(|c| c.id == updated.id) {
+        *c = updated.clone();
+    }
+    state.save_best_of(&comparisons);
+}
+
+/// Run the task `n` times in parallel, each on its own polecat worktree.
+/// `agents` entries are `agent` or `agent@model` and are used round-robin;
+/// the model is passed with the agent's model option.
+#[tauri::command]
+pub async fn execute_task_best_of(
+    task_id: String,
+    agents: Vec<String>,
+    n: Option<u32>,
+    template_name: Option<String>,
+    app: AppHandle,
+) -> Result<BestOfComparison, String> {
+    tokio::task::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let specs: Vec<(String, Option<String>)> = agents
+            .iter()
+            .map(|a| parse_agent_spec(a))
+            .filter(|(a, _)| !a.is_empty())
+            .collect();
+        if specs.is_empty() {
+            return Err("At least one agent is required".to_string());
+        }
+        for (agent, model_tag) in &specs {
+            if let Some(model) = model_tag {
+                crate::commands::workers::model_args(agent, model)?;
+            }
+        }
+        let count = n.map(|n| n as usize).unwrap_or(specs.len()).max(1);
+
+        let task = {
+            let tasks = state.tasks.lock().unwrap();
+            tasks
+                .iter()
+                .find(|t| t.id == task_id)
+                .cloned()
+                .ok_or_else(|| "Task not found".to_string())?
+        };
+        let rig_path = {
+            let rigs = state.rigs.lock().unwrap();
+            let rig = rigs
+                .iter()
+                .find(|r| r.id == task.rig_id)
+                .ok_or_else(|| "Rig not found".to_string())?;
+            rig.path.clone()
+        };
+        let max_polecats = state.settings.lock().unwrap().max_polecats_per_rig;
+        let active_polecats = {
+            let workers = state.workers.lock().unwrap();
+            workers
+                .iter()
+                .filter(|w| {
+                    w.rig_id == task.rig_id && w.worker_type == WorkerType::Polecat && w.status == WorkerStatusEnum::Running
+                })
+                .count()
+        } + crate::scheduler::queued(&state)
+            .iter()
+            .filter(|q| q.polecat && q.rig_id == task.rig_id)
+            .count();
+        if active_polecats + count > max_polecats {
+            return Err(format!(
+                "Best-of-{} exceeds the polecat limit of {} per rig ({} already running or queued)",
+                count, max_polecats, active_polecats
+            ));
+        }
+        if crate::commands::budgets::rig_spawning_paused(&state, &task.rig_id) {
+            return Err("Spawning is paused for this rig (budget exceeded)".to_string());
+        }
+
+        // Every candidate gets the same task and template so only the agent/model differs.
+        let (template, fragments) = crate::commands::templates::resolve_template(
+            &state,
+            template_name.as_deref().unwrap_or_default(),
+            Some(&task.rig_id),
+            None,
+        )?;
+        let template_name = template.name.clone();
+        let base_branch = crate::git::get_current_branch(&rig_path).unwrap_or_else(|| "main".to_string());
+        // Rendered once per agent, since templates can branch on the agent CLI.
+        let mut rendered_by_agent: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+        for (agent, _) in &specs {
+            if rendered_by_agent.contains_key(agent) {
+                continue;
+            }
+            let mut ctx = crate::prompt_context::build_prompt_context(
+                &state,
+                &crate::prompt_context::ContextRequest {
+                    rig_id: &task.rig_id,
+                    task_id: Some(&task.id),
+                    agent_cli: agent,
+                    ..Default::default()
+                },
+            );
+            ctx.crew.branch = base_branch.clone();
+            let rendered = crate::templates::render_task_template(&template, &fragments, &ctx)?;
+            rendered_by_agent.insert(agent.clone(), rendered);
+        }
+
+        let comparison_id = uuid::Uuid::new_v4().to_string();
+        let mut entries = Vec::new();
+        let mut errors = Vec::new();
+        for i in 0..count {
+            let (agent, model_tag) = specs[i % specs.len()].clone();
+            let crew = match create_polecat_crew(&state, &task.rig_id, None) {
+                Ok(crew) => crew,
+                Err(e) => {
+                    errors.push(format!("{}: {}", agent, e));
+                    continue;
+                }
+            };
+            let mut run = Run::new(
+                task.id.clone(),
+                String::new(),
+                crew.id.clone(),
+                task.rig_id.clone(),
+                agent.clone(),
+                template_name.clone(),
+                rendered_by_agent[&agent].clone(),
+            );
+            run.model_tag = model_tag.clone();
+            run.comparison_id = Some(comparison_id.clone());
+            // Candidates beyond the free slots wait in the scheduler queue.
+            let run = match launch_run(&app, run, WorkerType::Polecat) {
+                Ok(run) => run,
+                Err(e) => {
+                    errors.push(format!("{}: {}", agent, e));
+                    continue;
+                }
+            };
+            entries.push(BestOfEntry {
+                run_id: run.id,
+                worker_id: run.worker_id,
+                crew_id: crew.id,
+                branch: crew.branch,
+                agent_type: agent,
+                model_tag,
+            });
+        }
+        if entries.is_empty() {
+            return Err(format!("No candidate could be started: {}", errors.join("; ")));
+        }
+
+        let comparison = BestOfComparison {
+            id: comparison_id,
+            task_id: task.id.clone(),
+            rig_id: task.rig_id.clone(),
+            template_name,
+            entries,
+            status: BestOfStatus::Open,
+            winner_run_id: None,
+            promoted_crew_id: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            resolved_at: None,
+        };
+        {
+            let mut comparisons = state.best_of.lock().unwrap();
+            comparisons.push(comparison.clone());
+            state.save_best_of(&comparisons);
+        }
+        state.append_audit_event(&AuditEvent::new(
+            task.rig_id.clone(),
+            None,
+            Some(task.id.clone()),
+            AuditEventType::BestOfStarted,
+            serde_json::json!({
+                "comparison_id": comparison.id,
+                "candidates": comparison.entries.iter().map(|e| serde_json::json!({
+                    "run_id": e.run_id,
+                    "agent_type": e.agent_type,
+                    "model_tag": e.model_tag,
+                })).collect::<Vec<_>>(),
+                "errors": errors,
+            })
+            .to_string(),
+        ));
+        let _ = app.emit("data-changed", "");
+        Ok(comparison)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[tauri::command]
+pub fn list_best_of(rig_id: String, state: State<AppState>) -> Vec<BestOfComparison> {
+    let comparisons = state.best_of.lock().unwrap();
+    comparisons.iter().filter(|c| c.rig_id == rig_id).cloned().collect()
+}
+
+/// Side-by-side comparison of the candidates: diff size, verification, cost and time.
+#[tauri::command]
+pub fn get_best_of_report(id: String, state: State<AppState>) -> Result<BestOfReport, String> {
+    let comparison = {
+        let comparisons = state.best_of.lock().unwrap();
+        comparisons
+            .iter()
+            .find(|c| c.id == id)
+            .cloned()
+            .ok_or_else(|| "Comparison not found".to_string())?
+    };
+    Ok(build_report(&state, comparison))
+}
+
+/// Merge the winning candidate's branch into the task's crew (or `crew_id`)
+/// and remove every candidate worktree.
+#[tauri::command]
+pub fn promote_best_of(
+    id: String,
+    run_id: String,
+    crew_id: Option<String>,
+    state: State<AppState>,
+    app: AppHandle,
+) -> Result<BestOfComparison, String> {
+    let mut comparison = find_open_comparison(&state, &id)?;
+    let entry = comparison
+        .entries
+        .iter()
+        .find(|e| e.run_id == run_id)
+        .cloned()
+        .ok_or_else(|| "Run is not a candidate of this comparison".to_string())?;
+    let winner = {
+        let runs = state.runs.lock().unwrap();
+        runs.iter()
+            .find(|r| r.id == run_id)
+            .cloned()
+            .ok_or_else(|| "Run not found".to_string())?
+    };
+    if winner.status != RunStatus::Completed {
+        return Err("Only a completed candidate can be promoted".to_string());
+    }
+
+    // Target: the given crew, else the crew of the task's latest regular run.
+    let candidate_crews: Vec<&str> = comparison.entries.iter().map(|e| e.crew_id.as_str()).collect();
+    let target_crew_id = match crew_id {
+        Some(id) => id,
+        None => {
+            let runs = state.runs.lock().unwrap();
+            runs.iter()
+                .rev()
+                .find(|r| r.task_id == comparison.task_id && r.comparison_id.is_none())
+                .map(|r| r.crew_id.clone())
+                .ok_or_else(|| "The task has no crew yet; choose a crew to promote into".to_string())?
+        }
+    };
+    if candidate_crews.contains(&target_crew_id.as_str()) {
+        return Err("Cannot promote into a candidate worktree".to_string());
+    }
+    let target_path = {
+        let crews = state.crews.lock().unwrap();
+        crews
+            .iter()
+            .find(|c| c.id == target_crew_id && c.status == crate::models::crew::CrewStatus::Active)
+            .map(|c| c.path.clone())
+            .ok_or_else(|| "Target crew not found".to_string())?
+    };
+
+    // Anything the agent left uncommitted belongs to the result too.
+    let winner_path = {
+        let crews = state.crews.lock().unwrap();
+        crews.iter().find(|c| c.id == entry.crew_id).map(|c| c.path.clone())
+    };
+    if let Some(path) = winner_path {
+        if crate::git::has_uncommitted_changes(&path).unwrap_or(false) {
+            crate::git::commit_all(&path, &format!("best-of: {} result for task {}", entry.agent_type, comparison.task_id))?;
+        }
+    }
+    if let Err(e) = crate::git::merge_branch_no_edit(&target_path, &entry.branch) {
+        crate::git::abort_merge(&target_path);
+        return Err(e);
+    }
+    if let Some(ctx) = crate::lifecycle::HookContext::for_worker(&state, &entry_worker_id(&state, &entry)) {
+        let mut ctx = ctx
+            .set("TOWNUI_MERGED_BRANCH", entry.branch.clone())
+            .set(
+                "TOWNUI_MERGE_TARGET",
+                crate::git::get_current_branch(&target_path).unwrap_or_default(),
+            );
+        ctx.cwd = target_path.clone();
+        crate::lifecycle::spawn_hooks(&app, LifecycleEvent::PostMerge, ctx);
+    }
+
+    clean_up_candidates(&state, &app, &comparison);
+    comparison.status = BestOfStatus::Promoted;
+    comparison.winner_run_id = Some(run_id.clone());
+    comparison.promoted_crew_id = Some(target_crew_id.clone());
+    comparison.resolved_at = Some(chrono::Utc::now().to_rfc3339());
+    resolve_comparison(&state, &comparison);
+
+    // A verified winner completes the task, same as a regular verified run.
+    let verified = winner.checks.is_empty() || checks_passed(&winner.checks);
+    let mut task_done = false;
+    {
+        let mut tasks = state.tasks.lock().unwrap();
+        if let Some(task) = tasks.iter_mut().find(|t| t.id == comparison.task_id) {
+            task.outcome = Some(format!(
+                "Best-of winner: {}{} (run {})",
+                entry.agent_type,
+                entry.model_tag.as_ref().map(|t| format!("@{}", t)).unwrap_or_default(),
+                run_id
+            ));
+            if verified && matches!(task.status, TaskStatus::Todo | TaskStatus::InProgress) {
+                task.status = TaskStatus::Done;
+                task.completed_at = Some(chrono::Utc::now().to_rfc3339());
+                task_done = true;
+            }
+            task.updated_at = chrono::Utc::now().to_rfc3339();
+            state.save_tasks(&tasks);
+        }
+    }
+
+    if task_done {
+        crate::lifecycle::task_done(&app, &comparison.task_id);
+    }
+
+    state.append_audit_event(&AuditEvent::new(
+        comparison.rig_id.clone(),
+        None,
+        Some(comparison.task_id.clone()),
+        AuditEventType::BestOfPromoted,
+        serde_json::json!({
+            "comparison_id": comparison.id,
+            "run_id": run_id,
+            "branch": entry.branch,
+            "crew_id": target_crew_id,
+        })
+        .to_string(),
+    ));
+    let _ = app.emit("data-changed", "");
+    Ok(comparison)
+}
+
+/// Drop every candidate without promoting one.
+#[tauri::command]
+pub fn discard_best_of(id: String, state: State<AppState>, app: AppHandle) -> Result<BestOfComparison, String> {
+    let mut comparison = find_open_comparison(&state, &id)?;
+    clean_up_candidates(&state, &app, &comparison);
+    comparison.status = BestOfStatus::Discarded;
+    comparison.resolved_at = Some(chrono::Utc::now().to_rfc3339());
+    resolve_comparison(&state, &comparison);
+
+    state.append_audit_event(&AuditEvent::new(
+        comparison.rig_id.clone(),
+        None,
+        Some(comparison.task_id.clone()),
+        AuditEventType::BestOfDiscarded,
+        serde_json::json!({ "comparison_id": comparison.id }).to_string(),
+    ));
+    let _ = app.emit("data-changed", "");
+    Ok(comparison)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_diff_stat_summary_line() {
+        let stat = " src/lib.rs        | 12 +++++++-----\n src/main.rs       |  3 +++\n 2 files changed, 10 insertions(+), 5 deletions(-)";
+        assert_eq!(parse_diff_summary(stat), (2, 10, 5));
+        assert_eq!(parse_diff_summary(" 1 file changed, 1 deletion(-)"), (1, 0, 1));
+        assert_eq!(parse_diff_summary(""), (0, 0, 0));
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::{HashMap, HashSet};
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::models::audit::{AuditEvent, AuditEventType};
+use crate::models::budget::{BudgetScope, BudgetStatus, BudgetUsage};
+use crate::models::task::TaskStatus;
+use crate::models::worker::{Run, RunStatus, WorkerStatusEnum};
+use crate::state::AppState;
+
+fn utc_date(ts: &str) -> Option<String> {
+    chrono::DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|dt| dt.with_timezone(&chrono::Utc).format("%Y-%m-%d").to_string())
+}
+
+/// Snapshot of all runs, with token/cost figures for still-running runs taken
+/// from the usage their output has reported so far, so budgets see live spend.
+fn runs_with_live_usage(state: &AppState) -> Vec<Run> {
+    let prices = state.settings.lock().unwrap().model_prices.clone();
+    let live = state.worker_usage.lock().unwrap().clone();
+    let mut runs = state.runs.lock().unwrap().clone();
+    for run in runs.iter_mut().filter(|r| r.status == RunStatus::Running) {
+        if let Some(usage) = live.get(&run.worker_id) {
+            crate::usage::apply_usage(run, usage, &prices);
+        }
+    }
+    runs
+}
+
+/// Map of task id → convoy id, from both `task.convoy_id` and `convoy.work_item_ids`.
+fn task_convoy_map(state: &AppState) -> HashMap<String, String> {
+    let mut map: HashMap<String, String> = {
+        let tasks = state.tasks.lock().unwrap();
+        tasks
+            .iter()
+            .filter_map(|t| t.convoy_id.clone().map(|c| (t.id.clone(), c)))
+            .collect()
+    };
+    let convoys = state.convoys.lock().unwrap();
+    for convoy in convoys.iter() {
+        for item in &convoy.work_item_ids {
+            map.entry(item.clone()).or_insert_with(|| convoy.convoy_id.clone());
+        }
+    }
+    map
+}
+
+fn scope_covers(
+    usage: &BudgetUsage,
+    run: &Run,
+    today: &str,
+    task_convoys: &HashMap<String, String>,
+) -> bool {
+    let started_today = utc_date(&run.started_at).as_deref() == Some(today);
+    match usage.scope {
+        BudgetScope::TownDaily => started_today,
+        BudgetScope::Rig => run.rig_id == usage.scope_id,
+        BudgetScope::RigDaily => usage.rig_id.as_deref() == Some(run.rig_id.as_str()) && started_today,
+        BudgetScope::Convoy => task_convoys.get(&run.task_id) == Some(&usage.scope_id),
+    }
+}
+
+/// Spend against every configured budget (town daily, rig, rig daily, convoy).
+fn compute_budget_usage(state: &AppState, runs: &[Run]) -> Vec<BudgetUsage> {
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let town_daily = state.settings.lock().unwrap().daily_budget_usd;
+    let rigs = state.rigs.lock().unwrap().clone();
+    let convoys = state.convoys.lock().unwrap().clone();
+    let task_convoys = task_convoy_map(state);
+
+    let mut configured = Vec::new();
+    if let Some(limit) = town_daily {
+        configured.push(BudgetUsage {
+            scope: BudgetScope::TownDaily,
+            scope_id: today.clone(),
+            rig_id: None,
+            label: "Town (today)".to_string(),
+            limit_usd: limit,
+            spent_usd: 0.0,
+            exceeded: false,
+        });
+    }
+    for rig in &rigs {
+        if let Some(limit) = rig.settings.budget_usd {
+            configured.push(BudgetUsage {
+                scope: BudgetScope::Rig,
+                scope_id: rig.id.clone(),
+                rig_id: Some(rig.id.clone()),
+                label: rig.name.clone(),
+                limit_usd: limit,
+                spent_usd: 0.0,
+                exceeded: false,
+            });
+        }
+        if let Some(limit) = rig.settings.daily_budget_usd {
+            configured.push(BudgetUsage {
+                scope: BudgetScope::RigDaily,
+                scope_id: format!("{}:{}", rig.id, today),
+                rig_id: Some(rig.id.clone()),
+                label: format!("{} (today)", rig.name),
+                limit_usd: limit,
+                spent_usd: 0.0,
+                exceeded: false,
+            });
+        }
+    }
+    for convoy in &convoys {
+        if let Some(limit) = convoy.budget_usd {
+            configured.push(BudgetUsage {
+                scope: BudgetScope::Convoy,
+                scope_id: convoy.convoy_id.clone(),
+                rig_id: convoy.rig_ids.first().cloned(),
+                label: convoy.title.clone(),
+                limit_usd: limit,
+                spent_usd: 0.0,
+                exceeded: false,
+            });
+        }
+    }
+
+    for usage in configured.iter_mut() {
+        usage.spent_usd = runs
+            .iter()
+            .filter(|r| scope_covers(usage, r, &today, &task_convoys))
+            .filter_map(|r| r.cost_usd)
+            .sum();
+        usage.exceeded = usage.spent_usd >= usage.limit_usd;
+    }
+    configured
+}
+
+/// True when a budget pause covers the whole rig (town daily, rig or rig daily).
+pub(crate) fn rig_spawning_paused(state: &AppState, rig_id: &str) -> bool {
+    let sup = state.supervisor.lock().unwrap();
+    sup.budget_pauses.iter().any(|u| match u.scope {
+        BudgetScope::TownDaily => true,
+        BudgetScope::Rig | BudgetScope::RigDaily => u.rig_id.as_deref() == Some(rig_id),
+        BudgetScope::Convoy => false,
+    })
+}
+
+/// Convoys whose budget is exhausted; propulsion skips their work items.
+pub(crate) fn paused_convoy_ids(state: &AppState) -> HashSet<String> {
+    let sup = state.supervisor.lock().unwrap();
+    sup.budget_pauses
+        .iter()
+        .filter(|u| u.scope == BudgetScope::Convoy)
+        .map(|u| u.scope_id.clone())
+        .collect()
+}
+
+/// Stop a run's worker and escalate its task with `reason`.
+fn halt_run(state: &AppState, app: &AppHandle, run: &Run, reason: &str) {
+    if let Err(e) = crate::commands::workers::stop_worker_inner(state, app, &run.worker_id) {
+        eprintln!("[budget] failed to stop worker {}: {e}", run.worker_id);
+    }
+
+    let mut tasks = state.tasks.lock().unwrap();
+    let Some(task) = tasks.iter_mut().find(|t| t.id == run.task_id) else {
+        return;
+    };
+    if task.status == TaskStatus::Done || task.status == TaskStatus::Cancelled {
+        return;
+    }
+    let old_status = task.status.clone();
+    task.status = TaskStatus::Escalated;
+    task.assigned_worker_id = None;
+    task.blocked_reason = Some(reason.to_string());
+    task.updated_at = chrono::Utc::now().to_rfc3339();
+
+    state.append_audit_event(&AuditEvent::new(
+        task.rig_id.clone(),
+        task.owner_actor_id.clone(),
+        Some(task.id.clone()),
+        AuditEventType::TaskStatusChanged,
+        serde_json::json!({
+            "old_status": old_status,
+            "new_status": &task.status,
+            "reason": reason,
+            "run_id": run.id,
+            "budget_enforced": true,
+        })
+        .to_string(),
+    ));
+    state.save_tasks(&tasks);
+}
+
+fn fallback_rig_id(state: &AppState, usage: &BudgetUsage, stopped: &[&Run]) -> String {
+    usage
+        .rig_id
+        .clone()
+        .or_else(|| stopped.first().map(|r| r.rig_id.clone()))
+        .or_else(|| state.rigs.lock().unwrap().first().map(|r| r.id.clone()))
+        .unwrap_or_default()
+}
+
+/// Supervisor pass: refresh budget pauses, stop workers running in exhausted
+/// scopes, and enforce per-run wall-clock and token caps.
+pub(crate) fn enforce_budgets(state: &AppState, app: &AppHandle) {
+    let runs = runs_with_live_usage(state);
+    let usages = compute_budget_usage(state, &runs);
+    let exceeded: Vec<BudgetUsage> = usages.into_iter().filter(|u| u.exceeded).collect();
+
+    let previous = {
+        let mut sup = state.supervisor.lock().unwrap();
+        std::mem::replace(&mut sup.budget_pauses, exceeded.clone())
+    };
+
+    let running_workers: HashSet<String> = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .filter(|w| w.status == WorkerStatusEnum::Running)
+            .map(|w| w.id.clone())
+            .collect()
+    };
+    let live_runs: Vec<&Run> = runs
+        .iter()
+        .filter(|r| r.status == RunStatus::Running && running_workers.contains(&r.worker_id))
+        .collect();
+
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let task_convoys = task_convoy_map(state);
+    let mut halted: HashSet<String> = HashSet::new();
+    let mut changed = false;
+
+    for usage in &exceeded {
+        let stopped: Vec<&Run> = live_runs
+            .iter()
+            .copied()
+            .filter(|r| !halted.contains(&r.worker_id))
+            .filter(|r| scope_covers(usage, r, &today, &task_convoys))
+            .collect();
+        let newly_exceeded = !previous
+            .iter()
+            .any(|p| p.scope == usage.scope && p.scope_id == usage.scope_id);
+        if !newly_exceeded && stopped.is_empty() {
+            continue;
+        }
+
+        let reason = format!(
+            "Budget exceeded: {} spent ${:.2} of ${:.2}",
+            usage.label, usage.spent_usd, usage.limit_usd
+        );
+        for run in &stopped {
+            halt_run(state, app, run, &reason);
+            halted.insert(run.worker_id.clone());
+        }
+
+        state.append_audit_event(&AuditEvent::new(
+            fallback_rig_id(state, usage, &stopped),
+            None,
+            None,
+            AuditEventType::BudgetExceeded,
+            serde_json::json!({
+                "scope": usage.scope,
+                "scope_id": usage.scope_id,
+                "label": usage.label,
+                "limit_usd": usage.limit_usd,
+                "spent_usd": usage.spent_usd,
+                "stopped_workers": stopped.iter().map(|r| r.worker_id.clone()).collect::<Vec<_>>(),
+            })
+            .to_string(),
+        ));
+        changed = true;
+    }
+
+    for lifted in previous
+        .iter()
+        .filter(|p| !exceeded.iter().any(|u| u.scope == p.scope && u.scope_id == p.scope_id))
+    {
+        state.append_audit_event(&AuditEvent::new(
+            fallback_rig_id(state, lifted, &[]),
+            None,
+            None,
+            AuditEventType::BudgetCleared,
+            serde_json::json!({
+                "scope": lifted.scope,
+                "scope_id": lifted.scope_id,
+                "label": lifted.label,
+            })
+            .to_string(),
+        ));
+        changed = true;
+    }
+
+    // Per-run limits: wall-clock and token caps (rig overrides town defaults).
+    let (default_secs, default_tokens) = {
+        let settings = state.settings.lock().unwrap();
+        (settings.max_run_seconds, settings.max_run_tokens)
+    };
+    let rig_limits: HashMap<String, (Option<u64>, Option<u64>)> = {
+        let rigs = state.rigs.lock().unwrap();
+        rigs.iter()
+            .map(|r| (r.id.clone(), (r.settings.max_run_seconds, r.settings.max_run_tokens)))
+            .collect()
+    };
+    let now_ts = chrono::Utc::now().timestamp();
+
+    for run in live_runs.iter().filter(|r| !halted.contains(&r.worker_id)) {
+        let (rig_secs, rig_tokens) = rig_limits.get(&run.rig_id).cloned().unwrap_or((None, None));
+        let max_secs = rig_secs.or(default_secs);
+        let max_tokens = rig_tokens.or(default_tokens);
+
+        let elapsed = chrono::DateTime::parse_from_rfc3339(&run.started_at)
+            .map(|t| now_ts.saturating_sub(t.timestamp()).max(0) as u64)
+            .unwrap_or(0);
+        let tokens = run.tokens_used();
+
+        let (limit, reason) = match (max_secs, max_tokens) {
+            (Some(secs), _) if elapsed > secs => (
+                "wall_clock",
+                format!("Run exceeded wall-clock limit: {elapsed}s > {secs}s"),
+            ),
+            (_, Some(cap)) if tokens > cap => (
+                "tokens",
+                format!("Run exceeded token cap: {tokens} > {cap}"),
+            ),
+            _ => continue,
+        };
+
+        halt_run(state, app, run, &reason);
+        state.append_audit_event(&AuditEvent::new(
+            run.rig_id.clone(),
+            None,
+            Some(run.task_id.clone()),
+            AuditEventType::RunLimitExceeded,
+            serde_json::json!({
+                "run_id": run.id,
+                "worker_id": run.worker_id,
+                "limit": limit,
+                "elapsed_seconds": elapsed,
+                "tokens": tokens,
+                "max_run_seconds": max_secs,
+                "max_run_tokens": max_tokens,
+            })
+            .to_string(),
+        ));
+        changed = true;
+    }
+
+    if changed {
+        let _ = app.emit("data-changed", "");
+    }
+}
+
+#[tauri::command]
+pub fn get_budget_status(state: State<AppState>) -> BudgetStatus {
+    let runs = runs_with_live_usage(&state);
+    let usages = compute_budget_usage(&state, &runs);
+    let paused = state.supervisor.lock().unwrap().budget_pauses.clone();
+    BudgetStatus {
+        checked_at: chrono::Utc::now().to_rfc3339(),
+        usages,
+        paused,
+    }
+}
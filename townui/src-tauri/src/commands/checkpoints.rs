@@ -0,0 +1,285 @@
+use std::thread;
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::models::audit::{AuditEvent, AuditEventType};
+use crate::models::worker::{LogEntry, Run, RunCheckpoint, RunStatus};
+use crate::state::AppState;
+
+const CHECKPOINT_REF_PREFIX: &str = "refs/townui/checkpoints";
+
+fn checkpoint_ref(run_id: &str, seq: u32) -> String {
+    format!("{}/{}/{}", CHECKPOINT_REF_PREFIX, run_id, seq)
+}
+
+fn find_run(state: &AppState, run_id: &str) -> Result<Run, String> {
+    let runs = state.runs.lock().unwrap();
+    runs.iter()
+        .find(|r| r.id == run_id)
+        .cloned()
+        .ok_or_else(|| "Run not found".to_string())
+}
+
+fn crew_path(state: &AppState, crew_id: &str) -> Option<String> {
+    let crews = state.crews.lock().unwrap();
+    crews.iter().find(|c| c.id == crew_id).map(|c| c.path.clone())
+}
+
+fn rig_path(state: &AppState, rig_id: &str) -> Option<String> {
+    let rigs = state.rigs.lock().unwrap();
+    rigs.iter().find(|r| r.id == rig_id).map(|r| r.path.clone())
+}
+
+/// Snapshot interval for a rig (rig override, else town default); `None` when off.
+fn checkpoint_interval(state: &AppState, rig_id: &str) -> Option<Duration> {
+    let rig_override = {
+        let rigs = state.rigs.lock().unwrap();
+        rigs.iter()
+            .find(|r| r.id == rig_id)
+            .and_then(|r| r.settings.checkpoint_interval_minutes)
+    };
+    rig_override
+        .or(state.settings.lock().unwrap().checkpoint_interval_minutes)
+        .filter(|m| *m > 0)
+        .map(|m| Duration::from_secs(m * 60))
+}
+
+fn log_to_worker(state: &AppState, app: &AppHandle, worker_id: &str, line: String) {
+    if worker_id.is_empty() {
+        return;
+    }
+    let entry = LogEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        stream: "system".to_string(),
+        line,
+    };
+    crate::commands::workers::emit_worker_log(state, app, worker_id, entry);
+}
+
+/// Snapshot `crew_id`'s worktree onto `run_id`. Returns `None` when nothing
+/// changed since the run's last snapshot. Snapshots beyond
+/// `max_checkpoints_per_run` are dropped oldest first.
+pub(crate) fn take_checkpoint(
+    state: &AppState,
+    run_id: &str,
+    crew_id: &str,
+    reason: &str,
+) -> Result<Option<RunCheckpoint>, String> {
+    let run = find_run(state, run_id)?;
+    let path = crew_path(state, crew_id).ok_or_else(|| "Crew not found".to_string())?;
+
+    let message = format!("WIP checkpoint ({}) for run {}", reason, run.id);
+    let (commit, tree) = crate::git::snapshot_worktree(&path, &message)?;
+    let diff_stat = crate::git::get_diff_stat(&path, run.base_commit.as_deref()).ok();
+
+    // The sequence number is taken and the snapshot recorded under one lock,
+    // so concurrent snapshots of the run never share a ref.
+    let max = state.settings.lock().unwrap().max_checkpoints_per_run.max(1);
+    let (checkpoint, dropped) = {
+        let mut runs = state.runs.lock().unwrap();
+        let Some(r) = runs.iter_mut().find(|r| r.id == run.id) else {
+            return Ok(None);
+        };
+        if r.checkpoints.last().is_some_and(|c| c.tree == tree && c.crew_id == crew_id) {
+            return Ok(None);
+        }
+        let checkpoint = RunCheckpoint {
+            seq: r.checkpoints.last().map(|c| c.seq + 1).unwrap_or(1),
+            commit,
+            tree,
+            crew_id: crew_id.to_string(),
+            reason: reason.to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            diff_stat,
+        };
+        r.checkpoints.push(checkpoint.clone());
+        let excess = r.checkpoints.len().saturating_sub(max);
+        let dropped: Vec<RunCheckpoint> = r.checkpoints.drain(..excess).collect();
+        state.save_runs(&runs);
+        (checkpoint, dropped)
+    };
+    if let Err(e) = crate::git::update_ref(&path, &checkpoint_ref(&run.id, checkpoint.seq), &checkpoint.commit) {
+        let mut runs = state.runs.lock().unwrap();
+        if let Some(r) = runs.iter_mut().find(|r| r.id == run.id) {
+            r.checkpoints.retain(|c| c.seq != checkpoint.seq);
+        }
+        state.save_runs(&runs);
+        return Err(e);
+    }
+    for old in dropped {
+        let _ = crate::git::delete_ref(&path, &checkpoint_ref(&run.id, old.seq));
+    }
+    Ok(Some(checkpoint))
+}
+
+/// Delete a run's snapshot refs and forget them.
+fn discard_checkpoints(state: &AppState, run_id: &str) -> Result<usize, String> {
+    let run = find_run(state, run_id)?;
+    if run.checkpoints.is_empty() {
+        return Ok(0);
+    }
+    // Refs live in the shared repository, so the rig path works even after a
+    // polecat worktree is gone.
+    let repo = rig_path(state, &run.rig_id).ok_or_else(|| "Rig not found".to_string())?;
+    for checkpoint in &run.checkpoints {
+        let _ = crate::git::delete_ref(&repo, &checkpoint_ref(&run.id, checkpoint.seq));
+    }
+    let mut runs = state.runs.lock().unwrap();
+    if let Some(r) = runs.iter_mut().find(|r| r.id == run_id) {
+        r.checkpoints.clear();
+    }
+    state.save_runs(&runs);
+    Ok(run.checkpoints.len())
+}
+
+/// Called once a run's worker has exited: a successful run's snapshots are
+/// cleaned up (its work is in the auto-commit), any other run gets a final
+/// snapshot of whatever it left in the worktree.
+pub(crate) fn settle_run_checkpoints(state: &AppState, app: &AppHandle, run_id: &str, succeeded: bool) {
+    let Ok(run) = find_run(state, run_id) else {
+        return;
+    };
+    if succeeded {
+        if !state.settings.lock().unwrap().keep_checkpoints_on_success {
+            let _ = discard_checkpoints(state, run_id);
+        }
+        return;
+    }
+    if checkpoint_interval(state, &run.rig_id).is_none() {
+        return;
+    }
+    match take_checkpoint(state, run_id, &run.crew_id, "exit") {
+        Ok(Some(c)) => log_to_worker(
+            state,
+            app,
+            &run.worker_id,
+            format!("[checkpoint] #{} saved the worktree left by the run ({})", c.seq, short(&c.commit)),
+        ),
+        Ok(None) => {}
+        Err(e) => log_to_worker(state, app, &run.worker_id, format!("[checkpoint] exit snapshot failed: {}", e)),
+    }
+}
+
+fn short(commit: &str) -> &str {
+    &commit[..commit.len().min(8)]
+}
+
+/// Background loop that snapshots running workers' worktrees once their
+/// rig's checkpoint interval has elapsed since the last snapshot.
+pub(crate) fn start_checkpoint_timer(app: AppHandle) {
+    let _ = thread::Builder::new()
+        .name("run-checkpoints".to_string())
+        .spawn(move || loop {
+            thread::sleep(Duration::from_secs(30));
+            let state = app.state::<AppState>();
+            let running: Vec<Run> = {
+                let runs = state.runs.lock().unwrap();
+                runs.iter().filter(|r| r.status == RunStatus::Running).cloned().collect()
+            };
+            let now = chrono::Utc::now();
+            for run in running {
+                let Some(interval) = checkpoint_interval(&state, &run.rig_id) else {
+                    continue;
+                };
+                let since = run
+                    .checkpoints
+                    .last()
+                    .map(|c| c.created_at.as_str())
+                    .unwrap_or(run.started_at.as_str());
+                let due = chrono::DateTime::parse_from_rfc3339(since)
+                    .map(|at| (now - at.with_timezone(&chrono::Utc)).to_std().unwrap_or_default() >= interval)
+                    .unwrap_or(true);
+                if !due {
+                    continue;
+                }
+                match take_checkpoint(&state, &run.id, &run.crew_id, "interval") {
+                    Ok(Some(c)) => {
+                        log_to_worker(
+                            &state,
+                            &app,
+                            &run.worker_id,
+                            format!("[checkpoint] #{} saved ({})", c.seq, short(&c.commit)),
+                        );
+                        let _ = app.emit("data-changed", "");
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("[checkpoint] run {}: {e}", run.id),
+                }
+            }
+        });
+}
+
+/// Snapshot the run's worktree now.
+#[tauri::command]
+pub fn create_run_checkpoint(run_id: String, state: State<AppState>, app: AppHandle) -> Result<Option<RunCheckpoint>, String> {
+    let run = find_run(&state, &run_id)?;
+    let checkpoint = take_checkpoint(&state, &run_id, &run.crew_id, "manual")?;
+    let _ = app.emit("data-changed", "");
+    Ok(checkpoint)
+}
+
+/// Roll a worktree back to one of the run's snapshots: the branch is reset to
+/// where it was at the snapshot and the snapshot's files come back as
+/// uncommitted changes. Defaults to the crew the snapshot was taken on; the
+/// current state is snapshotted first ("pre_restore") so the restore can be undone.
+#[tauri::command]
+pub fn restore_run_checkpoint(
+    run_id: String,
+    seq: u32,
+    crew_id: Option<String>,
+    state: State<AppState>,
+    app: AppHandle,
+) -> Result<Run, String> {
+    let run = find_run(&state, &run_id)?;
+    let checkpoint = run
+        .checkpoints
+        .iter()
+        .find(|c| c.seq == seq)
+        .cloned()
+        .ok_or_else(|| "Checkpoint not found".to_string())?;
+    let crew_id = crew_id.unwrap_or_else(|| checkpoint.crew_id.clone());
+
+    let path = {
+        let crews = state.crews.lock().unwrap();
+        let crew = crews
+            .iter()
+            .find(|c| c.id == crew_id)
+            .ok_or_else(|| "Crew not found".to_string())?;
+        if crew.rig_id != run.rig_id {
+            return Err("Checkpoints can only be restored onto a crew of the run's rig".to_string());
+        }
+        crew.path.clone()
+    };
+    if let Some(holder) = crate::commands::crews::crew_lease_holder(&state, &crew_id) {
+        return Err(format!("Crew is busy (held by worker {}); stop it before restoring", holder));
+    }
+
+    let pre_restore = take_checkpoint(&state, &run_id, &crew_id, "pre_restore")?;
+    crate::git::restore_snapshot(&path, &checkpoint.commit)?;
+
+    state.append_audit_event(&AuditEvent::new(
+        run.rig_id.clone(),
+        None,
+        Some(run.task_id.clone()),
+        AuditEventType::CheckpointRestored,
+        serde_json::json!({
+            "run_id": run_id,
+            "seq": seq,
+            "commit": checkpoint.commit,
+            "crew_id": crew_id,
+            "pre_restore_seq": pre_restore.as_ref().map(|c| c.seq),
+        })
+        .to_string(),
+    ));
+    let _ = app.emit("data-changed", "");
+    find_run(&state, &run_id)
+}
+
+/// Delete all of a run's snapshots. Returns how many were removed.
+#[tauri::command]
+pub fn discard_run_checkpoints(run_id: String, state: State<AppState>, app: AppHandle) -> Result<usize, String> {
+    let removed = discard_checkpoints(&state, &run_id)?;
+    let _ = app.emit("data-changed", "");
+    Ok(removed)
+}
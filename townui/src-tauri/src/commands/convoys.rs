@@ -1,4 +1,4 @@
-use tauri::State;
+use tauri::{AppHandle, State};
 
 use crate::models::audit::{AuditEvent, AuditEventType};
 use crate::models::convoy::{Convoy, ConvoyStatus, MergeStrategy};
@@ -105,6 +105,7 @@ pub fn add_item_to_convoy(
                 hook_id: None,
                 blocked_reason: None,
                 outcome: None,
+                retry_policy: None,
             });
             state.save_tasks(&tasks);
         }
@@ -162,6 +163,7 @@ pub fn convoy_land(
     convoy_id: String,
     land_notes: Option<String>,
     state: State<AppState>,
+    app: AppHandle,
 ) -> Result<Convoy, String> {
     // Collect work_item_ids + rig first
     let (work_item_ids, rig_id) = {
@@ -177,6 +179,7 @@ pub fn convoy_land(
     };
 
     // Mark all open tasks as Done
+    let mut closed = Vec::new();
     {
         let mut tasks = state.tasks.lock().unwrap();
         for task in tasks.iter_mut() {
@@ -186,6 +189,7 @@ pub fn convoy_land(
             {
                 task.status = crate::models::task::TaskStatus::Done;
                 task.completed_at = Some(chrono::Utc::now().to_rfc3339());
+                closed.push(task.id.clone());
                 state.append_audit_event(&AuditEvent::new(
                     task.rig_id.clone(),
                     None,
@@ -201,6 +205,9 @@ pub fn convoy_land(
         }
         state.save_tasks(&tasks);
     }
+    for task_id in &closed {
+        crate::lifecycle::task_done(&app, task_id);
+    }
 
     // Close convoy
     let updated = {
@@ -274,3 +281,42 @@ pub fn update_convoy_status(
 
     Ok(updated)
 }
+
+/// Set or clear the convoy's spend ceiling. Enforced by the supervisor budget pass.
+#[tauri::command]
+pub fn set_convoy_budget(
+    convoy_id: String,
+    budget_usd: Option<f64>,
+    state: State<AppState>,
+) -> Result<Convoy, String> {
+    if budget_usd.map(|b| b < 0.0 || !b.is_finite()).unwrap_or(false) {
+        return Err("Budget must be a non-negative amount".to_string());
+    }
+    let mut convoys = state.convoys.lock().unwrap();
+    let convoy = convoys
+        .iter_mut()
+        .find(|c| c.convoy_id == convoy_id)
+        .ok_or_else(|| "Convoy not found".to_string())?;
+
+    convoy.budget_usd = budget_usd;
+    convoy.updated_at = chrono::Utc::now().to_rfc3339();
+    let updated = convoy.clone();
+    state.save_convoys(&convoys);
+    drop(convoys);
+
+    let rig_id = updated.rig_ids.first().cloned().unwrap_or_default();
+    state.append_audit_event(&AuditEvent::new(
+        rig_id,
+        None,
+        None,
+        AuditEventType::ConvoyUpdated,
+        serde_json::json!({
+            "convoy_id": updated.convoy_id,
+            "action": "set_budget",
+            "budget_usd": updated.budget_usd,
+        })
+        .to_string(),
+    ));
+
+    Ok(updated)
+}
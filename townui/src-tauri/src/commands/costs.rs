@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::models::worker::{Run, RunStatus};
+use crate::state::AppState;
+
+/// Dimension used to bucket run spend.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CostGroupBy {
+    Rig,
+    Task,
+    Actor,
+    Convoy,
+    Agent,
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CostBucket {
+    pub key: String,
+    pub label: String,
+    pub runs_total: usize,
+    pub runs_completed: usize,
+    pub runs_failed: usize,
+    /// Runs that carry a cost figure (reported, estimated or manual).
+    pub runs_with_cost: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Tokens reported only as a combined figure (see `Run::total_tokens`).
+    pub combined_tokens: u64,
+    pub cost_usd: f64,
+    pub cost_per_completed: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CostReport {
+    pub generated_at: String,
+    pub group_by: CostGroupBy,
+    pub rig_scope: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub total_input_tokens: u64,
+    pub total_output_tokens: u64,
+    pub total_combined_tokens: u64,
+    pub total_cost_usd: f64,
+    pub buckets: Vec<CostBucket>,
+}
+
+fn parse_utc(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+}
+
+fn time_bucket_key(run: &Run, group_by: &CostGroupBy) -> String {
+    let Some(ts) = parse_utc(&run.started_at) else {
+        return "unknown".to_string();
+    };
+    match group_by {
+        CostGroupBy::Week => {
+            let iso = ts.iso_week();
+            format!("{}-W{:02}", iso.year(), iso.week())
+        }
+        CostGroupBy::Month => ts.format("%Y-%m").to_string(),
+        _ => ts.format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// Snapshot of lookups needed to label runs by rig/task/actor/convoy.
+struct CostLookups {
+    rig_names: HashMap<String, String>,
+    task_titles: HashMap<String, String>,
+    task_convoys: HashMap<String, String>,
+    task_owners: HashMap<String, String>,
+    worker_actors: HashMap<String, String>,
+    actor_names: HashMap<String, String>,
+    convoy_titles: HashMap<String, String>,
+}
+
+impl CostLookups {
+    fn load(state: &AppState) -> Self {
+        let rig_names = {
+            let rigs = state.rigs.lock().unwrap();
+            rigs.iter().map(|r| (r.id.clone(), r.name.clone())).collect()
+        };
+        let (task_titles, task_convoys, task_owners) = {
+            let tasks = state.tasks.lock().unwrap();
+            let titles = tasks.iter().map(|t| (t.id.clone(), t.title.clone())).collect();
+            let convoys = tasks
+                .iter()
+                .filter_map(|t| t.convoy_id.clone().map(|c| (t.id.clone(), c)))
+                .collect();
+            let owners = tasks
+                .iter()
+                .filter_map(|t| t.owner_actor_id.clone().map(|a| (t.id.clone(), a)))
+                .collect();
+            (titles, convoys, owners)
+        };
+        let worker_actors = {
+            let workers = state.workers.lock().unwrap();
+            workers
+                .iter()
+                .filter_map(|w| w.actor_id.clone().map(|a| (w.id.clone(), a)))
+                .collect()
+        };
+        let actor_names = {
+            let actors = state.actors.lock().unwrap();
+            actors.iter().map(|a| (a.actor_id.clone(), a.name.clone())).collect()
+        };
+        let convoy_titles = {
+            let convoys = state.convoys.lock().unwrap();
+            convoys
+                .iter()
+                .map(|c| (c.convoy_id.clone(), c.title.clone()))
+                .collect()
+        };
+        Self {
+            rig_names,
+            task_titles,
+            task_convoys,
+            task_owners,
+            worker_actors,
+            actor_names,
+            convoy_titles,
+        }
+    }
+
+    /// Actor for a run: the worker's actor first, then the task owner.
+    fn actor_for(&self, run: &Run) -> Option<&String> {
+        self.worker_actors
+            .get(&run.worker_id)
+            .or_else(|| self.task_owners.get(&run.task_id))
+    }
+
+    fn key_and_label(&self, run: &Run, group_by: &CostGroupBy) -> (String, String) {
+        let named = |key: Option<&String>, names: &HashMap<String, String>, none: &str| match key {
+            Some(k) => (k.clone(), names.get(k).cloned().unwrap_or_else(|| k.clone())),
+            None => (none.to_string(), none.to_string()),
+        };
+        match group_by {
+            CostGroupBy::Rig => named(Some(&run.rig_id), &self.rig_names, "unknown"),
+            CostGroupBy::Task => named(Some(&run.task_id), &self.task_titles, "unknown"),
+            CostGroupBy::Actor => named(self.actor_for(run), &self.actor_names, "unassigned"),
+            CostGroupBy::Convoy => named(
+                self.task_convoys.get(&run.task_id),
+                &self.convoy_titles,
+                "no_convoy",
+            ),
+            CostGroupBy::Agent => {
+                let key = run.model_tag.clone().unwrap_or_else(|| run.agent_type.clone());
+                (key.clone(), key)
+            }
+            CostGroupBy::Day | CostGroupBy::Week | CostGroupBy::Month => {
+                let key = time_bucket_key(run, group_by);
+                (key.clone(), key)
+            }
+        }
+    }
+}
+
+pub(crate) fn build_cost_report(
+    state: &AppState,
+    group_by: CostGroupBy,
+    rig_id: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+) -> CostReport {
+    let since_ts = since.as_deref().and_then(parse_utc);
+    let until_ts = until.as_deref().and_then(parse_utc);
+    let lookups = CostLookups::load(state);
+
+    let runs: Vec<Run> = {
+        let runs = state.runs.lock().unwrap();
+        runs.iter()
+            .filter(|r| rig_id.as_deref().map(|rid| r.rig_id == rid).unwrap_or(true))
+            .filter(|r| {
+                let started = parse_utc(&r.started_at);
+                let after_since = match (since_ts, started) {
+                    (Some(s), Some(t)) => t >= s,
+                    _ => true,
+                };
+                let before_until = match (until_ts, started) {
+                    (Some(u), Some(t)) => t < u,
+                    _ => true,
+                };
+                after_since && before_until
+            })
+            .cloned()
+            .collect()
+    };
+
+    let mut buckets: HashMap<String, CostBucket> = HashMap::new();
+    for run in &runs {
+        let (key, label) = lookups.key_and_label(run, &group_by);
+        let bucket = buckets.entry(key.clone()).or_insert_with(|| CostBucket {
+            key,
+            label,
+            runs_total: 0,
+            runs_completed: 0,
+            runs_failed: 0,
+            runs_with_cost: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            combined_tokens: 0,
+            cost_usd: 0.0,
+            cost_per_completed: None,
+        });
+        bucket.runs_total += 1;
+        match run.status {
+            RunStatus::Completed => bucket.runs_completed += 1,
+            RunStatus::Failed => bucket.runs_failed += 1,
+            _ => {}
+        }
+        bucket.input_tokens += run.input_tokens.unwrap_or(0);
+        bucket.output_tokens += run.output_tokens.unwrap_or(0);
+        bucket.combined_tokens += run.total_tokens.unwrap_or(0);
+        if let Some(cost) = run.cost_usd {
+            bucket.runs_with_cost += 1;
+            bucket.cost_usd += cost;
+        }
+    }
+
+    let mut buckets: Vec<CostBucket> = buckets
+        .into_values()
+        .map(|mut b| {
+            if b.runs_completed > 0 && b.runs_with_cost > 0 {
+                b.cost_per_completed = Some(b.cost_usd / b.runs_completed as f64);
+            }
+            b
+        })
+        .collect();
+
+    match group_by {
+        CostGroupBy::Day | CostGroupBy::Week | CostGroupBy::Month => {
+            buckets.sort_by(|a, b| a.key.cmp(&b.key))
+        }
+        _ => buckets.sort_by(|a, b| {
+            b.cost_usd
+                .partial_cmp(&a.cost_usd)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        }),
+    }
+
+    CostReport {
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        group_by,
+        rig_scope: rig_id,
+        since,
+        until,
+        total_input_tokens: buckets.iter().map(|b| b.input_tokens).sum(),
+        total_output_tokens: buckets.iter().map(|b| b.output_tokens).sum(),
+        total_combined_tokens: buckets.iter().map(|b| b.combined_tokens).sum(),
+        total_cost_usd: buckets.iter().map(|b| b.cost_usd).sum(),
+        buckets,
+    }
+}
+
+/// Aggregate run tokens and spend by rig, task, actor, convoy, agent or time bucket.
+#[tauri::command]
+pub fn get_cost_report(
+    group_by: CostGroupBy,
+    rig_id: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    state: State<AppState>,
+) -> CostReport {
+    build_cost_report(&state, group_by, rig_id, since, until)
+}
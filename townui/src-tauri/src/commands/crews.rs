@@ -1,9 +1,12 @@
 use std::fs;
 use serde::Serialize;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 
 use crate::git;
-use crate::models::crew::{Crew, CrewInfo, CrewStatus};
+use crate::models::crew::{BusyCrewPolicy, Crew, CrewInfo, CrewLease, CrewStatus};
+use crate::models::isolation::{ResourceLimits, SandboxPolicy};
+use crate::models::review::ReviewVerdict;
+use crate::models::worker::{RunStatus, WorkerStatusEnum};
 use crate::state::AppState;
 
 #[derive(Debug, Clone, Serialize)]
@@ -105,6 +108,18 @@ pub fn get_crew_presets() -> Vec<CrewPreset> {
     ]
 }
 
+/// Preset a crew was created from. Crews that predate the stored key are
+/// matched by name, which preset crews are given.
+pub(crate) fn crew_preset_key(crew: &Crew) -> Option<String> {
+    if crew.preset.is_some() {
+        return crew.preset.clone();
+    }
+    get_crew_presets()
+        .into_iter()
+        .find(|p| p.name.eq_ignore_ascii_case(&crew.name))
+        .map(|p| p.key)
+}
+
 #[tauri::command]
 pub async fn list_crews(rig_id: String, state: State<'_, AppState>) -> Result<Vec<CrewInfo>, String> {
     let rig_exists = {
@@ -150,6 +165,7 @@ pub fn create_crew(
     name: String,
     base_branch: String,
     push_to_remote: bool,
+    preset: Option<String>,
     state: State<AppState>,
     app: AppHandle,
 ) -> Result<CrewInfo, String> {
@@ -195,7 +211,8 @@ pub fn create_crew(
         }
     }
 
-    let crew = Crew::new(rig_id, name, branch_name, wt_path_str.clone());
+    let mut crew = Crew::new(rig_id, name, branch_name, wt_path_str.clone());
+    crew.preset = preset.filter(|p| !p.trim().is_empty());
     let branch = git::get_current_branch(&wt_path_str);
     let (status, changed) = git::get_status_info(&wt_path_str);
     let info = crew.to_info(branch, status, changed);
@@ -221,6 +238,34 @@ pub fn get_crew(id: String, state: State<AppState>) -> Result<CrewInfo, String>
     Ok(crew.to_info(branch, status, changed))
 }
 
+/// Set per-crew resource limit overrides and an optional sandbox policy replacing the rig's.
+#[tauri::command]
+pub fn update_crew_isolation(
+    id: String,
+    limits: ResourceLimits,
+    sandbox: Option<SandboxPolicy>,
+    state: State<AppState>,
+    app: AppHandle,
+) -> Result<CrewInfo, String> {
+    let crew = {
+        let mut crews = state.crews.lock().unwrap();
+        let crew = crews
+            .iter_mut()
+            .find(|c| c.id == id)
+            .ok_or_else(|| "Crew not found".to_string())?;
+        crew.limits = limits;
+        crew.sandbox = sandbox;
+        let updated = crew.clone();
+        state.save_crews(&crews);
+        updated
+    };
+
+    let branch = git::get_current_branch(&crew.path);
+    let (status, changed) = git::get_status_info(&crew.path);
+    let _ = app.emit("data-changed", "");
+    Ok(crew.to_info(branch, status, changed))
+}
+
 #[tauri::command]
 pub fn delete_crew(id: String, state: State<AppState>, app: AppHandle) -> Result<(), String> {
     // Step 1: Read crew data (lock crews, extract info, drop lock)
@@ -354,3 +399,347 @@ pub fn create_cross_rig_worktree(
     let _ = app.emit("data-changed", "");
     Ok(crew_path)
 }
+
+// ── Crew leases ──
+
+/// Leases are renewed while their worker runs. An expired one is only free
+/// to take once its holder is gone: after the machine sleeps, every lease
+/// looks expired while its workers are still alive.
+const CREW_LEASE_TTL_MINUTES: i64 = 30;
+
+fn lease_is_active(lease: &CrewLease) -> bool {
+    chrono::DateTime::parse_from_rfc3339(&lease.expires_at)
+        .map(|expiry| expiry > chrono::Utc::now())
+        .unwrap_or(false)
+}
+
+/// Whether the lease's holder still runs. A `spawning:` token is held by a
+/// guard in this process, which frees it when dropped.
+fn lease_holder_alive(state: &AppState, lease: &CrewLease) -> bool {
+    if lease.worker_id.starts_with("spawning:") {
+        return true;
+    }
+    let worker = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .find(|w| w.id == lease.worker_id && w.status == WorkerStatusEnum::Running)
+            .map(|w| (w.session.clone(), w.pid))
+    };
+    match worker {
+        Some((Some(session), _)) => crate::sessions::has_session(&session),
+        Some((None, Some(pid))) => crate::commands::workers::process_alive(pid),
+        Some((None, None)) => true,
+        None => false,
+    }
+}
+
+/// Whether the lease still blocks the crew. Call without the crews lock held.
+fn lease_is_held(state: &AppState, lease: &CrewLease) -> bool {
+    lease_is_active(lease) || lease_holder_alive(state, lease)
+}
+
+fn new_lease(worker_id: &str) -> CrewLease {
+    let now = chrono::Utc::now();
+    CrewLease {
+        worker_id: worker_id.to_string(),
+        acquired_at: now.to_rfc3339(),
+        expires_at: (now + chrono::Duration::minutes(CREW_LEASE_TTL_MINUTES)).to_rfc3339(),
+    }
+}
+
+/// Worker (or pending spawn) holding an active lease on the crew.
+pub(crate) fn crew_lease_holder(state: &AppState, crew_id: &str) -> Option<String> {
+    let lease = {
+        let crews = state.crews.lock().unwrap();
+        crews.iter().find(|c| c.id == crew_id).and_then(|c| c.lease.clone())
+    }?;
+    lease_is_held(state, &lease).then_some(lease.worker_id)
+}
+
+/// Lease taken for a spawn in progress. Dropping it without `commit` (the
+/// spawn failed) frees the crew again.
+pub(crate) struct CrewLeaseGuard {
+    app: AppHandle,
+    crew_id: String,
+    token: String,
+    committed: bool,
+}
+
+impl CrewLeaseGuard {
+    /// Hand the lease to the worker that was spawned.
+    pub(crate) fn commit(mut self, worker_id: &str) {
+        let state = self.app.state::<AppState>();
+        let mut crews = state.crews.lock().unwrap();
+        if let Some(lease) = crews
+            .iter_mut()
+            .find(|c| c.id == self.crew_id)
+            .and_then(|c| c.lease.as_mut())
+            .filter(|l| l.worker_id == self.token)
+        {
+            lease.worker_id = worker_id.to_string();
+            state.save_crews(&crews);
+        }
+        self.committed = true;
+    }
+}
+
+impl Drop for CrewLeaseGuard {
+    fn drop(&mut self) {
+        if !self.committed {
+            release_crew_lease(&self.app.state::<AppState>(), &self.crew_id, &self.token);
+        }
+    }
+}
+
+/// Claim the crew for a new worker; fails while another worker holds it.
+pub(crate) fn acquire_crew_lease(app: &AppHandle, crew_id: &str) -> Result<CrewLeaseGuard, String> {
+    let state = app.state::<AppState>();
+    let token = format!("spawning:{}", uuid::Uuid::new_v4());
+    let current = {
+        let crews = state.crews.lock().unwrap();
+        crews
+            .iter()
+            .find(|c| c.id == crew_id)
+            .map(|c| c.lease.clone())
+            .ok_or_else(|| "Crew not found".to_string())?
+    };
+    let held = current.as_ref().is_some_and(|l| lease_is_held(&state, l));
+    let mut crews = state.crews.lock().unwrap();
+    let crew = crews
+        .iter_mut()
+        .find(|c| c.id == crew_id)
+        .ok_or_else(|| "Crew not found".to_string())?;
+    // Checked unlocked above; a lease changed since then was just taken.
+    if held || crew.lease != current {
+        let holder = crew.lease.as_ref().map(|l| l.worker_id.clone()).unwrap_or_default();
+        return Err(format!("Crew '{}' is busy (held by worker {})", crew.name, holder));
+    }
+    crew.lease = Some(new_lease(&token));
+    state.save_crews(&crews);
+    Ok(CrewLeaseGuard {
+        app: app.clone(),
+        crew_id: crew_id.to_string(),
+        token,
+        committed: false,
+    })
+}
+
+/// Free the crew if `worker_id` still holds it.
+pub(crate) fn release_crew_lease(state: &AppState, crew_id: &str, worker_id: &str) {
+    let mut crews = state.crews.lock().unwrap();
+    if let Some(crew) = crews
+        .iter_mut()
+        .find(|c| c.id == crew_id && c.lease.as_ref().is_some_and(|l| l.worker_id == worker_id))
+    {
+        crew.lease = None;
+        state.save_crews(&crews);
+    }
+}
+
+/// Extend leases of running workers that are past half their TTL.
+pub(crate) fn renew_crew_leases(state: &AppState) {
+    let running: Vec<String> = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .filter(|w| w.status == WorkerStatusEnum::Running)
+            .map(|w| w.id.clone())
+            .collect()
+    };
+    let renew_before = chrono::Utc::now() + chrono::Duration::minutes(CREW_LEASE_TTL_MINUTES / 2);
+    let mut crews = state.crews.lock().unwrap();
+    let mut changed = false;
+    for lease in crews.iter_mut().filter_map(|c| c.lease.as_mut()) {
+        let due = chrono::DateTime::parse_from_rfc3339(&lease.expires_at)
+            .map(|expiry| expiry < renew_before)
+            .unwrap_or(true);
+        if due && running.contains(&lease.worker_id) {
+            lease.expires_at = (chrono::Utc::now() + chrono::Duration::minutes(CREW_LEASE_TTL_MINUTES)).to_rfc3339();
+            changed = true;
+        }
+    }
+    if changed {
+        state.save_crews(&crews);
+    }
+}
+
+/// On startup, free leases whose worker did not survive the restart.
+pub(crate) fn clear_stale_crew_leases(state: &AppState) {
+    let running: Vec<String> = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .filter(|w| w.status == WorkerStatusEnum::Running)
+            .map(|w| w.id.clone())
+            .collect()
+    };
+    let mut crews = state.crews.lock().unwrap();
+    let mut changed = false;
+    for crew in crews.iter_mut() {
+        if crew.lease.as_ref().is_some_and(|l| !running.contains(&l.worker_id)) {
+            crew.lease = None;
+            changed = true;
+        }
+    }
+    if changed {
+        state.save_crews(&crews);
+    }
+}
+
+/// First active, unleased crew of a rig (polecat worktrees excluded).
+pub(crate) fn idle_crew_id(state: &AppState, rig_id: &str) -> Option<String> {
+    let candidates: Vec<(String, Option<CrewLease>)> = {
+        let crews = state.crews.lock().unwrap();
+        crews
+            .iter()
+            .filter(|c| c.rig_id == rig_id && c.status == CrewStatus::Active && !c.branch.starts_with("polecat/"))
+            .map(|c| (c.id.clone(), c.lease.clone()))
+            .collect()
+    };
+    candidates
+        .into_iter()
+        .find(|(_, lease)| !lease.as_ref().is_some_and(|l| lease_is_held(state, l)))
+        .map(|(id, _)| id)
+}
+
+/// Crew a spawn that asked for `crew_id` should use, by the rig's busy-crew
+/// policy. Returning the busy crew itself means waiting for it in the queue.
+pub(crate) fn place_on_crew(state: &AppState, crew_id: &str) -> Result<String, String> {
+    if crew_lease_holder(state, crew_id).is_none() {
+        return Ok(crew_id.to_string());
+    }
+    let rig_id = {
+        let crews = state.crews.lock().unwrap();
+        crews
+            .iter()
+            .find(|c| c.id == crew_id)
+            .map(|c| c.rig_id.clone())
+            .ok_or_else(|| "Crew not found".to_string())?
+    };
+    let policy = {
+        let rigs = state.rigs.lock().unwrap();
+        rigs.iter()
+            .find(|r| r.id == rig_id)
+            .map(|r| r.settings.busy_crew_policy.clone())
+            .unwrap_or_default()
+    };
+    match policy {
+        BusyCrewPolicy::Queue => Ok(crew_id.to_string()),
+        BusyCrewPolicy::IdleCrew => Ok(idle_crew_id(state, &rig_id).unwrap_or_else(|| crew_id.to_string())),
+        BusyCrewPolicy::Polecat => {
+            let overflow = crate::commands::workers::create_polecat_crew(state, &rig_id, None)?;
+            let mut crews = state.crews.lock().unwrap();
+            if let Some(c) = crews.iter_mut().find(|c| c.id == overflow.id) {
+                c.overflow = true;
+            }
+            state.save_crews(&crews);
+            Ok(overflow.id)
+        }
+    }
+}
+
+/// Drop an overflow crew made for a spawn that then failed before its run
+/// was queued. Crews the policy merely picked are left alone.
+pub(crate) fn discard_overflow_crew(state: &AppState, crew_id: &str) {
+    let overflow = {
+        let crews = state.crews.lock().unwrap();
+        crews.iter().any(|c| c.id == crew_id && c.overflow)
+    };
+    if overflow {
+        crate::commands::workers::remove_polecat_crew(state, crew_id);
+    }
+}
+
+/// How long an overflow crew may sit without runs before it counts as
+/// abandoned; covers the gap between placing a spawn and queueing its run.
+const OVERFLOW_CREW_GRACE_SECS: i64 = 600;
+
+/// Remove overflow worktrees whose runs have all settled, or that were left
+/// without any run past the grace period. The branch is
+/// deleted only when it has no commits beyond the rig's branch; otherwise it
+/// stays for merge. The crew is leased while it is checked and removed, so
+/// no spawn can be placed on it in between.
+pub(crate) fn clean_up_overflow_crews(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let candidates: Vec<Crew> = {
+        let crews = state.crews.lock().unwrap();
+        crews
+            .iter()
+            .filter(|c| c.overflow && c.status == CrewStatus::Active)
+            .cloned()
+            .collect()
+    };
+    for crew in candidates {
+        let Ok(_guard) = acquire_crew_lease(app, &crew.id) else {
+            continue;
+        };
+        let settled = {
+            let runs = state.runs.lock().unwrap();
+            let mut on_crew = runs.iter().filter(|r| r.crew_id == crew.id).peekable();
+            if on_crew.peek().is_none() {
+                // Not yet used: the spawn that placed it may still be setting
+                // up, unless it has been empty for longer than that takes.
+                chrono::DateTime::parse_from_rfc3339(&crew.created_at).is_ok_and(|t| {
+                    (chrono::Utc::now() - t.with_timezone(&chrono::Utc)).num_seconds() > OVERFLOW_CREW_GRACE_SECS
+                })
+            } else {
+                on_crew.all(|r| {
+                    !matches!(r.status, RunStatus::Queued | RunStatus::Running)
+                        && r.retry_at.is_none()
+                        && !r.reviews.iter().any(|rv| rv.verdict == ReviewVerdict::Pending)
+                })
+            }
+        };
+        let worker_running = state
+            .workers
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|w| w.crew_id == crew.id && w.status == WorkerStatusEnum::Running);
+        if !settled || worker_running || crate::scheduler::has_spawns_for_crew(&state, &crew.id) {
+            continue;
+        }
+        let rig_path = {
+            let rigs = state.rigs.lock().unwrap();
+            rigs.iter().find(|r| r.id == crew.rig_id).map(|r| r.path.clone())
+        };
+        let Some(rig_path) = rig_path else {
+            continue;
+        };
+        if git::remove_worktree(&rig_path, &crew.path).is_err() {
+            continue;
+        }
+        let merged = git::get_current_branch(&rig_path)
+            .and_then(|base| git::count_commits_ahead(&rig_path, &base, &crew.branch).ok())
+            == Some(0);
+        if merged {
+            let _ = git::delete_branch(&rig_path, &crew.branch);
+        }
+        let mut crews = state.crews.lock().unwrap();
+        if let Some(c) = crews.iter_mut().find(|c| c.id == crew.id) {
+            c.status = CrewStatus::Removed;
+            c.lease = None;
+        }
+        state.save_crews(&crews);
+    }
+}
+
+/// Crew for rig-level work (hooks): an idle crew when there is one, else the
+/// first active crew placed by the busy-crew policy.
+pub(crate) fn pick_crew_for_rig(state: &AppState, rig_id: &str) -> Result<String, String> {
+    if let Some(crew_id) = idle_crew_id(state, rig_id) {
+        return Ok(crew_id);
+    }
+    let first = {
+        let crews = state.crews.lock().unwrap();
+        crews
+            .iter()
+            .find(|c| c.rig_id == rig_id && c.status == CrewStatus::Active && !c.branch.starts_with("polecat/"))
+            .map(|c| c.id.clone())
+    };
+    match first {
+        Some(crew_id) => place_on_crew(state, &crew_id),
+        None => Err("No active crew found in this rig".to_string()),
+    }
+}
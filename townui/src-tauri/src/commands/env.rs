@@ -0,0 +1,207 @@
+use tauri::{AppHandle, Emitter, State};
+
+use crate::models::audit::{AuditEvent, AuditEventType};
+use crate::models::env::{EnvProfile, EnvScope, EnvVar, ResolvedEnvVar, SecretsStatus};
+use crate::state::AppState;
+
+fn normalize_scope_id(scope: EnvScope, scope_id: Option<String>) -> Result<Option<String>, String> {
+    match scope {
+        EnvScope::Global => Ok(None),
+        _ => scope_id
+            .filter(|id| !id.trim().is_empty())
+            .map(Some)
+            .ok_or_else(|| "A rig, crew or actor id is required for this scope".to_string()),
+    }
+}
+
+/// Rig an env change is audited under; empty for global and actor scopes.
+fn audit_rig_id(state: &AppState, scope: EnvScope, scope_id: Option<&str>) -> String {
+    match (scope, scope_id) {
+        (EnvScope::Rig, Some(id)) => id.to_string(),
+        (EnvScope::Crew, Some(id)) => {
+            let crews = state.crews.lock().unwrap();
+            crews.iter().find(|c| c.id == id).map(|c| c.rig_id.clone()).unwrap_or_default()
+        }
+        _ => String::new(),
+    }
+}
+
+fn audit_env_change(state: &AppState, scope: EnvScope, scope_id: Option<&str>, key: &str, secret: bool, action: &str) {
+    state.append_audit_event(&AuditEvent::new(
+        audit_rig_id(state, scope, scope_id),
+        None,
+        None,
+        AuditEventType::EnvVarChanged,
+        serde_json::json!({
+            "action": action,
+            "scope": scope,
+            "scope_id": scope_id,
+            "key": key,
+            "secret": secret,
+        })
+        .to_string(),
+    ));
+}
+
+/// All env profiles; secret values are blanked.
+#[tauri::command]
+pub fn list_env_profiles(state: State<AppState>) -> Vec<EnvProfile> {
+    let profiles = state.env_profiles.lock().unwrap();
+    profiles
+        .iter()
+        .cloned()
+        .map(|mut p| {
+            for var in p.vars.iter_mut().filter(|v| v.secret) {
+                var.value.clear();
+            }
+            p
+        })
+        .collect()
+}
+
+/// Add or replace a variable in a scope. Secret values are encrypted before
+/// they are stored and require the secrets key to be unlocked.
+#[tauri::command]
+pub fn set_env_var(
+    scope: EnvScope,
+    scope_id: Option<String>,
+    key: String,
+    value: String,
+    secret: bool,
+    state: State<AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let key = key.trim().to_string();
+    if key.is_empty() || key.contains('=') || key.contains(char::is_whitespace) {
+        return Err("Invalid variable name".to_string());
+    }
+    let scope_id = normalize_scope_id(scope, scope_id)?;
+    let stored = if secret {
+        crate::secrets::seal(&state, &value)?
+    } else {
+        value
+    };
+
+    {
+        let mut profiles = state.env_profiles.lock().unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        let index = match profiles.iter().position(|p| p.scope == scope && p.scope_id == scope_id) {
+            Some(index) => index,
+            None => {
+                profiles.push(EnvProfile {
+                    scope,
+                    scope_id: scope_id.clone(),
+                    vars: Vec::new(),
+                    updated_at: now.clone(),
+                });
+                profiles.len() - 1
+            }
+        };
+        let profile = &mut profiles[index];
+        profile.vars.retain(|v| v.key != key);
+        profile.vars.push(EnvVar {
+            key: key.clone(),
+            value: stored,
+            secret,
+        });
+        profile.updated_at = now;
+        state.save_env_profiles(&profiles);
+    }
+    if secret {
+        crate::secrets::refresh_redactions(&state);
+    }
+
+    audit_env_change(&state, scope, scope_id.as_deref(), &key, secret, "set");
+    let _ = app.emit("data-changed", "");
+    Ok(())
+}
+
+#[tauri::command]
+pub fn remove_env_var(
+    scope: EnvScope,
+    scope_id: Option<String>,
+    key: String,
+    state: State<AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let scope_id = normalize_scope_id(scope, scope_id)?;
+    let removed = {
+        let mut profiles = state.env_profiles.lock().unwrap();
+        let profile = profiles
+            .iter_mut()
+            .find(|p| p.scope == scope && p.scope_id == scope_id)
+            .ok_or_else(|| "Variable not found".to_string())?;
+        let index = profile
+            .vars
+            .iter()
+            .position(|v| v.key == key)
+            .ok_or_else(|| "Variable not found".to_string())?;
+        let removed = profile.vars.remove(index);
+        profile.updated_at = chrono::Utc::now().to_rfc3339();
+        profiles.retain(|p| !p.vars.is_empty());
+        state.save_env_profiles(&profiles);
+        removed
+    };
+    // Keep redacting a removed secret for the rest of the session: it may
+    // still be in the environment of running workers.
+
+    audit_env_change(&state, scope, scope_id.as_deref(), &key, removed.secret, "remove");
+    let _ = app.emit("data-changed", "");
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_secrets_status(state: State<AppState>) -> SecretsStatus {
+    crate::secrets::status(&state)
+}
+
+#[tauri::command]
+pub fn unlock_secrets(passphrase: String, state: State<AppState>, app: AppHandle) -> Result<SecretsStatus, String> {
+    crate::secrets::unlock(&state, &passphrase)?;
+    let _ = app.emit("data-changed", "");
+    Ok(crate::secrets::status(&state))
+}
+
+/// Protect secrets with a passphrase (`Some`) or move them back to the OS
+/// keyring (`None`); every stored secret is re-encrypted under the new key.
+#[tauri::command]
+pub fn set_secrets_passphrase(
+    passphrase: Option<String>,
+    state: State<AppState>,
+    app: AppHandle,
+) -> Result<SecretsStatus, String> {
+    crate::secrets::change_key_source(&state, passphrase.as_deref())?;
+    let status = crate::secrets::status(&state);
+    state.append_audit_event(&AuditEvent::new(
+        String::new(),
+        None,
+        None,
+        AuditEventType::SecretsKeyChanged,
+        serde_json::json!({
+            "source": status.source,
+            "secret_count": status.secret_count,
+        })
+        .to_string(),
+    ));
+    let _ = app.emit("data-changed", "");
+    Ok(status)
+}
+
+/// The environment a worker on `crew_id` would get, with the layer each
+/// variable comes from. Secret values are masked.
+#[tauri::command]
+pub fn preview_worker_env(
+    crew_id: String,
+    actor_id: Option<String>,
+    state: State<AppState>,
+) -> Result<Vec<ResolvedEnvVar>, String> {
+    let rig_id = {
+        let crews = state.crews.lock().unwrap();
+        crews
+            .iter()
+            .find(|c| c.id == crew_id)
+            .map(|c| c.rig_id.clone())
+            .ok_or_else(|| "Crew not found".to_string())?
+    };
+    Ok(crate::secrets::preview_worker_env(&state, &rig_id, &crew_id, actor_id.as_deref()))
+}
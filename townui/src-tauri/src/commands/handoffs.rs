@@ -66,6 +66,7 @@ pub fn create_handoff(
                 hook_id: None,
                 blocked_reason: None,
                 outcome: None,
+                retry_policy: None,
             });
             state.save_tasks(&tasks);
         }
@@ -124,6 +125,7 @@ pub fn accept_handoff(
                 hook_id: None,
                 blocked_reason: None,
                 outcome: None,
+                retry_policy: None,
             });
             state.save_tasks(&tasks);
         }
@@ -1,9 +1,11 @@
 use serde::Serialize;
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Manager, State};
 
 use crate::models::audit::{AuditEvent, AuditEventType};
 use crate::models::hook::{Hook, HookStatus};
+use crate::models::scheduler::{QueuedSpawn, SpawnSource};
 use crate::models::task::{TaskStatus, TaskUpdateRequest};
+use crate::scheduler::Admission;
 use crate::state::AppState;
 
 const HOOK_LEASE_TTL_MINUTES: i64 = 45;
@@ -59,13 +61,11 @@ fn clear_hook_lease(hook: &mut Hook) {
     hook.lease_expires_at = None;
 }
 
+/// Crew to run hook work on: an idle crew of the rig, else one chosen by the
+/// rig's busy-crew policy.
 fn resolve_active_crew_id(rig_id: &str, state: &AppState) -> Result<String, String> {
-    let crews = state.crews.lock().unwrap();
-    crews
-        .iter()
-        .find(|c| c.rig_id == rig_id && c.status == crate::models::crew::CrewStatus::Active)
-        .map(|c| c.id.clone())
-        .ok_or_else(|| "No active crew found in this rig to execute hook work".to_string())
+    crate::commands::crews::pick_crew_for_rig(state, rig_id)
+        .map_err(|_| "No active crew found in this rig to execute hook work".to_string())
 }
 
 fn resolve_hook_agent_type(hook: &Hook, state: &AppState) -> String {
@@ -116,27 +116,25 @@ fn build_hook_task_prompt(
         )
     };
 
-    let crew_branch = {
-        let crews = state.crews.lock().unwrap();
-        crews
-            .iter()
-            .find(|c| c.id == crew_id)
-            .map(|c| c.branch.clone())
-            .ok_or_else(|| "Crew not found".to_string())?
-    };
-
-    let (rig_name, rig_path) = {
-        let rigs = state.rigs.lock().unwrap();
-        rigs.iter()
-            .find(|r| r.id == hook.rig_id)
-            .map(|r| (r.name.clone(), r.path.clone()))
-            .ok_or_else(|| "Rig not found".to_string())?
-    };
+    let ctx = crate::prompt_context::build_prompt_context(
+        state,
+        &crate::prompt_context::ContextRequest {
+            rig_id: &hook.rig_id,
+            task_id: Some(work_item_id),
+            crew_id: Some(crew_id),
+            agent_cli: agent_type,
+            actor_id: Some(&hook.attached_actor_id),
+            ..Default::default()
+        },
+    );
+    if ctx.crew.id.is_empty() {
+        return Err("Crew not found".to_string());
+    }
+    if ctx.rig.id.is_empty() {
+        return Err("Rig not found".to_string());
+    }
 
-    let template_name = {
-        let settings = state.settings.lock().unwrap();
-        settings.default_template.clone()
-    };
+    let (template, fragments) = crate::commands::templates::resolve_template(state, "", Some(&hook.rig_id), Some(crew_id))?;
 
     let title_trimmed = task_title.trim().to_string();
     let description_trimmed = task_description.trim().to_string();
@@ -158,15 +156,7 @@ fn build_hook_task_prompt(
         ));
     }
 
-    let rendered = crate::templates::render_builtin_template(
-        &template_name,
-        agent_type,
-        &task_title,
-        &task_description,
-        &rig_name,
-        &crew_branch,
-        &rig_path,
-    );
+    let rendered = crate::templates::render_task_template(&template, &fragments, &ctx)?;
 
     let mut prompt = format!(
         "[HOOK EXECUTION]\nHook ID: {}\nActor ID: {}\nTask ID: {}\nThis task was slung/assigned on-hook and should start immediately.\n\n{}",
@@ -183,10 +173,10 @@ fn build_hook_task_prompt(
 fn auto_execute_hook_work(
     hook: &Hook,
     work_item_id: &str,
+    crew_id: String,
     state: &AppState,
     app: AppHandle,
 ) -> Result<(String, String, String), String> {
-    let crew_id = resolve_active_crew_id(&hook.rig_id, state)?;
     let agent_type = resolve_hook_agent_type(hook, state);
     let prompt = build_hook_task_prompt(hook, work_item_id, &crew_id, &agent_type, state)?;
 
@@ -195,6 +185,7 @@ fn auto_execute_hook_work(
         agent_type.clone(),
         prompt,
         Some(hook.attached_actor_id.clone()),
+        Some(work_item_id),
         app,
     )?;
 
@@ -218,6 +209,7 @@ fn auto_execute_hook_work(
             hook_id: Some(Some(hook.hook_id.clone())),
             blocked_reason: Some(None),
             outcome: Some(None),
+            retry_policy: None,
         });
         state.save_tasks(&tasks);
     }
@@ -260,7 +252,62 @@ fn dispatch_hook_work(
         updated
     };
 
-    match auto_execute_hook_work(&assigned_hook, &work_item_id, state, app) {
+    let crew_id = resolve_active_crew_id(&assigned_hook.rig_id, state)?;
+    let agent_type = resolve_hook_agent_type(&assigned_hook, state);
+    let mut request = QueuedSpawn::new(
+        &assigned_hook.rig_id,
+        &agent_type,
+        crate::scheduler::task_priority(state, Some(&work_item_id)),
+        SpawnSource::Hook,
+        format!("hook {}", assigned_hook.hook_id),
+    );
+    request.crew_id = Some(crew_id.clone());
+    request.task_id = Some(work_item_id.clone());
+
+    let has_state_blob = state_blob.is_some();
+    let job_hook = assigned_hook.clone();
+    let job_work_item_id = work_item_id.clone();
+    let job_event_type = audit_event_type.clone();
+    let admission = crate::scheduler::submit(&app, request, move |app| {
+        start_hook_work(app, &job_hook, &job_work_item_id, crew_id, has_state_blob, job_event_type)
+    });
+    match admission {
+        Ok(Admission::Started(hook)) => Ok(hook),
+        // The hook stays assigned until the scheduler starts its worker.
+        Ok(Admission::Queued(spawn)) => {
+            let payload = serde_json::json!({
+                "hook_id": assigned_hook.hook_id,
+                "work_item_id": work_item_id,
+                "has_state_blob": has_state_blob,
+                "auto_executed": false,
+                "queued": spawn.id,
+                "waiting_on": spawn.waiting_on,
+            })
+            .to_string();
+            state.append_audit_event(&AuditEvent::new(
+                assigned_hook.rig_id.clone(),
+                Some(assigned_hook.attached_actor_id.clone()),
+                assigned_hook.current_work_id.clone(),
+                audit_event_type,
+                payload,
+            ));
+            Ok(assigned_hook)
+        }
+        Err(e) => Err(format!("Hook dispatch failed to auto-execute task: {}", e)),
+    }
+}
+
+/// Spawn the worker for an assigned hook and mark the hook running.
+fn start_hook_work(
+    app: &AppHandle,
+    assigned_hook: &Hook,
+    work_item_id: &str,
+    crew_id: String,
+    has_state_blob: bool,
+    audit_event_type: AuditEventType,
+) -> Result<Hook, String> {
+    let state = app.state::<AppState>();
+    match auto_execute_hook_work(assigned_hook, work_item_id, crew_id, &state, app.clone()) {
         Ok((worker_id, crew_id, agent_type)) => {
             let running_hook = {
                 let mut hooks = state.hooks.lock().unwrap();
@@ -278,7 +325,7 @@ fn dispatch_hook_work(
             let payload = serde_json::json!({
                 "hook_id": running_hook.hook_id,
                 "work_item_id": work_item_id,
-                "has_state_blob": state_blob.is_some(),
+                "has_state_blob": has_state_blob,
                 "auto_executed": true,
                 "worker_id": worker_id,
                 "crew_id": crew_id,
@@ -295,12 +342,11 @@ fn dispatch_hook_work(
 
             Ok(running_hook)
         }
-        Err(e) => {
-            let err_msg = e;
+        Err(err_msg) => {
             let payload = serde_json::json!({
                 "hook_id": assigned_hook.hook_id,
                 "work_item_id": work_item_id,
-                "has_state_blob": state_blob.is_some(),
+                "has_state_blob": has_state_blob,
                 "auto_executed": false,
                 "error": err_msg.clone(),
             })
@@ -313,7 +359,7 @@ fn dispatch_hook_work(
                 payload,
             ));
 
-            Err(format!("Hook dispatch failed to auto-execute task: {}", err_msg))
+            Err(err_msg)
         }
     }
 }
@@ -395,43 +441,51 @@ pub fn delete_hook(hook_id: String, state: State<AppState>) -> Result<(), String
 }
 
 #[tauri::command]
-pub fn assign_to_hook(
+pub async fn assign_to_hook(
     hook_id: String,
     work_item_id: String,
     state_blob: Option<String>,
-    state: State<AppState>,
     app: AppHandle,
 ) -> Result<Hook, String> {
-    dispatch_hook_work(
-        hook_id,
-        work_item_id,
-        state_blob,
-        &state,
-        app,
-        AuditEventType::HookAssigned,
-    )
+    tokio::task::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        dispatch_hook_work(
+            hook_id,
+            work_item_id,
+            state_blob,
+            &state,
+            app.clone(),
+            AuditEventType::HookAssigned,
+        )
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
 }
 
 #[tauri::command]
-pub fn sling(
+pub async fn sling(
     hook_id: String,
     work_item_id: String,
     state_blob: Option<String>,
-    state: State<AppState>,
     app: AppHandle,
 ) -> Result<Hook, String> {
-    dispatch_hook_work(
-        hook_id,
-        work_item_id,
-        state_blob,
-        &state,
-        app,
-        AuditEventType::HookSlung,
-    )
+    tokio::task::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        dispatch_hook_work(
+            hook_id,
+            work_item_id,
+            state_blob,
+            &state,
+            app.clone(),
+            AuditEventType::HookSlung,
+        )
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
 }
 
 #[tauri::command]
-pub fn done(hook_id: String, outcome: Option<String>, state: State<AppState>) -> Result<Hook, String> {
+pub fn done(hook_id: String, outcome: Option<String>, state: State<AppState>, app: AppHandle) -> Result<Hook, String> {
     let mut hooks = state.hooks.lock().unwrap();
     let hook = hooks
         .iter_mut()
@@ -447,9 +501,11 @@ pub fn done(hook_id: String, outcome: Option<String>, state: State<AppState>) ->
     drop(hooks);
 
     // update task outcome/done if there is current work item
+    let mut task_done = false;
     if let Some(task_id) = &work_item_id {
         let mut tasks = state.tasks.lock().unwrap();
         if let Some(task) = tasks.iter_mut().find(|t| t.id == *task_id) {
+            task_done = task.status != TaskStatus::Done;
             task.apply_update(TaskUpdateRequest {
                 title: None,
                 description: None,
@@ -464,6 +520,7 @@ pub fn done(hook_id: String, outcome: Option<String>, state: State<AppState>) ->
                 hook_id: Some(Some(updated.hook_id.clone())),
                 blocked_reason: Some(None),
                 outcome: Some(outcome.clone()),
+                retry_policy: None,
             });
             state.save_tasks(&tasks);
         }
@@ -494,82 +551,100 @@ pub fn done(hook_id: String, outcome: Option<String>, state: State<AppState>) ->
     state.append_audit_event(&AuditEvent::new(
         final_hook.rig_id.clone(),
         Some(final_hook.attached_actor_id.clone()),
-        work_item_id,
+        work_item_id.clone(),
         AuditEventType::HookDone,
         payload,
     ));
+    drop(hooks);
+    if let (true, Some(task_id)) = (task_done, work_item_id) {
+        crate::lifecycle::task_done(&app, &task_id);
+    }
 
     Ok(final_hook)
 }
 
 #[tauri::command]
-pub fn resume_hook(hook_id: String, state: State<AppState>, app: AppHandle) -> Result<Hook, String> {
-    let mut hooks = state.hooks.lock().unwrap();
-    let hook = hooks
-        .iter_mut()
-        .find(|h| h.hook_id == hook_id)
-        .ok_or_else(|| "Hook not found".to_string())?;
-
-    if hook.status == HookStatus::Running && hook_has_active_lease(hook) {
-        return Err(format!(
-            "Hook {} already running with active lease until {}",
-            hook.hook_id,
-            hook.lease_expires_at.clone().unwrap_or_default()
-        ));
-    }
-
-    hook.status = if hook.current_work_id.is_some() {
-        HookStatus::Running
-    } else {
-        HookStatus::Assigned
-    };
-    issue_hook_lease(hook);
-    hook.last_heartbeat = chrono::Utc::now().to_rfc3339();
-    let updated = hook.clone();
-    state.save_hooks(&hooks);
-    drop(hooks);
-
-    // Build a resume prompt from state_blob
-    let resume_prompt = if let Some(ref blob) = updated.state_blob {
-        format!(
-            "[RESUME] Continuing interrupted work. Previous state:\n{}\n\nPlease continue where you left off.",
-            blob
-        )
-    } else {
-        "[RESUME] Continuing interrupted work. No previous state available. Please check the current project state and continue.".to_string()
-    };
+pub async fn resume_hook(hook_id: String, app: AppHandle) -> Result<Hook, String> {
+    tokio::task::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let mut hooks = state.hooks.lock().unwrap();
+        let hook = hooks
+            .iter_mut()
+            .find(|h| h.hook_id == hook_id)
+            .ok_or_else(|| "Hook not found".to_string())?;
 
-    // Find an active crew and resolve an agent runtime
-    let crew_id = resolve_active_crew_id(&updated.rig_id, &state)?;
-    let agent_type = resolve_hook_agent_type(&updated, &state);
+        if hook.status == HookStatus::Running && hook_has_active_lease(hook) {
+            return Err(format!(
+                "Hook {} already running with active lease until {}",
+                hook.hook_id,
+                hook.lease_expires_at.clone().unwrap_or_default()
+            ));
+        }
 
-    // Audit: hook resumed
-    let payload = serde_json::json!({
-        "hook_id": updated.hook_id,
-        "work_item_id": updated.current_work_id,
-        "has_state_blob": updated.state_blob.is_some(),
-        "spawning_worker": true,
-    })
-    .to_string();
-    state.append_audit_event(&AuditEvent::new(
-        updated.rig_id.clone(),
-        Some(updated.attached_actor_id.clone()),
-        updated.current_work_id.clone(),
-        AuditEventType::HookResumed,
-        payload,
-    ));
+        hook.status = if hook.current_work_id.is_some() {
+            HookStatus::Running
+        } else {
+            HookStatus::Assigned
+        };
+        issue_hook_lease(hook);
+        hook.last_heartbeat = chrono::Utc::now().to_rfc3339();
+        let updated = hook.clone();
+        state.save_hooks(&hooks);
+        drop(hooks);
+
+        // Build a resume prompt from state_blob
+        let resume_prompt = if let Some(ref blob) = updated.state_blob {
+            format!(
+                "[RESUME] Continuing interrupted work. Previous state:\n{}\n\nPlease continue where you left off.",
+                blob
+            )
+        } else {
+            "[RESUME] Continuing interrupted work. No previous state available. Please check the current project state and continue.".to_string()
+        };
+
+        // Find an active crew and resolve an agent runtime
+        let crew_id = resolve_active_crew_id(&updated.rig_id, &state)?;
+        let agent_type = resolve_hook_agent_type(&updated, &state);
+
+        // Audit: hook resumed
+        let payload = serde_json::json!({
+            "hook_id": updated.hook_id,
+            "work_item_id": updated.current_work_id,
+            "has_state_blob": updated.state_blob.is_some(),
+            "spawning_worker": true,
+        })
+        .to_string();
+        state.append_audit_event(&AuditEvent::new(
+            updated.rig_id.clone(),
+            Some(updated.attached_actor_id.clone()),
+            updated.current_work_id.clone(),
+            AuditEventType::HookResumed,
+            payload,
+        ));
 
-    // Spawn a worker to continue the work
-    match super::workers::spawn_worker_for_actor(
-        crew_id,
-        agent_type,
-        resume_prompt,
-        Some(updated.attached_actor_id.clone()),
-        app,
-    ) {
-        Ok(worker) => {
+        // Spawn a worker to continue the work, through the scheduler
+        let mut request = QueuedSpawn::new(
+            &updated.rig_id,
+            &agent_type,
+            crate::scheduler::task_priority(&state, updated.current_work_id.as_deref()),
+            SpawnSource::Hook,
+            format!("resume hook {}", updated.hook_id),
+        );
+        request.crew_id = Some(crew_id.clone());
+        request.task_id = updated.current_work_id.clone();
+        let job_hook = updated.clone();
+        let admission = crate::scheduler::submit(&app, request, move |app| {
+            let worker = super::workers::spawn_worker_for_actor(
+                crew_id,
+                agent_type,
+                resume_prompt,
+                Some(job_hook.attached_actor_id.clone()),
+                job_hook.current_work_id.as_deref(),
+                app.clone(),
+            )?;
             // Link the worker to the hook's current work item if any
-            if let Some(ref task_id) = updated.current_work_id {
+            if let Some(ref task_id) = job_hook.current_work_id {
+                let state = app.state::<AppState>();
                 let mut tasks = state.tasks.lock().unwrap();
                 if let Some(task) = tasks.iter_mut().find(|t| t.id == *task_id) {
                     task.apply_update(TaskUpdateRequest {
@@ -581,25 +656,32 @@ pub fn resume_hook(hook_id: String, state: State<AppState>, app: AppHandle) -> R
                         assigned_worker_id: Some(Some(worker.id.clone())),
                         acceptance_criteria: None,
                         dependencies: None,
-                        owner_actor_id: Some(Some(updated.attached_actor_id.clone())),
+                        owner_actor_id: Some(Some(job_hook.attached_actor_id.clone())),
                         convoy_id: None,
-                        hook_id: Some(Some(updated.hook_id.clone())),
+                        hook_id: Some(Some(job_hook.hook_id.clone())),
                         blocked_reason: Some(None),
                         outcome: None,
+                        retry_policy: None,
                     });
                     state.save_tasks(&tasks);
                 }
             }
-            // Return the updated hook
-            let hooks = state.hooks.lock().unwrap();
-            hooks
-                .iter()
-                .find(|h| h.hook_id == updated.hook_id)
-                .cloned()
-                .ok_or_else(|| "Hook not found after resume".to_string())
+            Ok(())
+        });
+        if let Err(e) = admission {
+            return Err(format!("Resume hook: failed to spawn worker: {}", e));
         }
-        Err(e) => Err(format!("Resume hook: failed to spawn worker: {}", e)),
-    }
+
+        // Return the updated hook
+        let hooks = state.hooks.lock().unwrap();
+        hooks
+            .iter()
+            .find(|h| h.hook_id == updated.hook_id)
+            .cloned()
+            .ok_or_else(|| "Hook not found after resume".to_string())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
 }
 
 #[tauri::command]
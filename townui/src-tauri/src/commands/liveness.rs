@@ -0,0 +1,306 @@
+use std::time::{Duration, SystemTime};
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::models::audit::{AuditEvent, AuditEventType};
+use crate::models::liveness::{LivenessState, RecoveryStep, WorkerLiveness};
+use crate::models::task::TaskStatus;
+use crate::models::worker::{LogEntry, Worker, WorkerStatusEnum};
+use crate::state::AppState;
+
+/// CPU seconds per sample above which a quiet worker still counts as busy.
+const CPU_ACTIVE_THRESHOLD_SECONDS: f64 = 1.0;
+
+fn to_rfc3339(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339()
+}
+
+fn seconds_since(time: SystemTime) -> u64 {
+    SystemTime::now()
+        .duration_since(time)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+/// Collect liveness signals for a running worker and classify it. Recovery
+/// progress is carried over from the previous sample.
+fn sample_worker(state: &AppState, worker: &Worker, previous: Option<&WorkerLiveness>) -> WorkerLiveness {
+    let (idle_after, hung_after) = {
+        let settings = state.settings.lock().unwrap();
+        (settings.liveness_idle_seconds, settings.liveness_hung_seconds)
+    };
+
+    let last_output = state
+        .worker_last_output
+        .lock()
+        .unwrap()
+        .get(&worker.id)
+        .map(|at| SystemTime::now() - at.elapsed());
+    let crew_path = {
+        let crews = state.crews.lock().unwrap();
+        crews.iter().find(|c| c.id == worker.crew_id).map(|c| c.path.clone())
+    };
+    let last_file_change = crew_path.and_then(|p| crate::git::last_change_time(&p));
+    let started = chrono::DateTime::parse_from_rfc3339(&worker.started_at)
+        .ok()
+        .map(SystemTime::from);
+
+    // Quiet time runs from the latest sign of progress (or the start).
+    let latest_activity = [last_output, last_file_change, started]
+        .into_iter()
+        .flatten()
+        .max();
+    let quiet_seconds = latest_activity.map(seconds_since).unwrap_or(0);
+
+    let cpu_seconds = worker
+        .pid
+        .and_then(crate::commands::workers::process_tree_cpu_seconds);
+    let cpu_delta_seconds = match (cpu_seconds, previous.and_then(|p| p.cpu_seconds)) {
+        (Some(now), Some(before)) => Some((now - before).max(0.0)),
+        _ => None,
+    };
+    let cpu_busy = cpu_delta_seconds
+        .map(|d| d >= CPU_ACTIVE_THRESHOLD_SECONDS)
+        .unwrap_or(false);
+
+    let state_now = if worker.waiting_input.is_some() {
+        LivenessState::WaitingInput
+    } else if quiet_seconds < idle_after || cpu_busy {
+        LivenessState::Active
+    } else if quiet_seconds < hung_after {
+        LivenessState::Idle
+    } else {
+        LivenessState::Hung
+    };
+
+    // Only file changes made after the last step count as real progress and
+    // reset the ladder; output alone may just be the nudge being echoed.
+    let keep_ladder = previous
+        .and_then(|p| p.recovery_step_at.as_deref())
+        .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+        .map(|at| last_file_change.map(|c| c <= SystemTime::from(at)).unwrap_or(true))
+        .unwrap_or(false);
+    WorkerLiveness {
+        worker_id: worker.id.clone(),
+        state: state_now,
+        last_output_at: last_output.map(to_rfc3339),
+        last_file_change_at: last_file_change.map(to_rfc3339),
+        cpu_seconds,
+        cpu_delta_seconds,
+        quiet_seconds,
+        recovery_step: previous
+            .filter(|_| keep_ladder)
+            .and_then(|p| p.recovery_step.clone()),
+        recovery_step_at: previous
+            .filter(|_| keep_ladder)
+            .and_then(|p| p.recovery_step_at.clone()),
+        checked_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+fn log_system(state: &AppState, app: &AppHandle, worker_id: &str, line: String) {
+    let entry = LogEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        stream: "system".to_string(),
+        line,
+    };
+    crate::commands::workers::emit_worker_log(state, app, worker_id, entry);
+}
+
+fn escalate_hung_task(state: &AppState, worker: &Worker, quiet_seconds: u64) -> Option<String> {
+    let task_id = {
+        let runs = state.runs.lock().unwrap();
+        runs.iter().rev().find(|r| r.worker_id == worker.id).map(|r| r.task_id.clone())
+    };
+    let mut tasks = state.tasks.lock().unwrap();
+    let task = tasks.iter_mut().find(|t| {
+        t.status == TaskStatus::InProgress
+            && (Some(&t.id) == task_id.as_ref() || t.assigned_worker_id.as_deref() == Some(worker.id.as_str()))
+    })?;
+    task.status = TaskStatus::Escalated;
+    task.blocked_reason = Some(format!(
+        "Worker hung: no output, file change or CPU activity for {} min after nudge, re-prime and restart",
+        quiet_seconds / 60
+    ));
+    task.updated_at = chrono::Utc::now().to_rfc3339();
+    state.append_audit_event(&AuditEvent::new(
+        task.rig_id.clone(),
+        task.owner_actor_id.clone(),
+        Some(task.id.clone()),
+        AuditEventType::TaskStatusChanged,
+        serde_json::json!({
+            "old_status": "in_progress",
+            "new_status": "escalated",
+            "reason": task.blocked_reason,
+            "worker_id": worker.id,
+        })
+        .to_string(),
+    ));
+    let id = task.id.clone();
+    state.save_tasks(&tasks);
+    Some(id)
+}
+
+/// Apply the next ladder step to a hung worker. A restart runs in the
+/// background; its replacement inherits `ladder` so hanging again escalates.
+fn apply_recovery_step(
+    state: &AppState,
+    app: &AppHandle,
+    worker: &Worker,
+    step: &RecoveryStep,
+    ladder: WorkerLiveness,
+) {
+    let quiet_seconds = ladder.quiet_seconds;
+    let mut task_id = None;
+    let result: Result<(), String> = match step {
+        RecoveryStep::Nudge => crate::commands::workers::nudge_worker_pty(state, &worker.id),
+        RecoveryStep::Reprime => crate::commands::workers::reprime_worker(state, app, &worker.id),
+        RecoveryStep::Restart => {
+            crate::commands::workers::restart_worker_inner(state, app, &worker.id, move |state, new_id| {
+                let mut inherited = ladder;
+                inherited.worker_id = new_id.clone();
+                inherited.state = LivenessState::Active;
+                inherited.cpu_seconds = None;
+                inherited.recovery_step_at = Some(chrono::Utc::now().to_rfc3339());
+                state.worker_liveness.lock().unwrap().insert(new_id, inherited);
+            })
+        }
+        RecoveryStep::Escalate => {
+            task_id = escalate_hung_task(state, worker, quiet_seconds);
+            Ok(())
+        }
+    };
+    let step_name = serde_json::to_value(step)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default();
+    log_system(
+        state,
+        app,
+        &worker.id,
+        format!("[liveness] Hung for {}s: {}", quiet_seconds, step_name),
+    );
+    state.append_audit_event(&AuditEvent::new(
+        worker.rig_id.clone(),
+        worker.actor_id.clone(),
+        task_id,
+        AuditEventType::LivenessRecovery,
+        serde_json::json!({
+            "worker_id": worker.id,
+            "step": step,
+            "quiet_seconds": quiet_seconds,
+            "error": result.as_ref().err(),
+        })
+        .to_string(),
+    ));
+}
+
+/// Supervisor pass: sample every running worker, persist its liveness state
+/// and walk hung workers up the recovery ladder one step per interval.
+pub(crate) fn run_liveness_cycle(state: &AppState, app: &AppHandle) {
+    let step_interval = state.settings.lock().unwrap().polecat_nudge_after_seconds;
+    let running: Vec<Worker> = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .filter(|w| w.status == WorkerStatusEnum::Running)
+            .cloned()
+            .collect()
+    };
+
+    let mut samples = Vec::new();
+    {
+        let mut liveness = state.worker_liveness.lock().unwrap();
+        liveness.retain(|id, _| running.iter().any(|w| w.id == *id));
+        for worker in &running {
+            let sample = sample_worker(state, worker, liveness.get(&worker.id));
+            liveness.insert(worker.id.clone(), sample.clone());
+            samples.push(sample);
+        }
+    }
+
+    let mut changed = false;
+    {
+        let mut workers = state.workers.lock().unwrap();
+        for sample in &samples {
+            if let Some(w) = workers.iter_mut().find(|w| w.id == sample.worker_id) {
+                if w.liveness != sample.state {
+                    w.liveness = sample.state.clone();
+                    changed = true;
+                }
+            }
+        }
+        if changed {
+            state.save_workers(&workers);
+        }
+    }
+
+    for sample in samples.iter().filter(|s| s.state == LivenessState::Hung) {
+        let due = sample
+            .recovery_step_at
+            .as_deref()
+            .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| (chrono::Utc::now().timestamp() - at.timestamp()).max(0) as u64 >= step_interval)
+            .unwrap_or(true);
+        if !due {
+            continue;
+        }
+        let Some(step) = RecoveryStep::next(sample.recovery_step.as_ref()) else {
+            continue;
+        };
+        let Some(worker) = running.iter().find(|w| w.id == sample.worker_id) else {
+            continue;
+        };
+
+        let mut ladder = sample.clone();
+        ladder.recovery_step = Some(step.clone());
+        apply_recovery_step(state, app, worker, &step, ladder);
+        let mut liveness = state.worker_liveness.lock().unwrap();
+        if let Some(entry) = liveness.get_mut(&worker.id) {
+            entry.recovery_step = Some(step);
+            entry.recovery_step_at = Some(chrono::Utc::now().to_rfc3339());
+        }
+        changed = true;
+    }
+
+    if changed {
+        let _ = app.emit("data-changed", "");
+    }
+}
+
+/// Latest liveness sample for a worker (sampled now if the supervisor has not yet).
+#[tauri::command]
+pub fn get_worker_liveness(id: String, state: State<AppState>) -> Result<WorkerLiveness, String> {
+    if let Some(sample) = state.worker_liveness.lock().unwrap().get(&id) {
+        return Ok(sample.clone());
+    }
+    let worker = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .find(|w| w.id == id)
+            .cloned()
+            .ok_or_else(|| "Worker not found".to_string())?
+    };
+    if worker.status != WorkerStatusEnum::Running {
+        return Err("Worker is not running".to_string());
+    }
+    Ok(sample_worker(&state, &worker, None))
+}
+
+#[tauri::command]
+pub fn list_worker_liveness(rig_id: Option<String>, state: State<AppState>) -> Vec<WorkerLiveness> {
+    let rig_workers: Vec<String> = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .filter(|w| rig_id.as_ref().map(|r| &w.rig_id == r).unwrap_or(true))
+            .map(|w| w.id.clone())
+            .collect()
+    };
+    let liveness = state.worker_liveness.lock().unwrap();
+    rig_workers
+        .iter()
+        .filter_map(|id| liveness.get(id).cloned())
+        .collect()
+}
@@ -1,14 +1,26 @@
 pub mod actors;
 pub mod ai_inbox;
 pub mod audit;
+pub mod best_of;
+pub mod budgets;
+pub mod checkpoints;
 pub mod dogs;
+pub mod env;
 pub mod convoys;
+pub mod costs;
 pub mod crews;
 pub mod handoffs;
 pub mod hooks;
+pub mod liveness;
 pub mod operations;
+pub mod prompts;
+pub mod recordings;
 pub mod refinery;
+pub mod replay;
+pub mod retry;
+pub mod review;
 pub mod rigs;
+pub mod scheduler;
 pub mod terminal;
 pub mod seed;
 pub mod settings;
@@ -156,20 +156,6 @@ fn snapshot_town_status(state: &AppState) -> TownRuntimeStatus {
     }
 }
 
-#[cfg(target_os = "windows")]
-fn kill_pid(pid: u32) {
-    let _ = std::process::Command::new("taskkill")
-        .args(["/PID", &pid.to_string(), "/T", "/F"])
-        .output();
-}
-
-#[cfg(not(target_os = "windows"))]
-fn kill_pid(pid: u32) {
-    let _ = std::process::Command::new("kill")
-        .args(["-9", &pid.to_string()])
-        .output();
-}
-
 fn ensure_rig_exists(state: &AppState, rig_id: &str) -> Result<(), String> {
     let rigs = state.rigs.lock().unwrap();
     if rigs.iter().any(|r| r.id == rig_id) {
@@ -281,19 +267,17 @@ pub fn town_shutdown(state: State<AppState>, app: AppHandle) -> TownRuntimeStatu
     let _ = crate::commands::supervisor::stop_supervisor(state.clone(), app.clone());
     let _ = crate::commands::ai_inbox::stop_ai_inbox(state.clone());
 
-    {
-        let mut workers = state.workers.lock().unwrap();
-        let now = chrono::Utc::now().to_rfc3339();
-        for w in workers.iter_mut() {
-            if w.status == WorkerStatusEnum::Running {
-                if let Some(pid) = w.pid {
-                    kill_pid(pid);
-                }
-                w.status = WorkerStatusEnum::Stopped;
-                w.stopped_at = Some(now.clone());
-            }
-        }
-        state.save_workers(&workers);
+    let running: Vec<String> = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .filter(|w| w.status == WorkerStatusEnum::Running)
+            .map(|w| w.id.clone())
+            .collect()
+    };
+    // Staged stops run in the background so the UI stays responsive.
+    for id in running {
+        let _ = crate::commands::workers::begin_graceful_stop(&state, &app, &id, "shutdown");
     }
 
     let _ = app.emit("data-changed", "");
@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::models::audit::{AuditEvent, AuditEventType};
+use crate::models::prompt::{PromptAction, PromptMatch, PromptRule, PromptScope};
+use crate::models::task::TaskStatus;
+use crate::models::worker::{LogEntry, Worker, WorkerStatusEnum};
+use crate::state::AppState;
+
+/// Prefix of `blocked_reason` on tasks escalated by a prompt, so the
+/// escalation can be lifted again once the prompt is answered.
+const WAITING_REASON_PREFIX: &str = "Waiting for input: ";
+/// Screen lines above the cursor that are searched for a prompt.
+const SCREEN_WINDOW_LINES: usize = 8;
+/// Log lines searched by `PromptScope::Output` rules.
+const OUTPUT_WINDOW_LINES: usize = 10;
+const MAX_MATCH_CHARS: usize = 200;
+
+struct CompiledRule {
+    rule: PromptRule,
+    regex: Regex,
+}
+
+fn compile_rules(rules: &[PromptRule]) -> (Vec<CompiledRule>, Vec<String>) {
+    let mut compiled = Vec::new();
+    let mut errors = Vec::new();
+    for rule in rules {
+        match Regex::new(&rule.pattern) {
+            Ok(regex) => compiled.push(CompiledRule {
+                rule: rule.clone(),
+                regex,
+            }),
+            Err(e) => errors.push(format!("{}: {}", rule.id, e)),
+        }
+    }
+    (compiled, errors)
+}
+
+/// Text near the cursor on the emulated screen, where agents draw prompts.
+fn screen_window(state: &AppState, worker_id: &str) -> Option<String> {
+    let screens = state.worker_screens.lock().unwrap();
+    let snapshot = screens.get(worker_id)?.snapshot(false);
+    let end = (snapshot.cursor_row + 1).min(snapshot.lines.len());
+    // Prompts are often drawn in a box below the cursor line too; include a few rows.
+    let end = (end + 3).min(snapshot.lines.len());
+    let start = end.saturating_sub(SCREEN_WINDOW_LINES);
+    let text = snapshot.lines[start..end].join("\n");
+    Some(text.trim().to_string())
+}
+
+fn output_window(state: &AppState, worker_id: &str) -> String {
+    let logs = state.worker_logs.lock().unwrap();
+    logs.get(worker_id)
+        .map(|entries| {
+            let start = entries.len().saturating_sub(OUTPUT_WINDOW_LINES);
+            entries[start..]
+                .iter()
+                .map(|e| e.line.as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+fn detect<'a>(state: &AppState, worker: &Worker, rules: &'a [CompiledRule]) -> Option<(&'a PromptRule, String)> {
+    // Workers without a screen (or one that has drawn nothing yet) are
+    // matched on their output instead.
+    let screen = screen_window(state, &worker.id).filter(|s| !s.is_empty());
+    let output = output_window(state, &worker.id);
+    rules
+        .iter()
+        .filter(|c| c.rule.applies_to(&worker.agent_type))
+        .find_map(|c| {
+            let text = match c.rule.scope {
+                PromptScope::Screen => screen.as_deref().unwrap_or(&output),
+                PromptScope::Output => &output,
+            };
+            c.regex.find(text).map(|m| {
+                let matched: String = m.as_str().trim().chars().take(MAX_MATCH_CHARS).collect();
+                (&c.rule, matched)
+            })
+        })
+}
+
+fn log_system(state: &AppState, app: &AppHandle, worker_id: &str, line: String) {
+    let entry = LogEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        stream: "system".to_string(),
+        line,
+    };
+    crate::commands::workers::emit_worker_log(state, app, worker_id, entry);
+}
+
+fn worker_task_id(state: &AppState, worker_id: &str) -> Option<String> {
+    let run_task = {
+        let runs = state.runs.lock().unwrap();
+        runs.iter().rev().find(|r| r.worker_id == worker_id).map(|r| r.task_id.clone())
+    };
+    run_task.or_else(|| {
+        let tasks = state.tasks.lock().unwrap();
+        tasks
+            .iter()
+            .find(|t| t.assigned_worker_id.as_deref() == Some(worker_id))
+            .map(|t| t.id.clone())
+    })
+}
+
+fn auto_answer(state: &AppState, app: &AppHandle, worker: &Worker, rule: &PromptRule, matched: &str) {
+    let response = rule.response.clone().unwrap_or_else(|| "\r".to_string());
+    let sent = {
+        let mut writers = state.worker_writers.lock().unwrap();
+        writers
+            .get_mut(&worker.id)
+            .map(|w| w.write_all(response.as_bytes()).and_then(|_| w.flush()).is_ok())
+            .unwrap_or(false)
+    };
+    log_system(
+        state,
+        app,
+        &worker.id,
+        if sent {
+            format!("[prompt] Auto-answered \"{}\"", rule.name)
+        } else {
+            format!("[prompt] Could not auto-answer \"{}\": no writable terminal", rule.name)
+        },
+    );
+    state.append_audit_event(&AuditEvent::new(
+        worker.rig_id.clone(),
+        worker.actor_id.clone(),
+        worker_task_id(state, &worker.id),
+        AuditEventType::PromptAutoAnswered,
+        serde_json::json!({
+            "worker_id": worker.id,
+            "rule_id": rule.id,
+            "matched_text": matched,
+            "response": response,
+            "sent": sent,
+        })
+        .to_string(),
+    ));
+}
+
+fn escalate(state: &AppState, app: &AppHandle, worker: &Worker, rule: &PromptRule, matched: &str) {
+    let prompt = PromptMatch {
+        rule_id: rule.id.clone(),
+        rule_name: rule.name.clone(),
+        action: rule.action.clone(),
+        matched_text: matched.to_string(),
+        detected_at: chrono::Utc::now().to_rfc3339(),
+    };
+    {
+        let mut workers = state.workers.lock().unwrap();
+        if let Some(w) = workers.iter_mut().find(|w| w.id == worker.id) {
+            w.waiting_input = Some(prompt.clone());
+        }
+        state.save_workers(&workers);
+    }
+
+    let task_id = worker_task_id(state, &worker.id);
+    if let Some(task_id) = &task_id {
+        let mut tasks = state.tasks.lock().unwrap();
+        if let Some(task) = tasks
+            .iter_mut()
+            .find(|t| t.id == *task_id && t.status == TaskStatus::InProgress)
+        {
+            task.status = TaskStatus::Escalated;
+            task.blocked_reason = Some(format!("{}{} ({})", WAITING_REASON_PREFIX, rule.name, matched));
+            task.updated_at = chrono::Utc::now().to_rfc3339();
+            state.append_audit_event(&AuditEvent::new(
+                task.rig_id.clone(),
+                task.owner_actor_id.clone(),
+                Some(task.id.clone()),
+                AuditEventType::TaskStatusChanged,
+                serde_json::json!({
+                    "old_status": "in_progress",
+                    "new_status": "escalated",
+                    "reason": task.blocked_reason,
+                    "worker_id": worker.id,
+                })
+                .to_string(),
+            ));
+            state.save_tasks(&tasks);
+        }
+    }
+
+    log_system(state, app, &worker.id, format!("[prompt] Waiting for input: \"{}\"", rule.name));
+    state.append_audit_event(&AuditEvent::new(
+        worker.rig_id.clone(),
+        worker.actor_id.clone(),
+        task_id,
+        AuditEventType::PromptEscalated,
+        serde_json::json!({
+            "worker_id": worker.id,
+            "rule_id": rule.id,
+            "matched_text": matched,
+        })
+        .to_string(),
+    ));
+    let _ = app.emit("worker-waiting-input", (&worker.id, &prompt));
+    let _ = app.emit("data-changed", "");
+}
+
+/// The prompt is gone (answered by a human or the agent moved on): clear the
+/// waiting flag and lift the task escalation it caused.
+fn resolve(state: &AppState, app: &AppHandle, worker: &Worker) {
+    let Some(prompt) = worker.waiting_input.clone() else {
+        return;
+    };
+    {
+        let mut workers = state.workers.lock().unwrap();
+        if let Some(w) = workers.iter_mut().find(|w| w.id == worker.id) {
+            w.waiting_input = None;
+        }
+        state.save_workers(&workers);
+    }
+
+    let task_id = worker_task_id(state, &worker.id);
+    if let Some(task_id) = &task_id {
+        let mut tasks = state.tasks.lock().unwrap();
+        if let Some(task) = tasks.iter_mut().find(|t| {
+            t.id == *task_id
+                && t.status == TaskStatus::Escalated
+                && t.blocked_reason
+                    .as_deref()
+                    .map(|r| r.starts_with(WAITING_REASON_PREFIX))
+                    .unwrap_or(false)
+        }) {
+            task.status = TaskStatus::InProgress;
+            task.blocked_reason = None;
+            task.updated_at = chrono::Utc::now().to_rfc3339();
+            state.save_tasks(&tasks);
+        }
+    }
+
+    log_system(state, app, &worker.id, format!("[prompt] Resolved \"{}\"", prompt.rule_name));
+    state.append_audit_event(&AuditEvent::new(
+        worker.rig_id.clone(),
+        worker.actor_id.clone(),
+        task_id,
+        AuditEventType::PromptResolved,
+        serde_json::json!({
+            "worker_id": worker.id,
+            "rule_id": prompt.rule_id,
+            "detected_at": prompt.detected_at,
+        })
+        .to_string(),
+    ));
+    let _ = app.emit("data-changed", "");
+}
+
+/// Background watcher: once a running worker's output settles, match its
+/// screen/output against the prompt rules and auto-answer or escalate.
+pub(crate) fn start_prompt_watch(app: AppHandle) {
+    thread::Builder::new()
+        .name("prompt-watch".to_string())
+        .spawn(move || {
+            let mut rules_source: Option<Vec<PromptRule>> = None;
+            let mut rules: Vec<CompiledRule> = Vec::new();
+            // Output instant already evaluated, per worker.
+            let mut evaluated: HashMap<String, Instant> = HashMap::new();
+            // Last prompt acted on, per worker, so a visible prompt is handled once.
+            let mut handled: HashMap<String, (String, String)> = HashMap::new();
+
+            loop {
+                thread::sleep(Duration::from_millis(500));
+                let state = app.state::<AppState>();
+
+                let (configured, settle) = {
+                    let settings = state.settings.lock().unwrap();
+                    (settings.prompt_rules.clone(), settings.prompt_settle_ms)
+                };
+                if rules_source.as_ref() != Some(&configured) {
+                    let (compiled, errors) = compile_rules(&configured);
+                    for e in errors {
+                        eprintln!("[prompt] invalid rule {}", e);
+                    }
+                    rules = compiled;
+                    rules_source = Some(configured);
+                }
+
+                let running: Vec<Worker> = {
+                    let workers = state.workers.lock().unwrap();
+                    workers
+                        .iter()
+                        .filter(|w| w.status == WorkerStatusEnum::Running)
+                        .cloned()
+                        .collect()
+                };
+                let last_output = state.worker_last_output.lock().unwrap().clone();
+                evaluated.retain(|id, _| running.iter().any(|w| w.id == *id));
+                handled.retain(|id, _| running.iter().any(|w| w.id == *id));
+
+                for worker in &running {
+                    let Some(at) = last_output.get(&worker.id) else {
+                        continue;
+                    };
+                    if at.elapsed() < Duration::from_millis(settle) || evaluated.get(&worker.id) == Some(at) {
+                        continue;
+                    }
+                    evaluated.insert(worker.id.clone(), *at);
+
+                    match detect(&state, worker, &rules) {
+                        None => {
+                            handled.remove(&worker.id);
+                            resolve(&state, &app, worker);
+                        }
+                        Some((rule, matched)) => {
+                            let key = (rule.id.clone(), matched.clone());
+                            if handled.get(&worker.id) == Some(&key) {
+                                continue;
+                            }
+                            handled.insert(worker.id.clone(), key);
+                            match rule.action {
+                                PromptAction::AutoAnswer => auto_answer(&state, &app, worker, rule, &matched),
+                                PromptAction::Escalate => escalate(&state, &app, worker, rule, &matched),
+                            }
+                        }
+                    }
+                }
+            }
+        })
+        .ok();
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptRuleTestResult {
+    pub rule_id: String,
+    pub matched_text: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Try every configured rule (or `rules`, if given) against sample text.
+#[tauri::command]
+pub fn test_prompt_rules(
+    text: String,
+    agent_type: Option<String>,
+    rules: Option<Vec<PromptRule>>,
+    state: State<AppState>,
+) -> Vec<PromptRuleTestResult> {
+    let rules = rules.unwrap_or_else(|| state.settings.lock().unwrap().prompt_rules.clone());
+    rules
+        .iter()
+        .filter(|r| agent_type.as_deref().map(|a| r.applies_to(a)).unwrap_or(r.enabled))
+        .map(|r| match Regex::new(&r.pattern) {
+            Ok(re) => PromptRuleTestResult {
+                rule_id: r.id.clone(),
+                matched_text: re.find(&text).map(|m| m.as_str().to_string()),
+                error: None,
+            },
+            Err(e) => PromptRuleTestResult {
+                rule_id: r.id.clone(),
+                matched_text: None,
+                error: Some(e.to_string()),
+            },
+        })
+        .collect()
+}
@@ -0,0 +1,121 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::state::AppState;
+
+/// Default chunk size for streaming a recording to the UI.
+const DEFAULT_CHUNK_BYTES: u64 = 256 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingInfo {
+    pub worker_id: String,
+    pub path: String,
+    pub size_bytes: u64,
+    pub modified_at: Option<String>,
+    /// Still being written (the worker's PTY is live).
+    pub active: bool,
+}
+
+/// A slice of an asciicast file, always ending on a complete event line.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingChunk {
+    pub data: String,
+    pub next_offset: u64,
+    pub total_bytes: u64,
+    pub active: bool,
+}
+
+fn recording_info(state: &AppState, worker_id: &str) -> Option<RecordingInfo> {
+    let path = state.recording_path(worker_id);
+    let meta = fs::metadata(&path).ok()?;
+    let modified_at = meta
+        .modified()
+        .ok()
+        .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339());
+    Some(RecordingInfo {
+        worker_id: worker_id.to_string(),
+        path: path.to_string_lossy().to_string(),
+        size_bytes: meta.len(),
+        modified_at,
+        active: state.worker_casts.lock().unwrap().contains_key(worker_id),
+    })
+}
+
+#[tauri::command]
+pub fn list_recordings(state: State<AppState>) -> Vec<RecordingInfo> {
+    state.flush_recordings();
+    let Ok(entries) = fs::read_dir(state.recordings_dir()) else {
+        return Vec::new();
+    };
+    let mut recordings: Vec<RecordingInfo> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let worker_id = name.strip_suffix(".cast")?.to_string();
+            recording_info(&state, &worker_id)
+        })
+        .collect();
+    recordings.sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
+    recordings
+}
+
+/// Read a worker's recording from `offset` (bytes). Poll with `next_offset`
+/// while `active` to follow a live session.
+#[tauri::command]
+pub fn read_worker_recording(
+    id: String,
+    offset: Option<u64>,
+    max_bytes: Option<u64>,
+    state: State<AppState>,
+) -> Result<RecordingChunk, String> {
+    state.flush_recordings();
+    let info = recording_info(&state, &id).ok_or_else(|| "No recording for this worker".to_string())?;
+    let offset = offset.unwrap_or(0).min(info.size_bytes);
+    let want = max_bytes.unwrap_or(DEFAULT_CHUNK_BYTES).min(info.size_bytes - offset);
+
+    let mut file = fs::File::open(&info.path).map_err(|e| format!("Failed to open recording: {}", e))?;
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| format!("Failed to seek recording: {}", e))?;
+    let mut buf = vec![0u8; want as usize];
+    file.read_exact(&mut buf)
+        .map_err(|e| format!("Failed to read recording: {}", e))?;
+
+    // Only hand out whole event lines; a partial tail is returned on the next call.
+    let mut end = buf.iter().rposition(|b| *b == b'\n').map(|i| i + 1).unwrap_or(0);
+    buf.truncate(end);
+    if end == 0 && want > 0 {
+        // A single line longer than `max_bytes`: return it whole so the reader
+        // still makes progress.
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|e| format!("Failed to seek recording: {}", e))?;
+        BufReader::new(file)
+            .read_until(b'\n', &mut buf)
+            .map_err(|e| format!("Failed to read recording: {}", e))?;
+        if buf.ends_with(b"\n") {
+            end = buf.len();
+        } else {
+            buf.clear();
+        }
+    }
+    Ok(RecordingChunk {
+        data: String::from_utf8_lossy(&buf).to_string(),
+        next_offset: offset + end as u64,
+        total_bytes: info.size_bytes,
+        active: info.active,
+    })
+}
+
+/// Copy a worker's `.cast` file to `dest_path` (playable with `asciinema play`).
+#[tauri::command]
+pub fn export_worker_recording(id: String, dest_path: String, state: State<AppState>) -> Result<String, String> {
+    state.flush_recordings();
+    let source = state.recording_path(&id);
+    if !source.exists() {
+        return Err("No recording for this worker".to_string());
+    }
+    fs::copy(&source, &dest_path).map_err(|e| format!("Failed to export recording: {}", e))?;
+    Ok(dest_path)
+}
@@ -2,8 +2,10 @@ use serde::Serialize;
 use tauri::{AppHandle, Emitter, State};
 
 use crate::git;
+use crate::lifecycle;
 use crate::models::audit::{AuditEvent, AuditEventType};
 use crate::models::crew::CrewStatus;
+use crate::models::lifecycle::LifecycleEvent;
 use crate::state::AppState;
 
 #[derive(Debug, Clone, Serialize)]
@@ -62,6 +64,46 @@ fn list_active_crews(state: &AppState, rig_id: &str) -> Vec<crate::models::crew:
         .collect()
 }
 
+/// post_merge hooks for a crew branch just merged into the rig checkout; they
+/// run in place, before the next merge, and log into the crew's latest worker.
+fn run_post_merge_hooks(
+    state: &AppState,
+    app: Option<&AppHandle>,
+    crew: &crate::models::crew::Crew,
+    rig_path: &str,
+    base_branch: &str,
+) {
+    if lifecycle::hooks_for(state, &crew.rig_id, LifecycleEvent::PostMerge).is_empty() {
+        return;
+    }
+    let latest_worker = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .filter(|w| w.crew_id == crew.id)
+            .max_by(|a, b| a.started_at.cmp(&b.started_at))
+            .map(|w| w.id.clone())
+    };
+    let ctx = latest_worker
+        .and_then(|w| lifecycle::HookContext::for_worker(state, &w))
+        .or_else(|| lifecycle::HookContext::for_crew(state, &crew.id));
+    let Some(mut ctx) = ctx else {
+        return;
+    };
+    ctx = ctx
+        .set("TOWNUI_MERGED_BRANCH", crew.branch.clone())
+        .set("TOWNUI_MERGE_TARGET", base_branch);
+    ctx.cwd = rig_path.to_string();
+    match app {
+        Some(app) => {
+            lifecycle::run_hooks_logged(state, app, LifecycleEvent::PostMerge, &ctx);
+        }
+        None => {
+            lifecycle::run_hooks(state, LifecycleEvent::PostMerge, &ctx, |_| {});
+        }
+    }
+}
+
 #[tauri::command]
 pub fn get_refinery_queue(rig_id: String, state: State<AppState>) -> Result<Vec<RefineryQueueItem>, String> {
     let rig_path = resolve_rig_path(&state, &rig_id)?;
@@ -190,6 +232,7 @@ pub(crate) fn sync_rig_inner(
         match git::merge_branch_no_edit(&rig_path, &crew.branch) {
             Ok(_) => {
                 merged_branches.push(crew.branch.clone());
+                run_post_merge_hooks(state, app, &crew, &rig_path, &base_branch);
             }
             Err(e) => {
                 git::abort_merge(&rig_path);
@@ -0,0 +1,173 @@
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::workers::{create_polecat_crew, launch_run, remove_polecat_crew};
+use crate::models::audit::{AuditEvent, AuditEventType};
+use crate::models::crew::CrewStatus;
+use crate::models::worker::{ReplayOverrides, Run, WorkerType};
+use crate::state::AppState;
+
+fn find_run(state: &AppState, run_id: &str) -> Result<Run, String> {
+    let runs = state.runs.lock().unwrap();
+    runs.iter()
+        .find(|r| r.id == run_id)
+        .cloned()
+        .ok_or_else(|| "Run not found".to_string())
+}
+
+/// Start a fresh run of `run_id`'s task from the same base commit. The
+/// original prompt is reused verbatim unless a template override re-renders
+/// it. The replay is linked to the original (the root of a replay chain) and
+/// never moves the task.
+#[tauri::command]
+pub async fn replay_run(
+    run_id: String,
+    overrides: Option<ReplayOverrides>,
+    app: AppHandle,
+) -> Result<Run, String> {
+    tokio::task::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let overrides = overrides.unwrap_or_default();
+        let original = find_run(&state, &run_id)?;
+        let root_id = original.replay_of.clone().unwrap_or_else(|| original.id.clone());
+
+        if crate::commands::budgets::rig_spawning_paused(&state, &original.rig_id) {
+            return Err("Spawning is paused for this rig (budget exceeded)".to_string());
+        }
+
+        let agent_type = overrides
+            .agent_type
+            .clone()
+            .filter(|a| !a.trim().is_empty())
+            .unwrap_or_else(|| original.agent_type.clone());
+        // The original model only carries over to the same agent.
+        let model_tag = overrides
+            .model_tag
+            .clone()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .or_else(|| original.model_tag.clone().filter(|_| agent_type == original.agent_type));
+        if let Some(ref model) = model_tag {
+            crate::commands::workers::model_args(&agent_type, model)?;
+        }
+        let base = original
+            .base_commit
+            .clone()
+            .ok_or_else(|| "The run's base commit was not recorded, so it cannot be replayed".to_string())?;
+        let template_override = overrides.template_name.clone().filter(|t| !t.trim().is_empty());
+
+        // Where to run: a polecat off the base commit, or an existing crew still sitting on it.
+        let (crew, is_polecat) = if overrides.new_polecat {
+            (create_polecat_crew(&state, &original.rig_id, Some(&base))?, true)
+        } else {
+            let crew_id = overrides.crew_id.clone().unwrap_or_else(|| original.crew_id.clone());
+            let crew = {
+                let crews = state.crews.lock().unwrap();
+                crews
+                    .iter()
+                    .find(|c| c.id == crew_id && c.status == CrewStatus::Active)
+                    .cloned()
+                    .ok_or_else(|| "Crew not found; replay on a new polecat instead".to_string())?
+            };
+            if crate::git::has_uncommitted_changes(&crew.path).unwrap_or(false) {
+                return Err("Crew has uncommitted changes; replay on a new polecat instead".to_string());
+            }
+            if crate::git::head_commit(&crew.path).as_deref() != Some(base.as_str()) {
+                return Err("Crew has moved past the run's base commit; replay on a new polecat instead".to_string());
+            }
+            (crew, false)
+        };
+
+        let (template_name, prompt) = match template_override {
+            Some(template_name) => {
+                let template = crate::commands::templates::resolve_template(
+                    &state,
+                    &template_name,
+                    Some(&original.rig_id),
+                    Some(&crew.id),
+                );
+                let rendered = template.and_then(|(template, fragments)| {
+                    let task_exists = state.tasks.lock().unwrap().iter().any(|t| t.id == original.task_id);
+                    if !task_exists {
+                        return Err("Task or rig of the original run no longer exists".to_string());
+                    }
+                    let ctx = crate::prompt_context::build_prompt_context(
+                        &state,
+                        &crate::prompt_context::ContextRequest {
+                            rig_id: &original.rig_id,
+                            task_id: Some(&original.task_id),
+                            crew_id: Some(&crew.id),
+                            agent_cli: &agent_type,
+                            previous_run_id: original.previous_run_id.as_deref(),
+                            ..Default::default()
+                        },
+                    );
+                    if ctx.rig.id.is_empty() {
+                        return Err("Task or rig of the original run no longer exists".to_string());
+                    }
+                    crate::templates::render_task_template(&template, &fragments, &ctx)
+                        .map(|rendered| (template.name.clone(), rendered))
+                });
+                match rendered {
+                    Ok(rendered) => rendered,
+                    Err(e) => {
+                        if is_polecat {
+                            remove_polecat_crew(&state, &crew.id);
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            None => (original.template_name.clone(), original.rendered_prompt.clone()),
+        };
+
+        let worker_type = if is_polecat { WorkerType::Polecat } else { WorkerType::Crew };
+        let mut run = Run::new(
+            original.task_id.clone(),
+            String::new(),
+            crew.id.clone(),
+            original.rig_id.clone(),
+            agent_type,
+            template_name,
+            prompt,
+        );
+        run.model_tag = model_tag;
+        run.replay_of = Some(root_id.clone());
+        let run = launch_run(&app, run, worker_type)?;
+
+        state.append_audit_event(&AuditEvent::new(
+            run.rig_id.clone(),
+            None,
+            Some(run.task_id.clone()),
+            AuditEventType::RunReplayed,
+            serde_json::json!({
+                "run_id": run.id,
+                "replay_of": root_id,
+                "source_run_id": original.id,
+                "agent_type": run.agent_type,
+                "model_tag": run.model_tag,
+                "template_name": run.template_name,
+                "crew_id": crew.id,
+                "polecat": is_polecat,
+                "base_commit": run.base_commit.as_ref().or(original.base_commit.as_ref()),
+            })
+            .to_string(),
+        ));
+        let _ = app.emit("data-changed", "");
+        Ok(run)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// The original run followed by all of its replays, oldest first.
+#[tauri::command]
+pub fn list_run_replays(run_id: String, state: State<AppState>) -> Result<Vec<Run>, String> {
+    let run = find_run(&state, &run_id)?;
+    let root_id = run.replay_of.unwrap_or(run.id);
+    let runs = state.runs.lock().unwrap();
+    Ok(runs
+        .iter()
+        .filter(|r| r.id == root_id || r.replay_of.as_deref() == Some(root_id.as_str()))
+        .cloned()
+        .collect())
+}
@@ -0,0 +1,386 @@
+use std::thread;
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::workers::{execute_task_inner, RunFollowUp};
+use crate::models::audit::{AuditEvent, AuditEventType};
+use crate::models::crew::CrewStatus;
+use crate::models::retry::RetryPolicy;
+use crate::models::worker::{LogEntry, Run, RunStatus, WorkerStatusEnum};
+use crate::state::AppState;
+
+/// Log lines from the failed attempt quoted in the retry prompt.
+const FAILURE_CONTEXT_LINES: usize = 30;
+
+/// Why a retry could not start.
+#[derive(Debug)]
+enum RetryError {
+    /// The crew still has a running worker; the scheduler tries again later.
+    CrewBusy,
+    Failed(String),
+}
+
+impl From<String> for RetryError {
+    fn from(e: String) -> Self {
+        RetryError::Failed(e)
+    }
+}
+
+impl std::fmt::Display for RetryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RetryError::CrewBusy => write!(f, "Crew is busy"),
+            RetryError::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Effective policy for a task's runs: task → rig → town.
+pub(crate) fn resolve_retry_policy(state: &AppState, task_id: &str, rig_id: &str) -> RetryPolicy {
+    let task_policy = {
+        let tasks = state.tasks.lock().unwrap();
+        tasks.iter().find(|t| t.id == task_id).and_then(|t| t.retry_policy.clone())
+    };
+    if let Some(policy) = task_policy {
+        return policy;
+    }
+    let rig_policy = {
+        let rigs = state.rigs.lock().unwrap();
+        rigs.iter().find(|r| r.id == rig_id).and_then(|r| r.settings.retry_policy.clone())
+    };
+    rig_policy.unwrap_or_else(|| state.settings.lock().unwrap().retry_policy.clone())
+}
+
+fn log_retry(state: &AppState, app: &AppHandle, worker_id: &str, line: String) {
+    let entry = LogEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        stream: "system".to_string(),
+        line,
+    };
+    crate::commands::workers::emit_worker_log(state, app, worker_id, entry);
+}
+
+/// Called when a run has just failed: schedule the next attempt per the
+/// resolved policy, or record that the retry budget is used up.
+pub(crate) fn schedule_retry(state: &AppState, app: &AppHandle, worker_id: &str) {
+    let Some(run) = ({
+        let runs = state.runs.lock().unwrap();
+        runs.iter().rev().find(|r| r.worker_id == worker_id).cloned()
+    }) else {
+        return;
+    };
+    if run.status != RunStatus::Failed {
+        return;
+    }
+
+    let policy = resolve_retry_policy(state, &run.task_id, &run.rig_id);
+    if policy.max_attempts <= 1 {
+        return;
+    }
+    if run.attempt >= policy.max_attempts {
+        log_retry(
+            state,
+            app,
+            worker_id,
+            format!("[retry] Attempt {}/{} failed; no retries left", run.attempt, policy.max_attempts),
+        );
+        state.append_audit_event(&AuditEvent::new(
+            run.rig_id.clone(),
+            None,
+            Some(run.task_id.clone()),
+            AuditEventType::RunRetryExhausted,
+            serde_json::json!({
+                "run_id": run.id,
+                "attempt": run.attempt,
+                "max_attempts": policy.max_attempts,
+            })
+            .to_string(),
+        ));
+        return;
+    }
+
+    let delay = policy.delay_after(run.attempt);
+    let next_agent = policy.agent_for_attempt(run.attempt + 1, &run.agent_type);
+    let retry_at = (chrono::Utc::now() + chrono::Duration::seconds(delay as i64)).to_rfc3339();
+    {
+        let mut runs = state.runs.lock().unwrap();
+        if let Some(r) = runs.iter_mut().find(|r| r.id == run.id) {
+            r.retry_at = Some(retry_at.clone());
+        }
+        state.save_runs(&runs);
+    }
+    log_retry(
+        state,
+        app,
+        worker_id,
+        format!(
+            "[retry] Attempt {}/{} failed; retrying with {} in {}s",
+            run.attempt, policy.max_attempts, next_agent, delay
+        ),
+    );
+    state.append_audit_event(&AuditEvent::new(
+        run.rig_id.clone(),
+        None,
+        Some(run.task_id.clone()),
+        AuditEventType::RunRetryScheduled,
+        serde_json::json!({
+            "run_id": run.id,
+            "attempt": run.attempt,
+            "next_attempt": run.attempt + 1,
+            "next_agent": next_agent,
+            "retry_at": retry_at,
+        })
+        .to_string(),
+    ));
+}
+
+/// Prompt section describing why the previous attempt failed: the failing
+/// verification checks when there are any, else the tail of its output.
+pub(crate) fn failure_context(state: &AppState, run: &Run) -> String {
+    let failed_checks: Vec<_> = run.checks.iter().filter(|c| !c.passed).collect();
+    if !failed_checks.is_empty() {
+        let sections: Vec<String> = failed_checks
+            .iter()
+            .map(|c| {
+                let status = if c.timed_out {
+                    "timed out".to_string()
+                } else {
+                    format!("exit {}", c.exit_code.map(|e| e.to_string()).unwrap_or_else(|| "?".to_string()))
+                };
+                format!("### {} (`{}`): {}\n\n```\n{}\n```", c.name, c.command, status, c.output_tail)
+            })
+            .collect();
+        return format!(
+            "## Verification failed\n\n\
+             Attempt {} ({}) finished, but these checks failed in the worktree:\n\n{}\n\n\
+             Fix the failures above; the task is only done once every required check passes.",
+            run.attempt,
+            run.agent_type,
+            sections.join("\n\n")
+        );
+    }
+
+    let lines: Vec<String> = state
+        .load_log(&run.worker_id)
+        .into_iter()
+        .filter(|e| !e.line.trim().is_empty())
+        .map(|e| e.line)
+        .collect();
+    let tail = &lines[lines.len().saturating_sub(FAILURE_CONTEXT_LINES)..];
+    let exit = match run.exit_code {
+        Some(code) => format!("exited with code {}", code),
+        None => "terminated without an exit code".to_string(),
+    };
+    format!(
+        "## Previous attempt failed\n\n\
+         Attempt {} ({}) {}. Last output before the failure:\n\n\
+         ```\n{}\n```\n\n\
+         Continue from the current state of the branch, find the cause and avoid repeating the same failure.",
+        run.attempt,
+        run.agent_type,
+        exit,
+        tail.join("\n")
+    )
+}
+
+/// Follow-up for a task requeued after failed verification: links the next
+/// run to the failed one and carries the failing checks as feedback.
+pub(crate) fn verification_feedback(state: &AppState, task_id: &str) -> Option<RunFollowUp> {
+    let runs = state.runs.lock().unwrap();
+    let last = runs.iter().rev().find(|r| r.task_id == task_id)?;
+    let failed_verification = last.status == RunStatus::Failed && last.checks.iter().any(|c| !c.passed && c.required);
+    if !failed_verification || last.retry_at.is_some() {
+        return None;
+    }
+    let last = last.clone();
+    drop(runs);
+    Some(RunFollowUp {
+        previous_run_id: last.id.clone(),
+        attempt: last.attempt + 1,
+        revision_count: last.revision_count,
+        context: failure_context(state, &last),
+    })
+}
+
+/// Start the next attempt for `previous`. The crew must still exist and be idle.
+fn launch_retry(state: &AppState, app: &AppHandle, previous: &Run, agent_type: String) -> Result<Run, RetryError> {
+    {
+        let crews = state.crews.lock().unwrap();
+        let crew = crews
+            .iter()
+            .find(|c| c.id == previous.crew_id)
+            .ok_or_else(|| "Crew of the failed run no longer exists".to_string())?;
+        if crew.status == CrewStatus::Removed {
+            return Err(RetryError::Failed("Crew of the failed run was removed".to_string()));
+        }
+    }
+    let crew_busy = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .any(|w| w.crew_id == previous.crew_id && w.status == WorkerStatusEnum::Running)
+    };
+    if crew_busy {
+        return Err(RetryError::CrewBusy);
+    }
+
+    let follow_up = RunFollowUp {
+        previous_run_id: previous.id.clone(),
+        attempt: previous.attempt + 1,
+        revision_count: previous.revision_count,
+        context: failure_context(state, previous),
+    };
+    {
+        let mut runs = state.runs.lock().unwrap();
+        if let Some(r) = runs.iter_mut().find(|r| r.id == previous.id) {
+            r.retry_at = None;
+        }
+        state.save_runs(&runs);
+    }
+    let run = execute_task_inner(
+        app,
+        previous.task_id.clone(),
+        previous.crew_id.clone(),
+        agent_type,
+        previous.template_name.clone(),
+        Some(follow_up),
+    )?;
+
+    state.append_audit_event(&AuditEvent::new(
+        run.rig_id.clone(),
+        None,
+        Some(run.task_id.clone()),
+        AuditEventType::RunRetried,
+        serde_json::json!({
+            "run_id": run.id,
+            "previous_run_id": previous.id,
+            "attempt": run.attempt,
+            "agent_type": run.agent_type,
+        })
+        .to_string(),
+    ));
+    let _ = app.emit("data-changed", "");
+    Ok(run)
+}
+
+/// Background loop that starts retries once their backoff has elapsed. Due
+/// times live on the runs, so pending retries survive an app restart.
+pub(crate) fn start_retry_scheduler(app: AppHandle) {
+    let _ = thread::Builder::new()
+        .name("retry-scheduler".to_string())
+        .spawn(move || loop {
+            thread::sleep(Duration::from_secs(2));
+            let state = app.state::<AppState>();
+            let now = chrono::Utc::now();
+            let due: Vec<Run> = {
+                let runs = state.runs.lock().unwrap();
+                runs.iter()
+                    .filter(|r| r.status == RunStatus::Failed)
+                    .filter(|r| {
+                        r.retry_at
+                            .as_deref()
+                            .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+                            .map(|at| at <= now)
+                            .unwrap_or(false)
+                    })
+                    .cloned()
+                    .collect()
+            };
+            for run in due {
+                let policy = resolve_retry_policy(&state, &run.task_id, &run.rig_id);
+                let agent = policy.agent_for_attempt(run.attempt + 1, &run.agent_type);
+                match launch_retry(&state, &app, &run, agent) {
+                    Ok(next) => eprintln!("[retry] run {} → attempt {} ({})", run.id, next.attempt, next.id),
+                    // A busy crew is retried on the next tick.
+                    Err(RetryError::CrewBusy) => {}
+                    Err(RetryError::Failed(e)) => {
+                        eprintln!("[retry] giving up on run {}: {e}", run.id);
+                        let mut runs = state.runs.lock().unwrap();
+                        if let Some(r) = runs.iter_mut().find(|r| r.id == run.id) {
+                            r.retry_at = None;
+                        }
+                        state.save_runs(&runs);
+                        drop(runs);
+                        state.append_audit_event(&AuditEvent::new(
+                            run.rig_id.clone(),
+                            None,
+                            Some(run.task_id.clone()),
+                            AuditEventType::RunRetryExhausted,
+                            serde_json::json!({ "run_id": run.id, "attempt": run.attempt, "error": e })
+                                .to_string(),
+                        ));
+                    }
+                }
+            }
+        });
+}
+
+/// Retry a failed or cancelled run now, regardless of the policy's attempt limit.
+#[tauri::command]
+pub async fn retry_run(
+    run_id: String,
+    agent_type: Option<String>,
+    app: AppHandle,
+) -> Result<Run, String> {
+    tokio::task::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let run = {
+            let runs = state.runs.lock().unwrap();
+            runs.iter()
+                .find(|r| r.id == run_id)
+                .cloned()
+                .ok_or_else(|| "Run not found".to_string())?
+        };
+        if !matches!(run.status, RunStatus::Failed | RunStatus::Cancelled) {
+            return Err("Only failed or cancelled runs can be retried".to_string());
+        }
+        let agent = agent_type.filter(|a| !a.trim().is_empty()).unwrap_or_else(|| {
+            resolve_retry_policy(&state, &run.task_id, &run.rig_id).agent_for_attempt(run.attempt + 1, &run.agent_type)
+        });
+        launch_retry(&state, &app, &run, agent).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Drop a scheduled retry without starting it.
+#[tauri::command]
+pub fn cancel_retry(run_id: String, state: State<AppState>, app: AppHandle) -> Result<Run, String> {
+    let mut runs = state.runs.lock().unwrap();
+    let run = runs
+        .iter_mut()
+        .find(|r| r.id == run_id)
+        .ok_or_else(|| "Run not found".to_string())?;
+    run.retry_at = None;
+    let updated = run.clone();
+    state.save_runs(&runs);
+    drop(runs);
+    let _ = app.emit("data-changed", "");
+    Ok(updated)
+}
+
+/// Every attempt in the retry chain containing `run_id`, oldest first.
+#[tauri::command]
+pub fn get_run_attempts(run_id: String, state: State<AppState>) -> Result<Vec<Run>, String> {
+    let runs = state.runs.lock().unwrap();
+    let mut root = runs
+        .iter()
+        .find(|r| r.id == run_id)
+        .ok_or_else(|| "Run not found".to_string())?;
+    while let Some(prev) = root
+        .previous_run_id
+        .as_ref()
+        .and_then(|id| runs.iter().find(|r| &r.id == id))
+    {
+        root = prev;
+    }
+    let mut chain = vec![root.clone()];
+    while let Some(next) = runs
+        .iter()
+        .find(|r| r.previous_run_id.as_deref() == Some(chain[chain.len() - 1].id.as_str()))
+    {
+        chain.push(next.clone());
+    }
+    Ok(chain)
+}
@@ -0,0 +1,675 @@
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::workers::{execute_task_inner, RunFollowUp};
+use crate::models::audit::{AuditEvent, AuditEventType};
+use crate::models::review::{ReviewMode, ReviewPolicy, ReviewRecord, ReviewVerdict};
+use crate::models::scheduler::{QueuedSpawn, SpawnSource};
+use crate::models::task::TaskStatus;
+use crate::models::worker::{LogEntry, Run, RunStatus, WorkerType};
+use crate::scheduler::Admission;
+use crate::state::AppState;
+
+/// Diff characters handed to a reviewer; longer diffs are cut.
+const MAX_REVIEW_DIFF_CHARS: usize = 60_000;
+
+fn log_review(state: &AppState, app: &AppHandle, worker_id: &str, line: String) {
+    let entry = LogEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        stream: "system".to_string(),
+        line,
+    };
+    crate::commands::workers::emit_worker_log(state, app, worker_id, entry);
+}
+
+fn truncate_diff(diff: String) -> String {
+    if diff.len() <= MAX_REVIEW_DIFF_CHARS {
+        return diff;
+    }
+    let mut end = MAX_REVIEW_DIFF_CHARS;
+    while !diff.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}\n… diff truncated ({} bytes total)", &diff[..end], diff.len())
+}
+
+/// The change a run made: its stored patch (base commit to end), else its
+/// auto-commit when there was one, else the working tree.
+fn run_diff(state: &AppState, run_id: &str, crew_path: &str, commit: Option<&str>) -> String {
+    if let Some(patch) = state.load_run_patch(run_id) {
+        return patch;
+    }
+    let diff = match commit {
+        Some(c) => crate::git::get_commit_diff(crew_path, c),
+        None => crate::git::get_working_diff(crew_path),
+    };
+    diff.unwrap_or_default()
+}
+
+/// Rules mode: deterministic checks against the diff.
+fn rule_review(policy: &ReviewPolicy, criteria: Option<&str>, diff: &str) -> (ReviewVerdict, String) {
+    if diff.trim().is_empty() {
+        return (ReviewVerdict::Fail, "No changes were made.".to_string());
+    }
+    let mut problems = Vec::new();
+
+    let added: Vec<&str> = diff
+        .lines()
+        .filter(|l| l.starts_with('+') && !l.starts_with("+++"))
+        .collect();
+    for pattern in &policy.forbidden_patterns {
+        match regex::Regex::new(pattern) {
+            Ok(re) => {
+                if let Some(line) = added.iter().find(|l| re.is_match(l)) {
+                    problems.push(format!("Forbidden pattern `{}` added: {}", pattern, line.trim()));
+                }
+            }
+            Err(e) => problems.push(format!("Invalid forbidden pattern `{}`: {}", pattern, e)),
+        }
+    }
+
+    // Backticked terms in the criteria name things the change must touch.
+    if let Some(criteria) = criteria {
+        for term in criteria.split('`').skip(1).step_by(2) {
+            let term = term.trim();
+            if !term.is_empty() && !diff.contains(term) {
+                problems.push(format!("Acceptance criteria mention `{}` but the diff does not.", term));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        (ReviewVerdict::Pass, "All review rules passed.".to_string())
+    } else {
+        (ReviewVerdict::Fail, problems.join("\n"))
+    }
+}
+
+fn reviewer_prompt(title: &str, description: &str, criteria: Option<&str>, diff: &str) -> String {
+    format!(
+        "You are reviewing a change another coding agent made for the task below. Do not modify any files.\n\n\
+         ## Task\n{}\n\n{}\n\n\
+         ## Acceptance criteria\n{}\n\n\
+         ## Diff\n```diff\n{}\n```\n\n\
+         Check every acceptance criterion against the diff. Write your findings, then finish with a line \
+         `VERDICT: PASS` or `VERDICT: FAIL`. When failing, list the concrete changes still required after the verdict line.",
+        title,
+        description,
+        criteria.unwrap_or("(none given; judge against the task description)"),
+        diff
+    )
+}
+
+/// Read the reviewer's verdict and feedback from its output. Only a verdict
+/// alone on its line counts, and everything up to the echoed prompt (which
+/// names both verdicts) is skipped, so a reviewer that never answers is
+/// inconclusive rather than passing.
+fn parse_verdict(lines: &[String]) -> (ReviewVerdict, String) {
+    let names_both = |l: &String| {
+        let upper = l.to_ascii_uppercase();
+        upper.contains("VERDICT: PASS") && upper.contains("VERDICT: FAIL")
+    };
+    let lines = &lines[lines.iter().rposition(names_both).map(|i| i + 1).unwrap_or(0)..];
+    let re = regex::Regex::new(r"(?i)^\W*VERDICT:\s*\**\s*(PASS|FAIL)\**\W*$").unwrap();
+    let Some((index, verdict)) = lines.iter().enumerate().rev().find_map(|(i, l)| {
+        re.captures(l).map(|c| (i, c[1].to_ascii_uppercase()))
+    }) else {
+        let start = lines.len().saturating_sub(20);
+        return (ReviewVerdict::Inconclusive, lines[start..].join("\n"));
+    };
+    let after: Vec<&str> = lines[index + 1..]
+        .iter()
+        .map(|l| l.as_str())
+        .filter(|l| !l.trim().is_empty())
+        .collect();
+    let feedback = if after.is_empty() {
+        let start = index.saturating_sub(20);
+        lines[start..index].join("\n")
+    } else {
+        after.join("\n")
+    };
+    let verdict = if verdict == "PASS" { ReviewVerdict::Pass } else { ReviewVerdict::Fail };
+    (verdict, feedback)
+}
+
+/// Start reviewing a completed run if its rig has a review policy. Returns
+/// true when a review was started, in which case the review (not the run
+/// exit) decides whether the task is done.
+pub(crate) fn start_review(state: &AppState, app: &AppHandle, run_id: &str, commit: Option<&str>) -> bool {
+    let Some(run) = ({
+        let runs = state.runs.lock().unwrap();
+        runs.iter().find(|r| r.id == run_id).cloned()
+    }) else {
+        return false;
+    };
+    let policy = {
+        let rigs = state.rigs.lock().unwrap();
+        rigs.iter().find(|r| r.id == run.rig_id).and_then(|r| r.settings.review.clone())
+    };
+    let Some(policy) = policy else {
+        return false;
+    };
+    let Some(task) = ({
+        let tasks = state.tasks.lock().unwrap();
+        tasks.iter().find(|t| t.id == run.task_id).cloned()
+    }) else {
+        return false;
+    };
+    let Some(crew_path) = ({
+        let crews = state.crews.lock().unwrap();
+        crews.iter().find(|c| c.id == run.crew_id).map(|c| c.path.clone())
+    }) else {
+        return false;
+    };
+
+    let diff = run_diff(state, &run.id, &crew_path, commit);
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let record = match policy.mode {
+        ReviewMode::Rules => {
+            let (verdict, feedback) = rule_review(&policy, task.acceptance_criteria.as_deref(), &diff);
+            ReviewRecord {
+                mode: ReviewMode::Rules,
+                verdict,
+                feedback,
+                reviewer_worker_id: None,
+                started_at,
+                finished_at: Some(chrono::Utc::now().to_rfc3339()),
+            }
+        }
+        ReviewMode::Agent => {
+            let agent = policy.reviewer_agent.clone().unwrap_or_else(|| run.agent_type.clone());
+            let prompt = reviewer_prompt(
+                &task.title,
+                &task.description,
+                task.acceptance_criteria.as_deref(),
+                &truncate_diff(diff),
+            );
+            match create_review_crew(state, &run, &crew_path, commit) {
+                Ok(review_crew) => {
+                    // Recorded first: the scheduler may start the reviewer right away.
+                    let index = push_review(
+                        state,
+                        &run.id,
+                        ReviewRecord {
+                            mode: ReviewMode::Agent,
+                            verdict: ReviewVerdict::Pending,
+                            feedback: String::new(),
+                            reviewer_worker_id: None,
+                            started_at,
+                            finished_at: None,
+                        },
+                    );
+                    submit_reviewer(app, &run, review_crew.id, agent, prompt, index, policy.max_revisions);
+                    return true;
+                }
+                Err(e) => ReviewRecord {
+                    mode: ReviewMode::Agent,
+                    verdict: ReviewVerdict::Inconclusive,
+                    feedback: format!("Failed to start reviewer: {}", e),
+                    reviewer_worker_id: None,
+                    started_at,
+                    finished_at: Some(chrono::Utc::now().to_rfc3339()),
+                },
+            }
+        }
+    };
+
+    log_review(
+        state,
+        app,
+        &run.worker_id,
+        match record.mode {
+            ReviewMode::Rules => format!("[review] Revision {} reviewed by rules", run.revision_count),
+            ReviewMode::Agent => format!("[review] {}", record.feedback),
+        },
+    );
+    push_review(state, &run.id, record);
+    conclude_review(state, app, &run.id, policy.max_revisions);
+    true
+}
+
+/// Append a review to the run; returns its index in `run.reviews`.
+fn push_review(state: &AppState, run_id: &str, record: ReviewRecord) -> usize {
+    let mut runs = state.runs.lock().unwrap();
+    let Some(run) = runs.iter_mut().find(|r| r.id == run_id) else {
+        return 0;
+    };
+    run.reviews.push(record);
+    let index = run.reviews.len() - 1;
+    state.save_runs(&runs);
+    index
+}
+
+/// Hand the reviewer spawn to the scheduler. Once it starts, its worker is
+/// linked to review `index`; a spawn that fails ends the review as inconclusive.
+fn submit_reviewer(
+    app: &AppHandle,
+    run: &Run,
+    review_crew_id: String,
+    agent: String,
+    prompt: String,
+    index: usize,
+    max_revisions: u32,
+) {
+    let state = app.state::<AppState>();
+    let mut request = QueuedSpawn::new(
+        &run.rig_id,
+        &agent,
+        crate::scheduler::task_priority(&state, Some(&run.task_id)),
+        SpawnSource::Review,
+        format!("review {}", &run.id[..8.min(run.id.len())]),
+    );
+    request.crew_id = Some(review_crew_id.clone());
+    request.task_id = Some(run.task_id.clone());
+
+    let (run_id, run_worker_id, revision) = (run.id.clone(), run.worker_id.clone(), run.revision_count);
+    let result = crate::scheduler::submit(app, request, move |app| {
+        let state = app.state::<AppState>();
+        let spawned = crate::commands::workers::spawn_worker_inner(
+            review_crew_id.clone(),
+            agent,
+            prompt,
+            WorkerType::Crew,
+            None,
+            app.clone(),
+        );
+        {
+            let mut runs = state.runs.lock().unwrap();
+            if let Some(review) = runs
+                .iter_mut()
+                .find(|r| r.id == run_id)
+                .and_then(|r| r.reviews.get_mut(index))
+            {
+                match spawned {
+                    Ok(ref reviewer) => review.reviewer_worker_id = Some(reviewer.id.clone()),
+                    Err(ref e) => {
+                        review.verdict = ReviewVerdict::Inconclusive;
+                        review.feedback = format!("Failed to start reviewer: {}", e);
+                        review.finished_at = Some(chrono::Utc::now().to_rfc3339());
+                    }
+                }
+            }
+            state.save_runs(&runs);
+        }
+        match spawned {
+            Ok(ref reviewer) => log_review(
+                &state,
+                app,
+                &run_worker_id,
+                format!("[review] Reviewer worker {} started (revision {})", reviewer.id, revision),
+            ),
+            Err(ref e) => {
+                crate::commands::workers::remove_polecat_crew(&state, &review_crew_id);
+                log_review(&state, app, &run_worker_id, format!("[review] Failed to start reviewer: {}", e));
+                conclude_review(&state, app, &run_id, max_revisions);
+            }
+        }
+        spawned
+    });
+    if let Ok(Admission::Queued(_)) = result {
+        log_review(&state, app, &run.worker_id, "[review] Reviewer queued for a free slot".to_string());
+    }
+}
+
+/// Detached checkout of the reviewed commit for an agent reviewer, so it
+/// cannot change the crew's worktree. Removed once its verdict is in.
+fn create_review_crew(
+    state: &AppState,
+    run: &Run,
+    crew_path: &str,
+    commit: Option<&str>,
+) -> Result<crate::models::crew::Crew, String> {
+    let rig_path = {
+        let rigs = state.rigs.lock().unwrap();
+        rigs.iter()
+            .find(|r| r.id == run.rig_id)
+            .map(|r| r.path.clone())
+            .ok_or_else(|| "Rig not found".to_string())?
+    };
+    let commit = commit
+        .map(str::to_string)
+        .or_else(|| crate::git::head_commit(crew_path))
+        .ok_or_else(|| "Reviewed commit is unknown".to_string())?;
+    let slug = format!("review-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let wt_dir = state.worktrees_dir().join(&run.rig_id);
+    let _ = std::fs::create_dir_all(&wt_dir);
+    let wt_path = wt_dir.join(&slug).to_string_lossy().to_string();
+    crate::git::create_detached_worktree(&rig_path, &wt_path, &commit)?;
+
+    let crew = crate::models::crew::Crew::new(
+        run.rig_id.clone(),
+        format!("Review {}", &slug[7..]),
+        String::new(),
+        wt_path,
+    );
+    let mut crews = state.crews.lock().unwrap();
+    crews.push(crew.clone());
+    state.save_crews(&crews);
+    Ok(crew)
+}
+
+/// Called when any worker exits: if it was a reviewer, record its verdict
+/// on the reviewed run and continue the loop.
+pub(crate) fn finish_agent_review(state: &AppState, app: &AppHandle, worker_id: &str) {
+    let Some(run_id) = ({
+        let runs = state.runs.lock().unwrap();
+        runs.iter()
+            .find(|r| {
+                r.reviews.iter().any(|v| {
+                    v.verdict == ReviewVerdict::Pending && v.reviewer_worker_id.as_deref() == Some(worker_id)
+                })
+            })
+            .map(|r| r.id.clone())
+    }) else {
+        return;
+    };
+
+    let lines: Vec<String> = state
+        .load_log(worker_id)
+        .into_iter()
+        .filter(|e| e.stream != "system")
+        .map(|e| e.line)
+        .collect();
+    let (verdict, feedback) = parse_verdict(&lines);
+    let review_crew_id = {
+        let workers = state.workers.lock().unwrap();
+        workers.iter().find(|w| w.id == worker_id).map(|w| w.crew_id.clone())
+    };
+    if let Some(crew_id) = review_crew_id {
+        crate::commands::workers::remove_polecat_crew(state, &crew_id);
+    }
+    let rig_id = {
+        let mut runs = state.runs.lock().unwrap();
+        let Some(run) = runs.iter_mut().find(|r| r.id == run_id) else {
+            return;
+        };
+        if let Some(review) = run
+            .reviews
+            .iter_mut()
+            .find(|v| v.reviewer_worker_id.as_deref() == Some(worker_id))
+        {
+            review.verdict = verdict;
+            review.feedback = feedback;
+            review.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        }
+        let rig_id = run.rig_id.clone();
+        state.save_runs(&runs);
+        rig_id
+    };
+    let max_revisions = {
+        let rigs = state.rigs.lock().unwrap();
+        rigs.iter()
+            .find(|r| r.id == rig_id)
+            .and_then(|r| r.settings.review.as_ref())
+            .map(|p| p.max_revisions)
+            .unwrap_or(0)
+    };
+    conclude_review(state, app, &run_id, max_revisions);
+}
+
+/// Act on the run's latest review: finish the task on a pass, start a
+/// revision on a fail while revisions remain, escalate otherwise.
+fn conclude_review(state: &AppState, app: &AppHandle, run_id: &str, max_revisions: u32) {
+    let Some(run) = ({
+        let runs = state.runs.lock().unwrap();
+        runs.iter().find(|r| r.id == run_id).cloned()
+    }) else {
+        return;
+    };
+    let Some(review) = run.reviews.last().cloned() else {
+        return;
+    };
+    state.append_audit_event(&AuditEvent::new(
+        run.rig_id.clone(),
+        None,
+        Some(run.task_id.clone()),
+        AuditEventType::RunReviewed,
+        serde_json::json!({
+            "run_id": run.id,
+            "revision": run.revision_count,
+            "mode": review.mode,
+            "verdict": review.verdict,
+            "reviewer_worker_id": review.reviewer_worker_id,
+        })
+        .to_string(),
+    ));
+    log_review(
+        state,
+        app,
+        &run.worker_id,
+        format!(
+            "[review] Verdict: {}",
+            match review.verdict {
+                ReviewVerdict::Pending => "pending",
+                ReviewVerdict::Pass => "pass",
+                ReviewVerdict::Fail => "fail",
+                ReviewVerdict::Inconclusive => "inconclusive",
+            }
+        ),
+    );
+
+    match review.verdict {
+        ReviewVerdict::Pass => {
+            if set_task_status(state, &run, TaskStatus::Done, None) {
+                crate::lifecycle::task_done(app, &run.task_id);
+            }
+        }
+        ReviewVerdict::Fail if run.revision_count < max_revisions => {
+            match start_revision(app, &run, &review) {
+                Ok(next) => {
+                    state.append_audit_event(&AuditEvent::new(
+                        run.rig_id.clone(),
+                        None,
+                        Some(run.task_id.clone()),
+                        AuditEventType::RunRevisionStarted,
+                        serde_json::json!({
+                            "run_id": next.id,
+                            "previous_run_id": run.id,
+                            "revision": next.revision_count,
+                        })
+                        .to_string(),
+                    ));
+                }
+                Err(e) => {
+                    set_task_status(
+                        state,
+                        &run,
+                        TaskStatus::Escalated,
+                        Some(format!("Review failed and the revision could not start: {}", e)),
+                    );
+                }
+            }
+        }
+        ReviewVerdict::Fail => {
+            set_task_status(
+                state,
+                &run,
+                TaskStatus::Escalated,
+                Some(format!(
+                    "Review failed after {} revision(s): {}",
+                    run.revision_count, review.feedback
+                )),
+            );
+        }
+        ReviewVerdict::Inconclusive => {
+            set_task_status(
+                state,
+                &run,
+                TaskStatus::Escalated,
+                Some("Reviewer gave no verdict".to_string()),
+            );
+        }
+        ReviewVerdict::Pending => {}
+    }
+    let _ = app.emit("data-changed", "");
+}
+
+/// Follow-up run on the same crew carrying the review feedback.
+fn start_revision(app: &AppHandle, run: &Run, review: &ReviewRecord) -> Result<Run, String> {
+    let criteria = {
+        let state = app.state::<AppState>();
+        let tasks = state.tasks.lock().unwrap();
+        tasks
+            .iter()
+            .find(|t| t.id == run.task_id)
+            .and_then(|t| t.acceptance_criteria.clone())
+    };
+    let context = format!(
+        "## Review feedback (revision {})\n\n\
+         Your previous change was reviewed against the acceptance criteria and needs more work:\n\n{}\n\n\
+         {}Address every point above, building on the changes already on this branch.",
+        run.revision_count + 1,
+        review.feedback,
+        criteria
+            .map(|c| format!("Acceptance criteria:\n{}\n\n", c))
+            .unwrap_or_default()
+    );
+    execute_task_inner(
+        app,
+        run.task_id.clone(),
+        run.crew_id.clone(),
+        run.agent_type.clone(),
+        run.template_name.clone(),
+        Some(RunFollowUp {
+            previous_run_id: run.id.clone(),
+            attempt: run.attempt,
+            revision_count: run.revision_count + 1,
+            context,
+        }),
+    )
+}
+
+/// Returns whether the task's status changed.
+fn set_task_status(state: &AppState, run: &Run, status: TaskStatus, reason: Option<String>) -> bool {
+    let mut tasks = state.tasks.lock().unwrap();
+    let Some(task) = tasks.iter_mut().find(|t| t.id == run.task_id) else {
+        return false;
+    };
+    if task.status == TaskStatus::Todo {
+        task.status = TaskStatus::InProgress;
+    }
+    if task.status != TaskStatus::InProgress {
+        return false;
+    }
+    task.status = status.clone();
+    if status == TaskStatus::Done {
+        task.completed_at = Some(chrono::Utc::now().to_rfc3339());
+        task.outcome = Some(format!("Review passed after {} revision(s)", run.revision_count));
+    } else {
+        task.blocked_reason = reason;
+    }
+    task.updated_at = chrono::Utc::now().to_rfc3339();
+    state.append_audit_event(&AuditEvent::new(
+        task.rig_id.clone(),
+        task.owner_actor_id.clone(),
+        Some(task.id.clone()),
+        AuditEventType::TaskStatusChanged,
+        serde_json::json!({
+            "old_status": "in_progress",
+            "new_status": status,
+            "run_id": run.id,
+            "reason": task.blocked_reason,
+        })
+        .to_string(),
+    ));
+    state.save_tasks(&tasks);
+    true
+}
+
+/// Review a completed run on demand (e.g. after changing the rig's review policy).
+#[tauri::command]
+pub async fn review_run(run_id: String, app: AppHandle) -> Result<Run, String> {
+    tokio::task::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let run = {
+            let runs = state.runs.lock().unwrap();
+            runs.iter()
+                .find(|r| r.id == run_id)
+                .cloned()
+                .ok_or_else(|| "Run not found".to_string())?
+        };
+        if run.status != RunStatus::Completed {
+            return Err("Only completed runs can be reviewed".to_string());
+        }
+        if run.reviews.iter().any(|v| v.verdict == ReviewVerdict::Pending) {
+            return Err("A review of this run is already in progress".to_string());
+        }
+        if !start_review(&state, &app, &run_id, None) {
+            return Err("This rig has no review policy".to_string());
+        }
+        let runs = state.runs.lock().unwrap();
+        runs.iter()
+            .find(|r| r.id == run_id)
+            .cloned()
+            .ok_or_else(|| "Run not found".to_string())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn fail_verdict_takes_the_lines_after_it() {
+        let output = lines(
+            "All three criteria checked against the diff.\n\
+             - Login form validates email: yes\n\
+             - Error shown on 401: no\n\
+             \n\
+             **VERDICT: FAIL**\n\
+             1. Show the API error message when login returns 401.\n\
+             \n\
+             2. Add a test for the 401 path.",
+        );
+        let (verdict, feedback) = parse_verdict(&output);
+        assert_eq!(verdict, ReviewVerdict::Fail);
+        assert_eq!(
+            feedback,
+            "1. Show the API error message when login returns 401.\n2. Add a test for the 401 path."
+        );
+    }
+
+    #[test]
+    fn last_verdict_wins_and_pass_keeps_the_findings() {
+        let output = lines(
+            "I will end with `VERDICT: PASS` or `VERDICT: FAIL`.\n\
+             The change adds the retry flag and its test.\n\
+             Verdict: pass",
+        );
+        let (verdict, feedback) = parse_verdict(&output);
+        assert_eq!(verdict, ReviewVerdict::Pass);
+        assert!(feedback.ends_with("The change adds the retry flag and its test."));
+    }
+
+    #[test]
+    fn echoed_prompt_is_not_a_verdict() {
+        let output = lines(
+            "$ codex exec --full-auto \"You are reviewing a change another coding agent made for the task below. \
+             Do not modify any files. ## Task Add retries ... finish with a line `VERDICT: PASS` or `VERDICT: FAIL`. \
+             When failing, list the concrete changes still required after the verdict line.\"\n\
+             ERROR: stream error: 429 Too Many Requests; retrying in 30s\n\
+             ERROR: exceeded retry limit, last status: 429 Too Many Requests",
+        );
+        let (verdict, feedback) = parse_verdict(&output);
+        assert_eq!(verdict, ReviewVerdict::Inconclusive);
+        assert!(!feedback.contains("VERDICT"));
+    }
+
+    #[test]
+    fn verdict_must_stand_alone_on_its_line() {
+        let output = lines("If it were done I would write VERDICT: PASS here.\nSession ended.");
+        assert_eq!(parse_verdict(&output).0, ReviewVerdict::Inconclusive);
+    }
+
+    #[test]
+    fn missing_verdict_is_inconclusive() {
+        let (verdict, feedback) = parse_verdict(&lines("Error: rate limited, try again later"));
+        assert_eq!(verdict, ReviewVerdict::Inconclusive);
+        assert_eq!(feedback, "Error: rate limited, try again later");
+    }
+}
@@ -2,7 +2,7 @@ use std::path::Path;
 use tauri::{AppHandle, Emitter, State};
 
 use crate::git;
-use crate::models::rig::{Rig, RigInfo};
+use crate::models::rig::{Rig, RigInfo, RigSettings};
 use crate::state::AppState;
 
 #[tauri::command]
@@ -99,6 +99,33 @@ pub fn get_rig(id: String, state: State<AppState>) -> Result<RigInfo, String> {
     Ok(rig.to_info(branch, status, is_git))
 }
 
+#[tauri::command]
+pub fn update_rig_settings(
+    id: String,
+    settings: RigSettings,
+    state: State<AppState>,
+    app: AppHandle,
+) -> Result<RigInfo, String> {
+    let rig = {
+        let mut rigs = state.rigs.lock().unwrap();
+        let rig = rigs
+            .iter_mut()
+            .find(|r| r.id == id)
+            .ok_or_else(|| "Rig not found".to_string())?;
+        rig.settings = settings;
+        let updated = rig.clone();
+        state.save_rigs(&rigs);
+        updated
+    };
+
+    let is_git = git::is_git_repo(&rig.path);
+    let branch = if is_git { git::get_current_branch(&rig.path) } else { None };
+    let (status, _) = if is_git { git::get_status_info(&rig.path) } else { (None, 0) };
+
+    let _ = app.emit("data-changed", "");
+    Ok(rig.to_info(branch, status, is_git))
+}
+
 #[tauri::command]
 pub fn delete_rig(id: String, state: State<AppState>, app: AppHandle) -> Result<(), String> {
     let mut rigs = state.rigs.lock().unwrap();
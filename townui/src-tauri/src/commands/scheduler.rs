@@ -0,0 +1,35 @@
+use tauri::{AppHandle, Emitter, State};
+
+use crate::models::audit::{AuditEvent, AuditEventType};
+use crate::models::scheduler::{QueuedSpawn, SchedulerStatus};
+use crate::state::AppState;
+
+/// Running workers against each limit, and the spawn queue in admission order.
+#[tauri::command]
+pub fn get_scheduler_status(state: State<AppState>) -> SchedulerStatus {
+    crate::scheduler::status(&state)
+}
+
+/// Drop a waiting spawn. Its queued run is cancelled and a polecat worktree
+/// created for it is removed.
+#[tauri::command]
+pub fn cancel_queued_spawn(id: String, state: State<AppState>, app: AppHandle) -> Result<QueuedSpawn, String> {
+    let spawn = crate::scheduler::cancel(&state, &id).ok_or_else(|| "Queued spawn not found".to_string())?;
+    if let (true, Some(crew_id)) = (spawn.polecat, spawn.crew_id.as_deref()) {
+        crate::commands::workers::remove_polecat_crew(&state, crew_id);
+    }
+    state.append_audit_event(&AuditEvent::new(
+        spawn.rig_id.clone(),
+        None,
+        spawn.task_id.clone(),
+        AuditEventType::SpawnCancelled,
+        serde_json::json!({
+            "spawn_id": spawn.id,
+            "source": spawn.source,
+            "run_id": spawn.run_id,
+        })
+        .to_string(),
+    ));
+    let _ = app.emit("data-changed", "");
+    Ok(spawn)
+}
@@ -6,6 +6,7 @@ use std::os::windows::process::CommandExt;
 use tauri::State;
 
 use crate::models::settings::AppSettings;
+use crate::sandbox::SandboxCapabilities;
 use crate::state::AppState;
 
 /// Windows: CREATE_NO_WINDOW flag
@@ -81,3 +82,9 @@ pub fn validate_cli_path(path: String) -> Result<String, String> {
         Err(format!("'{}' exited with error: {}", path, stderr))
     }
 }
+
+/// Report which worker isolation mechanisms (cgroups, rlimits, bubblewrap, Landlock) are usable here.
+#[tauri::command]
+pub fn get_sandbox_capabilities() -> SandboxCapabilities {
+    crate::sandbox::detect_capabilities()
+}
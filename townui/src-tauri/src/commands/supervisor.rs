@@ -7,7 +7,8 @@ use tauri::{AppHandle, Emitter, Manager, State};
 
 use crate::models::audit::{AuditEvent, AuditEventType};
 use crate::models::hook::{Hook, HookStatus};
-use crate::models::task::{Task, TaskStatus};
+use crate::models::scheduler::{QueuedSpawn, SpawnSource};
+use crate::models::task::{Task, TaskPriority, TaskStatus};
 use crate::models::worker::WorkerStatusEnum;
 use crate::state::AppState;
 
@@ -235,10 +236,15 @@ fn reconcile_queue_inner(state: &AppState, rig_id: Option<&str>, app: Option<&Ap
                         task.status = TaskStatus::Escalated;
                     }
                     task.assigned_worker_id = None;
-                    task.blocked_reason = Some(format!(
-                        "Queue reconciler marked orphaned work: {}",
-                        decision.reason
-                    ));
+                    if task.status != TaskStatus::Escalated {
+                        task.blocked_reason = None;
+                    } else if task.blocked_reason.is_none() {
+                        // Keep a more specific reason (e.g. budget enforcement) if one is already set.
+                        task.blocked_reason = Some(format!(
+                            "Queue reconciler marked orphaned work: {}",
+                            decision.reason
+                        ));
+                    }
                     task.updated_at = now.clone();
 
                     let payload = serde_json::json!({
@@ -314,11 +320,10 @@ fn reconcile_queue_inner(state: &AppState, rig_id: Option<&str>, app: Option<&Ap
 /// Propulsion: for each rig that has pending Todo tasks but no running workers,
 /// auto-spawn a worker on the first idle crew in that rig.
 fn run_propulsion_cycle(state: &AppState, app: &AppHandle) {
-    let (tasks_snapshot, workers_snapshot, crews_snapshot) = {
+    let (tasks_snapshot, workers_snapshot) = {
         let t = state.tasks.lock().unwrap();
         let w = state.workers.lock().unwrap();
-        let c = state.crews.lock().unwrap();
-        (t.clone(), w.clone(), c.clone())
+        (t.clone(), w.clone())
     };
 
     // Collect rig_ids that have at least one running worker
@@ -335,55 +340,74 @@ fn run_propulsion_cycle(state: &AppState, app: &AppHandle) {
         .map(|t| t.rig_id.clone())
         .collect();
 
-    // Find rigs that need propulsion = have work but no running worker
+    // Find rigs that need propulsion = have work but no running worker and no
+    // propulsion spawn already queued, skipping rigs paused by an exhausted budget.
+    let queued = crate::scheduler::queued(state);
     let rigs_to_push: Vec<String> = rigs_with_work
         .into_iter()
         .filter(|rid| !busy_rigs.contains(rid))
+        .filter(|rid| !queued.iter().any(|q| q.source == SpawnSource::Propulsion && q.rig_id == *rid))
+        .filter(|rid| !crate::commands::budgets::rig_spawning_paused(state, rid))
         .collect();
 
     if rigs_to_push.is_empty() {
         return;
     }
 
+    let paused_convoys = crate::commands::budgets::paused_convoy_ids(state);
+
     for rig_id in &rigs_to_push {
-        // Pick the first active crew in this rig
-        let crew_id = match crews_snapshot.iter().find(|c| {
-            &c.rig_id == rig_id
-                && c.status == crate::models::crew::CrewStatus::Active
-        }) {
-            Some(c) => c.id.clone(),
-            None => continue,
+        // Pick an active crew in this rig that no worker holds
+        let Some(crew_id) = crate::commands::crews::idle_crew_id(state, rig_id) else {
+            continue;
         };
 
         let task_id = tasks_snapshot
             .iter()
-            .find(|t| &t.rig_id == rig_id && t.status == TaskStatus::Todo)
+            .find(|t| {
+                &t.rig_id == rig_id
+                    && t.status == TaskStatus::Todo
+                    && !t
+                        .convoy_id
+                        .as_ref()
+                        .map(|c| paused_convoys.contains(c))
+                        .unwrap_or(false)
+            })
             .map(|t| t.id.clone());
-
-        let spawn_result = crate::commands::workers::spawn_worker_for_propulsion(
-            state,
-            app,
+        let Some(task_id) = task_id else {
+            continue;
+        };
+        let agent_type = crate::commands::workers::background_agent_type(state);
+        let mut request = QueuedSpawn::new(
             rig_id,
-            &crew_id,
-            task_id.as_deref(),
+            &agent_type,
+            crate::scheduler::task_priority(state, Some(&task_id)),
+            SpawnSource::Propulsion,
+            "propulsion",
         );
-        match spawn_result {
-            Ok(worker_id) => {
-                state.append_audit_event(&AuditEvent::new(
-                    rig_id.clone(),
-                    None,
-                    task_id,
-                    AuditEventType::WorkerSpawned,
-                    serde_json::json!({
-                        "propulsion": true,
-                        "crew_id": crew_id,
-                        "worker_id": worker_id,
-                    }).to_string(),
-                ));
-            }
-            Err(e) => {
-                eprintln!("[propulsion] failed to spawn worker for rig {rig_id}: {e}");
-            }
+        request.crew_id = Some(crew_id.clone());
+        request.task_id = Some(task_id.clone());
+
+        let rig = rig_id.clone();
+        let spawn_result = crate::scheduler::submit(app, request, move |app| {
+            let state = app.state::<AppState>();
+            let worker_id =
+                crate::commands::workers::spawn_worker_for_propulsion(&state, app, &rig, &crew_id, Some(&task_id))?;
+            state.append_audit_event(&AuditEvent::new(
+                rig,
+                None,
+                Some(task_id),
+                AuditEventType::WorkerSpawned,
+                serde_json::json!({
+                    "propulsion": true,
+                    "crew_id": crew_id,
+                    "worker_id": worker_id,
+                }).to_string(),
+            ));
+            Ok(())
+        });
+        if let Err(e) = spawn_result {
+            eprintln!("[propulsion] failed to spawn worker for rig {rig_id}: {e}");
         }
     }
 
@@ -392,16 +416,11 @@ fn run_propulsion_cycle(state: &AppState, app: &AppHandle) {
 
 /// Witness: per-rig polecat lifecycle management.
 /// - Spawn polecats for rigs that have no running polecat but have open hooks.
-/// - Nudge stuck polecats (no heartbeat for > polecat_nudge_after_seconds).
 /// - Recycle (stop) polecats for rigs that have no remaining hooks.
+///
+/// Stuck workers are handled by the liveness recovery ladder, not here.
 fn run_witness_cycle(state: &AppState, app: &AppHandle) {
-    let (max_polecats, nudge_after_seconds) = {
-        let settings = state.settings.lock().unwrap();
-        (
-            settings.max_polecats_per_rig,
-            settings.polecat_nudge_after_seconds,
-        )
-    };
+    let max_polecats = state.settings.lock().unwrap().max_polecats_per_rig;
 
     let rig_ids: Vec<String> = {
         let rigs = state.rigs.lock().unwrap();
@@ -438,32 +457,37 @@ fn run_witness_cycle(state: &AppState, app: &AppHandle) {
         if open_hooks == 0 && running_polecats > 0 {
             for pw in &polecat_workers {
                 eprintln!("[witness] recycling idle polecat {} on rig {rig_id}", pw.id);
-                let _ = crate::commands::workers::stop_worker_inner(state, &pw.id);
+                let _ = crate::commands::workers::stop_worker_inner(state, app, &pw.id);
             }
             continue;
         }
 
-        // Nudge stuck polecats
-        let now_ts = chrono::Utc::now().timestamp() as u64;
-        for pw in &polecat_workers {
-            if let Ok(started) = chrono::DateTime::parse_from_rfc3339(&pw.started_at) {
-                let elapsed = now_ts.saturating_sub(started.timestamp() as u64);
-                if elapsed > nudge_after_seconds {
-                    // Send a nudge character to the PTY
-                    let _ = crate::commands::workers::nudge_worker_pty(state, &pw.id);
-                    eprintln!("[witness] nudged polecat {} (elapsed {elapsed}s)", pw.id);
-                }
-            }
+        // Spawn new polecats up to max if we have open hooks (unless the rig's budget is exhausted)
+        if crate::commands::budgets::rig_spawning_paused(state, rig_id) {
+            continue;
         }
-
-        // Spawn new polecats up to max if we have open hooks
-        if open_hooks > 0 && running_polecats < max_polecats {
-            let to_spawn = (max_polecats - running_polecats).min(open_hooks);
+        // Polecats already waiting in the scheduler count towards the target.
+        let queued_polecats = crate::scheduler::queued(state)
+            .iter()
+            .filter(|q| q.source == SpawnSource::Witness && q.rig_id == *rig_id)
+            .count();
+        let wanted = running_polecats + queued_polecats;
+        if open_hooks > 0 && wanted < max_polecats {
+            let to_spawn = (max_polecats - wanted).min(open_hooks.saturating_sub(queued_polecats));
             for _ in 0..to_spawn {
-                let result = crate::commands::workers::spawn_polecat_inner(state, app, rig_id);
-                match result {
-                    Ok(id) => eprintln!("[witness] spawned polecat {id} on rig {rig_id}"),
-                    Err(e) => eprintln!("[witness] failed to spawn polecat on rig {rig_id}: {e}"),
+                let agent_type = crate::commands::workers::background_agent_type(state);
+                let mut request =
+                    QueuedSpawn::new(rig_id, &agent_type, TaskPriority::Medium, SpawnSource::Witness, "witness polecat");
+                request.polecat = true;
+                let rig = rig_id.clone();
+                let result = crate::scheduler::submit(app, request, move |app| {
+                    let state = app.state::<AppState>();
+                    let id = crate::commands::workers::spawn_polecat_inner(&state, app, &rig)?;
+                    eprintln!("[witness] spawned polecat {id} on rig {rig}");
+                    Ok(())
+                });
+                if let Err(e) = result {
+                    eprintln!("[witness] failed to spawn polecat on rig {rig_id}: {e}");
                 }
             }
         }
@@ -508,6 +532,12 @@ fn spawn_supervisor_loop(app: AppHandle) {
                     }
                 }
 
+                // Budget and per-run limit enforcement (may pause propulsion/witness for a scope)
+                crate::commands::budgets::enforce_budgets(&state, &app);
+
+                // Liveness sampling and the hung-worker recovery ladder
+                crate::commands::liveness::run_liveness_cycle(&state, &app);
+
                 // Propulsion enforcement
                 propulsion_tick += interval;
                 let (propulsion_enabled, propulsion_interval, witness_auto_spawn) = {
@@ -670,6 +700,9 @@ pub fn compact_state(
         if removed > 0 {
             state.save_runs(&runs);
         }
+        // Patches outlive worktrees, not their runs.
+        let live: HashSet<String> = runs.iter().map(|r| r.id.clone()).collect();
+        state.prune_run_patches(&live);
         removed
     };
 
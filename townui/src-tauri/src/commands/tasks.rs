@@ -2,6 +2,7 @@ use serde::Serialize;
 use tauri::{AppHandle, Emitter, State};
 
 use crate::models::audit::{AuditEvent, AuditEventType};
+use crate::models::liveness::LivenessState;
 use crate::models::task::{Task, TaskPriority, TaskStatus, TaskUpdateRequest};
 use crate::models::worker::WorkerStatusEnum;
 use crate::state::AppState;
@@ -114,6 +115,11 @@ pub fn update_task(
         payload,
     ));
 
+    drop(tasks);
+    if updated.status == TaskStatus::Done && old_status != TaskStatus::Done {
+        crate::lifecycle::task_done(&app, &updated.id);
+    }
+
     let _ = app.emit("data-changed", "");
     Ok(updated)
 }
@@ -173,6 +179,8 @@ pub struct StuckTaskInfo {
     pub title: String,
     pub minutes_stuck: i64,
     pub assigned_worker_id: Option<String>,
+    /// Why the task counts as stuck: the worker's liveness state, or "no_update" when only the task timestamp is stale.
+    pub reason: String,
 }
 
 #[tauri::command]
@@ -186,20 +194,43 @@ pub fn get_health_metrics(rig_id: String, stuck_threshold_minutes: Option<i64>,
     let threshold = stuck_threshold_minutes.unwrap_or(30);
     let now = chrono::Utc::now();
 
+    let liveness = state.worker_liveness.lock().unwrap();
     let mut stuck_tasks = Vec::new();
     for task in &rig_tasks {
-        if task.status == TaskStatus::InProgress {
-            if let Ok(updated) = chrono::DateTime::parse_from_rfc3339(&task.updated_at) {
-                let minutes = (now - updated.with_timezone(&chrono::Utc)).num_minutes();
-                if minutes >= threshold {
-                    stuck_tasks.push(StuckTaskInfo {
-                        task_id: task.id.clone(),
-                        title: task.title.clone(),
-                        minutes_stuck: minutes,
-                        assigned_worker_id: task.assigned_worker_id.clone(),
-                    });
+        if task.status != TaskStatus::InProgress {
+            continue;
+        }
+        // A running worker's own activity (output, file changes, CPU) is a better signal than the task timestamp.
+        let live_worker = task.assigned_worker_id.as_ref().and_then(|wid| {
+            workers
+                .iter()
+                .find(|w| &w.id == wid && w.status == WorkerStatusEnum::Running)
+                .and_then(|w| liveness.get(&w.id))
+        });
+        let (minutes, reason) = match live_worker {
+            Some(sample) => {
+                if matches!(sample.state, LivenessState::Active | LivenessState::WaitingInput) {
+                    continue;
                 }
+                let reason = serde_json::to_value(&sample.state)
+                    .ok()
+                    .and_then(|v| v.as_str().map(str::to_string))
+                    .unwrap_or_default();
+                ((sample.quiet_seconds / 60) as i64, reason)
             }
+            None => match chrono::DateTime::parse_from_rfc3339(&task.updated_at) {
+                Ok(updated) => ((now - updated.with_timezone(&chrono::Utc)).num_minutes(), "no_update".to_string()),
+                Err(_) => continue,
+            },
+        };
+        if minutes >= threshold {
+            stuck_tasks.push(StuckTaskInfo {
+                task_id: task.id.clone(),
+                title: task.title.clone(),
+                minutes_stuck: minutes,
+                assigned_worker_id: task.assigned_worker_id.clone(),
+                reason,
+            });
         }
     }
 
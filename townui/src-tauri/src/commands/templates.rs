@@ -3,7 +3,7 @@ use tauri::State;
 use serde::{Deserialize, Serialize};
 
 use crate::state::AppState;
-use crate::templates;
+use crate::templates::{self, Template, TemplateSource};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemplateInfo {
@@ -11,52 +11,102 @@ pub struct TemplateInfo {
     pub description: String,
     pub content: String,
     pub is_builtin: bool,
+    pub source: TemplateSource,
+    pub path: Option<String>,
+    pub required_vars: Vec<String>,
+    pub recommended_agent: Option<String>,
 }
 
-#[tauri::command]
-pub fn list_templates(state: State<AppState>) -> Vec<TemplateInfo> {
-    let mut result: Vec<TemplateInfo> = templates::get_builtin_templates()
-        .into_iter()
-        .map(|t| TemplateInfo {
-            name: t.name,
-            description: t.description,
-            content: t.content,
-            is_builtin: t.is_builtin,
-        })
-        .collect();
-
-    let custom = templates::load_custom_templates(&state.templates_dir());
-    for t in custom {
-        result.push(TemplateInfo {
+impl From<Template> for TemplateInfo {
+    fn from(t: Template) -> Self {
+        Self {
             name: t.name,
             description: t.description,
             content: t.content,
             is_builtin: t.is_builtin,
-        });
+            source: t.source,
+            path: t.path,
+            required_vars: t.required_vars,
+            recommended_agent: t.recommended_agent,
+        }
     }
+}
 
-    result
+/// Templates visible to a rig and crew (either may be unset), most specific
+/// source winning per name. A crew implies its rig.
+pub(crate) fn template_registry(state: &AppState, rig_id: Option<&str>, crew_id: Option<&str>) -> Vec<Template> {
+    let (crew_rig_id, crew_path) = match crew_id {
+        Some(crew_id) => {
+            let crews = state.crews.lock().unwrap();
+            crews
+                .iter()
+                .find(|c| c.id == crew_id)
+                .map(|c| (Some(c.rig_id.clone()), Some(c.path.clone())))
+                .unwrap_or((None, None))
+        }
+        None => (None, None),
+    };
+    let rig_id = rig_id.map(str::to_string).or(crew_rig_id);
+    let rig_path = rig_id.and_then(|rig_id| {
+        let rigs = state.rigs.lock().unwrap();
+        rigs.iter().find(|r| r.id == rig_id).map(|r| r.path.clone())
+    });
+    templates::resolve_templates(&state.templates_dir(), rig_path.as_deref(), crew_path.as_deref())
+}
+
+/// Look a template up by name for a rig/crew; an empty name means the
+/// town's `default_template`. Returned with the registry it came from, which
+/// its `{% include %}`s resolve against.
+pub(crate) fn resolve_template(
+    state: &AppState,
+    name: &str,
+    rig_id: Option<&str>,
+    crew_id: Option<&str>,
+) -> Result<(Template, Vec<Template>), String> {
+    let name = if name.trim().is_empty() {
+        state.settings.lock().unwrap().default_template.clone()
+    } else {
+        name.trim().to_string()
+    };
+    let registry = template_registry(state, rig_id, crew_id);
+    let template = registry
+        .iter()
+        .find(|t| t.name == name)
+        .cloned()
+        .ok_or_else(|| format!("Template '{}' not found", name))?;
+    Ok((template, registry))
+}
+
+#[tauri::command]
+pub fn list_templates(
+    rig_id: Option<String>,
+    crew_id: Option<String>,
+    state: State<AppState>,
+) -> Vec<TemplateInfo> {
+    template_registry(&state, rig_id.as_deref(), crew_id.as_deref())
+        .into_iter()
+        .map(TemplateInfo::from)
+        .collect()
 }
 
 #[tauri::command]
 pub fn render_template(
     name: String,
     vars: HashMap<String, String>,
+    rig_id: Option<String>,
+    crew_id: Option<String>,
     state: State<AppState>,
 ) -> Result<String, String> {
-    // Find the template
-    let all_templates = templates::get_builtin_templates();
-    let custom = templates::load_custom_templates(&state.templates_dir());
-
-    let template_content = all_templates
-        .iter()
-        .chain(custom.iter())
-        .find(|t| t.name == name)
-        .map(|t| t.content.clone())
-        .ok_or_else(|| format!("Template '{}' not found", name))?;
+    let (template, registry) = resolve_template(&state, &name, rig_id.as_deref(), crew_id.as_deref())?;
+    let ctx = templates::vars_context(&vars);
 
     // Validate that all required variables are provided
-    let missing = templates::validate_variables(&template_content, &vars);
+    let mut missing = templates::validate_variables(&template.content, &vars)?;
+    for var in templates::missing_required_vars(&template, &ctx) {
+        if !missing.contains(&var) {
+            missing.push(var);
+        }
+    }
     if !missing.is_empty() {
         return Err(format!(
             "Missing template variables: {}",
@@ -64,5 +114,5 @@ pub fn render_template(
         ));
     }
 
-    Ok(templates::render_template(&template_content, &vars))
+    templates::render_with_context(&template.name, &template.content, &ctx, &registry)
 }
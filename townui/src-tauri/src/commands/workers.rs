@@ -6,13 +6,24 @@ use portable_pty::{native_pty_system, CommandBuilder, PtySize};
 use tauri::{AppHandle, Emitter, Manager, State};
 
 use crate::models::audit::{AuditEvent, AuditEventType};
-use crate::models::worker::{LogEntry, Run, RunStatus, Worker, WorkerStatusEnum, WorkerType};
-use crate::state::AppState;
+use crate::models::hook::HookStatus;
+use crate::models::lifecycle::LifecycleEvent;
+use crate::models::priming::PrimingDelivery;
+use crate::models::scheduler::{QueuedSpawn, SpawnSource};
+use crate::models::settings::SessionBackend;
+use crate::models::task::TaskStatus;
+use crate::models::verification::{checks_passed, quality_from_checks, CheckOutcome};
+use crate::models::worker::{
+    LogEntry, Run, RunStatus, Worker, WorkerCheckpoint, WorkerStatusEnum, WorkerType,
+};
+use crate::priming::PrimingPlan;
+use crate::scheduler::Admission;
+use crate::state::{AppState, PtyMasterHandle, WorkerWriter};
 
 // ── Cross-platform helpers ──
 
 #[cfg(target_os = "windows")]
-fn kill_process_tree(pid: u32) {
+pub(crate) fn kill_process_tree(pid: u32) {
     let _ = Command::new("taskkill")
         .args(["/PID", &pid.to_string(), "/T", "/F"])
         .output();
@@ -96,7 +107,7 @@ fn resolve_windows_cli_path(cli: &str) -> Option<String> {
 }
 
 #[cfg(target_os = "macos")]
-fn kill_process_tree(pid: u32) {
+pub(crate) fn kill_process_tree(pid: u32) {
     // Try process group kill first, fallback to direct kill
     let pgid_result = Command::new("kill")
         .args(["-9", &format!("-{}", pid)])
@@ -109,10 +120,15 @@ fn kill_process_tree(pid: u32) {
                 .output();
         }
     }
+    // PTY workers run an interactive shell as session leader; job control moves
+    // the agent into its own process group, so also sweep the whole session.
+    let _ = Command::new("pkill")
+        .args(["-9", "-s", &pid.to_string()])
+        .output();
 }
 
 #[cfg(target_os = "linux")]
-fn kill_process_tree(pid: u32) {
+pub(crate) fn kill_process_tree(pid: u32) {
     // Try process group kill first, fallback to direct kill
     let pgid_result = Command::new("kill")
         .args(["-9", &format!("-{}", pid)])
@@ -125,13 +141,187 @@ fn kill_process_tree(pid: u32) {
                 .output();
         }
     }
+    // PTY workers run an interactive shell as session leader; job control moves
+    // the agent into its own process group, so also sweep the whole session.
+    let _ = Command::new("pkill")
+        .args(["-9", "-s", &pid.to_string()])
+        .output();
 }
 
 #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
-fn kill_process_tree(pid: u32) {
+pub(crate) fn kill_process_tree(pid: u32) {
     eprintln!("kill_process_tree: unsupported platform, cannot kill pid {}", pid);
 }
 
+/// Polite termination: SIGTERM to the worker's process group and session
+/// (Windows: `taskkill /T` without `/F`).
+#[cfg(target_os = "windows")]
+fn terminate_process_tree(pid: u32) {
+    let _ = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T"])
+        .output();
+}
+
+#[cfg(unix)]
+fn terminate_process_tree(pid: u32) {
+    let _ = Command::new("kill")
+        .args(["-TERM", &format!("-{}", pid)])
+        .output();
+    let _ = Command::new("pkill")
+        .args(["-TERM", "-s", &pid.to_string()])
+        .output();
+    let _ = Command::new("kill").args(["-TERM", &pid.to_string()]).output();
+}
+
+#[cfg(not(any(target_os = "windows", unix)))]
+fn terminate_process_tree(pid: u32) {
+    kill_process_tree(pid);
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn process_alive(pid: u32) -> bool {
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).contains(&pid.to_string()))
+        .unwrap_or(false)
+}
+
+#[cfg(unix)]
+pub(crate) fn process_alive(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(target_os = "windows", unix)))]
+pub(crate) fn process_alive(_pid: u32) -> bool {
+    false
+}
+
+/// Whether anything besides the session leader (the PTY shell) is still running,
+/// i.e. whether the agent itself has exited. Falls back to leader liveness.
+#[cfg(unix)]
+fn agent_still_running(pid: u32) -> bool {
+    match Command::new("pgrep").args(["-s", &pid.to_string()]).output() {
+        Ok(o) if o.status.success() || o.status.code() == Some(1) => {
+            String::from_utf8_lossy(&o.stdout)
+                .lines()
+                .filter_map(|l| l.trim().parse::<u32>().ok())
+                .any(|p| p != pid)
+        }
+        _ => process_alive(pid),
+    }
+}
+
+#[cfg(not(unix))]
+fn agent_still_running(pid: u32) -> bool {
+    process_alive(pid)
+}
+
+/// Total CPU seconds (user + system) of every process in the worker's
+/// session or process group, read from `/proc`.
+#[cfg(target_os = "linux")]
+pub(crate) fn process_tree_cpu_seconds(pid: u32) -> Option<f64> {
+    // USER_HZ is 100 on every mainstream Linux configuration.
+    const CLOCK_TICKS: f64 = 100.0;
+    let mut ticks: u64 = 0;
+    let mut found = false;
+    for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+        if !entry.file_name().to_string_lossy().chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        // Fields after the parenthesised command name: state ppid pgrp session ... utime(12) stime(13)
+        let Some(rest) = stat.rfind(')').map(|i| &stat[i + 2..]) else {
+            continue;
+        };
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        let (Some(pgrp), Some(session)) = (fields.get(2), fields.get(3)) else {
+            continue;
+        };
+        let owner = pid.to_string();
+        if *pgrp != owner && *session != owner {
+            continue;
+        }
+        found = true;
+        ticks += fields.get(11).and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+        ticks += fields.get(12).and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+    }
+    found.then_some(ticks as f64 / CLOCK_TICKS)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn process_tree_cpu_seconds(_pid: u32) -> Option<f64> {
+    None
+}
+
+fn wait_until(timeout_ms: u64, mut done: impl FnMut() -> bool) -> bool {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout_ms);
+    loop {
+        if done() {
+            return true;
+        }
+        if std::time::Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(std::time::Duration::from_millis(200));
+    }
+}
+
+/// In-agent command that asks an interactive adapter to quit cleanly.
+fn adapter_exit_command(agent_type: &str) -> Option<&'static str> {
+    match agent_type {
+        "claude" | "aider" | "goose" => Some("/exit"),
+        "codex" | "gemini" | "amazon-q" => Some("/quit"),
+        _ => None,
+    }
+}
+
+/// Staged stop: adapter interrupt over the PTY (Ctrl-C, then the adapter's exit
+/// command and `exit` for the shell) → SIGTERM → SIGKILL, each stage bounded by
+/// its grace period. `on_interrupted` runs once the interrupt stage is over,
+/// before any signal. Holds the PTY master until the end so the session stays open.
+fn run_staged_stop(
+    pid: u32,
+    agent_type: &str,
+    writer: Option<WorkerWriter>,
+    master: Option<PtyMasterHandle>,
+    interrupt_grace_ms: u64,
+    term_grace_ms: u64,
+    on_interrupted: impl FnOnce(),
+) {
+    if let Some(mut w) = writer {
+        let _ = w.write_all(b"\x03");
+        let _ = w.flush();
+        thread::sleep(std::time::Duration::from_millis(300));
+        if let Some(cmd) = adapter_exit_command(agent_type) {
+            let _ = w.write_all(format!("{}\r", cmd).as_bytes());
+            let _ = w.flush();
+        }
+        let agent_exited = wait_until(interrupt_grace_ms, || !agent_still_running(pid));
+        on_interrupted();
+        if agent_exited {
+            let _ = w.write_all(b"exit\r");
+            let _ = w.flush();
+            if wait_until(1500, || !process_alive(pid)) {
+                drop(master);
+                return;
+            }
+        }
+    }
+
+    terminate_process_tree(pid);
+    if !wait_until(term_grace_ms, || !process_alive(pid)) {
+        kill_process_tree(pid);
+    }
+    drop(master);
+}
+
 /// Strip ANSI escape sequences from PTY output lines.
 /// Handles CSI sequences (\x1b[...X), OSC sequences (\x1b]...BEL/ST), and simple two-char escapes.
 fn strip_ansi_escapes(input: &str) -> String {
@@ -209,6 +399,50 @@ fn should_use_non_pty_spawn(_agent_type: &str, _initial_prompt: &str) -> bool {
     false
 }
 
+/// Whether the launched agent ends up reading its terminal. One-shot runs
+/// (a prompt on the command line, `--print`, `--message`) never do, so
+/// nothing may be typed into them.
+fn agent_reads_terminal(agent_type: &str, initial_prompt: &str) -> bool {
+    if should_use_non_pty_spawn(agent_type, initial_prompt) {
+        return false;
+    }
+    match agent_type {
+        // `exec` runs the prompt, then the interactive CLI starts.
+        "codex" => true,
+        "copilot" | "amazon-q" | "aider" | "goose" | "openhands" | "swe-agent" | "cline" | "augment"
+        | "roo" | "tabby" | "cody" | "sweep" | "auto-coder" | "devin" | "replit" => false,
+        _ => initial_prompt.is_empty(),
+    }
+}
+
+/// Arguments selecting `model` on the agent's CLI, placed right after the
+/// executable. Agents without a model option reject a model.
+pub(crate) fn model_args(agent_type: &str, model: &str) -> Result<Vec<String>, String> {
+    let valid = !model.is_empty()
+        && model.chars().all(|c| c.is_ascii_alphanumeric() || "._:/-".contains(c));
+    if !valid {
+        return Err(format!("Invalid model '{}'", model));
+    }
+    match agent_type {
+        "claude" | "gemini" | "aider" => Ok(vec!["--model".to_string(), model.to_string()]),
+        // A config override applies to `exec` and to the interactive session after it.
+        "codex" => Ok(vec!["-c".to_string(), format!("model={}", model)]),
+        _ => Err(format!("Agent '{}' cannot be given a model", agent_type)),
+    }
+}
+
+/// Shell argument carrying the priming text for a system prompt flag. The
+/// text was written to the priming file, which keeps its line breaks intact.
+#[cfg(not(target_os = "windows"))]
+fn priming_flag_value(_text: &str) -> String {
+    format!("\"$(cat {})\"", crate::priming::PRIMING_FILE)
+}
+
+#[cfg(target_os = "windows")]
+fn priming_flag_value(text: &str) -> String {
+    format!("\"{}\"", sanitize_prompt_for_shell(text))
+}
+
 fn worker_status_to_str(status: &WorkerStatusEnum) -> &'static str {
     match status {
         WorkerStatusEnum::Running => "running",
@@ -286,6 +520,118 @@ fn try_auto_commit_for_completed_run(
     Ok(Some((commit_hash, rig_id, task_id)))
 }
 
+/// Run the rig's verification pipeline in the crew worktree of a cleanly
+/// exited run. `None` when the rig has no checks configured.
+fn verify_completed_run(state: &AppState, app: &AppHandle, worker_id: &str) -> Option<Vec<CheckOutcome>> {
+    let (crew_path, rig_id, _, _) = resolve_run_context(state, worker_id)?;
+    let checks = {
+        let rigs = state.rigs.lock().unwrap();
+        rigs.iter().find(|r| r.id == rig_id)?.settings.verification.clone()
+    };
+    if checks.is_empty() {
+        return None;
+    }
+    let timeout = state.settings.lock().unwrap().verification_timeout_seconds;
+
+    let log = |line: String| {
+        let entry = LogEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            stream: "system".to_string(),
+            line,
+        };
+        emit_worker_log(state, app, worker_id, entry);
+    };
+    log(format!("[verify] Running {} check(s) in {}", checks.len(), crew_path));
+    let outcomes = crate::verification::run_checks(&crew_path, &checks, timeout, |o| {
+        let result = if o.passed {
+            "passed".to_string()
+        } else if o.timed_out {
+            "timed out".to_string()
+        } else {
+            format!("failed (exit {})", o.exit_code.map(|c| c.to_string()).unwrap_or_else(|| "?".to_string()))
+        };
+        log(format!("[verify] {}: {} in {:.1}s", o.name, result, o.duration_ms as f64 / 1000.0));
+    });
+    Some(outcomes)
+}
+
+/// Move the run's task to Done when verification passed; otherwise requeue it
+/// (unless a retry is already scheduled) so the next run gets the failing checks as feedback.
+/// Returns the task id when it moved to Done.
+fn apply_verification_to_task(state: &AppState, worker_id: &str, passed: bool) -> Option<String> {
+    let run = {
+        let runs = state.runs.lock().unwrap();
+        runs.iter().rev().find(|r| r.worker_id == worker_id).cloned()
+    }?;
+    if !passed && run.retry_at.is_some() {
+        return None;
+    }
+
+    let mut tasks = state.tasks.lock().unwrap();
+    let task = tasks.iter_mut().find(|t| t.id == run.task_id)?;
+    let old_status = task.status.clone();
+    if passed {
+        if task.status == TaskStatus::Todo {
+            task.status = TaskStatus::InProgress;
+        }
+        if task.status != TaskStatus::InProgress {
+            return None;
+        }
+        let names: Vec<&str> = run.checks.iter().map(|c| c.name.as_str()).collect();
+        task.status = TaskStatus::Done;
+        task.completed_at = Some(chrono::Utc::now().to_rfc3339());
+        task.outcome = Some(format!("Verified: {}", names.join(", ")));
+    } else {
+        if !matches!(task.status, TaskStatus::Todo | TaskStatus::InProgress) {
+            return None;
+        }
+        let failed: Vec<&str> = run
+            .checks
+            .iter()
+            .filter(|c| !c.passed && c.required)
+            .map(|c| c.name.as_str())
+            .collect();
+        task.status = TaskStatus::Todo;
+        task.assigned_worker_id = None;
+        task.outcome = Some(format!("Verification failed: {}", failed.join(", ")));
+    }
+    task.updated_at = chrono::Utc::now().to_rfc3339();
+    state.append_audit_event(&AuditEvent::new(
+        task.rig_id.clone(),
+        task.owner_actor_id.clone(),
+        Some(task.id.clone()),
+        AuditEventType::TaskStatusChanged,
+        serde_json::json!({
+            "old_status": old_status,
+            "new_status": task.status,
+            "run_id": run.id,
+            "verification": if passed { "passed" } else { "failed" },
+        })
+        .to_string(),
+    ));
+    let done = (task.status == TaskStatus::Done).then(|| task.id.clone());
+    state.save_tasks(&tasks);
+    done
+}
+
+/// Surface isolation settings that could not be applied on this machine.
+fn log_launch_warnings(state: &AppState, app: &AppHandle, worker_id: &str, warnings: &[String]) {
+    for warning in warnings {
+        let entry = LogEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            stream: "stderr".to_string(),
+            line: format!("[isolation] {}", warning),
+        };
+        emit_worker_log(state, app, worker_id, entry);
+    }
+}
+
+fn log_entries(state: &AppState, app: &AppHandle, worker_id: &str, entries: &[LogEntry]) {
+    for entry in entries {
+        emit_worker_log(state, app, worker_id, entry.clone());
+    }
+}
+
 fn persist_spawned_worker(
     state: &AppState,
     rig_id: &str,
@@ -329,6 +675,15 @@ fn persist_spawned_worker(
     worker
 }
 
+/// Append `entry` to a worker's log and send the stored copy, with secrets
+/// redacted, to the UI.
+pub(crate) fn emit_worker_log(state: &AppState, app: &AppHandle, worker_id: &str, entry: LogEntry) {
+    let entry = state.append_worker_log(worker_id, entry);
+    if let Err(e) = app.emit("worker-log", (worker_id, &entry)) {
+        eprintln!("Failed to emit worker-log: {}", e);
+    }
+}
+
 fn spawn_output_reader<R: Read + Send + 'static>(
     thread_name: String,
     reader: R,
@@ -344,51 +699,75 @@ fn spawn_output_reader<R: Read + Send + 'static>(
             let mut reader = reader;
             let mut buf = [0u8; 4096];
             let mut line_buf = String::new();
+            let mut carry = String::new();
+
+            let handle = |data: String, line_buf: &mut String| {
+                state
+                    .worker_last_output
+                    .lock()
+                    .unwrap()
+                    .insert(worker_id.clone(), std::time::Instant::now());
+
+                if emit_raw_terminal_data {
+                    state.record_output(&worker_id, &data);
+                    if let Some(screen) = state.worker_screens.lock().unwrap().get_mut(&worker_id) {
+                        screen.process(&data);
+                    }
+                    if let Err(e) = app.emit("worker-pty-data", (&worker_id, &data)) {
+                        eprintln!("Failed to emit worker-pty-data: {}", e);
+                    }
+                }
+
+                line_buf.push_str(&data);
+                while let Some(pos) = line_buf.find('\n') {
+                    let raw_line = line_buf[..pos].trim_end_matches('\r').to_string();
+                    let clean_line = strip_ansi_escapes(&raw_line);
+                    if !clean_line.trim().is_empty() {
+                        emit_worker_log(
+                            &state,
+                            &app,
+                            &worker_id,
+                            LogEntry {
+                                timestamp: chrono::Utc::now().to_rfc3339(),
+                                stream: stream.to_string(),
+                                line: clean_line,
+                            },
+                        );
+                    }
+                    *line_buf = line_buf[pos + 1..].to_string();
+                }
+            };
 
             loop {
                 match reader.read(&mut buf) {
                     Ok(0) => break,
                     Ok(n) => {
-                        let data = String::from_utf8_lossy(&buf[..n]).to_string();
-
-                        if emit_raw_terminal_data {
-                            if let Err(e) = app.emit("worker-pty-data", (&worker_id, &data)) {
-                                eprintln!("Failed to emit worker-pty-data: {}", e);
-                            }
-                        }
-
-                        line_buf.push_str(&data);
-                        while let Some(pos) = line_buf.find('\n') {
-                            let raw_line = line_buf[..pos].trim_end_matches('\r').to_string();
-                            let clean_line = strip_ansi_escapes(&raw_line);
-                            if !clean_line.trim().is_empty() {
-                                let entry = LogEntry {
-                                    timestamp: chrono::Utc::now().to_rfc3339(),
-                                    stream: stream.to_string(),
-                                    line: clean_line,
-                                };
-                                state.append_worker_log(&worker_id, entry.clone());
-                                if let Err(e) = app.emit("worker-log", (&worker_id, &entry)) {
-                                    eprintln!("Failed to emit worker-log: {}", e);
-                                }
-                            }
-                            line_buf = line_buf[pos + 1..].to_string();
-                        }
+                        // Scrub secret env values before the output reaches the UI, screen or
+                        // recording; a secret split across reads is held back until it is whole.
+                        let data = state.redact_stream(&mut carry, &String::from_utf8_lossy(&buf[..n]));
+                        handle(data, &mut line_buf);
                     }
                     Err(_) => break,
                 }
             }
+            let rest = state.redact_stream(&mut carry, "");
+            if !rest.is_empty() {
+                handle(rest, &mut line_buf);
+            }
 
             if !line_buf.trim().is_empty() {
                 let clean = strip_ansi_escapes(&line_buf);
                 if !clean.trim().is_empty() {
-                    let entry = LogEntry {
-                        timestamp: chrono::Utc::now().to_rfc3339(),
-                        stream: stream.to_string(),
-                        line: clean,
-                    };
-                    state.append_worker_log(&worker_id, entry.clone());
-                    let _ = app.emit("worker-log", (&worker_id, &entry));
+                    emit_worker_log(
+                        &state,
+                        &app,
+                        &worker_id,
+                        LogEntry {
+                            timestamp: chrono::Utc::now().to_rfc3339(),
+                            stream: stream.to_string(),
+                            line: clean,
+                        },
+                    );
                 }
             }
         })
@@ -408,6 +787,39 @@ fn finalize_worker_exit(
     let mut auto_commit_hash: Option<String> = None;
     let mut auto_commit_error: Option<String> = None;
 
+    // `delete_worker` already dropped the worker, its log and its lease; logging
+    // here would only re-create the log file.
+    let deleted = !state.workers.lock().unwrap().iter().any(|w| w.id == worker_id);
+    if deleted {
+        {
+            let mut runs = state.runs.lock().unwrap();
+            if let Some(run) = runs
+                .iter_mut()
+                .find(|r| r.worker_id == worker_id && r.status == RunStatus::Running)
+            {
+                run.status = RunStatus::Cancelled;
+                run.finished_at = Some(chrono::Utc::now().to_rfc3339());
+                run.exit_code = exit_code;
+            }
+            state.save_runs(&runs);
+        }
+        state.worker_last_output.lock().unwrap().remove(&worker_id);
+        let _ = app.emit("data-changed", "");
+        crate::scheduler::dispatch(&app);
+        return;
+    }
+
+    // A graceful stop marks the worker Stopped before the process exits; keep
+    // that status instead of reporting the interrupt as a failure.
+    let stop_requested = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .any(|w| w.id == worker_id && w.status == WorkerStatusEnum::Stopped)
+    };
+    let final_status = if stop_requested { WorkerStatusEnum::Stopped } else { final_status };
+    remove_priming_file(&state, &worker_id);
+
     if final_status == WorkerStatusEnum::Failed {
         let failure_line = match exit_code {
             Some(code) => format!("Process exited with non-zero code: {}", code),
@@ -419,14 +831,35 @@ fn finalize_worker_exit(
             line: failure_line,
         };
 
-        state.append_worker_log(&worker_id, failure_entry.clone());
+        emit_worker_log(&state, &app, &worker_id, failure_entry);
+    }
+
+    // Verification gate: only a run whose required checks pass is committed and completes its task.
+    let checks = if final_status == WorkerStatusEnum::Completed {
+        verify_completed_run(&state, &app, &worker_id)
+    } else {
+        None
+    };
+    let verified = checks.as_deref().map(checks_passed).unwrap_or(true);
 
-        if let Err(e) = app.emit("worker-log", (&worker_id, &failure_entry)) {
-            eprintln!("Failed to emit worker-log failure entry: {}", e);
-        }
+    // A required pre_commit hook that fails leaves the changes uncommitted.
+    let commit_blocked = final_status == WorkerStatusEnum::Completed
+        && verified
+        && crate::lifecycle::run_worker_hooks(&state, &app, LifecycleEvent::PreCommit, &worker_id, &[])
+            .iter()
+            .any(|o| o.required && !o.passed);
+    if commit_blocked {
+        let e = "a required pre_commit hook failed".to_string();
+        let warn_entry = LogEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            stream: "stderr".to_string(),
+            line: format!("Auto-commit skipped: {}", e),
+        };
+        emit_worker_log(&state, &app, &worker_id, warn_entry);
+        auto_commit_error = Some(e);
     }
 
-    if final_status == WorkerStatusEnum::Completed {
+    if final_status == WorkerStatusEnum::Completed && verified && !commit_blocked {
         match try_auto_commit_for_completed_run(&state, &worker_id) {
             Ok(Some((commit_hash, rig_id, task_id))) => {
                 auto_commit_hash = Some(commit_hash.clone());
@@ -435,8 +868,7 @@ fn finalize_worker_exit(
                     stream: "stdout".to_string(),
                     line: format!("Auto-committed changes: {}", commit_hash),
                 };
-                state.append_worker_log(&worker_id, info_entry.clone());
-                let _ = app.emit("worker-log", (&worker_id, &info_entry));
+                emit_worker_log(&state, &app, &worker_id, info_entry);
 
                 state.append_audit_event(&AuditEvent::new(
                     rig_id,
@@ -459,17 +891,18 @@ fn finalize_worker_exit(
                     stream: "stderr".to_string(),
                     line: format!("Auto-commit skipped: {}", e),
                 };
-                state.append_worker_log(&worker_id, warn_entry.clone());
-                let _ = app.emit("worker-log", (&worker_id, &warn_entry));
+                emit_worker_log(&state, &app, &worker_id, warn_entry);
             }
         }
     }
 
-    let (is_polecat, polecat_crew_id) = {
+    let (is_polecat, worker_crew_id) = {
         let mut workers = state.workers.lock().unwrap();
         let result = if let Some(w) = workers.iter_mut().find(|w| w.id == worker_id) {
             w.status = final_status.clone();
-            w.stopped_at = Some(chrono::Utc::now().to_rfc3339());
+            if w.stopped_at.is_none() || !stop_requested {
+                w.stopped_at = Some(chrono::Utc::now().to_rfc3339());
+            }
             (w.worker_type == WorkerType::Polecat, Some(w.crew_id.clone()))
         } else {
             (false, None)
@@ -478,25 +911,44 @@ fn finalize_worker_exit(
         result
     };
 
-    let crew_path = {
+    let (run_id, base_commit, crew_path) = {
         let runs = state.runs.lock().unwrap();
-        let crew_id = runs
+        let run = runs
             .iter()
             .find(|r| r.worker_id == worker_id)
-            .map(|r| r.crew_id.clone());
+            .map(|r| (r.id.clone(), r.base_commit.clone(), r.crew_id.clone()));
         drop(runs);
 
-        crew_id.and_then(|cid| {
-            let crews = state.crews.lock().unwrap();
-            crews.iter().find(|c| c.id == cid).map(|c| c.path.clone())
-        })
+        match run {
+            Some((run_id, base_commit, cid)) => {
+                let crews = state.crews.lock().unwrap();
+                let path = crews.iter().find(|c| c.id == cid).map(|c| c.path.clone());
+                (Some(run_id), base_commit, path)
+            }
+            None => (None, None, None),
+        }
     };
 
-    let diff_stats = crew_path.and_then(|path| crate::git::get_diff_stat(&path).ok());
+    // Diff against the run's own base commit, and keep the full patch for after the worktree is gone.
+    let end_commit = crew_path.as_deref().and_then(crate::git::head_commit);
+    let diff_stats = crew_path
+        .as_deref()
+        .and_then(|path| crate::git::get_diff_stat(path, base_commit.as_deref()).ok());
+    if let (Some(path), Some(run_id)) = (crew_path.as_deref(), run_id.as_deref()) {
+        if let Ok(patch) = crate::git::get_diff_since(path, base_commit.as_deref()) {
+            state.save_run_patch(run_id, &patch);
+        }
+    }
+
+    // Usage is parsed from the full on-disk log; the tail cache may have dropped early lines.
+    let usage = crate::usage::parse_usage_from_logs(&state.load_log(&worker_id));
+    let model_prices = state.settings.lock().unwrap().model_prices.clone();
 
     {
         let run_status = match final_status {
+            WorkerStatusEnum::Completed if !verified => RunStatus::Failed,
             WorkerStatusEnum::Completed => RunStatus::Completed,
+            WorkerStatusEnum::Stopped => RunStatus::Cancelled,
             _ => RunStatus::Failed,
         };
         let mut runs = state.runs.lock().unwrap();
@@ -505,40 +957,40 @@ fn finalize_worker_exit(
             run.finished_at = Some(chrono::Utc::now().to_rfc3339());
             run.exit_code = exit_code;
             run.diff_stats = diff_stats;
+            run.end_commit = end_commit;
+            if let Some(ref outcomes) = checks {
+                run.quality_signal = quality_from_checks(outcomes);
+                run.checks = outcomes.clone();
+            }
+            crate::usage::apply_usage(run, &usage, &model_prices);
         }
         state.save_runs(&runs);
     }
-
-    {
-        let mut logs = state.worker_logs.lock().unwrap();
-        if let Some(entries) = logs.remove(&worker_id) {
-            state.save_log(&worker_id, &entries);
-        }
+    // Snapshots are cleaned up once the work is committed; anything else keeps them plus a final one.
+    if let Some(ref run_id) = run_id {
+        let succeeded = final_status == WorkerStatusEnum::Completed && verified && auto_commit_error.is_none();
+        crate::commands::checkpoints::settle_run_checkpoints(&state, &app, run_id, succeeded);
     }
+    crate::lifecycle::run_worker_hooks(&state, &app, LifecycleEvent::PostExit, &worker_id, &[]);
 
-    if is_polecat {
-        if let Some(crew_id) = polecat_crew_id {
-            let polecat_crew = {
-                let crews = state.crews.lock().unwrap();
-                crews.iter().find(|c| c.id == crew_id).cloned()
-            };
-            if let Some(crew) = polecat_crew {
-                let rig_path = {
-                    let rigs = state.rigs.lock().unwrap();
-                    rigs.iter()
-                        .find(|r| r.id == crew.rig_id)
-                        .map(|r| r.path.clone())
-                };
-                if let Some(rp) = rig_path {
-                    let _ = crate::git::remove_worktree(&rp, &crew.path);
-                    let _ = crate::git::delete_branch(&rp, &crew.branch);
-                }
-                let mut crews = state.crews.lock().unwrap();
-                if let Some(c) = crews.iter_mut().find(|c| c.id == crew.id) {
-                    c.status = crate::models::crew::CrewStatus::Removed;
-                }
-                state.save_crews(&crews);
-            }
+    // Retry, review and restart lines after this point go straight to disk.
+    state.close_worker_log(&worker_id);
+    state.finish_recording(&worker_id);
+    state.worker_screens.lock().unwrap().remove(&worker_id);
+    state.worker_last_output.lock().unwrap().remove(&worker_id);
+
+    // Best-of candidates keep their worktree until the comparison is resolved.
+    let (comparison_id, is_replay) = {
+        let runs = state.runs.lock().unwrap();
+        runs.iter()
+            .rev()
+            .find(|r| r.worker_id == worker_id)
+            .map(|r| (r.comparison_id.clone(), r.replay_of.is_some()))
+            .unwrap_or((None, false))
+    };
+    if is_polecat && comparison_id.is_none() {
+        if let Some(ref crew_id) = worker_crew_id {
+            remove_polecat_crew(&state, crew_id);
         }
     }
 
@@ -553,6 +1005,7 @@ fn finalize_worker_exit(
 
     let audit_type = match final_status {
         WorkerStatusEnum::Completed => AuditEventType::WorkerCompleted,
+        WorkerStatusEnum::Stopped => AuditEventType::WorkerStopped,
         _ => AuditEventType::WorkerFailed,
     };
     let rig_id_for_audit = {
@@ -577,22 +1030,159 @@ fn finalize_worker_exit(
         .to_string(),
     ));
 
+    // Done with the worktree: the reviewer, a revision or the next queued spawn may take the crew.
+    if let Some(ref crew_id) = worker_crew_id {
+        crate::commands::crews::release_crew_lease(&state, crew_id, &worker_id);
+    }
+
+    // Best-of candidates are judged by their comparison and replays by the run they
+    // replay; neither is retried or reviewed on its own, nor moves the task.
+    let detached = comparison_id.is_some() || is_replay;
+    if detached {
+        let _ = app.emit("data-changed", "");
+    } else if final_status == WorkerStatusEnum::Failed || !verified {
+        crate::commands::retry::schedule_retry(&state, &app, &worker_id);
+    }
+    // Review-and-revise loop: when the rig reviews runs, the review decides whether the task is done.
+    let reviewing = !detached
+        && final_status == WorkerStatusEnum::Completed
+        && verified
+        && {
+            let run_id = {
+                let runs = state.runs.lock().unwrap();
+                runs.iter().rev().find(|r| r.worker_id == worker_id).map(|r| r.id.clone())
+            };
+            run_id
+                .map(|id| crate::commands::review::start_review(&state, &app, &id, auto_commit_hash.as_deref()))
+                .unwrap_or(false)
+        };
+    if checks.is_some() && !detached && !reviewing {
+        if let Some(task_id) = apply_verification_to_task(&state, &worker_id, verified) {
+            crate::lifecycle::task_done(&app, &task_id);
+        }
+        let _ = app.emit("data-changed", "");
+    }
+    crate::commands::review::finish_agent_review(&state, &app, &worker_id);
+
     if let Err(e) = app.emit("worker-status", (&worker_id, worker_status_to_str(&final_status))) {
         eprintln!("Failed to emit worker-status: {}", e);
     }
+    // The worker's slot is free: admit the next queued spawn.
+    crate::scheduler::dispatch(&app);
+}
+
+/// Create a throwaway worktree on a fresh `polecat/…` branch off `base`
+/// (default: the rig's current branch) and register it as a crew.
+pub(crate) fn create_polecat_crew(
+    state: &AppState,
+    rig_id: &str,
+    base: Option<&str>,
+) -> Result<crate::models::crew::Crew, String> {
+    let rig_path = {
+        let rigs = state.rigs.lock().unwrap();
+        rigs.iter()
+            .find(|r| r.id == rig_id)
+            .map(|r| r.path.clone())
+            .ok_or_else(|| "Rig not found".to_string())?
+    };
+
+    let polecat_slug = format!("polecat-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let branch_name = format!("polecat/{}", polecat_slug);
+    let wt_dir = state.worktrees_dir().join(rig_id);
+    let _ = std::fs::create_dir_all(&wt_dir);
+    let wt_path = wt_dir.join(&polecat_slug);
+    let wt_path_str = wt_path.to_string_lossy().to_string();
+
+    let base_branch = match base {
+        Some(b) => b.to_string(),
+        None => crate::git::get_current_branch(&rig_path).unwrap_or_else(|| "main".to_string()),
+    };
+
+    crate::git::create_worktree(&rig_path, &wt_path_str, &branch_name, &base_branch)?;
+
+    // Register temporary crew
+    let crew = crate::models::crew::Crew::new(
+        rig_id.to_string(),
+        format!("Polecat {}", &polecat_slug[..8]),
+        branch_name,
+        wt_path_str,
+    );
+    {
+        let mut crews = state.crews.lock().unwrap();
+        crews.push(crew.clone());
+        state.save_crews(&crews);
+    }
+    Ok(crew)
+}
+
+/// Remove a polecat's (or reviewer's) worktree and branch and mark its crew Removed.
+pub(crate) fn remove_polecat_crew(state: &AppState, crew_id: &str) {
+    let polecat_crew = {
+        let crews = state.crews.lock().unwrap();
+        crews.iter().find(|c| c.id == crew_id).cloned()
+    };
+    let Some(crew) = polecat_crew else {
+        return;
+    };
+    let rig_path = {
+        let rigs = state.rigs.lock().unwrap();
+        rigs.iter()
+            .find(|r| r.id == crew.rig_id)
+            .map(|r| r.path.clone())
+    };
+    if let Some(rp) = rig_path {
+        let _ = crate::git::remove_worktree(&rp, &crew.path);
+        // Detached checkouts (reviewers) have no branch of their own.
+        if !crew.branch.is_empty() {
+            let _ = crate::git::delete_branch(&rp, &crew.branch);
+        }
+    }
+    let mut crews = state.crews.lock().unwrap();
+    if let Some(c) = crews.iter_mut().find(|c| c.id == crew.id) {
+        c.status = crate::models::crew::CrewStatus::Removed;
+    }
+    state.save_crews(&crews);
 }
 
 // ── Core spawn logic ──
 
-fn spawn_worker_inner(
+pub(crate) fn spawn_worker_inner(
+    crew_id: String,
+    agent_type: String,
+    initial_prompt: String,
+    worker_type: WorkerType,
+    actor_id: Option<String>,
+    app: AppHandle,
+) -> Result<Worker, String> {
+    spawn_worker_with(crew_id, agent_type, initial_prompt, worker_type, actor_id, SpawnExtras::default(), app)
+}
+
+/// Optional parts of a spawn's command line.
+#[derive(Default)]
+struct SpawnExtras<'a> {
+    priming: Option<&'a PrimingPlan>,
+    /// Task the worker works on, for the launch-time priming context.
+    task_id: Option<&'a str>,
+    /// Passed with the agent's model option (see `model_args`).
+    model: Option<&'a str>,
+}
+
+/// Spawn a worker; a `SystemPrompt` or `ContextFile` priming plan goes into
+/// its command line. Terminal injection is left to `start_priming`.
+fn spawn_worker_with(
     crew_id: String,
     agent_type: String,
     initial_prompt: String,
     worker_type: WorkerType,
     actor_id: Option<String>,
+    extras: SpawnExtras<'_>,
     app: AppHandle,
 ) -> Result<Worker, String> {
     let state = app.state::<AppState>();
+    let model_args = match extras.model {
+        Some(model) => model_args(&agent_type, model)?,
+        None => Vec::new(),
+    };
 
     // Find crew to get its path and rig_id
     let crews = state.crews.lock().unwrap();
@@ -602,8 +1192,27 @@ fn spawn_worker_inner(
         .ok_or_else(|| "Crew not found".to_string())?;
     let cwd = crew.path.clone();
     let rig_id = crew.rig_id.clone();
+    let crew_limits = crew.limits.clone();
+    let crew_sandbox = crew.sandbox.clone();
     drop(crews);
 
+    // One worker per worktree: held from here on, freed again if the spawn fails.
+    let lease = crate::commands::crews::acquire_crew_lease(&app, &crew_id)?;
+
+    // Resource limits / sandbox: rig settings with crew overrides on top.
+    let launch = {
+        let rigs = state.rigs.lock().unwrap();
+        let rig = rigs.iter().find(|r| r.id == rig_id);
+        let rig_path = rig.map(|r| r.path.clone()).unwrap_or_default();
+        let limits = rig
+            .map(|r| r.settings.limits.merged(&crew_limits))
+            .unwrap_or(crew_limits);
+        let sandbox = crew_sandbox
+            .or_else(|| rig.map(|r| r.settings.sandbox.clone()))
+            .unwrap_or_default();
+        crate::sandbox::build_launch_wrapper(&limits, &sandbox, &cwd, &rig_path)
+    };
+
     // Resolve the CLI command
     let settings = state.settings.lock().unwrap();
     let has_custom_path = settings.cli_paths.contains_key(&agent_type);
@@ -612,8 +1221,14 @@ fn spawn_worker_inner(
         .get(&agent_type)
         .cloned()
         .unwrap_or_else(|| agent_type.clone());
-    let env_vars = settings.env_vars.clone();
     drop(settings);
+    // The executable plus its model option; the checks below look at the executable alone.
+    let cli_command = std::iter::once(cli_path.clone())
+        .chain(model_args.iter().cloned())
+        .collect::<Vec<_>>()
+        .join(" ");
+    // Layered env profiles; secrets are decrypted only here, at spawn time.
+    let env_vars = crate::secrets::resolve_worker_env(&state, &rig_id, &crew_id, actor_id.as_deref())?;
 
     // Validate CLI exists before trying to spawn
     #[cfg(target_os = "windows")]
@@ -654,51 +1269,107 @@ fn spawn_worker_inner(
         }
     }
 
+    // pre_spawn hooks run before the worker exists; their log is replayed into it once it does.
+    let mut pre_spawn_log = Vec::new();
+    if let Some(ctx) = crate::lifecycle::HookContext::for_crew(&state, &crew_id) {
+        let ctx = ctx
+            .set("TOWNUI_AGENT", agent_type.clone())
+            .set("TOWNUI_WORKER_TYPE", if worker_type == WorkerType::Polecat { "polecat" } else { "crew" });
+        let outcomes = crate::lifecycle::run_hooks(&state, LifecycleEvent::PreSpawn, &ctx, |e| pre_spawn_log.push(e));
+        if let Some(failed) = outcomes.iter().find(|o| o.required && !o.passed) {
+            return Err(format!(
+                "Required pre_spawn hook failed ({}): {}",
+                if failed.timed_out { "timed out".to_string() } else { format!("exit {:?}", failed.exit_code) },
+                failed.command
+            ));
+        }
+    }
 
-    // Build the full command string to send into the interactive shell
-    let prompt_for_shell = sanitize_prompt_for_shell(&initial_prompt);
-    let prompt_for_arg = sanitize_prompt_for_arg(&initial_prompt);
-    let agent_command = match agent_type.as_str() {
+    // Priming the agent reads at startup. Rendered before the worker exists,
+    // so the context has no worker id.
+    let priming_args = match extras.priming.map(|p| (p, p.delivery)) {
+        Some((plan, delivery @ (PrimingDelivery::SystemPrompt | PrimingDelivery::ContextFile))) => {
+            let ctx = crate::prompt_context::build_prompt_context(
+                &state,
+                &crate::prompt_context::ContextRequest {
+                    rig_id: &rig_id,
+                    task_id: extras.task_id,
+                    crew_id: Some(&crew_id),
+                    agent_cli: &agent_type,
+                    actor_id: actor_id.as_deref(),
+                    ..Default::default()
+                },
+            );
+            let (text, render_error) = crate::priming::render_priming(&state, plan.profile.as_ref(), &ctx);
+            if let Some(line) = render_error {
+                pre_spawn_log.push(LogEntry {
+                    timestamp: chrono::Utc::now().to_rfc3339(),
+                    stream: "system".to_string(),
+                    line,
+                });
+            }
+            // Kept out of commits and snapshots; removed when the worker exits.
+            crate::git::exclude_locally(&cwd, &format!("/{}", crate::priming::PRIMING_FILE))?;
+            std::fs::write(std::path::Path::new(&cwd).join(crate::priming::PRIMING_FILE), &text)
+                .map_err(|e| format!("Failed to write priming file: {}", e))?;
+            let adapter = crate::priming::adapter_priming(&agent_type);
+            match delivery {
+                PrimingDelivery::SystemPrompt => adapter
+                    .system_prompt_flag
+                    .map(|flag| format!(" {} {}", flag, priming_flag_value(&text))),
+                _ => adapter
+                    .context_file_flag
+                    .map(|flag| format!(" {} {}", flag, crate::priming::PRIMING_FILE)),
+            }
+            .unwrap_or_default()
+        }
+        _ => String::new(),
+    };
+
+    // Build the full command string to send into the interactive shell
+    let prompt_for_shell = sanitize_prompt_for_shell(&initial_prompt);
+    let prompt_for_arg = sanitize_prompt_for_arg(&initial_prompt);
+    let agent_command = match agent_type.as_str() {
         "claude" => {
             if initial_prompt.is_empty() {
-                cli_path.clone()
+                format!("{}{}", cli_command, priming_args)
             } else {
                 let prompt_path = std::path::Path::new(&cwd).join(".townui_prompt.txt");
                 let _ = std::fs::write(&prompt_path, &initial_prompt);
-                format!("{} --print < .townui_prompt.txt", cli_path)
+                format!("{}{} --print < .townui_prompt.txt", cli_command, priming_args)
             }
         },
         "codex" => {
             if initial_prompt.is_empty() {
-                cli_path.clone()
+                cli_command.clone()
             } else {
                 // Run one-shot task first, then keep the session inside Codex interactive CLI.
                 format!(
                     "{} exec --full-auto -c model_reasoning_effort=low \"{}\" && {}",
-                    cli_path, prompt_for_shell, cli_path
+                    cli_command, prompt_for_shell, cli_command
                 )
             }
         }
         "chatgpt" | "gemini" | "mentat" | "gpt-engineer" | "continue"
         | "trae" | "pear" | "void" | "tabnine" | "supermaven"
         | "codestory" | "double" | "cursor" | "windsurf" | "bolt" => {
-            if initial_prompt.is_empty() { cli_path.clone() }
-            else { format!("{} --prompt \"{}\"", cli_path, prompt_for_shell) }
-        }
-        "copilot" => format!("{} copilot suggest \"{}\"", cli_path, prompt_for_shell),
-        "amazon-q" => format!("{} chat \"{}\"", cli_path, prompt_for_shell),
-        "aider" => format!("{} --message \"{}\" --yes-always --no-git", cli_path, prompt_for_shell),
-        "goose" => format!("{} session --message \"{}\"", cli_path, prompt_for_shell),
-        "openhands" | "swe-agent" => format!("{} run --task \"{}\"", cli_path, prompt_for_shell),
-        "cline" | "augment" | "roo" => format!("{} --message \"{}\"", cli_path, prompt_for_shell),
-        "tabby" | "cody" => format!("{} chat --message \"{}\"", cli_path, prompt_for_shell),
-        "sweep" => format!("{} run \"{}\"", cli_path, prompt_for_shell),
-        "auto-coder" => format!("{} --task \"{}\"", cli_path, prompt_for_shell),
-        "devin" => format!("{} run --task \"{}\"", cli_path, prompt_for_shell),
-        "replit" => format!("{} agent --task \"{}\"", cli_path, prompt_for_shell),
+            if initial_prompt.is_empty() { cli_command.clone() }
+            else { format!("{} --prompt \"{}\"", cli_command, prompt_for_shell) }
+        }
+        "copilot" => format!("{} copilot suggest \"{}\"", cli_command, prompt_for_shell),
+        "amazon-q" => format!("{} chat \"{}\"", cli_command, prompt_for_shell),
+        "aider" => format!("{}{} --message \"{}\" --yes-always --no-git", cli_command, priming_args, prompt_for_shell),
+        "goose" => format!("{} session --message \"{}\"", cli_command, prompt_for_shell),
+        "openhands" | "swe-agent" => format!("{} run --task \"{}\"", cli_command, prompt_for_shell),
+        "cline" | "augment" | "roo" => format!("{} --message \"{}\"", cli_command, prompt_for_shell),
+        "tabby" | "cody" => format!("{} chat --message \"{}\"", cli_command, prompt_for_shell),
+        "sweep" => format!("{} run \"{}\"", cli_command, prompt_for_shell),
+        "auto-coder" => format!("{} --task \"{}\"", cli_command, prompt_for_shell),
+        "devin" => format!("{} run --task \"{}\"", cli_command, prompt_for_shell),
+        "replit" => format!("{} agent --task \"{}\"", cli_command, prompt_for_shell),
         _ => {
-            if initial_prompt.is_empty() { cli_path.clone() }
-            else { format!("{} \"{}\"", cli_path, prompt_for_shell) }
+            if initial_prompt.is_empty() { cli_command.clone() }
+            else { format!("{} \"{}\"", cli_command, prompt_for_shell) }
         }
     };
 
@@ -724,8 +1395,22 @@ fn spawn_worker_inner(
             }
         };
         #[cfg(not(target_os = "windows"))]
-        let mut cmd = std::process::Command::new(&resolved_cli);
+        let mut cmd = match launch.prefix.split_first() {
+            Some((program, args)) => {
+                let mut c = std::process::Command::new(program);
+                c.args(args).arg(&resolved_cli);
+                c
+            }
+            None => std::process::Command::new(&resolved_cli),
+        };
+        // Own process group so `kill -9 -<pid>` reaches every descendant.
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
 
+        cmd.args(&model_args);
         cmd.current_dir(&cwd);
         for (k, v) in &env_vars {
             cmd.env(k, v);
@@ -761,7 +1446,10 @@ fn spawn_worker_inner(
             actor_id,
             pid,
         );
+        lease.commit(&worker.id);
         let worker_id = worker.id.clone();
+        log_launch_warnings(&state, &app, &worker_id, &launch.warnings);
+        log_entries(&state, &app, &worker_id, &pre_spawn_log);
 
         if let Some(stdout) = child.stdout.take() {
             spawn_output_reader(
@@ -810,6 +1498,58 @@ fn spawn_worker_inner(
         return Ok(worker);
     }
 
+    let (use_session_host, scrollback_lines) = {
+        let settings = state.settings.lock().unwrap();
+        (
+            settings.session_backend == SessionBackend::Tmux,
+            settings.session_scrollback_lines,
+        )
+    };
+    if use_session_host && crate::sessions::tmux_available() {
+        let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string());
+        let session = crate::sessions::new_session_name();
+        let exit_file = crate::sessions::exit_code_path(&state.sessions_dir(), &session);
+        crate::sessions::create_session(
+            &session,
+            &cwd,
+            &shell,
+            &env_vars,
+            &exit_file,
+            scrollback_lines,
+            (120, 24),
+        )?;
+
+        let pid = crate::sessions::pane_pid(&session);
+        let mut worker = persist_spawned_worker(
+            &state,
+            &rig_id,
+            &crew_id,
+            &agent_type,
+            worker_type,
+            actor_id,
+            pid,
+        );
+        lease.commit(&worker.id);
+        worker.session = Some(session.clone());
+        {
+            let mut workers = state.workers.lock().unwrap();
+            if let Some(w) = workers.iter_mut().find(|w| w.id == worker.id) {
+                w.session = Some(session.clone());
+            }
+            state.save_workers(&workers);
+        }
+        log_launch_warnings(&state, &app, &worker.id, &launch.warnings);
+        log_entries(&state, &app, &worker.id, &pre_spawn_log);
+
+        let started = attach_session_pty(&state, &app, &worker.id, &session)
+            .and_then(|_| crate::sessions::send_line(&session, &launch.wrap_shell_command(&agent_command)));
+        if let Err(e) = started {
+            abort_session_spawn(&state, &worker.id, &crew_id, &session);
+            return Err(e);
+        }
+        return Ok(worker);
+    }
+
     #[cfg(target_os = "windows")]
     let mut cmd = CommandBuilder::new("cmd");
     #[cfg(not(target_os = "windows"))]
@@ -848,7 +1588,12 @@ fn spawn_worker_inner(
         actor_id,
         pid,
     );
+    lease.commit(&worker.id);
     let worker_id = worker.id.clone();
+    log_launch_warnings(&state, &app, &worker_id, &launch.warnings);
+    log_entries(&state, &app, &worker_id, &pre_spawn_log);
+    state.start_recording(&worker_id, 120, 24, &recording_title(&agent_type, &worker_id));
+    start_screen(&state, &worker_id);
 
     drop(pair.slave);
 
@@ -870,7 +1615,7 @@ fn spawn_worker_inner(
     {
         let mut writers = state.worker_writers.lock().unwrap();
         if let Some(w) = writers.get_mut(&worker_id) {
-            let cmd_line = format!("{}\r\n", agent_command);
+            let cmd_line = format!("{}\r\n", launch.wrap_shell_command(&agent_command));
             let _ = w.write_all(cmd_line.as_bytes());
             let _ = w.flush();
         }
@@ -916,110 +1661,417 @@ fn spawn_worker_inner(
     Ok(worker)
 }
 
-// ── Startup Priming ──
+/// Lines kept above the emulated screen for `get_worker_screen`.
+const SCREEN_SCROLLBACK_LINES: usize = 2000;
 
-/// Build the priming context string injected right after an agent starts.
-/// Mirrors Gas Town's `gt prime` / startup context injection.
-fn build_priming_context(state: &AppState, worker_id: &str, crew_name: Option<&str>, task_title: Option<&str>, rig_id: &str) -> String {
-    let custom_template = {
-        let settings = state.settings.lock().unwrap();
-        settings.priming_template.clone()
+fn start_screen(state: &AppState, worker_id: &str) {
+    state.worker_screens.lock().unwrap().insert(
+        worker_id.to_string(),
+        crate::vt::Screen::new(24, 120, SCREEN_SCROLLBACK_LINES),
+    );
+}
+
+fn recording_title(agent_type: &str, worker_id: &str) -> String {
+    format!("{} worker {}", agent_type, &worker_id[..8.min(worker_id.len())])
+}
+
+// ── Session host (tmux) ──
+
+/// Attach an app-owned PTY client to a worker's tmux session and wire its
+/// reader, writer and exit handling exactly like a directly spawned PTY.
+fn attach_session_pty(
+    state: &AppState,
+    app: &AppHandle,
+    worker_id: &str,
+    session: &str,
+) -> Result<(), String> {
+    let argv = crate::sessions::attach_argv(session);
+    let mut cmd = CommandBuilder::new(&argv[0]);
+    cmd.args(&argv[1..]);
+    cmd.env("TERM", "xterm-256color");
+
+    let pair = native_pty_system()
+        .openpty(PtySize {
+            rows: 24,
+            cols: 120,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("Failed to open PTY: {}", e))?;
+    let mut client = pair
+        .slave
+        .spawn_command(cmd)
+        .map_err(|e| format!("Failed to attach to session {}: {}", session, e))?;
+    drop(pair.slave);
+
+    let reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|e| format!("Failed to clone PTY reader: {}", e))?;
+    let writer = pair
+        .master
+        .take_writer()
+        .map_err(|e| format!("Failed to take PTY writer: {}", e))?;
+    state
+        .worker_writers
+        .lock()
+        .unwrap()
+        .insert(worker_id.to_string(), writer);
+    state
+        .worker_pty_masters
+        .lock()
+        .unwrap()
+        .insert(worker_id.to_string(), pair.master);
+
+    let agent_type = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .find(|w| w.id == worker_id)
+            .map(|w| w.agent_type.clone())
+            .unwrap_or_default()
     };
-    if let Some(tpl) = custom_template {
-        return tpl
-            .replace("{{rig_id}}", rig_id)
-            .replace("{{worker_id}}", worker_id)
-            .replace("{{crew_name}}", crew_name.unwrap_or(""))
-            .replace("{{task_title}}", task_title.unwrap_or(""));
-    }
-    // Default priming: concise context injection
-    let mut parts = vec![
-        format!("# TownUI Context (do not reply to this message)"),
-        format!("rig: {}", rig_id),
-        format!("worker_id: {}", &worker_id[..8.min(worker_id.len())]),
-    ];
-    if let Some(cn) = crew_name { parts.push(format!("crew: {}", cn)); }
-    if let Some(tt) = task_title { parts.push(format!("task: {}", tt)); }
-    parts.push("# End of context. Proceed with your task.".to_string());
-    parts.join("\n")
-}
-
-fn start_priming_if_enabled(state: &AppState, worker: &Worker, app: &AppHandle) {
-    let (enabled, delay_ms) = {
-        let settings = state.settings.lock().unwrap();
-        (settings.startup_priming_enabled, settings.priming_delay_ms)
+    state.start_recording(worker_id, 120, 24, &recording_title(&agent_type, worker_id));
+    start_screen(state, worker_id);
+
+    spawn_output_reader(
+        format!("worker-{}-pty", &worker_id[..8]),
+        reader,
+        worker_id.to_string(),
+        "stdout",
+        true,
+        app.clone(),
+    );
+
+    let app_wait = app.clone();
+    let worker_id_wait = worker_id.to_string();
+    let session = session.to_string();
+    thread::Builder::new()
+        .name(format!("worker-{}-wait", &worker_id_wait[..8]))
+        .spawn(move || {
+            let client_status = client.wait();
+            let state = app_wait.state::<AppState>();
+            let stop_requested = {
+                let workers = state.workers.lock().unwrap();
+                workers
+                    .iter()
+                    .any(|w| w.id == worker_id_wait && w.status == WorkerStatusEnum::Stopped)
+            };
+            if stop_requested {
+                // The staged stop kills the pane; make sure the session is gone too.
+                if !wait_until(3000, || !crate::sessions::has_session(&session)) {
+                    crate::sessions::kill_session(&session);
+                }
+            } else if crate::sessions::has_session(&session) {
+                // Only the client went away (app exiting or detached); the agent lives on.
+                return;
+            }
+            let exit_file = crate::sessions::exit_code_path(&state.sessions_dir(), &session);
+            let exit_code = crate::sessions::take_exit_code(&exit_file)
+                .or_else(|| client_status.ok().map(|s| s.exit_code() as i32));
+            let final_status = match exit_code {
+                Some(0) => WorkerStatusEnum::Completed,
+                _ => WorkerStatusEnum::Failed,
+            };
+            finalize_worker_exit(app_wait, worker_id_wait, final_status, exit_code);
+        })
+        .map_err(|e| format!("Failed to spawn wait thread: {}", e))?;
+    Ok(())
+}
+
+/// Undo a tmux spawn that failed after its worker was registered: end the
+/// session, mark the worker failed and free its crew.
+fn abort_session_spawn(state: &AppState, worker_id: &str, crew_id: &str, session: &str) {
+    crate::sessions::kill_session(session);
+    {
+        let mut workers = state.workers.lock().unwrap();
+        if let Some(w) = workers.iter_mut().find(|w| w.id == worker_id) {
+            w.status = WorkerStatusEnum::Failed;
+            w.stopped_at = Some(chrono::Utc::now().to_rfc3339());
+        }
+        state.save_workers(&workers);
+    }
+    crate::commands::crews::release_crew_lease(state, crew_id, worker_id);
+    state.worker_writers.lock().unwrap().remove(worker_id);
+    state.worker_pty_masters.lock().unwrap().remove(worker_id);
+    state.close_worker_log(worker_id);
+    state.finish_recording(worker_id);
+    state.worker_screens.lock().unwrap().remove(worker_id);
+}
+
+/// Log entries loaded back into the tail cache when reattaching a session.
+const LOG_TAIL_RESTORE: usize = 500;
+
+/// Rediscover workers marked running from a previous app session: reattach
+/// the ones whose tmux session is still alive and finalize the rest.
+pub(crate) fn reattach_worker_sessions(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let running: Vec<Worker> = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .filter(|w| w.status == WorkerStatusEnum::Running)
+            .cloned()
+            .collect()
     };
-    if !enabled { return; }
+    if running.is_empty() {
+        return;
+    }
+    let host_available = crate::sessions::tmux_available();
+
+    for worker in running {
+        let live_session = worker
+            .session
+            .clone()
+            .filter(|s| host_available && crate::sessions::has_session(s));
+        match live_session {
+            Some(session) => {
+                if let Err(e) = reattach_worker(&state, app, &worker, &session) {
+                    eprintln!("[sessions] failed to reattach worker {}: {}", worker.id, e);
+                }
+            }
+            None => finalize_lost_session(&state, app, &worker, host_available),
+        }
+    }
+    let _ = app.emit("data-changed", "");
+}
 
-    // Gather context for priming
-    let (crew_name, task_title) = {
-        let crew_name = {
-            let crews = state.crews.lock().unwrap();
-            crews.iter().find(|c| c.id == worker.crew_id).map(|c| c.name.clone())
-        };
-        let task_title = {
-            let runs = state.runs.lock().unwrap();
-            runs.iter()
-                .find(|r| r.worker_id == worker.id)
-                .and_then(|r| {
-                    let tasks = state.tasks.lock().unwrap();
-                    tasks.iter().find(|t| t.id == r.task_id).map(|t| t.title.clone())
-                })
-        };
-        (crew_name, task_title)
+fn reattach_worker(state: &AppState, app: &AppHandle, worker: &Worker, session: &str) -> Result<(), String> {
+    // Warm the tail cache from the on-disk log; new output keeps appending to it.
+    let total = state.count_log_entries(&worker.id);
+    let tail = state.load_log_page(&worker.id, total.saturating_sub(LOG_TAIL_RESTORE), None);
+    state.worker_logs.lock().unwrap().insert(worker.id.clone(), tail);
+    // Usage reported before the app restarted counts toward live budgets too.
+    let usage = crate::usage::parse_usage_from_logs(&state.load_log(&worker.id));
+    state.worker_usage.lock().unwrap().insert(worker.id.clone(), usage);
+    state.append_worker_log(
+        &worker.id,
+        LogEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            stream: "system".to_string(),
+            line: format!("[session] Reattached to {}", session),
+        },
+    );
+
+    let pid = crate::sessions::pane_pid(session).or(worker.pid);
+    {
+        let mut workers = state.workers.lock().unwrap();
+        if let Some(w) = workers.iter_mut().find(|w| w.id == worker.id) {
+            w.pid = pid;
+        }
+        state.save_workers(&workers);
+    }
+
+    attach_session_pty(state, app, &worker.id, session)?;
+
+    state.append_audit_event(&AuditEvent::new(
+        worker.rig_id.clone(),
+        worker.actor_id.clone(),
+        None,
+        AuditEventType::WorkerReattached,
+        serde_json::json!({
+            "worker_id": worker.id,
+            "session": session,
+            "pid": pid,
+        })
+        .to_string(),
+    ));
+    Ok(())
+}
+
+fn finalize_lost_session(state: &AppState, app: &AppHandle, worker: &Worker, host_available: bool) {
+    let exit_code = worker.session.as_ref().and_then(|s| {
+        crate::sessions::take_exit_code(&crate::sessions::exit_code_path(&state.sessions_dir(), s))
+    });
+    let reason = match (&worker.session, host_available) {
+        (Some(_), true) if exit_code.is_some() => "session exited while TownUI was closed",
+        (Some(_), true) => "session no longer exists",
+        (Some(_), false) => "tmux is not available to reattach the session",
+        (None, _) => "worker had no session host and did not survive the restart",
     };
 
-    let priming_text = build_priming_context(
-        state,
+    state.append_worker_log(
         &worker.id,
-        crew_name.as_deref(),
-        task_title.as_deref(),
-        &worker.rig_id,
+        LogEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            stream: "system".to_string(),
+            line: format!("[session] Lost on restart: {}", reason),
+        },
     );
-    let worker_id = worker.id.clone();
+
+    state.append_audit_event(&AuditEvent::new(
+        worker.rig_id.clone(),
+        worker.actor_id.clone(),
+        None,
+        AuditEventType::WorkerSessionLost,
+        serde_json::json!({
+            "worker_id": worker.id,
+            "session": worker.session,
+            "stale_pid": worker.pid,
+            "exit_code": exit_code,
+            "reason": reason,
+        })
+        .to_string(),
+    ));
+
+    let final_status = match exit_code {
+        Some(0) => WorkerStatusEnum::Completed,
+        _ => WorkerStatusEnum::Failed,
+    };
+    finalize_worker_exit(app.clone(), worker.id.clone(), final_status, exit_code);
+}
+
+// ── Startup Priming ──
+
+/// Build the priming text typed into a running agent, from its latest run.
+/// Mirrors Gas Town's `gt prime` / startup context injection.
+fn build_priming_context(
+    state: &AppState,
+    app: &AppHandle,
+    worker: &Worker,
+    profile: Option<&crate::models::priming::PrimingProfile>,
+) -> String {
+    let run = {
+        let runs = state.runs.lock().unwrap();
+        runs.iter().rev().find(|r| r.worker_id == worker.id).cloned()
+    };
+    let mut ctx = crate::prompt_context::build_prompt_context(
+        state,
+        &crate::prompt_context::ContextRequest {
+            rig_id: &worker.rig_id,
+            task_id: run.as_ref().map(|r| r.task_id.as_str()),
+            crew_id: Some(&worker.crew_id),
+            agent_cli: &worker.agent_type,
+            worker_id: Some(&worker.id),
+            actor_id: worker.actor_id.as_deref(),
+            previous_run_id: run.as_ref().and_then(|r| r.previous_run_id.as_deref()),
+        },
+    );
+    // Workers spawned outside a run still carry a label for what they are doing.
+    if ctx.task.title.is_empty() {
+        ctx.task.title = worker.task_label.clone().unwrap_or_default();
+    }
+    let (text, render_error) = crate::priming::render_priming(state, profile, &ctx);
+    if let Some(line) = render_error {
+        log_priming(state, app, &worker.id, line);
+    }
+    text
+}
+
+fn log_priming(state: &AppState, app: &AppHandle, worker_id: &str, line: String) {
+    let entry = LogEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        stream: "system".to_string(),
+        line,
+    };
+    emit_worker_log(state, app, worker_id, entry);
+}
+
+/// Delete the launch-time priming file from the worker's worktree.
+fn remove_priming_file(state: &AppState, worker_id: &str) {
+    let crew_id = {
+        let workers = state.workers.lock().unwrap();
+        workers.iter().find(|w| w.id == worker_id).map(|w| w.crew_id.clone())
+    };
+    let crew_path = crew_id.and_then(|crew_id| {
+        let crews = state.crews.lock().unwrap();
+        crews.iter().find(|c| c.id == crew_id).map(|c| c.path.clone())
+    });
+    if let Some(path) = crew_path {
+        let _ = std::fs::remove_file(std::path::Path::new(&path).join(crate::priming::PRIMING_FILE));
+    }
+}
+
+fn mark_primed(state: &AppState, worker_id: &str) {
+    let mut workers = state.workers.lock().unwrap();
+    if let Some(w) = workers.iter_mut().find(|w| w.id == worker_id) {
+        w.startup_primed = true;
+    }
+    state.save_workers(&workers);
+}
+
+/// Finish priming a freshly spawned worker. Command-line and context-file
+/// deliveries already happened at launch; terminal injection waits for the
+/// agent's ready pattern (or `priming_delay_ms` without one) first.
+fn start_priming(state: &AppState, worker: &Worker, plan: &PrimingPlan, app: &AppHandle) {
+    let profile_note = plan
+        .profile
+        .as_ref()
+        .map(|p| format!(" (profile '{}')", p.name))
+        .unwrap_or_default();
+    match plan.delivery {
+        PrimingDelivery::SystemPrompt | PrimingDelivery::ContextFile => {
+            mark_primed(state, &worker.id);
+            let how = if plan.delivery == PrimingDelivery::SystemPrompt { "system prompt" } else { "context file" };
+            log_priming(state, app, &worker.id, format!("[primed] Startup context passed as {}{}", how, profile_note));
+            return;
+        }
+        PrimingDelivery::PtyInject => {}
+        PrimingDelivery::Auto | PrimingDelivery::Off => {
+            if plan.profile.as_ref().is_some_and(|p| p.delivery != PrimingDelivery::Off) {
+                log_priming(
+                    state,
+                    app,
+                    &worker.id,
+                    format!("[priming] Skipped{}: {} runs one-shot and has no priming flag", profile_note, worker.agent_type),
+                );
+            }
+            return;
+        }
+    }
+
+    let (delay_ms, timeout_ms) = {
+        let settings = state.settings.lock().unwrap();
+        (settings.priming_delay_ms, settings.priming_ready_timeout_ms)
+    };
+    let worker = worker.clone();
+    let plan = plan.clone();
     let app = app.clone();
 
     thread::Builder::new()
-        .name(format!("priming-{}", &worker_id[..8.min(worker_id.len())]))
+        .name(format!("priming-{}", &worker.id[..8.min(worker.id.len())]))
         .spawn(move || {
-            // Wait for agent to initialise
-            thread::sleep(std::time::Duration::from_millis(delay_ms));
-
             let state = app.state::<AppState>();
 
+            // Wait for the agent to accept input
+            match plan.ready_pattern.as_deref() {
+                Some(pattern) => {
+                    let timeout = std::time::Duration::from_millis(timeout_ms);
+                    match crate::priming::wait_for_ready(&state, &worker.id, pattern, timeout) {
+                        Ok(true) => {}
+                        Ok(false) => log_priming(
+                            &state,
+                            &app,
+                            &worker.id,
+                            format!("[priming] Ready pattern not seen after {} ms; injecting anyway", timeout_ms),
+                        ),
+                        Err(e) => {
+                            log_priming(&state, &app, &worker.id, format!("[priming] {}", e));
+                            thread::sleep(std::time::Duration::from_millis(delay_ms));
+                        }
+                    }
+                }
+                None => thread::sleep(std::time::Duration::from_millis(delay_ms)),
+            }
+
             // Check worker is still running before injecting
             let still_running = {
                 let workers = state.workers.lock().unwrap();
-                workers.iter().any(|w| w.id == worker_id && w.status == WorkerStatusEnum::Running)
+                workers.iter().any(|w| w.id == worker.id && w.status == WorkerStatusEnum::Running)
             };
             if !still_running { return; }
 
-            // Send priming text via PTY writer
+            // Rendered now so the text includes the run linked after the spawn
+            let priming_text = build_priming_context(&state, &app, &worker, plan.profile.as_ref());
             {
                 let mut writers = state.worker_writers.lock().unwrap();
-                if let Some(writer) = writers.get_mut(&worker_id) {
+                if let Some(writer) = writers.get_mut(&worker.id) {
                     let _ = writer.write_all(format!("{}\r\n", priming_text).as_bytes());
                     let _ = writer.flush();
                 }
             }
-
-            // Mark worker as primed
-            {
-                let mut workers = state.workers.lock().unwrap();
-                if let Some(w) = workers.iter_mut().find(|w| w.id == worker_id) {
-                    w.startup_primed = true;
-                }
-                state.save_workers(&workers);
-            }
-
-            // Log
-            let entry = LogEntry {
-                timestamp: chrono::Utc::now().to_rfc3339(),
-                stream: "system".to_string(),
-                line: "[primed] Startup context injected".to_string(),
-            };
-            state.append_worker_log(&worker_id, entry.clone());
-            let _ = app.emit("worker-log", (&worker_id, &entry));
+            mark_primed(&state, &worker.id);
+            log_priming(&state, &app, &worker.id, format!("[primed] Startup context injected{}", profile_note));
         })
         .ok();
 }
@@ -1031,14 +2083,25 @@ pub(crate) fn spawn_worker_for_actor(
     agent_type: String,
     initial_prompt: String,
     actor_id: Option<String>,
+    task_id: Option<&str>,
     app: AppHandle,
 ) -> Result<Worker, String> {
-    let res = spawn_worker_inner(
+    let priming = {
+        let state = app.state::<AppState>();
+        let reads_terminal = agent_reads_terminal(&agent_type, &initial_prompt);
+        crate::priming::plan_priming(&state, &crew_id, &agent_type, actor_id.as_deref(), reads_terminal)
+    };
+    let res = spawn_worker_with(
         crew_id,
         agent_type,
         initial_prompt,
         WorkerType::Crew,
         actor_id,
+        SpawnExtras {
+            priming: priming.as_ref(),
+            task_id,
+            ..Default::default()
+        },
         app.clone(),
     );
     if let Ok(ref worker) = res {
@@ -1069,119 +2132,93 @@ pub(crate) fn spawn_worker_for_actor(
             }
             state.save_workers(&workers);
         }
-        start_priming_if_enabled(&state, worker, &app);
+        if let Some(ref plan) = priming {
+            start_priming(&state, worker, plan, &app);
+        }
         let _ = app.emit("data-changed", "");
     }
     res
 }
 
+/// Scheduler request for an interactive spawn, which cannot wait in the queue.
+fn manual_spawn_request(rig_id: &str, crew_id: Option<&str>, agent_type: &str, polecat: bool) -> QueuedSpawn {
+    let mut request = QueuedSpawn::new(
+        rig_id,
+        agent_type,
+        crate::models::task::TaskPriority::Critical,
+        SpawnSource::Manual,
+        "manual spawn",
+    );
+    request.crew_id = crew_id.map(str::to_string);
+    request.polecat = polecat;
+    request
+}
+
 #[tauri::command]
-pub fn spawn_worker(
+pub async fn spawn_worker(
     crew_id: String,
     agent_type: String,
     initial_prompt: String,
     app: AppHandle,
 ) -> Result<Worker, String> {
-    spawn_worker_for_actor(crew_id, agent_type, initial_prompt, None, app)
+    // Off the main thread: pre_spawn hooks may block for a while.
+    tokio::task::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let rig_id = {
+            let crews = state.crews.lock().unwrap();
+            crews
+                .iter()
+                .find(|c| c.id == crew_id)
+                .map(|c| c.rig_id.clone())
+                .ok_or_else(|| "Crew not found".to_string())?
+        };
+        let request = manual_spawn_request(&rig_id, Some(&crew_id), &agent_type, false);
+        crate::scheduler::submit_now(&app, request, |app| {
+            spawn_worker_for_actor(crew_id, agent_type, initial_prompt, None, None, app.clone())
+        })
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
 }
 
 #[tauri::command]
-pub fn spawn_polecat(
+pub async fn spawn_polecat(
     rig_id: String,
     agent_type: String,
     initial_prompt: String,
     actor_id: Option<String>,
-    state: State<AppState>,
     app: AppHandle,
 ) -> Result<Worker, String> {
-    // Get rig path
-    let rig_path = {
-        let rigs = state.rigs.lock().unwrap();
-        rigs.iter()
-            .find(|r| r.id == rig_id)
-            .map(|r| r.path.clone())
-            .ok_or_else(|| "Rig not found".to_string())?
-    };
+    tokio::task::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let request = manual_spawn_request(&rig_id, None, &agent_type, true);
+        crate::scheduler::submit_now(&app, request, |app| {
+            let crew_id = create_polecat_crew(&state, &rig_id, None)?.id;
+            let res = spawn_worker_inner(crew_id.clone(), agent_type, initial_prompt, WorkerType::Polecat, actor_id, app.clone());
+            match res {
+                Ok(_) => {
+                    let _ = app.emit("data-changed", "");
+                }
+                Err(_) => remove_polecat_crew(&state, &crew_id),
+            }
+            res
+        })
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
 
-    // Create a temporary worktree for this polecat
-    let polecat_slug = format!("polecat-{}", &uuid::Uuid::new_v4().to_string()[..8]);
-    let branch_name = format!("polecat/{}", polecat_slug);
-    let wt_dir = state.worktrees_dir().join(&rig_id);
-    let _ = std::fs::create_dir_all(&wt_dir);
-    let wt_path = wt_dir.join(&polecat_slug);
-    let wt_path_str = wt_path.to_string_lossy().to_string();
-
-    // Determine base branch
-    let base_branch = crate::git::get_current_branch(&rig_path)
-        .unwrap_or_else(|| "main".to_string());
-
-    crate::git::create_worktree(&rig_path, &wt_path_str, &branch_name, &base_branch)?;
-
-    // Register as a temporary crew
-    let crew = crate::models::crew::Crew::new(
-        rig_id.clone(),
-        format!("Polecat {}", &polecat_slug[..8]),
-        branch_name,
-        wt_path_str,
-    );
-    let crew_id = crew.id.clone();
-    {
-        let mut crews = state.crews.lock().unwrap();
-        crews.push(crew);
-        state.save_crews(&crews);
-    }
-
-    let res = spawn_worker_inner(crew_id, agent_type, initial_prompt, WorkerType::Polecat, actor_id, app.clone());
-    if res.is_ok() {
-        let _ = app.emit("data-changed", "");
-    }
-    res
-}
-
-#[tauri::command]
-pub fn stop_worker(id: String, state: State<AppState>, app: AppHandle) -> Result<(), String> {
-    let mut workers = state.workers.lock().unwrap();
-    let worker = workers
-        .iter_mut()
-        .find(|w| w.id == id)
-        .ok_or_else(|| "Worker not found".to_string())?;
-
-    if let Some(pid) = worker.pid {
-        kill_process_tree(pid);
-    }
-
-    worker.status = WorkerStatusEnum::Stopped;
-    worker.stopped_at = Some(chrono::Utc::now().to_rfc3339());
-    let rig_id = worker.rig_id.clone();
-    let worker_id = worker.id.clone();
-    state.save_workers(&workers);
-    drop(workers);
-
-    // Remove writer and PTY master
-    {
-        let mut writers = state.worker_writers.lock().unwrap();
-        writers.remove(&worker_id);
-    }
-    {
-        let mut masters = state.worker_pty_masters.lock().unwrap();
-        masters.remove(&worker_id);
-    }
-
-    // Audit: worker stopped
-    state.append_audit_event(&AuditEvent::new(
-        rig_id,
-        None,
-        None,
-        AuditEventType::WorkerStopped,
-        serde_json::json!({ "worker_id": worker_id }).to_string(),
-    ));
-
-    let _ = app.emit("data-changed", "");
-    Ok(())
-}
+#[tauri::command]
+pub fn stop_worker(id: String, state: State<AppState>, app: AppHandle) -> Result<(), String> {
+    // The staged interrupt → SIGTERM → SIGKILL sequence runs on its own thread.
+    begin_graceful_stop(&state, &app, &id, "user")?;
+    let _ = app.emit("data-changed", "");
+    Ok(())
+}
 
 #[tauri::command]
 pub fn delete_worker(id: String, state: State<AppState>, app: AppHandle) -> Result<(), String> {
+    remove_priming_file(&state, &id);
     let mut workers = state.workers.lock().unwrap();
     let idx = workers
         .iter()
@@ -1196,10 +2233,16 @@ pub fn delete_worker(id: String, state: State<AppState>, app: AppHandle) -> Resu
             kill_process_tree(pid);
         }
     }
+    if let Some(session) = &worker.session {
+        crate::sessions::kill_session(session);
+    }
 
+    // Exit handling skips deleted workers, so free the crew here.
+    let crew_id = worker.crew_id.clone();
     workers.remove(idx);
     state.save_workers(&workers);
     drop(workers);
+    crate::commands::crews::release_crew_lease(&state, &crew_id, &id);
 
     // Also remove in-memory logs
     {
@@ -1217,8 +2260,11 @@ pub fn delete_worker(id: String, state: State<AppState>, app: AppHandle) -> Resu
         masters.remove(&id);
     }
 
-    // Delete log file from disk
+    // Delete log file and recording from disk
     state.delete_log(&id);
+    state.finish_recording(&id);
+    std::fs::remove_file(state.recording_path(&id)).ok();
+    state.worker_screens.lock().unwrap().remove(&id);
 
     let _ = app.emit("data-changed", "");
     Ok(())
@@ -1245,107 +2291,331 @@ pub fn list_workers(rig_id: String, state: State<AppState>) -> Vec<Worker> {
 }
 
 #[tauri::command]
-pub fn get_worker_logs(id: String, state: State<AppState>) -> Vec<LogEntry> {
-    // First check in-memory logs
-    let logs = state.worker_logs.lock().unwrap();
-    if let Some(entries) = logs.get(&id) {
-        return entries.clone();
+pub fn get_worker_logs(
+    id: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    state: State<AppState>,
+) -> Vec<LogEntry> {
+    // Paged reads always go to the full on-disk log
+    if offset.is_some() || limit.is_some() {
+        return state.load_log_page(&id, offset.unwrap_or(0), limit);
+    }
+
+    // Live workers are served from the in-memory tail
+    let running = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .any(|w| w.id == id && w.status == WorkerStatusEnum::Running)
+    };
+    if running {
+        let logs = state.worker_logs.lock().unwrap();
+        if let Some(entries) = logs.get(&id) {
+            return entries.clone();
+        }
     }
-    drop(logs);
 
     // Fall back to disk
     state.load_log(&id)
 }
 
+/// Total number of log entries on disk for a worker, for paging with `get_worker_logs`.
+#[tauri::command]
+pub fn count_worker_logs(id: String, state: State<AppState>) -> usize {
+    state.count_log_entries(&id)
+}
+
 // ── Run/Execute commands ──
 
 #[tauri::command]
-pub fn execute_task(
+pub async fn execute_task(
     task_id: String,
     crew_id: String,
     agent_type: String,
     template_name: String,
-    state: State<AppState>,
     app: AppHandle,
 ) -> Result<Run, String> {
-    let effective_agent_type = if agent_type.trim().is_empty() {
-        let settings = state.settings.lock().unwrap();
-        if settings.default_cli.trim().is_empty() {
-            "claude".to_string()
-        } else {
-            settings.default_cli.clone()
-        }
-    } else {
-        agent_type.clone()
-    };
+    tokio::task::spawn_blocking(move || {
+        execute_task_inner(&app, task_id, crew_id, agent_type, template_name, None)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Links a new run to the one it follows up on (a retry of a failed attempt).
+pub(crate) struct RunFollowUp {
+    pub previous_run_id: String,
+    pub attempt: u32,
+    pub revision_count: u32,
+    /// Appended to the rendered prompt (e.g. why the last attempt failed).
+    pub context: String,
+}
+
+pub(crate) fn execute_task_inner(
+    app: &AppHandle,
+    task_id: String,
+    crew_id: String,
+    agent_type: String,
+    template_name: String,
+    follow_up: Option<RunFollowUp>,
+) -> Result<Run, String> {
+    let state = app.state::<AppState>();
 
     // Get task
     let tasks = state.tasks.lock().unwrap();
-    let task = tasks
-        .iter()
-        .find(|t| t.id == task_id)
-        .ok_or_else(|| "Task not found".to_string())?;
-    let task_title = task.title.clone();
-    let task_description = task.description.clone();
+    if !tasks.iter().any(|t| t.id == task_id) {
+        return Err("Task not found".to_string());
+    }
     drop(tasks);
 
+    // A task requeued by a failed verification carries the failing checks into its next run.
+    let follow_up = follow_up.or_else(|| crate::commands::retry::verification_feedback(&state, &task_id));
+
     // Get crew
     let crews = state.crews.lock().unwrap();
     let crew = crews
         .iter()
         .find(|c| c.id == crew_id)
         .ok_or_else(|| "Crew not found".to_string())?;
-    let crew_branch = crew.branch.clone();
     let rig_id = crew.rig_id.clone();
     drop(crews);
 
     // Get rig
     let rigs = state.rigs.lock().unwrap();
-    let rig = rigs
-        .iter()
-        .find(|r| r.id == rig_id)
-        .ok_or_else(|| "Rig not found".to_string())?;
-    let rig_name = rig.name.clone();
-    let rig_path = rig.path.clone();
+    if !rigs.iter().any(|r| r.id == rig_id) {
+        return Err("Rig not found".to_string());
+    }
     drop(rigs);
 
-    // Render prompt template
-    let rendered = crate::templates::render_builtin_template(
-        &template_name,
-        &effective_agent_type,
-        &task_title,
-        &task_description,
-        &rig_name,
-        &crew_branch,
-        &rig_path,
-    );
+    let (template, fragments) = crate::commands::templates::resolve_template(&state, &template_name, Some(&rig_id), Some(&crew_id))?;
+    let effective_agent_type = if !agent_type.trim().is_empty() {
+        agent_type.clone()
+    } else if let Some(ref agent) = template.recommended_agent {
+        agent.clone()
+    } else {
+        let settings = state.settings.lock().unwrap();
+        if settings.default_cli.trim().is_empty() {
+            "claude".to_string()
+        } else {
+            settings.default_cli.clone()
+        }
+    };
 
-    // Spawn the worker via inner function (no State<> dependency)
-    let worker = spawn_worker_inner(
-        crew_id.clone(),
-        effective_agent_type.clone(),
-        rendered.clone(),
-        WorkerType::Crew,
-        None,
-        app,
-    )?;
+    // A fresh run on a busy crew goes where the rig's busy-crew policy says;
+    // follow-ups stay on their crew to build on its branch. Placed last, so
+    // an overflow worktree is only made for a run that gets this far.
+    let placed_crew_id = if follow_up.is_none() {
+        crate::commands::crews::place_on_crew(&state, &crew_id)?
+    } else {
+        crew_id.clone()
+    };
+    let launch = || -> Result<Run, String> {
+        // Render prompt template
+        let ctx = crate::prompt_context::build_prompt_context(
+            &state,
+            &crate::prompt_context::ContextRequest {
+                rig_id: &rig_id,
+                task_id: Some(&task_id),
+                crew_id: Some(&placed_crew_id),
+                agent_cli: &effective_agent_type,
+                previous_run_id: follow_up.as_ref().map(|f| f.previous_run_id.as_str()),
+                ..Default::default()
+            },
+        );
+        let mut rendered = crate::templates::render_task_template(&template, &fragments, &ctx)?;
+        if let Some(ref f) = follow_up {
+            rendered.push_str("\n\n");
+            rendered.push_str(&f.context);
+        }
 
-    // Create run record
-    let run = Run::new(
-        task_id,
-        worker.id.clone(),
-        crew_id,
-        rig_id,
-        effective_agent_type,
-        template_name,
-        rendered,
+        // Create the run record; the scheduler spawns its worker now or once a slot frees.
+        let mut run = Run::new(
+            task_id,
+            String::new(),
+            placed_crew_id.clone(),
+            rig_id,
+            effective_agent_type,
+            template.name,
+            rendered,
+        );
+        if let Some(f) = follow_up {
+            run.previous_run_id = Some(f.previous_run_id);
+            run.attempt = f.attempt;
+            run.revision_count = f.revision_count;
+        }
+        launch_run(app, run, WorkerType::Crew)
+    };
+    let result = launch();
+    if result.is_err() && placed_crew_id != crew_id {
+        crate::commands::crews::discard_overflow_crew(&state, &placed_crew_id);
+    }
+    result
+}
+
+fn run_spawn_source(run: &Run) -> SpawnSource {
+    if run.comparison_id.is_some() {
+        SpawnSource::BestOf
+    } else if run.replay_of.is_some() {
+        SpawnSource::Replay
+    } else if run.previous_run_id.is_some() {
+        SpawnSource::Retry
+    } else {
+        SpawnSource::Task
+    }
+}
+
+fn submit_run(app: &AppHandle, run: &Run, worker_type: WorkerType) -> Result<Admission<Run>, String> {
+    let state = app.state::<AppState>();
+    let label = {
+        let tasks = state.tasks.lock().unwrap();
+        tasks
+            .iter()
+            .find(|t| t.id == run.task_id)
+            .map(|t| t.title.chars().take(48).collect::<String>())
+            .unwrap_or_else(|| run.template_name.clone())
+    };
+    let mut request = QueuedSpawn::new(
+        &run.rig_id,
+        &run.agent_type,
+        crate::scheduler::task_priority(&state, Some(&run.task_id)),
+        run_spawn_source(run),
+        label,
     );
+    request.crew_id = Some(run.crew_id.clone());
+    request.polecat = worker_type == WorkerType::Polecat;
+    request.task_id = Some(run.task_id.clone());
+    request.run_id = Some(run.id.clone());
+
+    let run_id = run.id.clone();
+    crate::scheduler::submit(app, request, move |app| start_queued_run(app, &run_id, worker_type))
+}
 
+/// Hand a prepared run (empty `worker_id`) to the scheduler. It is stored as
+/// `Queued` and its worker is spawned now or when a slot frees. When the spawn
+/// fails right away the run is dropped and the error returned; a polecat crew
+/// is removed either way.
+pub(crate) fn launch_run(app: &AppHandle, mut run: Run, worker_type: WorkerType) -> Result<Run, String> {
+    let state = app.state::<AppState>();
+    run.status = RunStatus::Queued;
+    {
+        let mut runs = state.runs.lock().unwrap();
+        runs.push(run.clone());
+        state.save_runs(&runs);
+    }
+    match submit_run(app, &run, worker_type) {
+        Ok(Admission::Started(started)) => Ok(started),
+        Ok(Admission::Queued(_)) => Ok(run),
+        Err(e) => {
+            let mut runs = state.runs.lock().unwrap();
+            runs.retain(|r| r.id != run.id);
+            state.save_runs(&runs);
+            Err(e)
+        }
+    }
+}
+
+/// Spawn the worker of a queued run. The base commit is taken now, since the
+/// crew may have moved while the run waited.
+fn start_queued_run(app: &AppHandle, run_id: &str, worker_type: WorkerType) -> Result<Run, String> {
+    let state = app.state::<AppState>();
+    let run = {
+        let runs = state.runs.lock().unwrap();
+        runs.iter()
+            .find(|r| r.id == run_id && r.status == RunStatus::Queued)
+            .cloned()
+            .ok_or_else(|| "Run is no longer queued".to_string())?
+    };
+    let crew_path = {
+        let crews = state.crews.lock().unwrap();
+        crews.iter().find(|c| c.id == run.crew_id).map(|c| c.path.clone())
+    };
+    let base_commit = crew_path.as_deref().and_then(crate::git::head_commit);
+    let reads_terminal = agent_reads_terminal(&run.agent_type, &run.rendered_prompt);
+    let priming = crate::priming::plan_priming(&state, &run.crew_id, &run.agent_type, None, reads_terminal);
+
+    let spawned = spawn_worker_with(
+        run.crew_id.clone(),
+        run.agent_type.clone(),
+        run.rendered_prompt.clone(),
+        worker_type.clone(),
+        None,
+        SpawnExtras {
+            priming: priming.as_ref(),
+            model: run.model_tag.as_deref(),
+            task_id: Some(&run.task_id),
+        },
+        app.clone(),
+    );
     let mut runs = state.runs.lock().unwrap();
-    runs.push(run.clone());
+    let Some(stored) = runs.iter_mut().find(|r| r.id == run_id) else {
+        return Err("Run not found".to_string());
+    };
+    let result = match &spawned {
+        Ok(worker) => {
+            stored.worker_id = worker.id.clone();
+            stored.status = RunStatus::Running;
+            stored.started_at = chrono::Utc::now().to_rfc3339();
+            stored.base_commit = base_commit;
+            Ok(stored.clone())
+        }
+        Err(e) => {
+            stored.status = RunStatus::Failed;
+            stored.finished_at = Some(chrono::Utc::now().to_rfc3339());
+            Err(e.clone())
+        }
+    };
     state.save_runs(&runs);
+    drop(runs);
+    // After the run is linked, so an injected priming text can render its task.
+    if let (Ok(worker), Some(plan)) = (&spawned, priming.as_ref()) {
+        start_priming(&state, worker, plan, app);
+    }
 
-    Ok(run)
+    match result {
+        Ok(ref started) => {
+            if let Some(ref comparison_id) = started.comparison_id {
+                let mut comparisons = state.best_of.lock().unwrap();
+                if let Some(entry) = comparisons
+                    .iter_mut()
+                    .filter(|c| c.id == *comparison_id)
+                    .flat_map(|c| c.entries.iter_mut())
+                    .find(|e| e.run_id == started.id)
+                {
+                    entry.worker_id = started.worker_id.clone();
+                    state.save_best_of(&comparisons);
+                }
+            }
+        }
+        Err(_) if worker_type == WorkerType::Polecat => remove_polecat_crew(&state, &run.crew_id),
+        Err(_) => {}
+    }
+    result
+}
+
+/// Put runs left `Queued` by the previous session back in the scheduler queue.
+pub(crate) fn requeue_stored_runs(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let queued: Vec<(Run, WorkerType)> = {
+        let runs = state.runs.lock().unwrap();
+        let crews = state.crews.lock().unwrap();
+        runs.iter()
+            .filter(|r| r.status == RunStatus::Queued)
+            .map(|r| {
+                // Best-of and replay polecats; busy-crew overflow worktrees run as crews.
+                let polecat = (r.comparison_id.is_some() || r.replay_of.is_some())
+                    && crews
+                        .iter()
+                        .any(|c| c.id == r.crew_id && c.branch.starts_with("polecat/"));
+                (r.clone(), if polecat { WorkerType::Polecat } else { WorkerType::Crew })
+            })
+            .collect()
+    };
+    for (run, worker_type) in queued {
+        if let Err(e) = submit_run(app, &run, worker_type) {
+            eprintln!("[scheduler] failed to restart queued run {}: {}", run.id, e);
+        }
+    }
 }
 
 #[tauri::command]
@@ -1374,14 +2644,59 @@ pub fn get_run_logs(id: String, state: State<AppState>) -> Result<Vec<LogEntry>,
     let worker_id = run.worker_id.clone();
     drop(runs);
 
-    // Get logs for the worker
-    let logs = state.worker_logs.lock().unwrap();
-    if let Some(entries) = logs.get(&worker_id) {
-        return Ok(entries.clone());
+    Ok(state.load_log(&worker_id))
+}
+
+/// Full patch of a run: the stored artifact once it finished, else the live
+/// worktree diff against the run's base commit.
+fn load_run_patch(state: &AppState, run: &Run) -> Result<(String, bool), String> {
+    if let Some(patch) = state.load_run_patch(&run.id) {
+        return Ok((patch, false));
     }
-    drop(logs);
+    let crew_path = {
+        let crews = state.crews.lock().unwrap();
+        crews
+            .iter()
+            .find(|c| c.id == run.crew_id && c.status == crate::models::crew::CrewStatus::Active)
+            .map(|c| c.path.clone())
+    };
+    let path = crew_path.ok_or_else(|| "No patch was recorded for this run and its worktree is gone".to_string())?;
+    Ok((crate::git::get_diff_since(&path, run.base_commit.as_deref())?, true))
+}
 
-    Ok(state.load_log(&worker_id))
+#[tauri::command]
+pub fn get_run_patch(run_id: String, state: State<AppState>) -> Result<String, String> {
+    let run = {
+        let runs = state.runs.lock().unwrap();
+        runs.iter()
+            .find(|r| r.id == run_id)
+            .cloned()
+            .ok_or_else(|| "Run not found".to_string())?
+    };
+    load_run_patch(&state, &run).map(|(patch, _)| patch)
+}
+
+/// Per-file, per-hunk diff of a run, available after its worktree is removed.
+#[tauri::command]
+pub fn get_run_diff(run_id: String, state: State<AppState>) -> Result<crate::diff::RunDiff, String> {
+    let run = {
+        let runs = state.runs.lock().unwrap();
+        runs.iter()
+            .find(|r| r.id == run_id)
+            .cloned()
+            .ok_or_else(|| "Run not found".to_string())?
+    };
+    let (patch, live) = load_run_patch(&state, &run)?;
+    let files = crate::diff::parse_patch(&patch);
+    Ok(crate::diff::RunDiff {
+        run_id: run.id,
+        base_commit: run.base_commit,
+        end_commit: run.end_commit,
+        live,
+        additions: files.iter().map(|f| f.additions).sum(),
+        deletions: files.iter().map(|f| f.deletions).sum(),
+        files,
+    })
 }
 
 #[tauri::command]
@@ -1421,9 +2736,64 @@ pub fn resize_worker_pty(
             pixel_height: 0,
         })
         .map_err(|e| format!("PTY resize failed: {}", e))?;
+    drop(masters);
+    state.record_resize(&id, cols, rows);
+    if let Some(screen) = state.worker_screens.lock().unwrap().get_mut(&id) {
+        screen.resize(rows, cols);
+    }
     Ok(())
 }
 
+/// Pane history of a tmux-hosted worker, with escape sequences, for repainting
+/// the terminal after a reattach.
+#[tauri::command]
+pub fn get_worker_scrollback(id: String, state: State<AppState>) -> Result<String, String> {
+    let session = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .find(|w| w.id == id)
+            .ok_or_else(|| "Worker not found".to_string())?
+            .session
+            .clone()
+            .ok_or_else(|| "Worker has no session host".to_string())?
+    };
+    let lines = state.settings.lock().unwrap().session_scrollback_lines;
+    crate::sessions::capture_scrollback(&session, lines, true)
+}
+
+/// Current emulated screen (grid, cursor, optional scrollback) of a PTY worker.
+#[tauri::command]
+pub fn get_worker_screen(
+    id: String,
+    include_scrollback: Option<bool>,
+    state: State<AppState>,
+) -> Result<crate::vt::ScreenSnapshot, String> {
+    let screens = state.worker_screens.lock().unwrap();
+    let screen = screens
+        .get(&id)
+        .ok_or_else(|| "No terminal screen for this worker".to_string())?;
+    Ok(screen.snapshot(include_scrollback.unwrap_or(false)))
+}
+
+/// Live sessions on the TownUI tmux server, matched to their workers.
+#[tauri::command]
+pub fn list_worker_sessions(state: State<AppState>) -> Vec<crate::sessions::WorkerSessionInfo> {
+    let workers = state.workers.lock().unwrap();
+    crate::sessions::list_sessions()
+        .into_iter()
+        .map(|(session, pane_pid, attached_clients)| crate::sessions::WorkerSessionInfo {
+            worker_id: workers
+                .iter()
+                .find(|w| w.session.as_deref() == Some(session.as_str()))
+                .map(|w| w.id.clone()),
+            session,
+            pane_pid,
+            attached_clients,
+        })
+        .collect()
+}
+
 #[tauri::command]
 pub fn write_to_worker(id: String, input: String, state: State<AppState>) -> Result<(), String> {
     let mut writers = state.worker_writers.lock().unwrap();
@@ -1444,9 +2814,11 @@ pub fn write_to_worker(id: String, input: String, state: State<AppState>) -> Res
 /// Tag a run with a model identifier (e.g., "claude-sonnet-4", "codex-mini").
 #[tauri::command]
 pub fn set_run_model_tag(run_id: String, model_tag: String, state: State<AppState>) -> Result<(), String> {
+    let model_prices = state.settings.lock().unwrap().model_prices.clone();
     let mut runs = state.runs.lock().unwrap();
     let run = runs.iter_mut().find(|r| r.id == run_id).ok_or("Run not found")?;
     run.model_tag = Some(model_tag);
+    crate::usage::reprice_run(run, &model_prices);
     state.save_runs(&runs);
     Ok(())
 }
@@ -1461,6 +2833,42 @@ pub fn set_run_quality_signal(run_id: String, quality_signal: f32, state: State<
     Ok(())
 }
 
+/// Record token usage / spend for a run from an external source (agent hook, billing export).
+/// A `cost_usd` given here is kept as-is; otherwise cost is estimated from `model_prices`.
+#[tauri::command]
+pub fn record_run_usage(
+    run_id: String,
+    input_tokens: Option<u64>,
+    output_tokens: Option<u64>,
+    cost_usd: Option<f64>,
+    state: State<AppState>,
+) -> Result<Run, String> {
+    let model_prices = state.settings.lock().unwrap().model_prices.clone();
+    let mut runs = state.runs.lock().unwrap();
+    let run = runs.iter_mut().find(|r| r.id == run_id).ok_or("Run not found")?;
+    if input_tokens.is_some() {
+        run.input_tokens = input_tokens;
+    }
+    if output_tokens.is_some() {
+        run.output_tokens = output_tokens;
+    }
+    match cost_usd {
+        Some(cost) => {
+            run.cost_usd = Some(cost.max(0.0));
+            run.cost_source = Some("manual".to_string());
+        }
+        None => {
+            if run.cost_source.as_deref() == Some("manual") {
+                run.cost_source = None;
+            }
+            crate::usage::reprice_run(run, &model_prices);
+        }
+    }
+    let updated = run.clone();
+    state.save_runs(&runs);
+    Ok(updated)
+}
+
 #[derive(serde::Serialize, Clone)]
 pub struct ModelStats {
     pub model_tag: String,
@@ -1472,7 +2880,18 @@ pub struct ModelStats {
     pub avg_duration_secs: Option<f64>,
     /// Average quality signal (only runs that have one).
     pub avg_quality_signal: Option<f64>,
+    /// Average `revision_count` of the last run in each review chain.
     pub avg_revision_count: f64,
+    pub total_input_tokens: u64,
+    pub total_output_tokens: u64,
+    /// Tokens reported only as a combined figure (see `Run::total_tokens`).
+    pub total_combined_tokens: u64,
+    /// Sum of known run costs in USD.
+    pub total_cost_usd: f64,
+    /// Average cost over runs that have a cost figure.
+    pub avg_cost_usd: Option<f64>,
+    /// Total spend divided by completed runs — "cost per successful task".
+    pub cost_per_completed: Option<f64>,
 }
 
 /// Return per-model aggregate stats — mirrors Gas Town `bd stats --group-by=model`.
@@ -1489,9 +2908,15 @@ pub fn list_run_stats(rig_id: Option<String>, state: State<AppState>) -> Vec<Mod
         durations: Vec<f64>,
         quality_signals: Vec<f32>,
         revision_counts: Vec<u32>,
+        input_tokens: u64,
+        output_tokens: u64,
+        combined_tokens: u64,
+        costs: Vec<f64>,
     }
 
     let mut map: HashMap<String, Acc> = HashMap::new();
+    let superseded: std::collections::HashSet<&str> =
+        runs.iter().filter_map(|r| r.previous_run_id.as_deref()).collect();
 
     for run in runs.iter() {
         if rig_id.as_deref().map(|rid| run.rig_id != rid).unwrap_or(false) { continue; }
@@ -1500,6 +2925,7 @@ pub fn list_run_stats(rig_id: Option<String>, state: State<AppState>) -> Vec<Mod
             agent_type: run.agent_type.clone(),
             total: 0, completed: 0, failed: 0,
             durations: vec![], quality_signals: vec![], revision_counts: vec![],
+            input_tokens: 0, output_tokens: 0, combined_tokens: 0, costs: vec![],
         });
         acc.total += 1;
         match run.status {
@@ -1513,7 +2939,14 @@ pub fn list_run_stats(rig_id: Option<String>, state: State<AppState>) -> Vec<Mod
             acc.durations.push((end - *start).num_seconds() as f64);
         }
         if let Some(q) = run.quality_signal { acc.quality_signals.push(q); }
-        acc.revision_counts.push(run.revision_count);
+        // Only the last run of a revision/retry chain says how many loops the task took.
+        if !superseded.contains(run.id.as_str()) {
+            acc.revision_counts.push(run.revision_count);
+        }
+        acc.input_tokens += run.input_tokens.unwrap_or(0);
+        acc.output_tokens += run.output_tokens.unwrap_or(0);
+        acc.combined_tokens += run.total_tokens.unwrap_or(0);
+        if let Some(c) = run.cost_usd { acc.costs.push(c); }
     }
 
     let avg = |v: &[f64]| if v.is_empty() { None } else { Some(v.iter().sum::<f64>() / v.len() as f64) };
@@ -1522,6 +2955,9 @@ pub fn list_run_stats(rig_id: Option<String>, state: State<AppState>) -> Vec<Mod
     let mut result: Vec<ModelStats> = map.into_iter().map(|(model_tag, acc)| {
         let avg_rc = if acc.revision_counts.is_empty() { 0.0 }
                      else { acc.revision_counts.iter().map(|&x| x as f64).sum::<f64>() / acc.revision_counts.len() as f64 };
+        let total_cost: f64 = acc.costs.iter().sum();
+        let cost_per_completed = if acc.completed == 0 || acc.costs.is_empty() { None }
+                                 else { Some(total_cost / acc.completed as f64) };
         ModelStats {
             model_tag,
             agent_type: acc.agent_type,
@@ -1531,6 +2967,12 @@ pub fn list_run_stats(rig_id: Option<String>, state: State<AppState>) -> Vec<Mod
             avg_duration_secs: avg(&acc.durations),
             avg_quality_signal: avg_f32(&acc.quality_signals),
             avg_revision_count: avg_rc,
+            total_input_tokens: acc.input_tokens,
+            total_output_tokens: acc.output_tokens,
+            total_combined_tokens: acc.combined_tokens,
+            total_cost_usd: total_cost,
+            avg_cost_usd: avg(&acc.costs),
+            cost_per_completed,
         }
     }).collect();
 
@@ -1541,47 +2983,179 @@ pub fn list_run_stats(rig_id: Option<String>, state: State<AppState>) -> Vec<Mod
 // ─── Inner helpers for supervisor / witness use ─────────────────────────────
 
 /// Stop a worker without requiring tauri::State — usable from supervisor thread.
-pub fn stop_worker_inner(state: &AppState, id: &str) -> Result<(), String> {
-    let (pid, rig_id, worker_id) = {
+pub fn stop_worker_inner(state: &AppState, app: &AppHandle, id: &str) -> Result<(), String> {
+    begin_graceful_stop(state, app, id, "supervisor").map(|_| ())
+}
+
+/// Last output lines, uncommitted diff stat and agent/run state for a worker about to stop.
+fn capture_worker_checkpoint(state: &AppState, worker: &Worker, reason: &str) -> WorkerCheckpoint {
+    let tail_len = state.settings.lock().unwrap().checkpoint_log_lines;
+    let last_output: Vec<String> = {
+        let logs = state.worker_logs.lock().unwrap();
+        logs.get(&worker.id)
+            .map(|entries| {
+                let lines: Vec<&LogEntry> =
+                    entries.iter().filter(|e| !e.line.trim().is_empty()).collect();
+                let start = lines.len().saturating_sub(tail_len);
+                lines[start..].iter().map(|e| e.line.clone()).collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let run = {
+        let runs = state.runs.lock().unwrap();
+        runs.iter().rev().find(|r| r.worker_id == worker.id).cloned()
+    };
+    let task_id = run.as_ref().map(|r| r.task_id.clone()).or_else(|| {
+        let tasks = state.tasks.lock().unwrap();
+        tasks.iter()
+            .find(|t| t.assigned_worker_id.as_deref() == Some(worker.id.as_str()))
+            .map(|t| t.id.clone())
+    });
+    let diff_stat = crew_diff_stat(state, &worker.crew_id);
+    let elapsed_seconds = chrono::DateTime::parse_from_rfc3339(&worker.started_at)
+        .ok()
+        .map(|t| chrono::Utc::now().timestamp().saturating_sub(t.timestamp()).max(0) as u64);
+
+    WorkerCheckpoint {
+        worker_id: worker.id.clone(),
+        run_id: run.map(|r| r.id),
+        task_id,
+        agent_type: worker.agent_type.clone(),
+        reason: reason.to_string(),
+        captured_at: chrono::Utc::now().to_rfc3339(),
+        elapsed_seconds,
+        startup_primed: worker.startup_primed,
+        diff_stat,
+        last_output,
+        interrupt_output: Vec::new(),
+    }
+}
+
+/// Uncommitted changes in a crew's worktree, if any.
+fn crew_diff_stat(state: &AppState, crew_id: &str) -> Option<String> {
+    let crew_path = {
+        let crews = state.crews.lock().unwrap();
+        crews.iter().find(|c| c.id == crew_id).map(|c| c.path.clone())
+    };
+    crew_path
+        .and_then(|p| crate::git::get_working_diff_stat(&p).ok())
+        .filter(|s| !s.is_empty())
+}
+
+/// Complete a checkpoint once the agent has answered the interrupt: add the
+/// output it printed since `log_offset`, refresh the diff stat (it may have
+/// saved work on the way out) and store it on the hook again.
+fn record_interrupt_response(state: &AppState, crew_id: &str, mut checkpoint: WorkerCheckpoint, log_offset: usize) {
+    let tail_len = state.settings.lock().unwrap().checkpoint_log_lines;
+    let lines: Vec<String> = state
+        .load_log_page(&checkpoint.worker_id, log_offset, None)
+        .into_iter()
+        .filter(|e| e.stream != "system" && !e.line.trim().is_empty())
+        .map(|e| e.line)
+        .collect();
+    let start = lines.len().saturating_sub(tail_len);
+    checkpoint.interrupt_output = lines[start..].to_vec();
+    checkpoint.diff_stat = crew_diff_stat(state, crew_id);
+    checkpoint.captured_at = chrono::Utc::now().to_rfc3339();
+    store_checkpoint_on_hook(state, &checkpoint);
+}
+
+/// Store a checkpoint as the `state_blob` of the hook carrying the worker's task.
+fn store_checkpoint_on_hook(state: &AppState, checkpoint: &WorkerCheckpoint) -> Option<String> {
+    let mut hooks = state.hooks.lock().unwrap();
+    let idx = hooks
+        .iter()
+        .position(|h| h.worker_id.as_deref() == Some(checkpoint.worker_id.as_str()))
+        .or_else(|| {
+            let task_id = checkpoint.task_id.as_deref()?;
+            hooks.iter().position(|h| {
+                h.current_work_id.as_deref() == Some(task_id)
+                    && matches!(h.status, HookStatus::Assigned | HookStatus::Running)
+            })
+        })?;
+    let hook = &mut hooks[idx];
+    hook.state_blob = serde_json::to_string_pretty(checkpoint).ok();
+    hook.last_heartbeat = chrono::Utc::now().to_rfc3339();
+    let hook_id = hook.hook_id.clone();
+    state.save_hooks(&hooks);
+    Some(hook_id)
+}
+
+/// Mark a worker stopped, checkpoint it into its hook, and start the staged
+/// stop on a background thread, which adds the agent's answer to the
+/// interrupt to the checkpoint. Returns the thread handle when a process was
+/// still running (callers that must wait, like app exit, can join it).
+pub(crate) fn begin_graceful_stop(
+    state: &AppState,
+    app: &AppHandle,
+    id: &str,
+    reason: &str,
+) -> Result<Option<thread::JoinHandle<()>>, String> {
+    let (worker, was_running) = {
         let mut workers = state.workers.lock().unwrap();
         let worker = workers
             .iter_mut()
             .find(|w| w.id == id)
             .ok_or_else(|| "Worker not found".to_string())?;
-        let pid = worker.pid;
-        let rid = worker.rig_id.clone();
-        let wid = worker.id.clone();
+        let was_running = worker.status == WorkerStatusEnum::Running;
         worker.status = WorkerStatusEnum::Stopped;
         worker.stopped_at = Some(chrono::Utc::now().to_rfc3339());
+        let snapshot = worker.clone();
         state.save_workers(&workers);
-        (pid, rid, wid)
+        (snapshot, was_running)
     };
 
-    if let Some(p) = pid {
-        kill_process_tree(p);
-    }
+    let writer = state.worker_writers.lock().unwrap().remove(&worker.id);
+    let master = state.worker_pty_masters.lock().unwrap().remove(&worker.id);
 
-    {
-        let mut writers = state.worker_writers.lock().unwrap();
-        writers.remove(&worker_id);
-    }
-    {
-        let mut masters = state.worker_pty_masters.lock().unwrap();
-        masters.remove(&worker_id);
-    }
+    let checkpoint = was_running.then(|| capture_worker_checkpoint(state, &worker, reason));
+    let checkpoint_hook_id = checkpoint
+        .as_ref()
+        .and_then(|c| store_checkpoint_on_hook(state, c));
 
     state.append_audit_event(&AuditEvent::new(
-        rig_id,
-        None,
-        None,
+        worker.rig_id.clone(),
+        worker.actor_id.clone(),
+        checkpoint.as_ref().and_then(|c| c.task_id.clone()),
         AuditEventType::WorkerStopped,
-        serde_json::json!({ "worker_id": worker_id, "auto": true }).to_string(),
+        serde_json::json!({
+            "worker_id": worker.id,
+            "reason": reason,
+            "auto": reason != "user",
+            "graceful": true,
+            "checkpoint_hook_id": checkpoint_hook_id,
+            "diff_stat": checkpoint.as_ref().and_then(|c| c.diff_stat.clone()),
+        })
+        .to_string(),
     ));
 
-    Ok(())
+    let Some(pid) = worker.pid.filter(|_| was_running) else {
+        return Ok(None);
+    };
+    let (interrupt_grace_ms, term_grace_ms) = {
+        let settings = state.settings.lock().unwrap();
+        (settings.stop_interrupt_grace_ms, settings.stop_term_grace_ms)
+    };
+    let agent_type = worker.agent_type.clone();
+    let crew_id = worker.crew_id.clone();
+    // Whatever is logged from here on answers the interrupt.
+    let log_offset = state.count_log_entries(&worker.id);
+    let app = app.clone();
+    thread::Builder::new()
+        .name(format!("worker-{}-stop", &worker.id[..8]))
+        .spawn(move || {
+            run_staged_stop(pid, &agent_type, writer, master, interrupt_grace_ms, term_grace_ms, || {
+                if let Some(checkpoint) = checkpoint {
+                    record_interrupt_response(&app.state::<AppState>(), &crew_id, checkpoint, log_offset);
+                }
+            })
+        })
+        .map(Some)
+        .map_err(|e| format!("Failed to spawn stop thread: {}", e))
 }
 
-/// Send a nudge newline to a worker's PTY (used by Witness for stuck polecats).
+/// Send a nudge newline to a worker's PTY (first step of the liveness recovery ladder).
 pub fn nudge_worker_pty(state: &AppState, id: &str) -> Result<(), String> {
     let mut writers = state.worker_writers.lock().unwrap();
     if let Some(writer) = writers.get_mut(id) {
@@ -1591,48 +3165,181 @@ pub fn nudge_worker_pty(state: &AppState, id: &str) -> Result<(), String> {
     Ok(())
 }
 
-/// Spawn a polecat on a rig without tauri::State wrapper (for Witness cycle).
-pub fn spawn_polecat_inner(state: &AppState, app: &AppHandle, rig_id: &str) -> Result<String, String> {
-    // Get rig path
-    let rig_path = {
-        let rigs = state.rigs.lock().unwrap();
-        rigs.iter()
-            .find(|r| r.id == rig_id)
-            .map(|r| r.path.clone())
-            .ok_or_else(|| "Rig not found".to_string())?
+/// Re-inject the startup priming context into a running worker right away.
+pub(crate) fn reprime_worker(state: &AppState, app: &AppHandle, id: &str) -> Result<(), String> {
+    let worker = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .find(|w| w.id == id)
+            .cloned()
+            .ok_or_else(|| "Worker not found".to_string())?
     };
+    let target = crate::priming::priming_target(state, &worker.crew_id, &worker.agent_type, worker.actor_id.as_deref());
+    let profile = crate::priming::match_profile(state, &target);
+    let priming_text = build_priming_context(state, app, &worker, profile.as_ref());
+    {
+        let mut writers = state.worker_writers.lock().unwrap();
+        let writer = writers
+            .get_mut(id)
+            .ok_or_else(|| "Worker has no writable terminal".to_string())?;
+        writer
+            .write_all(format!("{}\r\n", priming_text).as_bytes())
+            .and_then(|_| writer.flush())
+            .map_err(|e| e.to_string())?;
+    }
+    let entry = LogEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        stream: "system".to_string(),
+        line: "[primed] Context re-injected".to_string(),
+    };
+    emit_worker_log(state, app, id, entry);
+    Ok(())
+}
 
-    let polecat_slug = format!("polecat-{}", &uuid::Uuid::new_v4().to_string()[..8]);
-    let branch_name = format!("polecat/{}", polecat_slug);
-    let wt_dir = state.worktrees_dir().join(rig_id);
-    let _ = std::fs::create_dir_all(&wt_dir);
-    let wt_path = wt_dir.join(&polecat_slug);
-    let wt_path_str = wt_path.to_string_lossy().to_string();
-
-    let base_branch = crate::git::get_current_branch(&rig_path)
-        .unwrap_or_else(|| "main".to_string());
+/// Restart a worker in the background: stop it (waiting for the staged stop)
+/// and start a fresh one with the same agent on the same crew and task.
+/// `on_replaced` gets the new worker's id once it is running; a replacement
+/// that has to queue for a slot is not reported. Polecats are only stopped:
+/// their worktree is removed on exit and Witness spawns a replacement.
+pub(crate) fn restart_worker_inner(
+    state: &AppState,
+    app: &AppHandle,
+    id: &str,
+    on_replaced: impl FnOnce(&AppState, String) + Send + 'static,
+) -> Result<(), String> {
+    let worker = {
+        let workers = state.workers.lock().unwrap();
+        workers
+            .iter()
+            .find(|w| w.id == id)
+            .cloned()
+            .ok_or_else(|| "Worker not found".to_string())?
+    };
+    let app = app.clone();
+    thread::Builder::new()
+        .name(format!("worker-{}-restart", &worker.id[..8.min(worker.id.len())]))
+        .spawn(move || {
+            let state = app.state::<AppState>();
+            let line = match restart_worker(&state, &app, &worker) {
+                Ok(Some(new_id)) => {
+                    let line = format!("[restart] Replaced by worker {}", &new_id[..8.min(new_id.len())]);
+                    on_replaced(&state, new_id);
+                    line
+                }
+                Ok(None) if worker.worker_type == WorkerType::Polecat => "[restart] Polecat stopped".to_string(),
+                Ok(None) => "[restart] Replacement queued for a free slot".to_string(),
+                Err(e) => format!("[restart] Failed: {}", e),
+            };
+            let entry = LogEntry {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                stream: "system".to_string(),
+                line,
+            };
+            emit_worker_log(&state, &app, &worker.id, entry);
+            let _ = app.emit("data-changed", "");
+        })
+        .map(|_| ())
+        .map_err(|e| format!("Failed to spawn restart thread: {}", e))
+}
 
-    crate::git::create_worktree(&rig_path, &wt_path_str, &branch_name, &base_branch)?;
+/// The blocking part of `restart_worker_inner`. A worker that ran a task is
+/// relaunched as a new run of the same prompt through the scheduler, which
+/// also waits out the old worker's crew lease.
+fn restart_worker(state: &AppState, app: &AppHandle, worker: &Worker) -> Result<Option<String>, String> {
+    if let Some(handle) = begin_graceful_stop(state, app, &worker.id, "restart")? {
+        let _ = handle.join();
+    }
+    if worker.worker_type == WorkerType::Polecat {
+        return Ok(None);
+    }
 
-    // Register temporary crew
-    let crew = crate::models::crew::Crew::new(
-        rig_id.to_string(),
-        format!("Polecat {}", &polecat_slug[..8]),
-        branch_name,
-        wt_path_str,
-    );
-    let crew_id = crew.id.clone();
+    let previous = {
+        let runs = state.runs.lock().unwrap();
+        runs.iter().rev().find(|r| r.worker_id == worker.id).cloned()
+    };
+    let new_worker_id = match previous {
+        Some(previous) => {
+            let mut run = Run::new(
+                previous.task_id.clone(),
+                String::new(),
+                previous.crew_id.clone(),
+                previous.rig_id.clone(),
+                previous.agent_type.clone(),
+                previous.template_name.clone(),
+                previous.rendered_prompt.clone(),
+            );
+            run.model_tag = previous.model_tag.clone();
+            run.attempt = previous.attempt;
+            run.revision_count = previous.revision_count;
+            run.previous_run_id = Some(previous.id.clone());
+            let started = launch_run(app, run, worker.worker_type.clone())?;
+            if started.worker_id.is_empty() {
+                return Ok(None);
+            }
+            started.worker_id
+        }
+        None => {
+            let prompt = worker
+                .task_label
+                .as_ref()
+                .map(|t| format!("Work on task: {}", t))
+                .unwrap_or_default();
+            let mut request = QueuedSpawn::new(
+                &worker.rig_id,
+                &worker.agent_type,
+                crate::models::task::TaskPriority::High,
+                SpawnSource::Restart,
+                format!("restart {}", &worker.id[..8.min(worker.id.len())]),
+            );
+            request.crew_id = Some(worker.crew_id.clone());
+            let (crew_id, agent_type, worker_type, actor_id) = (
+                worker.crew_id.clone(),
+                worker.agent_type.clone(),
+                worker.worker_type.clone(),
+                worker.actor_id.clone(),
+            );
+            let admission = crate::scheduler::submit(app, request, move |app| {
+                spawn_worker_inner(crew_id, agent_type, prompt, worker_type, actor_id, app.clone())
+            })?;
+            match admission {
+                Admission::Started(started) => started.id,
+                Admission::Queued(_) => return Ok(None),
+            }
+        }
+    };
     {
-        let mut crews = state.crews.lock().unwrap();
-        crews.push(crew);
-        state.save_crews(&crews);
+        let mut workers = state.workers.lock().unwrap();
+        if let Some(w) = workers.iter_mut().find(|w| w.id == new_worker_id) {
+            w.task_label = worker.task_label.clone();
+            w.crew_name = worker.crew_name.clone();
+        }
+        state.save_workers(&workers);
     }
+    {
+        let mut tasks = state.tasks.lock().unwrap();
+        let mut changed = false;
+        for task in tasks.iter_mut().filter(|t| t.assigned_worker_id.as_deref() == Some(worker.id.as_str())) {
+            task.assigned_worker_id = Some(new_worker_id.clone());
+            changed = true;
+        }
+        if changed {
+            state.save_tasks(&tasks);
+        }
+    }
+    Ok(Some(new_worker_id))
+}
 
-    // Resolve default agent type
-    let agent_type = {
-        let settings = state.settings.lock().unwrap();
-        settings.cli_paths.keys().next().cloned().unwrap_or_else(|| "claude".to_string())
-    };
+/// Agent used by Witness and propulsion spawns: the first configured CLI.
+pub(crate) fn background_agent_type(state: &AppState) -> String {
+    let settings = state.settings.lock().unwrap();
+    settings.cli_paths.keys().next().cloned().unwrap_or_else(|| "claude".to_string())
+}
+
+/// Spawn a polecat on a rig without tauri::State wrapper (for Witness cycle).
+pub fn spawn_polecat_inner(state: &AppState, app: &AppHandle, rig_id: &str) -> Result<String, String> {
+    let crew_id = create_polecat_crew(state, rig_id, None)?.id;
+    let agent_type = background_agent_type(state);
 
     let worker = spawn_worker_inner(
         crew_id,
@@ -1653,10 +3360,7 @@ pub fn spawn_worker_for_propulsion(
     crew_id: &str,
     task_id: Option<&str>,
 ) -> Result<String, String> {
-    let agent_type = {
-        let settings = state.settings.lock().unwrap();
-        settings.cli_paths.keys().next().cloned().unwrap_or_else(|| "claude".to_string())
-    };
+    let agent_type = background_agent_type(state);
 
     let prompt = task_id
         .map(|tid| {
@@ -154,7 +154,8 @@ pub fn cook_formula(
 
     let mut steps = Vec::new();
     for step in &template.steps {
-        let missing = crate::templates::validate_variables(&step.command_template, &variables);
+        let missing = crate::templates::validate_variables(&step.command_template, &variables)
+            .map_err(|e| format!("Step '{}': {}", step.step_id, e))?;
         if !missing.is_empty() {
             return Err(format!(
                 "Step '{}' missing variables: {}",
@@ -162,7 +163,8 @@ pub fn cook_formula(
                 missing.join(", ")
             ));
         }
-        let command_resolved = crate::templates::render_template(&step.command_template, &variables);
+        let command_resolved = crate::templates::render_template(&step.command_template, &variables)
+            .map_err(|e| format!("Step '{}': {}", step.step_id, e))?;
         steps.push(ProtomoleculeStep {
             step_id: step.step_id.clone(),
             title: step.title.clone(),
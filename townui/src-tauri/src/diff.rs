@@ -0,0 +1,194 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffLineKind {
+    Context,
+    Add,
+    Remove,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub content: String,
+    pub old_line: Option<u32>,
+    pub new_line: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffHunk {
+    /// The `@@ -a,b +c,d @@ context` line.
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileDiff {
+    pub path: String,
+    /// Previous path of a renamed file.
+    pub old_path: Option<String>,
+    pub change: FileChangeKind,
+    pub binary: bool,
+    pub additions: u32,
+    pub deletions: u32,
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// A run's change set, from its stored patch or (while it runs) its worktree.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunDiff {
+    pub run_id: String,
+    pub base_commit: Option<String>,
+    pub end_commit: Option<String>,
+    /// True when read from the live worktree rather than the stored patch.
+    pub live: bool,
+    pub additions: u32,
+    pub deletions: u32,
+    pub files: Vec<FileDiff>,
+}
+
+/// `-12,3` / `+7` → (start, count); a missing count means 1.
+fn parse_range(raw: &str) -> (u32, u32) {
+    let raw = &raw[1..];
+    match raw.split_once(',') {
+        Some((start, count)) => (start.parse().unwrap_or(0), count.parse().unwrap_or(0)),
+        None => (raw.parse().unwrap_or(0), 1),
+    }
+}
+
+fn parse_hunk_header(line: &str) -> Option<DiffHunk> {
+    let mut parts = line.strip_prefix("@@ ")?.split_whitespace();
+    let (old_start, old_lines) = parse_range(parts.next().filter(|p| p.starts_with('-'))?);
+    let (new_start, new_lines) = parse_range(parts.next().filter(|p| p.starts_with('+'))?);
+    Some(DiffHunk {
+        header: line.to_string(),
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        lines: Vec::new(),
+    })
+}
+
+/// Path from a `--- a/x` / `+++ b/x` line; `None` for `/dev/null`.
+fn strip_side(raw: &str) -> Option<String> {
+    let raw = raw.trim_end_matches('\t');
+    if raw == "/dev/null" {
+        return None;
+    }
+    Some(
+        raw.strip_prefix("a/")
+            .or_else(|| raw.strip_prefix("b/"))
+            .unwrap_or(raw)
+            .to_string(),
+    )
+}
+
+/// Parse unified `git diff` output into per-file, per-hunk structure.
+pub fn parse_patch(patch: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    let (mut old_no, mut new_no) = (0u32, 0u32);
+
+    for line in patch.lines() {
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            // `a/old b/new`; refined below by ---/+++ and rename headers.
+            let path = rest
+                .rsplit_once(" b/")
+                .map(|(_, new)| new.to_string())
+                .unwrap_or_else(|| rest.to_string());
+            files.push(FileDiff {
+                path,
+                old_path: None,
+                change: FileChangeKind::Modified,
+                binary: false,
+                additions: 0,
+                deletions: 0,
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+
+        if let Some(hunk) = file.hunks.last_mut() {
+            let (kind, content) = match line.as_bytes().first() {
+                Some(b'+') => (Some(DiffLineKind::Add), &line[1..]),
+                Some(b'-') => (Some(DiffLineKind::Remove), &line[1..]),
+                Some(b' ') => (Some(DiffLineKind::Context), &line[1..]),
+                None => (Some(DiffLineKind::Context), ""),
+                _ => (None, line),
+            };
+            if let Some(kind) = kind {
+                let (old_line, new_line) = match kind {
+                    DiffLineKind::Add => {
+                        new_no += 1;
+                        file.additions += 1;
+                        (None, Some(new_no - 1))
+                    }
+                    DiffLineKind::Remove => {
+                        old_no += 1;
+                        file.deletions += 1;
+                        (Some(old_no - 1), None)
+                    }
+                    DiffLineKind::Context => {
+                        old_no += 1;
+                        new_no += 1;
+                        (Some(old_no - 1), Some(new_no - 1))
+                    }
+                };
+                hunk.lines.push(DiffLine {
+                    kind,
+                    content: content.to_string(),
+                    old_line,
+                    new_line,
+                });
+                continue;
+            }
+        }
+
+        if line.starts_with("@@ ") {
+            if let Some(hunk) = parse_hunk_header(line) {
+                old_no = hunk.old_start;
+                new_no = hunk.new_start;
+                file.hunks.push(hunk);
+            }
+        } else if line.starts_with("new file mode") {
+            file.change = FileChangeKind::Added;
+        } else if line.starts_with("deleted file mode") {
+            file.change = FileChangeKind::Deleted;
+        } else if let Some(from) = line.strip_prefix("rename from ") {
+            file.change = FileChangeKind::Renamed;
+            file.old_path = Some(from.to_string());
+        } else if let Some(to) = line.strip_prefix("rename to ") {
+            file.path = to.to_string();
+        } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+            file.binary = true;
+        } else if let Some(old) = line.strip_prefix("--- ") {
+            if file.change == FileChangeKind::Deleted {
+                if let Some(path) = strip_side(old) {
+                    file.path = path;
+                }
+            }
+        } else if let Some(new) = line.strip_prefix("+++ ") {
+            if let Some(path) = strip_side(new) {
+                file.path = path;
+            }
+        }
+    }
+    files
+}
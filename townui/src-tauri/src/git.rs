@@ -160,26 +160,46 @@ pub fn create_worktree(
     }
 }
 
-pub fn get_diff_stat(worktree_path: &str) -> Result<String, String> {
-    // Try diff against previous commit first
+/// Check out `commit` into a new worktree without a branch.
+pub fn create_detached_worktree(repo_path: &str, worktree_path: &str, commit: &str) -> Result<(), String> {
     let output = Command::new("git")
-        .args(["diff", "--stat", "HEAD~1..HEAD"])
-        .current_dir(worktree_path)
-        .output();
+        .args(["worktree", "add", "--detach", worktree_path, commit])
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| format!("Failed to run git worktree add: {}", e))?;
 
-    match output {
-        Ok(o) if o.status.success() => {
-            let stat = String::from_utf8_lossy(&o.stdout).trim().to_string();
-            if !stat.is_empty() {
-                return Ok(stat);
-            }
-        }
-        _ => {}
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "git worktree add failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
     }
+}
 
-    // Fallback: diff of working tree
+/// Commit hash `HEAD` points at.
+pub fn head_commit(path: &str) -> Option<String> {
     let output = Command::new("git")
-        .args(["diff", "--stat"])
+        .args(["rev-parse", "HEAD"])
+        .current_dir(path)
+        .output()
+        .ok()?;
+    if output.status.success() {
+        let hash = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if hash.is_empty() { None } else { Some(hash) }
+    } else {
+        None
+    }
+}
+
+/// `git diff --stat` of everything since `base` (commits plus uncommitted
+/// changes, untracked files included); without a base, only the uncommitted
+/// changes.
+pub fn get_diff_stat(worktree_path: &str, base: Option<&str>) -> Result<String, String> {
+    let tree = worktree_tree(worktree_path)?;
+    let output = Command::new("git")
+        .args(["diff", "--stat", base.unwrap_or("HEAD"), &tree])
         .current_dir(worktree_path)
         .output()
         .map_err(|e| format!("Failed to run git diff --stat: {}", e))?;
@@ -194,6 +214,26 @@ pub fn get_diff_stat(worktree_path: &str) -> Result<String, String> {
     }
 }
 
+/// Full patch of everything since `base`, untracked files included, with
+/// rename detection; without a base, only the uncommitted changes.
+pub fn get_diff_since(worktree_path: &str, base: Option<&str>) -> Result<String, String> {
+    let tree = worktree_tree(worktree_path)?;
+    let output = Command::new("git")
+        .args(["diff", "--find-renames", base.unwrap_or("HEAD"), &tree])
+        .current_dir(worktree_path)
+        .output()
+        .map_err(|e| format!("Failed to run git diff: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!(
+            "git diff failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
 pub fn remove_worktree(repo_path: &str, worktree_path: &str) -> Result<(), String> {
     let output = Command::new("git")
         .args(["worktree", "remove", worktree_path, "--force"])
@@ -264,6 +304,96 @@ pub fn delete_remote_branch(repo_path: &str, branch_name: &str) -> Result<(), St
     }
 }
 
+/// `git diff --stat HEAD`: staged and unstaged changes not yet committed.
+pub fn get_working_diff_stat(path: &str) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(["diff", "--stat", "HEAD"])
+        .current_dir(path)
+        .output()
+        .map_err(|e| format!("Failed to run git diff --stat HEAD: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(format!(
+            "git diff --stat HEAD failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Full patch introduced by `commit` (`git show --format= --patch`).
+pub fn get_commit_diff(path: &str, commit: &str) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(["show", "--format=", "--patch", commit])
+        .current_dir(path)
+        .output()
+        .map_err(|e| format!("Failed to run git show: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!(
+            "git show {} failed: {}",
+            commit,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// `git diff HEAD`: full patch of staged and unstaged changes.
+pub fn get_working_diff(path: &str) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(["diff", "HEAD"])
+        .current_dir(path)
+        .output()
+        .map_err(|e| format!("Failed to run git diff HEAD: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!(
+            "git diff HEAD failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Most recent modification time among changed/untracked files and the
+/// worktree's HEAD reflog (which moves on every commit).
+pub fn last_change_time(path: &str) -> Option<std::time::SystemTime> {
+    let status = Command::new("git")
+        .args(["status", "--porcelain", "-z", "--untracked-files=all"])
+        .current_dir(path)
+        .output()
+        .ok()?;
+    let mut latest: Option<std::time::SystemTime> = None;
+    let mut consider = |p: &Path| {
+        if let Ok(modified) = std::fs::metadata(p).and_then(|m| m.modified()) {
+            latest = Some(latest.map_or(modified, |l| l.max(modified)));
+        }
+    };
+    if status.status.success() {
+        // Entries are "XY path\0"; renames carry an extra "orig\0" entry.
+        for entry in String::from_utf8_lossy(&status.stdout).split('\0') {
+            if entry.len() > 3 {
+                consider(&Path::new(path).join(&entry[3..]));
+            }
+        }
+    }
+    let git_dir = Command::new("git")
+        .args(["rev-parse", "--absolute-git-dir"])
+        .current_dir(path)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+    if let Some(git_dir) = git_dir {
+        consider(&Path::new(&git_dir).join("logs").join("HEAD"));
+    }
+    latest
+}
+
 pub fn has_uncommitted_changes(path: &str) -> Result<bool, String> {
     let output = Command::new("git")
         .args(["status", "--porcelain"])
@@ -281,6 +411,33 @@ pub fn has_uncommitted_changes(path: &str) -> Result<bool, String> {
     }
 }
 
+/// Add `pattern` to the repository's `info/exclude` (shared by its worktrees)
+/// unless it is already listed.
+pub fn exclude_locally(path: &str, pattern: &str) -> Result<(), String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", "info/exclude"])
+        .current_dir(path)
+        .output()
+        .map_err(|e| format!("Failed to run git rev-parse --git-path: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git rev-parse --git-path failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let exclude = Path::new(path).join(String::from_utf8_lossy(&output.stdout).trim());
+    let current = std::fs::read_to_string(&exclude).unwrap_or_default();
+    if current.lines().any(|line| line.trim() == pattern) {
+        return Ok(());
+    }
+    if let Some(dir) = exclude.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let separator = if current.is_empty() || current.ends_with('\n') { "" } else { "\n" };
+    std::fs::write(&exclude, format!("{}{}{}\n", current, separator, pattern))
+        .map_err(|e| format!("Failed to update {}: {}", exclude.display(), e))
+}
+
 pub fn fetch_all(repo_path: &str) -> Result<(), String> {
     let output = Command::new("git")
         .args(["fetch", "--all", "--prune"])
@@ -423,3 +580,127 @@ pub fn commit_all(repo_path: &str, message: &str) -> Result<String, String> {
         ))
     }
 }
+
+/// Tree object of the worktree as it is on disk, untracked (not ignored)
+/// files included. Stages into a throwaway index so the agent's own index is
+/// left alone.
+fn worktree_tree(path: &str) -> Result<String, String> {
+    let git = |args: &[&str], index: Option<&Path>| -> Result<String, String> {
+        let mut cmd = Command::new("git");
+        cmd.args(args).current_dir(path);
+        if let Some(index) = index {
+            cmd.env("GIT_INDEX_FILE", index);
+        }
+        let output = cmd
+            .output()
+            .map_err(|e| format!("Failed to run git {}: {}", args[0], e))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Err(format!(
+                "git {} failed: {}",
+                args[0],
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    };
+
+    // One index per call: snapshots and diffs of the same worktree may overlap.
+    let index_name = format!("townui-index-{}", uuid::Uuid::new_v4());
+    let index_path = git(&["rev-parse", "--git-path", &index_name], None)?;
+    let index = Path::new(path).join(index_path);
+    let tree = git(&["read-tree", "HEAD"], Some(&index))
+        .and_then(|_| git(&["add", "-A"], Some(&index)))
+        .and_then(|_| git(&["write-tree"], Some(&index)));
+    let _ = std::fs::remove_file(&index);
+    tree
+}
+
+/// Commit the worktree's current state (tracked and untracked files) as a
+/// child of `HEAD` without touching the branch, index or files. Returns the
+/// commit and its tree.
+pub fn snapshot_worktree(path: &str, message: &str) -> Result<(String, String), String> {
+    let git = |args: &[&str]| -> Result<String, String> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(path)
+            .output()
+            .map_err(|e| format!("Failed to run git {}: {}", args[0], e))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Err(format!(
+                "git {} failed: {}",
+                args[0],
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    };
+
+    let head = git(&["rev-parse", "HEAD"])?;
+    let tree = worktree_tree(path)?;
+    let commit = git(&["commit-tree", &tree, "-p", &head, "-m", message])?;
+    Ok((commit, tree))
+}
+
+pub fn update_ref(repo_path: &str, ref_name: &str, commit: &str) -> Result<(), String> {
+    let output = Command::new("git")
+        .args(["update-ref", ref_name, commit])
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| format!("Failed to run git update-ref: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "git update-ref failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+pub fn delete_ref(repo_path: &str, ref_name: &str) -> Result<(), String> {
+    let output = Command::new("git")
+        .args(["update-ref", "-d", ref_name])
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| format!("Failed to run git update-ref -d: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "git update-ref -d failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Roll a worktree back to a `snapshot_worktree` commit: the branch is reset
+/// to the snapshot's parent and the snapshot's files come back as
+/// uncommitted changes. Untracked files not in the snapshot are removed.
+pub fn restore_snapshot(path: &str, snapshot: &str) -> Result<(), String> {
+    let parent = format!("{}^", snapshot);
+    let steps: [&[&str]; 4] = [
+        &["reset", "--hard", &parent],
+        &["clean", "-fd"],
+        &["read-tree", "--reset", "-u", snapshot],
+        &["reset", "-q"],
+    ];
+    for args in steps {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(path)
+            .output()
+            .map_err(|e| format!("Failed to run git {}: {}", args[0], e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+    }
+    Ok(())
+}
@@ -1,29 +1,28 @@
+pub mod asciicast;
 pub mod commands;
+pub mod diff;
 pub mod git;
+pub mod lifecycle;
 pub mod models;
+pub mod priming;
+pub mod prompt_context;
+pub mod sandbox;
+pub mod scheduler;
+pub mod secrets;
+pub mod sessions;
 pub mod state;
 pub mod templates;
+pub mod usage;
+pub mod verification;
+pub mod vt;
 
 use notify::{recommended_watcher, EventKind, RecursiveMode, Watcher};
 use models::worker::WorkerStatusEnum;
 use state::AppState;
 use tauri::{Emitter, Manager, RunEvent};
 
-/// Kill a process tree by PID (used during shutdown cleanup).
-fn kill_pid(pid: u32) {
-    #[cfg(target_os = "windows")]
-    {
-        let _ = std::process::Command::new("taskkill")
-            .args(["/PID", &pid.to_string(), "/T", "/F"])
-            .output();
-    }
-    #[cfg(not(target_os = "windows"))]
-    {
-        let _ = std::process::Command::new("kill")
-            .args(["-9", &pid.to_string()])
-            .output();
-    }
-}
+/// Longest app exit waits for workers to stop before killing them.
+const SHUTDOWN_STOP_WAIT: std::time::Duration = std::time::Duration::from_secs(4);
 
 fn start_tasks_file_watch(app_handle: tauri::AppHandle) {
     let watch_dir = app_handle.state::<AppState>().town_dir.clone();
@@ -75,13 +74,35 @@ fn start_tasks_file_watch(app_handle: tauri::AppHandle) {
         .ok();
 }
 
+/// Periodically flush buffered worker logs and recordings so a crash loses at most a second of output.
+fn start_log_flusher(app_handle: tauri::AppHandle) {
+    std::thread::Builder::new()
+        .name("worker-log-flush".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            let state = app_handle.state::<AppState>();
+            state.flush_worker_logs();
+            state.flush_recordings();
+        })
+        .ok();
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let app = tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .manage(AppState::new())
         .setup(|app| {
+            secrets::init(&app.state::<AppState>());
             start_tasks_file_watch(app.handle().clone());
+            start_log_flusher(app.handle().clone());
+            commands::prompts::start_prompt_watch(app.handle().clone());
+            commands::retry::start_retry_scheduler(app.handle().clone());
+            scheduler::start_scheduler(app.handle().clone());
+            commands::checkpoints::start_checkpoint_timer(app.handle().clone());
+            commands::workers::reattach_worker_sessions(app.handle());
+            commands::crews::clear_stale_crew_leases(&app.state::<AppState>());
+            commands::workers::requeue_stored_runs(app.handle());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -90,11 +111,13 @@ pub fn run() {
             commands::rigs::create_rig,
             commands::rigs::get_rig,
             commands::rigs::delete_rig,
+            commands::rigs::update_rig_settings,
             // Crews
             commands::crews::list_crews,
             commands::crews::create_crew,
             commands::crews::get_crew,
             commands::crews::delete_crew,
+            commands::crews::update_crew_isolation,
             commands::crews::list_branches,
             commands::crews::get_crew_presets,
             commands::crews::create_cross_rig_worktree,
@@ -134,6 +157,7 @@ pub fn run() {
             commands::convoys::add_item_to_convoy,
             commands::convoys::update_convoy_status,
             commands::convoys::convoy_land,
+            commands::convoys::set_convoy_budget,
             // Actors
             commands::actors::list_actors,
             commands::actors::create_actor,
@@ -147,17 +171,58 @@ pub fn run() {
             commands::workers::get_worker_status,
             commands::workers::list_workers,
             commands::workers::get_worker_logs,
+            commands::workers::count_worker_logs,
             commands::workers::execute_task,
             commands::workers::list_runs,
             commands::workers::get_run,
             commands::workers::get_run_logs,
+            commands::workers::get_run_patch,
+            commands::workers::get_run_diff,
             commands::workers::open_in_explorer,
             commands::workers::write_to_worker,
             commands::workers::resize_worker_pty,
+            commands::workers::get_worker_scrollback,
+            commands::workers::list_worker_sessions,
+            commands::workers::get_worker_screen,
+            commands::prompts::test_prompt_rules,
+            commands::liveness::get_worker_liveness,
+            commands::liveness::list_worker_liveness,
+            commands::retry::retry_run,
+            commands::retry::cancel_retry,
+            commands::retry::get_run_attempts,
+            commands::review::review_run,
+            commands::best_of::execute_task_best_of,
+            commands::best_of::list_best_of,
+            commands::best_of::get_best_of_report,
+            commands::best_of::promote_best_of,
+            commands::best_of::discard_best_of,
+            commands::replay::replay_run,
+            commands::replay::list_run_replays,
+            // Env profiles & secrets
+            commands::env::list_env_profiles,
+            commands::env::set_env_var,
+            commands::env::remove_env_var,
+            commands::env::get_secrets_status,
+            commands::env::unlock_secrets,
+            commands::env::set_secrets_passphrase,
+            commands::env::preview_worker_env,
+            // Scheduler
+            commands::scheduler::get_scheduler_status,
+            commands::scheduler::cancel_queued_spawn,
+            // Run checkpoints
+            commands::checkpoints::create_run_checkpoint,
+            commands::checkpoints::restore_run_checkpoint,
+            commands::checkpoints::discard_run_checkpoints,
+            commands::recordings::list_recordings,
+            commands::recordings::read_worker_recording,
+            commands::recordings::export_worker_recording,
             commands::workers::spawn_polecat,
             commands::workers::set_run_model_tag,
             commands::workers::set_run_quality_signal,
             commands::workers::list_run_stats,
+            commands::workers::record_run_usage,
+            commands::costs::get_cost_report,
+            commands::budgets::get_budget_status,
             // Templates
             commands::templates::list_templates,
             commands::templates::render_template,
@@ -165,6 +230,7 @@ pub fn run() {
             commands::settings::get_settings,
             commands::settings::update_settings,
             commands::settings::validate_cli_path,
+            commands::settings::get_sandbox_capabilities,
             // Audit
             commands::audit::list_audit_events,
             commands::audit::get_task_audit_events,
@@ -221,30 +287,52 @@ pub fn run() {
 
     app.run(|app_handle, event| {
         if let RunEvent::ExitRequested { .. } = &event {
-            // Kill all running worker processes on app exit
+            // Stop running workers in stages (checkpointing each), all at once, and
+            // wait for them up to SHUTDOWN_STOP_WAIT; stragglers are killed.
+            // Workers hosted in a tmux session keep running and are reattached on next start.
             let state = app_handle.state::<AppState>();
-            if let Ok(workers) = state.workers.lock() {
-                for worker in workers.iter() {
-                    if worker.status == WorkerStatusEnum::Running {
-                        if let Some(pid) = worker.pid {
-                            eprintln!("[shutdown] Killing worker {} (pid {})", worker.id, pid);
-                            kill_pid(pid);
-                        }
-                    }
-                }
+            let running: Vec<(String, Option<u32>)> = state
+                .workers
+                .lock()
+                .map(|workers| {
+                    workers
+                        .iter()
+                        .filter(|w| w.status == WorkerStatusEnum::Running && w.session.is_none())
+                        .map(|w| (w.id.clone(), w.pid))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let stops: Vec<(Option<u32>, std::thread::JoinHandle<()>)> = running
+                .into_iter()
+                .filter_map(|(id, pid)| {
+                    eprintln!("[shutdown] Stopping worker {}", id);
+                    let app = app_handle.clone();
+                    std::thread::Builder::new()
+                        .name(format!("shutdown-{}", &id[..8.min(id.len())]))
+                        .spawn(move || {
+                            let state = app.state::<AppState>();
+                            if let Ok(Some(handle)) = commands::workers::begin_graceful_stop(&state, &app, &id, "shutdown") {
+                                let _ = handle.join();
+                            }
+                        })
+                        .ok()
+                        .map(|handle| (pid, handle))
+                })
+                .collect();
+            let deadline = std::time::Instant::now() + SHUTDOWN_STOP_WAIT;
+            while stops.iter().any(|(_, h)| !h.is_finished()) && std::time::Instant::now() < deadline {
+                std::thread::sleep(std::time::Duration::from_millis(50));
             }
-
-            // Flush any in-memory logs to disk
-            let drained_logs = {
-                if let Ok(mut logs) = state.worker_logs.lock() {
-                    logs.drain().collect::<Vec<(String, Vec<_>)>>()
-                } else {
-                    Vec::new()
+            for (pid, handle) in &stops {
+                if let (Some(pid), false) = (pid, handle.is_finished()) {
+                    eprintln!("[shutdown] Worker pid {} did not stop in time; killing it", pid);
+                    commands::workers::kill_process_tree(*pid);
                 }
-            };
-            for (worker_id, entries) in drained_logs {
-                state.save_log(&worker_id, &entries);
             }
+
+            // Flush buffered log lines and recordings to disk
+            state.flush_worker_logs();
+            state.flush_recordings();
         }
     });
 }
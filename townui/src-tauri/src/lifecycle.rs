@@ -0,0 +1,311 @@
+//! Lifecycle hooks: user scripts run around agent runs (pre_spawn, post_exit,
+//! pre_commit, post_merge, on_task_done). Town hooks run before rig hooks;
+//! the run context is passed as `TOWNUI_*` environment variables and output
+//! goes to the worker log with `stream: "hook"`.
+
+use std::thread;
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+
+use crate::models::audit::{AuditEvent, AuditEventType};
+use crate::models::lifecycle::{HookOutcome, LifecycleEvent, LifecycleHook};
+use crate::models::worker::LogEntry;
+use crate::state::AppState;
+
+/// Output lines of a hook copied into the worker log.
+const HOOK_LOG_LINES: usize = 60;
+
+/// Where a hook runs and what it is told about the run.
+#[derive(Debug, Clone, Default)]
+pub struct HookContext {
+    pub rig_id: String,
+    pub cwd: String,
+    /// Worker whose log receives the hook output.
+    pub worker_id: Option<String>,
+    vars: Vec<(String, String)>,
+}
+
+impl HookContext {
+    pub fn new(rig_id: &str, cwd: &str) -> Self {
+        Self {
+            rig_id: rig_id.to_string(),
+            cwd: cwd.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn set(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.vars.retain(|(k, _)| k != key);
+        self.vars.push((key.to_string(), value.into()));
+        self
+    }
+
+    fn set_opt(self, key: &str, value: Option<impl Into<String>>) -> Self {
+        match value {
+            Some(v) => self.set(key, v),
+            None => self,
+        }
+    }
+
+    /// Rig and crew variables; runs in the crew worktree.
+    pub fn for_crew(state: &AppState, crew_id: &str) -> Option<Self> {
+        let crew = {
+            let crews = state.crews.lock().unwrap();
+            crews.iter().find(|c| c.id == crew_id).cloned()?
+        };
+        let (rig_name, rig_path) = {
+            let rigs = state.rigs.lock().unwrap();
+            let rig = rigs.iter().find(|r| r.id == crew.rig_id)?;
+            (rig.name.clone(), rig.path.clone())
+        };
+        Some(
+            Self::new(&crew.rig_id, &crew.path)
+                .set("TOWNUI_RIG_ID", crew.rig_id.clone())
+                .set("TOWNUI_RIG_NAME", rig_name)
+                .set("TOWNUI_RIG_PATH", rig_path)
+                .set("TOWNUI_CREW_ID", crew.id.clone())
+                .set("TOWNUI_CREW_PATH", crew.path.clone())
+                .set("TOWNUI_BRANCH", crew.branch),
+        )
+    }
+
+    /// Crew variables plus the worker, its latest run and that run's task.
+    pub fn for_worker(state: &AppState, worker_id: &str) -> Option<Self> {
+        let worker = {
+            let workers = state.workers.lock().unwrap();
+            workers.iter().find(|w| w.id == worker_id).cloned()?
+        };
+        let mut ctx = Self::for_crew(state, &worker.crew_id)?
+            .set("TOWNUI_WORKER_ID", worker.id.clone())
+            .set("TOWNUI_AGENT", worker.agent_type.clone())
+            .set("TOWNUI_WORKER_STATUS", enum_str(&worker.status));
+        ctx.worker_id = Some(worker.id.clone());
+
+        let run = {
+            let runs = state.runs.lock().unwrap();
+            runs.iter().rev().find(|r| r.worker_id == worker.id).cloned()
+        };
+        if let Some(run) = run {
+            let patch_path = state.patch_path(&run.id);
+            ctx = ctx
+                .set("TOWNUI_RUN_ID", run.id.clone())
+                .set("TOWNUI_RUN_STATUS", enum_str(&run.status))
+                .set("TOWNUI_TEMPLATE", run.template_name.clone())
+                .set_opt("TOWNUI_EXIT_CODE", run.exit_code.map(|c| c.to_string()))
+                .set_opt("TOWNUI_BASE_COMMIT", run.base_commit.clone())
+                .set_opt("TOWNUI_END_COMMIT", run.end_commit.clone())
+                .set_opt(
+                    "TOWNUI_PATCH_FILE",
+                    patch_path.exists().then(|| patch_path.to_string_lossy().to_string()),
+                );
+            ctx = ctx.with_task(state, &run.task_id);
+        }
+        Some(ctx)
+    }
+
+    /// Task variables for `task_id`, when the task exists.
+    pub fn with_task(self, state: &AppState, task_id: &str) -> Self {
+        let task = {
+            let tasks = state.tasks.lock().unwrap();
+            tasks.iter().find(|t| t.id == task_id).cloned()
+        };
+        match task {
+            Some(task) => self
+                .set("TOWNUI_TASK_ID", task.id)
+                .set("TOWNUI_TASK_TITLE", task.title)
+                .set("TOWNUI_TASK_STATUS", enum_str(&task.status))
+                .set("TOWNUI_TASK_PRIORITY", enum_str(&task.priority)),
+            None => self,
+        }
+    }
+}
+
+/// Serialized name of a unit enum variant (`"in_progress"`).
+fn enum_str<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Hooks bound to `event` for a rig: town hooks first, then the rig's own.
+pub fn hooks_for(state: &AppState, rig_id: &str, event: LifecycleEvent) -> Vec<LifecycleHook> {
+    let mut hooks: Vec<LifecycleHook> = {
+        let settings = state.settings.lock().unwrap();
+        settings.lifecycle_hooks.iter().filter(|h| h.event == event).cloned().collect()
+    };
+    let rigs = state.rigs.lock().unwrap();
+    if let Some(rig) = rigs.iter().find(|r| r.id == rig_id) {
+        hooks.extend(rig.settings.lifecycle_hooks.iter().filter(|h| h.event == event).cloned());
+    }
+    hooks
+}
+
+fn hook_entry(line: String) -> LogEntry {
+    LogEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        stream: "hook".to_string(),
+        line,
+    }
+}
+
+/// Run every hook bound to `event`, in order, handing each log line to
+/// `on_log`. A failed hook does not stop the ones after it.
+pub fn run_hooks(
+    state: &AppState,
+    event: LifecycleEvent,
+    ctx: &HookContext,
+    mut on_log: impl FnMut(LogEntry),
+) -> Vec<HookOutcome> {
+    let hooks = hooks_for(state, &ctx.rig_id, event);
+    if hooks.is_empty() {
+        return Vec::new();
+    }
+    let default_timeout = state.settings.lock().unwrap().lifecycle_hook_timeout_seconds;
+    let mut env = ctx.vars.clone();
+    env.push(("TOWNUI_EVENT".to_string(), event.as_str().to_string()));
+
+    hooks
+        .iter()
+        .map(|hook| {
+            on_log(hook_entry(format!("[{}] $ {}", event.as_str(), hook.command)));
+            let timeout = Duration::from_secs(hook.timeout_seconds.unwrap_or(default_timeout));
+            let result = crate::verification::run_shell(&ctx.cwd, &hook.command, &env, timeout);
+            let tail = state.redact(&crate::verification::tail_lines(&result.output, HOOK_LOG_LINES));
+            for line in tail.lines() {
+                on_log(hook_entry(line.to_string()));
+            }
+            let status = if result.timed_out {
+                "timed out".to_string()
+            } else {
+                match result.exit_code {
+                    Some(code) => format!("exit {}", code),
+                    None => "killed".to_string(),
+                }
+            };
+            on_log(hook_entry(format!(
+                "[{}] {} in {:.1}s",
+                event.as_str(),
+                status,
+                result.duration_ms as f64 / 1000.0
+            )));
+
+            let outcome = HookOutcome {
+                event,
+                command: hook.command.clone(),
+                required: hook.required,
+                passed: result.passed,
+                exit_code: result.exit_code,
+                timed_out: result.timed_out,
+                duration_ms: result.duration_ms,
+            };
+            if !outcome.passed {
+                state.append_audit_event(&AuditEvent::new(
+                    ctx.rig_id.clone(),
+                    None,
+                    ctx.vars
+                        .iter()
+                        .find(|(k, _)| k == "TOWNUI_TASK_ID")
+                        .map(|(_, v)| v.clone()),
+                    AuditEventType::LifecycleHookFailed,
+                    serde_json::json!({
+                        "event": event,
+                        "command": hook.command,
+                        "required": hook.required,
+                        "exit_code": result.exit_code,
+                        "timed_out": result.timed_out,
+                        "worker_id": ctx.worker_id,
+                        "output_tail": tail,
+                    })
+                    .to_string(),
+                ));
+            }
+            outcome
+        })
+        .collect()
+}
+
+/// Run hooks and write their output into the context's worker log.
+pub fn run_hooks_logged(state: &AppState, app: &AppHandle, event: LifecycleEvent, ctx: &HookContext) -> Vec<HookOutcome> {
+    run_hooks(state, event, ctx, |entry| {
+        if let Some(ref worker_id) = ctx.worker_id {
+            crate::commands::workers::emit_worker_log(state, app, worker_id, entry);
+        }
+    })
+}
+
+/// Run hooks for a worker's run with its full context; `extra` adds event-specific variables.
+pub fn run_worker_hooks(
+    state: &AppState,
+    app: &AppHandle,
+    event: LifecycleEvent,
+    worker_id: &str,
+    extra: &[(&str, String)],
+) -> Vec<HookOutcome> {
+    let Some(mut ctx) = HookContext::for_worker(state, worker_id) else {
+        return Vec::new();
+    };
+    if hooks_for(state, &ctx.rig_id, event).is_empty() {
+        return Vec::new();
+    }
+    for (k, v) in extra {
+        ctx = ctx.set(k, v.clone());
+    }
+    run_hooks_logged(state, app, event, &ctx)
+}
+
+/// Run hooks on a background thread (post_merge, on_task_done) so callers never wait on user scripts.
+pub fn spawn_hooks(app: &AppHandle, event: LifecycleEvent, ctx: HookContext) {
+    if hooks_for(&app.state::<AppState>(), &ctx.rig_id, event).is_empty() {
+        return;
+    }
+    let app = app.clone();
+    let _ = thread::Builder::new()
+        .name(format!("lifecycle-{}", event.as_str()))
+        .spawn(move || {
+            let state = app.state::<AppState>();
+            run_hooks_logged(&state, &app, event, &ctx);
+        });
+}
+
+/// Fire on_task_done for a task that just moved to Done, in the worktree of its latest run.
+pub fn task_done(app: &AppHandle, task_id: &str) {
+    let state = app.state::<AppState>();
+    let rig_id = {
+        let tasks = state.tasks.lock().unwrap();
+        let Some(task) = tasks.iter().find(|t| t.id == task_id) else {
+            return;
+        };
+        task.rig_id.clone()
+    };
+    let worker_id = {
+        let runs = state.runs.lock().unwrap();
+        runs.iter().rev().find(|r| r.task_id == task_id).map(|r| r.worker_id.clone())
+    };
+    if hooks_for(&state, &rig_id, LifecycleEvent::OnTaskDone).is_empty() {
+        return;
+    }
+
+    // Prefer the latest run's worktree; fall back to the rig checkout once it is gone.
+    let rig_path = {
+        let rigs = state.rigs.lock().unwrap();
+        rigs.iter().find(|r| r.id == rig_id).map(|r| r.path.clone())
+    };
+    let Some(rig_path) = rig_path else {
+        return;
+    };
+    let mut ctx = worker_id
+        .as_deref()
+        .and_then(|w| HookContext::for_worker(&state, w))
+        .unwrap_or_else(|| {
+            HookContext::new(&rig_id, &rig_path)
+                .set("TOWNUI_RIG_ID", rig_id.clone())
+                .set("TOWNUI_RIG_PATH", rig_path.clone())
+        })
+        .with_task(&state, task_id);
+    if !std::path::Path::new(&ctx.cwd).is_dir() {
+        ctx.cwd = rig_path;
+    }
+    spawn_hooks(app, LifecycleEvent::OnTaskDone, ctx);
+}
@@ -2,5 +2,10 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // Landlock launcher mode: confine and exec an agent command, never starts the UI.
+    #[cfg(target_os = "linux")]
+    if std::env::args().nth(1).as_deref() == Some(townui_lib::sandbox::LANDLOCK_EXEC_FLAG) {
+        townui_lib::sandbox::landlock_exec_main();
+    }
     townui_lib::run();
 }
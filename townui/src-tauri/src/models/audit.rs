@@ -34,6 +34,30 @@ pub enum AuditEventType {
     StateCompacted,
     RefinerySynced,
     RefinerySyncFailed,
+    BudgetExceeded,
+    BudgetCleared,
+    RunLimitExceeded,
+    WorkerReattached,
+    WorkerSessionLost,
+    PromptAutoAnswered,
+    PromptEscalated,
+    PromptResolved,
+    LivenessRecovery,
+    RunRetryScheduled,
+    RunRetried,
+    RunRetryExhausted,
+    RunReviewed,
+    RunRevisionStarted,
+    BestOfStarted,
+    BestOfPromoted,
+    BestOfDiscarded,
+    RunReplayed,
+    LifecycleHookFailed,
+    EnvVarChanged,
+    SecretsKeyChanged,
+    SpawnQueued,
+    SpawnCancelled,
+    CheckpointRestored,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
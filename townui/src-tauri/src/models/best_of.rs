@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BestOfStatus {
+    /// Candidates running or awaiting a decision.
+    Open,
+    /// A winner was merged into the target crew; the other polecats are gone.
+    Promoted,
+    /// Every candidate was thrown away.
+    Discarded,
+}
+
+/// One candidate of a best-of-N comparison: a run on its own polecat worktree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BestOfEntry {
+    pub run_id: String,
+    pub worker_id: String,
+    pub crew_id: String,
+    pub branch: String,
+    pub agent_type: String,
+    #[serde(default)]
+    pub model_tag: Option<String>,
+}
+
+/// The same task and prompt run N times in parallel so the results can be compared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BestOfComparison {
+    pub id: String,
+    pub task_id: String,
+    pub rig_id: String,
+    pub template_name: String,
+    pub entries: Vec<BestOfEntry>,
+    pub status: BestOfStatus,
+    #[serde(default)]
+    pub winner_run_id: Option<String>,
+    /// Crew the winning branch was merged into.
+    #[serde(default)]
+    pub promoted_crew_id: Option<String>,
+    pub created_at: String,
+    #[serde(default)]
+    pub resolved_at: Option<String>,
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetScope {
+    /// Town-wide spend for the current UTC day.
+    TownDaily,
+    /// Lifetime spend on a rig.
+    Rig,
+    /// Spend on a rig for the current UTC day.
+    RigDaily,
+    /// Spend on a convoy's work items.
+    Convoy,
+}
+
+/// Spend against one configured budget, as of the last check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetUsage {
+    pub scope: BudgetScope,
+    /// Rig id, convoy id, or the UTC date for `town_daily`.
+    pub scope_id: String,
+    /// Rig the scope belongs to (`None` for town-wide scopes).
+    pub rig_id: Option<String>,
+    pub label: String,
+    pub limit_usd: f64,
+    pub spent_usd: f64,
+    pub exceeded: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub checked_at: String,
+    pub usages: Vec<BudgetUsage>,
+    /// Scopes where propulsion and witness spawning are currently paused.
+    pub paused: Vec<BudgetUsage>,
+}
@@ -51,6 +51,9 @@ pub struct Convoy {
     /// Notes written when landing the convoy (summary of changes).
     #[serde(default)]
     pub land_notes: Option<String>,
+    /// Spend ceiling in USD across all runs on this convoy's work items.
+    #[serde(default)]
+    pub budget_usd: Option<f64>,
 }
 
 impl Convoy {
@@ -70,6 +73,7 @@ impl Convoy {
             owner_actor_id: None,
             merge_strategy: MergeStrategy::default(),
             land_notes: None,
+            budget_usd: None,
         }
     }
 }
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::models::isolation::{ResourceLimits, SandboxPolicy};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Crew {
     pub id: String,
@@ -9,6 +11,44 @@ pub struct Crew {
     pub path: String,
     pub created_at: String,
     pub status: CrewStatus,
+    /// Field-wise overrides of the rig's resource limits.
+    #[serde(default)]
+    pub limits: ResourceLimits,
+    /// Replaces the rig's sandbox policy when set.
+    #[serde(default)]
+    pub sandbox: Option<SandboxPolicy>,
+    /// Worker currently holding the worktree; at most one at a time.
+    #[serde(default)]
+    pub lease: Option<CrewLease>,
+    /// Key of the crew preset it was created from (`frontend`, `qa`, ...).
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// Polecat worktree made by `BusyCrewPolicy::Polecat`; removed once idle.
+    #[serde(default)]
+    pub overflow: bool,
+}
+
+/// Exclusive claim on a crew worktree, renewed while its worker runs.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CrewLease {
+    /// Holding worker, or a `spawning:` token while its process is being started.
+    pub worker_id: String,
+    pub acquired_at: String,
+    pub expires_at: String,
+}
+
+/// What a spawn does when the crew it wants is leased by another worker.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BusyCrewPolicy {
+    /// Wait in the scheduler queue until the crew is free.
+    #[default]
+    Queue,
+    /// Use another idle crew of the rig; wait if there is none.
+    IdleCrew,
+    /// Create a fresh polecat worktree for the spawn. The worktree is removed
+    /// once its runs settle; a branch with commits is kept for merge.
+    Polecat,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -30,6 +70,10 @@ pub struct CrewInfo {
     pub git_branch: Option<String>,
     pub git_status: Option<String>,
     pub changed_files: u32,
+    pub limits: ResourceLimits,
+    pub sandbox: Option<SandboxPolicy>,
+    pub lease: Option<CrewLease>,
+    pub preset: Option<String>,
 }
 
 impl Crew {
@@ -42,6 +86,11 @@ impl Crew {
             path,
             created_at: chrono::Utc::now().to_rfc3339(),
             status: CrewStatus::Active,
+            limits: ResourceLimits::default(),
+            sandbox: None,
+            lease: None,
+            preset: None,
+            overflow: false,
         }
     }
 
@@ -62,6 +111,10 @@ impl Crew {
             git_branch,
             git_status,
             changed_files,
+            limits: self.limits.clone(),
+            sandbox: self.sandbox.clone(),
+            lease: self.lease.clone(),
+            preset: self.preset.clone(),
         }
     }
 }
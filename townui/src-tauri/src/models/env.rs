@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+/// Layer an environment profile applies to. Later layers override earlier
+/// ones: global → rig → crew → actor.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvScope {
+    Global,
+    Rig,
+    Crew,
+    Actor,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvVar {
+    pub key: String,
+    /// Plain value, or for secrets `base64(nonce || AES-256-GCM ciphertext)`.
+    /// Never sent to the UI for secrets.
+    pub value: String,
+    #[serde(default)]
+    pub secret: bool,
+}
+
+/// Variables for one scope (`scope_id` is the rig, crew or actor id; none for global).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvProfile {
+    pub scope: EnvScope,
+    #[serde(default)]
+    pub scope_id: Option<String>,
+    pub vars: Vec<EnvVar>,
+    pub updated_at: String,
+}
+
+/// Where the key that encrypts secret values comes from.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretKeySource {
+    /// Random key kept in the OS keyring (Keychain, Credential Manager, Secret Service).
+    #[default]
+    Keyring,
+    /// Key derived with Argon2id from a passphrase, for headless machines without
+    /// a keyring. Unlocked per session or via `TOWNUI_SECRETS_PASSPHRASE`.
+    Passphrase,
+}
+
+/// `secrets.json`: how to obtain the key and how to check it is the right one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecretsConfig {
+    #[serde(default)]
+    pub source: SecretKeySource,
+    /// Base64 Argon2 salt (passphrase source).
+    #[serde(default)]
+    pub salt: Option<String>,
+    /// A known value encrypted with the current key; a key that cannot decrypt
+    /// it is wrong.
+    #[serde(default)]
+    pub check: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecretsStatus {
+    pub source: SecretKeySource,
+    pub unlocked: bool,
+    pub secret_count: usize,
+    /// Why the key could not be loaded (keyring unavailable, wrong passphrase…).
+    pub error: Option<String>,
+}
+
+/// One variable of a worker's effective environment, with the layer it came from.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedEnvVar {
+    pub key: String,
+    /// Masked for secrets.
+    pub value: String,
+    pub secret: bool,
+    /// `None` for the legacy `AppSettings.env_vars` map.
+    pub scope: Option<EnvScope>,
+}
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+fn default_true() -> bool { true }
+
+/// Per-worker resource ceilings. Enforced on Linux via a transient cgroup v2
+/// scope (`systemd-run --user --scope`) when available, else via rlimits (`prlimit`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ResourceLimits {
+    /// Memory ceiling in MiB (cgroup `MemoryMax`, rlimit fallback: `RLIMIT_AS`).
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
+    /// CPU quota in percent of one core, e.g. 200 = two cores (cgroup only).
+    #[serde(default)]
+    pub cpu_percent: Option<u32>,
+    /// Maximum number of processes/threads (cgroup `TasksMax`, rlimit fallback: `RLIMIT_NPROC`).
+    #[serde(default)]
+    pub max_pids: Option<u64>,
+    /// Total CPU time in seconds (`RLIMIT_CPU`).
+    #[serde(default)]
+    pub cpu_seconds: Option<u64>,
+    /// Maximum open file descriptors (`RLIMIT_NOFILE`).
+    #[serde(default)]
+    pub max_open_files: Option<u64>,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Field-wise override: values set on `other` win.
+    pub fn merged(&self, other: &ResourceLimits) -> ResourceLimits {
+        ResourceLimits {
+            memory_mb: other.memory_mb.or(self.memory_mb),
+            cpu_percent: other.cpu_percent.or(self.cpu_percent),
+            max_pids: other.max_pids.or(self.max_pids),
+            cpu_seconds: other.cpu_seconds.or(self.cpu_seconds),
+            max_open_files: other.max_open_files.or(self.max_open_files),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxKind {
+    #[default]
+    None,
+    /// `bwrap`: system dirs and the rig read-only (see `read_only_root`), worktree read-write.
+    Bubblewrap,
+    /// Landlock LSM: system dirs and the rig read-only, the worktree and `writable_paths` read-write.
+    Landlock,
+}
+
+/// Filesystem sandbox for agent processes (Linux only).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SandboxPolicy {
+    #[serde(default)]
+    pub kind: SandboxKind,
+    /// Bubblewrap: mount only the system dirs (`/usr`, `/bin`, `/lib*`, `/etc`)
+    /// and the paths below, over an empty home and temp dirs. Turning it off
+    /// keeps the host writable and only protects the rig checkout. Landlock
+    /// always behaves this way.
+    #[serde(default = "default_true")]
+    pub read_only_root: bool,
+    /// Let the agent write the rig's `.git` dir so it can commit from its worktree.
+    #[serde(default = "default_true")]
+    pub allow_git_writes: bool,
+    /// Bubblewrap: keep network access (`--unshare-net` when false).
+    #[serde(default = "default_true")]
+    pub network: bool,
+    /// Extra writable paths (agent config/cache dirs such as `~/.claude`).
+    #[serde(default)]
+    pub writable_paths: Vec<String>,
+    /// Extra read-only paths: the agent's install dir when it lives outside
+    /// the system dirs (e.g. `~/.local`, `~/.nvm`) and credentials it reads.
+    #[serde(default)]
+    pub readable_paths: Vec<String>,
+}
+
+impl Default for SandboxPolicy {
+    fn default() -> Self {
+        Self {
+            kind: SandboxKind::None,
+            read_only_root: true,
+            allow_git_writes: true,
+            network: true,
+            writable_paths: Vec::new(),
+            readable_paths: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+/// Points in a run's life where user scripts can run.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleEvent {
+    /// Before the agent process starts, in the crew worktree (e.g. `npm ci`).
+    PreSpawn,
+    /// After the agent exits and its run is finalized, before a polecat worktree is removed.
+    PostExit,
+    /// Before the auto-commit of a completed run (formatters, secret scrubbing).
+    PreCommit,
+    /// After a crew or candidate branch is merged, in the merge target.
+    PostMerge,
+    /// After a task moves to Done.
+    OnTaskDone,
+}
+
+impl LifecycleEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LifecycleEvent::PreSpawn => "pre_spawn",
+            LifecycleEvent::PostExit => "post_exit",
+            LifecycleEvent::PreCommit => "pre_commit",
+            LifecycleEvent::PostMerge => "post_merge",
+            LifecycleEvent::OnTaskDone => "on_task_done",
+        }
+    }
+}
+
+/// A user script bound to a lifecycle event. Town hooks run first, then rig hooks.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LifecycleHook {
+    pub event: LifecycleEvent,
+    /// Shell command; run context arrives as `TOWNUI_*` environment variables.
+    pub command: String,
+    /// Overrides the town `lifecycle_hook_timeout_seconds`.
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
+    /// pre_spawn: a failure aborts the spawn. pre_commit: a failure skips the
+    /// auto-commit. Ignored for the other events.
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// Result of one lifecycle hook execution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookOutcome {
+    pub event: LifecycleEvent,
+    pub command: String,
+    pub required: bool,
+    pub passed: bool,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+}
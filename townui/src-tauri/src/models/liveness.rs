@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+/// Activity classification of a running worker.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LivenessState {
+    /// Recent output, file changes or CPU use.
+    #[default]
+    Active,
+    /// Quiet for longer than `liveness_idle_seconds`.
+    Idle,
+    /// Sitting on an escalated interactive prompt.
+    WaitingInput,
+    /// No output, file change or CPU for longer than `liveness_hung_seconds`.
+    Hung,
+}
+
+/// Steps of the recovery ladder applied to hung workers, in order.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryStep {
+    Nudge,
+    Reprime,
+    Restart,
+    Escalate,
+}
+
+impl RecoveryStep {
+    pub fn next(step: Option<&RecoveryStep>) -> Option<RecoveryStep> {
+        match step {
+            None => Some(RecoveryStep::Nudge),
+            Some(RecoveryStep::Nudge) => Some(RecoveryStep::Reprime),
+            Some(RecoveryStep::Reprime) => Some(RecoveryStep::Restart),
+            Some(RecoveryStep::Restart) => Some(RecoveryStep::Escalate),
+            Some(RecoveryStep::Escalate) => None,
+        }
+    }
+}
+
+/// Liveness signals and recovery progress for one worker (runtime only).
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerLiveness {
+    pub worker_id: String,
+    pub state: LivenessState,
+    pub last_output_at: Option<String>,
+    pub last_file_change_at: Option<String>,
+    /// Total CPU seconds used by the worker's process session (Linux).
+    pub cpu_seconds: Option<f64>,
+    /// CPU seconds used since the previous sample.
+    pub cpu_delta_seconds: Option<f64>,
+    /// Seconds since the most recent output or file change.
+    pub quiet_seconds: u64,
+    pub recovery_step: Option<RecoveryStep>,
+    pub recovery_step_at: Option<String>,
+    pub checked_at: String,
+}
@@ -1,12 +1,24 @@
 pub mod actor;
 pub mod audit;
+pub mod best_of;
+pub mod budget;
 pub mod convoy;
 pub mod crew;
 pub mod dog;
+pub mod env;
 pub mod handoff;
 pub mod hook;
+pub mod isolation;
+pub mod lifecycle;
+pub mod liveness;
+pub mod priming;
+pub mod prompt;
+pub mod retry;
+pub mod review;
 pub mod rig;
+pub mod scheduler;
 pub mod settings;
 pub mod task;
+pub mod verification;
 pub mod worker;
 pub mod workflow;
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+fn default_true() -> bool { true }
+
+/// How startup priming reaches an agent.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PrimingDelivery {
+    /// The best channel the agent adapter supports: system prompt flag,
+    /// then context file, then terminal injection.
+    #[default]
+    Auto,
+    /// Passed on the agent's command line as a system prompt.
+    SystemPrompt,
+    /// Written to a file in the worktree that the agent is told to read.
+    ContextFile,
+    /// Typed into the agent's terminal once it shows it is ready for input.
+    PtyInject,
+    /// Not delivered.
+    Off,
+}
+
+/// Startup priming for the workers it matches. Unset keys match anything;
+/// the profile with the most keys set wins, the earlier one on a tie.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PrimingProfile {
+    pub id: String,
+    pub name: String,
+    /// Role of the worker's actor (case-insensitive).
+    #[serde(default)]
+    pub role: Option<String>,
+    /// Preset key of the worker's crew (`frontend`, `qa`, ...).
+    #[serde(default)]
+    pub crew_preset: Option<String>,
+    #[serde(default)]
+    pub rig_id: Option<String>,
+    /// Agent adapter (case-insensitive).
+    #[serde(default)]
+    pub agent_type: Option<String>,
+    /// Rendered against the prompt context; empty falls back to the town's
+    /// `priming_template`, then the default header.
+    #[serde(default)]
+    pub template: String,
+    /// Preferred channel; falls back to one the adapter supports.
+    #[serde(default)]
+    pub delivery: PrimingDelivery,
+    /// Regex the agent's screen must show before terminal injection,
+    /// replacing the adapter's default.
+    #[serde(default)]
+    pub ready_pattern: Option<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+/// What a profile is matched against.
+#[derive(Debug, Clone, Default)]
+pub struct PrimingTarget {
+    pub role: Option<String>,
+    pub crew_preset: Option<String>,
+    pub rig_id: String,
+    pub agent_type: String,
+}
+
+fn key_matches(key: &Option<String>, value: Option<&str>) -> Option<bool> {
+    let key = key.as_deref().map(str::trim).filter(|k| !k.is_empty())?;
+    Some(value.is_some_and(|v| v.eq_ignore_ascii_case(key)))
+}
+
+impl PrimingProfile {
+    /// Number of keys that matched `target`, or `None` when any set key
+    /// does not match.
+    pub fn specificity(&self, target: &PrimingTarget) -> Option<usize> {
+        if !self.enabled {
+            return None;
+        }
+        let checks = [
+            key_matches(&self.role, target.role.as_deref()),
+            key_matches(&self.crew_preset, target.crew_preset.as_deref()),
+            key_matches(&self.rig_id, Some(&target.rig_id)),
+            key_matches(&self.agent_type, Some(&target.agent_type)),
+        ];
+        if checks.contains(&Some(false)) {
+            return None;
+        }
+        Some(checks.iter().flatten().count())
+    }
+}
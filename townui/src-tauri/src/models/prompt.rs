@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+fn default_true() -> bool { true }
+
+/// What text a prompt rule is matched against.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptScope {
+    /// The bottom of the emulated terminal screen, up to the cursor (PTY workers).
+    #[default]
+    Screen,
+    /// The most recent log lines (works for non-PTY workers too).
+    Output,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptAction {
+    /// Type `response` into the worker.
+    AutoAnswer,
+    /// Mark the worker waiting for input and escalate its task.
+    Escalate,
+}
+
+/// A regex that recognises an agent waiting on an interactive prompt.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptRule {
+    pub id: String,
+    pub name: String,
+    /// Adapters this rule applies to (case-insensitive); empty = all.
+    #[serde(default)]
+    pub agent_types: Vec<String>,
+    pub pattern: String,
+    #[serde(default)]
+    pub scope: PromptScope,
+    pub action: PromptAction,
+    /// Text sent for `AutoAnswer` (e.g. `"y\r"` or `"\r"`).
+    #[serde(default)]
+    pub response: Option<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl PromptRule {
+    pub fn applies_to(&self, agent_type: &str) -> bool {
+        self.enabled
+            && (self.agent_types.is_empty()
+                || self.agent_types.iter().any(|a| a.eq_ignore_ascii_case(agent_type)))
+    }
+}
+
+/// A detected prompt, stored on the worker while it waits for input.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptMatch {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub action: PromptAction,
+    pub matched_text: String,
+    pub detected_at: String,
+}
+
+fn rule(
+    id: &str,
+    name: &str,
+    agent_types: &[&str],
+    pattern: &str,
+    action: PromptAction,
+    response: Option<&str>,
+) -> PromptRule {
+    PromptRule {
+        id: id.to_string(),
+        name: name.to_string(),
+        agent_types: agent_types.iter().map(|a| a.to_string()).collect(),
+        pattern: pattern.to_string(),
+        scope: PromptScope::Screen,
+        action,
+        response: response.map(|r| r.to_string()),
+        enabled: true,
+    }
+}
+
+const TRUST_FOLDER_PATTERN: &str =
+    r"(?i)(do you trust the files in this folder|trust this (folder|directory)\?)";
+
+/// Built-in rules: accept "press enter" pauses; escalate trust dialogs,
+/// command approvals and login prompts. Trust is the user's call; the rule
+/// keeps its response so switching it to auto-answer is one change.
+pub fn default_prompt_rules() -> Vec<PromptRule> {
+    vec![
+        rule(
+            "trust-folder",
+            "Trust folder dialog",
+            &["claude", "gemini", "codex"],
+            TRUST_FOLDER_PATTERN,
+            PromptAction::Escalate,
+            Some("\r"),
+        ),
+        rule(
+            "press-enter",
+            "Press Enter to continue",
+            &[],
+            r"(?i)press (enter|return) to continue",
+            PromptAction::AutoAnswer,
+            Some("\r"),
+        ),
+        rule(
+            "command-approval",
+            "Command / edit approval",
+            &[],
+            r"(?i)(do you want to (proceed|make this edit|create|run)|allow (this|the) command|\[y/n\]\s*:?\s*$|\(y/n\)\s*:?\s*$)",
+            PromptAction::Escalate,
+            None,
+        ),
+        rule(
+            "login-required",
+            "Login / API key required",
+            &[],
+            r"(?i)(please (log ?in|sign ?in)|authentication required|not logged in|invalid api key|api key (is )?(missing|not set))",
+            PromptAction::Escalate,
+            None,
+        ),
+    ]
+}
+
+/// Settings saved before the trust rule escalated carry it unchanged as an
+/// auto-answer; turn that untouched copy into the new default.
+pub fn upgrade_default_rules(rules: &mut [PromptRule]) {
+    for rule in rules.iter_mut() {
+        if rule.id == "trust-folder" && rule.pattern == TRUST_FOLDER_PATTERN && rule.action == PromptAction::AutoAnswer {
+            rule.action = PromptAction::Escalate;
+        }
+    }
+}
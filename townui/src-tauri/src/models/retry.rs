@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+fn default_max_attempts() -> u32 { 1 }
+fn default_backoff_seconds() -> u64 { 30 }
+fn default_backoff_multiplier() -> f64 { 2.0 }
+fn default_max_backoff_seconds() -> u64 { 900 }
+
+/// How a failed run is retried. Resolved task → rig → town; the first one set wins.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first run; 1 disables retries.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    #[serde(default = "default_backoff_seconds")]
+    pub backoff_seconds: u64,
+    /// Factor applied to the delay for each further retry.
+    #[serde(default = "default_backoff_multiplier")]
+    pub backoff_multiplier: f64,
+    /// Upper bound for the delay between attempts.
+    #[serde(default = "default_max_backoff_seconds")]
+    pub max_backoff_seconds: u64,
+    /// Agents to switch to on successive retries (e.g. `["codex", "aider"]`).
+    /// The last one is reused once the chain runs out; empty keeps the original agent.
+    #[serde(default)]
+    pub fallback_agents: Vec<String>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_max_attempts(),
+            backoff_seconds: default_backoff_seconds(),
+            backoff_multiplier: default_backoff_multiplier(),
+            max_backoff_seconds: default_max_backoff_seconds(),
+            fallback_agents: Vec::new(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Seconds to wait before starting attempt `failed_attempt + 1`.
+    pub fn delay_after(&self, failed_attempt: u32) -> u64 {
+        let exponent = failed_attempt.saturating_sub(1) as i32;
+        let delay = self.backoff_seconds as f64 * self.backoff_multiplier.max(1.0).powi(exponent);
+        (delay.round() as u64).min(self.max_backoff_seconds)
+    }
+
+    /// Agent for a given attempt (1-based); attempt 1 always uses `first_agent`.
+    pub fn agent_for_attempt(&self, attempt: u32, first_agent: &str) -> String {
+        if attempt <= 1 || self.fallback_agents.is_empty() {
+            return first_agent.to_string();
+        }
+        let index = ((attempt - 2) as usize).min(self.fallback_agents.len() - 1);
+        self.fallback_agents[index].clone()
+    }
+}
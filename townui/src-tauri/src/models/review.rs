@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+fn default_max_revisions() -> u32 { 2 }
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewMode {
+    /// Built-in checker: non-empty diff, backticked terms from the acceptance
+    /// criteria present in the diff, no `forbidden_patterns` in added lines.
+    #[default]
+    Rules,
+    /// A reviewer agent reads the criteria and diff and answers `VERDICT: PASS|FAIL`.
+    Agent,
+}
+
+/// Review-and-revise loop run after a run completes (and passes verification).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReviewPolicy {
+    #[serde(default)]
+    pub mode: ReviewMode,
+    /// Follow-up runs allowed after a failed review before the task is escalated.
+    #[serde(default = "default_max_revisions")]
+    pub max_revisions: u32,
+    /// Agent mode: reviewer CLI; defaults to the agent that made the change.
+    #[serde(default)]
+    pub reviewer_agent: Option<String>,
+    /// Rules mode: regexes that must not appear in added lines (e.g. `dbg!\(`).
+    #[serde(default)]
+    pub forbidden_patterns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewVerdict {
+    /// Reviewer agent still running.
+    Pending,
+    Pass,
+    Fail,
+    /// Reviewer finished without a verdict; needs a human.
+    Inconclusive,
+}
+
+/// One review of a run, stored on the Run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewRecord {
+    pub mode: ReviewMode,
+    pub verdict: ReviewVerdict,
+    pub feedback: String,
+    #[serde(default)]
+    pub reviewer_worker_id: Option<String>,
+    pub started_at: String,
+    #[serde(default)]
+    pub finished_at: Option<String>,
+}
@@ -1,5 +1,65 @@
 use serde::{Deserialize, Serialize};
 
+use crate::models::crew::BusyCrewPolicy;
+use crate::models::isolation::{ResourceLimits, SandboxPolicy};
+use crate::models::lifecycle::LifecycleHook;
+use crate::models::retry::RetryPolicy;
+use crate::models::review::ReviewPolicy;
+use crate::models::verification::VerificationCheck;
+
+/// Per-rig overrides for town-wide policy. Every field is optional so older
+/// `rigs.json` files load unchanged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RigSettings {
+    /// Lifetime spend ceiling in USD for all runs on this rig.
+    #[serde(default)]
+    pub budget_usd: Option<f64>,
+    /// Spend ceiling in USD per UTC day for this rig.
+    #[serde(default)]
+    pub daily_budget_usd: Option<f64>,
+    /// Wall-clock limit for a single run (overrides the town default).
+    #[serde(default)]
+    pub max_run_seconds: Option<u64>,
+    /// Token cap (input + output) for a single run (overrides the town default).
+    #[serde(default)]
+    pub max_run_tokens: Option<u64>,
+    /// Resource ceilings applied to every worker on this rig (Linux).
+    #[serde(default)]
+    pub limits: ResourceLimits,
+    /// Filesystem sandbox for agents on this rig (Linux).
+    #[serde(default)]
+    pub sandbox: SandboxPolicy,
+    /// Retry policy for failed runs on this rig (overrides the town default).
+    #[serde(default)]
+    pub retry_policy: Option<RetryPolicy>,
+    /// Checks run in the crew worktree after an agent exits cleanly. The task
+    /// only moves to Done when every required check passes.
+    #[serde(default)]
+    pub verification: Vec<VerificationCheck>,
+    /// Review-and-revise loop against the task's acceptance criteria; off when unset.
+    #[serde(default)]
+    pub review: Option<ReviewPolicy>,
+    /// User scripts run around agent runs on this rig, after the town hooks.
+    #[serde(default)]
+    pub lifecycle_hooks: Vec<LifecycleHook>,
+    /// Running workers allowed on this rig; unlimited when unset.
+    #[serde(default)]
+    pub max_concurrent_workers: Option<usize>,
+    /// Fair-share weight against other rigs when queued spawns compete for a slot (default 1).
+    #[serde(default)]
+    pub scheduler_weight: Option<u32>,
+    /// Where task, hook and propulsion spawns go when their crew is busy.
+    #[serde(default)]
+    pub busy_crew_policy: BusyCrewPolicy,
+    /// Minutes between WIP worktree snapshots (overrides the town default).
+    #[serde(default)]
+    pub checkpoint_interval_minutes: Option<u64>,
+    /// Repo-relative file exposed to prompts as `rig.conventions`; defaults to the
+    /// first of `.townui/conventions.md`, `CONVENTIONS.md` or `AGENTS.md` that exists.
+    #[serde(default)]
+    pub conventions_file: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Rig {
     pub id: String,
@@ -7,6 +67,8 @@ pub struct Rig {
     pub path: String,
     pub created_at: String,
     pub last_opened: String,
+    #[serde(default)]
+    pub settings: RigSettings,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +81,7 @@ pub struct RigInfo {
     pub git_branch: Option<String>,
     pub git_status: Option<String>,
     pub is_git_repo: bool,
+    pub settings: RigSettings,
 }
 
 impl Rig {
@@ -30,6 +93,7 @@ impl Rig {
             path,
             created_at: now.clone(),
             last_opened: now,
+            settings: RigSettings::default(),
         }
     }
 
@@ -43,6 +107,7 @@ impl Rig {
             git_branch,
             git_status,
             is_git_repo,
+            settings: self.settings.clone(),
         }
     }
 }
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::task::TaskPriority;
+
+/// What asked for a worker.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SpawnSource {
+    Manual,
+    Task,
+    Retry,
+    Hook,
+    Propulsion,
+    Witness,
+    BestOf,
+    Replay,
+    Review,
+    Restart,
+}
+
+/// A spawn waiting for a slot (or, in `get_scheduler_status`, its place in line).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedSpawn {
+    pub id: String,
+    pub rig_id: String,
+    /// Crew the worker will run on; unset when the spawn creates its own polecat.
+    pub crew_id: Option<String>,
+    pub agent_type: String,
+    pub priority: TaskPriority,
+    pub source: SpawnSource,
+    pub polecat: bool,
+    pub task_id: Option<String>,
+    /// Run created in the `queued` state for this spawn.
+    pub run_id: Option<String>,
+    pub label: String,
+    pub enqueued_at: String,
+    /// The limit currently holding it back.
+    #[serde(default)]
+    pub waiting_on: Option<String>,
+}
+
+/// Running workers against a limit.
+#[derive(Debug, Clone, Serialize)]
+pub struct SlotUsage {
+    /// Rig id or agent type.
+    pub key: String,
+    pub running: usize,
+    pub limit: Option<usize>,
+    pub queued: usize,
+    /// Fair-share weight (rigs only).
+    pub weight: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SchedulerStatus {
+    pub running: usize,
+    pub max_concurrent_workers: Option<usize>,
+    /// 1-minute load average divided by CPU count, where the platform reports it.
+    pub load_per_cpu: Option<f64>,
+    pub max_load_per_cpu: Option<f64>,
+    pub rigs: Vec<SlotUsage>,
+    pub agents: Vec<SlotUsage>,
+    /// In admission order.
+    pub queue: Vec<QueuedSpawn>,
+}
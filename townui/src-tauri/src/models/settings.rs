@@ -1,9 +1,42 @@
 use serde::{Deserialize, Serialize};
 
+use crate::models::lifecycle::LifecycleHook;
+use crate::models::priming::PrimingProfile;
+use crate::models::prompt::{default_prompt_rules, PromptRule};
+use crate::models::retry::RetryPolicy;
+
 fn default_true() -> bool { true }
 fn default_priming_delay_ms() -> u64 { 1500 }
+fn default_priming_ready_timeout_ms() -> u64 { 20_000 }
 fn default_propulsion_interval() -> u64 { 60 }
 fn default_max_polecats() -> usize { 5 }
+fn default_stop_grace_ms() -> u64 { 5000 }
+fn default_checkpoint_log_lines() -> usize { 40 }
+fn default_session_scrollback_lines() -> usize { 5000 }
+fn default_prompt_settle_ms() -> u64 { 800 }
+fn default_liveness_idle_seconds() -> u64 { 120 }
+fn default_liveness_hung_seconds() -> u64 { 600 }
+fn default_verification_timeout_seconds() -> u64 { 600 }
+fn default_lifecycle_hook_timeout_seconds() -> u64 { 300 }
+fn default_max_checkpoints_per_run() -> usize { 20 }
+
+/// Where interactive (PTY) agent sessions live.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionBackend {
+    /// PTY owned by the app process; sessions end when TownUI exits.
+    #[default]
+    Pty,
+    /// Detached tmux session per worker; survives app restarts and is reattached on startup.
+    Tmux,
+}
+
+/// Per-model token pricing in USD per million tokens.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelPrice {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
@@ -22,9 +55,16 @@ pub struct AppSettings {
     /// Extra context injected at agent startup (system info, rig state, role).
     #[serde(default)]
     pub priming_template: Option<String>,
-    /// Milliseconds to wait after spawn before sending priming (give agent time to init).
+    /// Milliseconds to wait after spawn before typing the priming when the
+    /// agent has no ready pattern.
     #[serde(default = "default_priming_delay_ms")]
     pub priming_delay_ms: u64,
+    /// Profiles choosing priming text and delivery by role, crew preset, rig and agent.
+    #[serde(default)]
+    pub priming_profiles: Vec<PrimingProfile>,
+    /// How long to wait for an agent's ready pattern before typing the priming anyway.
+    #[serde(default = "default_priming_ready_timeout_ms")]
+    pub priming_ready_timeout_ms: u64,
 
     // ── Propulsion / Witness ──
     /// When true, Supervisor auto-assigns idle workers to queued tasks (propulsion).
@@ -39,9 +79,104 @@ pub struct AppSettings {
     /// Maximum concurrent polecats per rig.
     #[serde(default = "default_max_polecats")]
     pub max_polecats_per_rig: usize,
-    /// Seconds a polecat can be idle before Witness nudges it.
+    /// Seconds between recovery-ladder steps (nudge → re-prime → restart → escalate) for a hung worker.
     #[serde(default = "default_propulsion_interval")]
     pub polecat_nudge_after_seconds: u64,
+
+    // ── Scheduler ──
+    /// Running workers allowed across all rigs; unlimited when unset.
+    #[serde(default)]
+    pub max_concurrent_workers: Option<usize>,
+    /// Running workers allowed per agent type (e.g. to respect a provider's rate limits).
+    #[serde(default)]
+    pub max_workers_per_agent: std::collections::HashMap<String, usize>,
+    /// Hold queued spawns while the 1-minute load average per CPU is above this (Unix).
+    #[serde(default)]
+    pub max_load_per_cpu: Option<f64>,
+
+    // ── Cost accounting ──
+    /// Price table keyed by run `model_tag` (falls back to `agent_type`).
+    /// Used to estimate cost when an agent reports tokens but no dollar figure.
+    #[serde(default)]
+    pub model_prices: std::collections::HashMap<String, ModelPrice>,
+
+    // ── Budgets ──
+    /// Town-wide spend ceiling in USD per UTC day. Propulsion/witness pause once exceeded.
+    #[serde(default)]
+    pub daily_budget_usd: Option<f64>,
+    /// Default wall-clock limit for a single run; rigs may override.
+    #[serde(default)]
+    pub max_run_seconds: Option<u64>,
+    /// Default token cap (input + output) for a single run; rigs may override.
+    #[serde(default)]
+    pub max_run_tokens: Option<u64>,
+
+    // ── Graceful stop ──
+    /// Milliseconds to wait after the adapter interrupt (Ctrl-C / exit command) before SIGTERM.
+    #[serde(default = "default_stop_grace_ms")]
+    pub stop_interrupt_grace_ms: u64,
+    /// Milliseconds to wait after SIGTERM before SIGKILL.
+    #[serde(default = "default_stop_grace_ms")]
+    pub stop_term_grace_ms: u64,
+    /// Output lines captured into the hook checkpoint when a worker is stopped.
+    #[serde(default = "default_checkpoint_log_lines")]
+    pub checkpoint_log_lines: usize,
+
+    // ── Session host ──
+    #[serde(default)]
+    pub session_backend: SessionBackend,
+    /// Pane history kept by the session host and restored on reattach.
+    #[serde(default = "default_session_scrollback_lines")]
+    pub session_scrollback_lines: usize,
+    /// Record PTY workers as asciicast v2 files under `recordings/`.
+    #[serde(default = "default_true")]
+    pub record_sessions: bool,
+
+    // ── Interactive prompts ──
+    /// Rules that detect agents waiting on a prompt; first match wins.
+    #[serde(default = "default_prompt_rules")]
+    pub prompt_rules: Vec<PromptRule>,
+    /// Quiet time after the last output before the screen is checked for a prompt.
+    #[serde(default = "default_prompt_settle_ms")]
+    pub prompt_settle_ms: u64,
+
+    // ── Liveness ──
+    /// Quiet seconds (no output, file change or CPU) before a worker counts as idle.
+    #[serde(default = "default_liveness_idle_seconds")]
+    pub liveness_idle_seconds: u64,
+    /// Quiet seconds before a worker counts as hung and the recovery ladder starts.
+    #[serde(default = "default_liveness_hung_seconds")]
+    pub liveness_hung_seconds: u64,
+
+    // ── Retries ──
+    /// Town default for retrying failed runs; rigs and tasks may override.
+    #[serde(default)]
+    pub retry_policy: RetryPolicy,
+
+    // ── Verification ──
+    /// Default per-check timeout for rig verification pipelines.
+    #[serde(default = "default_verification_timeout_seconds")]
+    pub verification_timeout_seconds: u64,
+
+    // ── Lifecycle hooks ──
+    /// User scripts run around agent runs on every rig, before the rig's own hooks.
+    #[serde(default)]
+    pub lifecycle_hooks: Vec<LifecycleHook>,
+    /// Default timeout for a lifecycle hook script.
+    #[serde(default = "default_lifecycle_hook_timeout_seconds")]
+    pub lifecycle_hook_timeout_seconds: u64,
+
+    // ── Checkpoints ──
+    /// Minutes between WIP snapshots of a running worker's worktree; unset disables them.
+    /// Rigs may override.
+    #[serde(default)]
+    pub checkpoint_interval_minutes: Option<u64>,
+    /// Oldest snapshots beyond this many per run are dropped.
+    #[serde(default = "default_max_checkpoints_per_run")]
+    pub max_checkpoints_per_run: usize,
+    /// Keep a run's snapshots after it completes instead of cleaning them up.
+    #[serde(default)]
+    pub keep_checkpoints_on_success: bool,
 }
 
 fn default_cli() -> String {
@@ -118,11 +253,37 @@ impl Default for AppSettings {
             startup_priming_enabled: true,
             priming_template: None,
             priming_delay_ms: default_priming_delay_ms(),
+            priming_profiles: Vec::new(),
+            priming_ready_timeout_ms: default_priming_ready_timeout_ms(),
             propulsion_enabled: false,
             propulsion_interval_seconds: default_propulsion_interval(),
             witness_auto_spawn: false,
             max_polecats_per_rig: default_max_polecats(),
             polecat_nudge_after_seconds: default_propulsion_interval(),
+            max_concurrent_workers: None,
+            max_workers_per_agent: std::collections::HashMap::new(),
+            max_load_per_cpu: None,
+            model_prices: std::collections::HashMap::new(),
+            daily_budget_usd: None,
+            max_run_seconds: None,
+            max_run_tokens: None,
+            stop_interrupt_grace_ms: default_stop_grace_ms(),
+            stop_term_grace_ms: default_stop_grace_ms(),
+            checkpoint_log_lines: default_checkpoint_log_lines(),
+            session_backend: SessionBackend::default(),
+            session_scrollback_lines: default_session_scrollback_lines(),
+            record_sessions: true,
+            prompt_rules: default_prompt_rules(),
+            prompt_settle_ms: default_prompt_settle_ms(),
+            liveness_idle_seconds: default_liveness_idle_seconds(),
+            liveness_hung_seconds: default_liveness_hung_seconds(),
+            retry_policy: RetryPolicy::default(),
+            verification_timeout_seconds: default_verification_timeout_seconds(),
+            lifecycle_hooks: Vec::new(),
+            lifecycle_hook_timeout_seconds: default_lifecycle_hook_timeout_seconds(),
+            checkpoint_interval_minutes: None,
+            max_checkpoints_per_run: default_max_checkpoints_per_run(),
+            keep_checkpoints_on_success: false,
         }
     }
 }
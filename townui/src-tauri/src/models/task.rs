@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+use crate::models::retry::RetryPolicy;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum TaskPriority {
     Low,
@@ -42,6 +44,9 @@ pub struct Task {
     pub completed_at: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// Retry policy for this task's runs (overrides the rig and town policy).
+    #[serde(default)]
+    pub retry_policy: Option<RetryPolicy>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,6 +64,7 @@ pub struct TaskUpdateRequest {
     pub hook_id: Option<Option<String>>,
     pub blocked_reason: Option<Option<String>>,
     pub outcome: Option<Option<String>>,
+    pub retry_policy: Option<Option<RetryPolicy>>,
 }
 
 impl Task {
@@ -90,6 +96,7 @@ impl Task {
             completed_at: None,
             created_at: now.clone(),
             updated_at: now,
+            retry_policy: None,
         }
     }
 
@@ -146,6 +153,9 @@ impl Task {
         if let Some(outcome) = update.outcome {
             self.outcome = outcome;
         }
+        if let Some(retry_policy) = update.retry_policy {
+            self.retry_policy = retry_policy;
+        }
         self.updated_at = chrono::Utc::now().to_rfc3339();
     }
 
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+fn default_true() -> bool { true }
+
+/// One step of a rig's verification pipeline, run in the crew worktree after
+/// an agent exits successfully.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VerificationCheck {
+    /// Short label such as "build", "lint" or "test".
+    pub name: String,
+    /// Shell command, e.g. `cargo test --workspace`.
+    pub command: String,
+    /// Overrides the town `verification_timeout_seconds`.
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
+    /// A failing optional check lowers the quality signal but does not fail the run.
+    #[serde(default = "default_true")]
+    pub required: bool,
+}
+
+/// Result of one verification check, stored on the Run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckOutcome {
+    pub name: String,
+    pub command: String,
+    pub required: bool,
+    pub passed: bool,
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub timed_out: bool,
+    pub duration_ms: u64,
+    /// Last lines of combined stdout/stderr.
+    pub output_tail: String,
+}
+
+/// True when every required check passed.
+pub fn checks_passed(outcomes: &[CheckOutcome]) -> bool {
+    outcomes.iter().all(|c| c.passed || !c.required)
+}
+
+/// Quality signal in [0.0 – 5.0] derived from check results: the share of
+/// passing checks, capped at 2.0 when a required check failed.
+pub fn quality_from_checks(outcomes: &[CheckOutcome]) -> Option<f32> {
+    if outcomes.is_empty() {
+        return None;
+    }
+    let passed = outcomes.iter().filter(|c| c.passed).count() as f32;
+    let score = 5.0 * passed / outcomes.len() as f32;
+    Some(if checks_passed(outcomes) { score } else { score.min(2.0) })
+}
@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 
+use crate::models::liveness::LivenessState;
+use crate::models::prompt::PromptMatch;
+use crate::models::review::ReviewRecord;
+use crate::models::verification::CheckOutcome;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum WorkerStatusEnum {
@@ -37,6 +42,15 @@ pub struct Worker {
     /// Optional crew name for terminal header display.
     #[serde(default)]
     pub crew_name: Option<String>,
+    /// Session host name when the agent runs in a detached tmux session.
+    #[serde(default)]
+    pub session: Option<String>,
+    /// Set while the worker sits on an escalated interactive prompt.
+    #[serde(default)]
+    pub waiting_input: Option<PromptMatch>,
+    /// Latest liveness classification from the supervisor.
+    #[serde(default)]
+    pub liveness: LivenessState,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +63,8 @@ pub struct LogEntry {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum RunStatus {
+    /// Waiting in the scheduler queue for a worker slot; `worker_id` is empty.
+    Queued,
     Running,
     Completed,
     Failed,
@@ -71,14 +87,122 @@ pub struct Run {
     pub exit_code: Option<i32>,
     pub diff_stats: Option<String>,
     /// Optional model tag for A/B testing (e.g., "claude-opus-4", "codex-mini").
+    /// Set before launch, it is passed to the agent with its model option.
     #[serde(default)]
     pub model_tag: Option<String>,
     /// Optional human/automated quality signal [0.0 – 5.0].
     #[serde(default)]
     pub quality_signal: Option<f32>,
-    /// Number of revision loops before completion (proxy for difficulty).
+    /// Review revisions before this one in its chain; retries count in `attempt`.
     #[serde(default)]
     pub revision_count: u32,
+    /// Prompt/input tokens consumed by the run, if known.
+    #[serde(default)]
+    pub input_tokens: Option<u64>,
+    /// Completion/output tokens produced by the run, if known.
+    #[serde(default)]
+    pub output_tokens: Option<u64>,
+    /// Combined token count when the agent reports no input/output split
+    /// (codex `tokens used: N`). Not priced: no rate applies to a mix.
+    #[serde(default)]
+    pub total_tokens: Option<u64>,
+    /// Spend in USD — agent-reported when available, otherwise estimated from `model_prices`.
+    #[serde(default)]
+    pub cost_usd: Option<f64>,
+    /// Where `cost_usd` came from: "reported", "estimated" or "manual".
+    #[serde(default)]
+    pub cost_source: Option<String>,
+    /// 1-based attempt number within a retry chain.
+    #[serde(default = "default_attempt")]
+    pub attempt: u32,
+    /// Run this one follows up on (the failed attempt it retries).
+    #[serde(default)]
+    pub previous_run_id: Option<String>,
+    /// When the next retry of this failed run is due; cleared once it starts or is cancelled.
+    #[serde(default)]
+    pub retry_at: Option<String>,
+    /// Outcomes of the rig's verification pipeline, in pipeline order.
+    #[serde(default)]
+    pub checks: Vec<CheckOutcome>,
+    /// Reviews of this run against the acceptance criteria (normally one).
+    #[serde(default)]
+    pub reviews: Vec<ReviewRecord>,
+    /// Best-of-N comparison this run is a candidate in; such runs leave the task to the comparison.
+    #[serde(default)]
+    pub comparison_id: Option<String>,
+    /// Worktree `HEAD` when the run started; the run's diff is taken against it.
+    #[serde(default)]
+    pub base_commit: Option<String>,
+    /// Worktree `HEAD` when the run finished (after any auto-commit).
+    #[serde(default)]
+    pub end_commit: Option<String>,
+    /// Run this one replays (same prompt and base commit, possibly another agent or template).
+    #[serde(default)]
+    pub replay_of: Option<String>,
+    /// WIP snapshots of the worktree taken while the run was going, oldest first.
+    #[serde(default)]
+    pub checkpoints: Vec<RunCheckpoint>,
+}
+
+fn default_attempt() -> u32 { 1 }
+
+/// What `replay_run` changes relative to the original run; unset fields keep the original's.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayOverrides {
+    #[serde(default)]
+    pub agent_type: Option<String>,
+    /// Model passed to the agent; the original's carries over only when the agent is unchanged.
+    #[serde(default)]
+    pub model_tag: Option<String>,
+    /// Re-render the prompt from this template instead of reusing the original prompt verbatim.
+    #[serde(default)]
+    pub template_name: Option<String>,
+    /// Run on a fresh polecat worktree branched from the original base commit.
+    #[serde(default)]
+    pub new_polecat: bool,
+    /// Crew to run on when not using a polecat; defaults to the original run's crew.
+    #[serde(default)]
+    pub crew_id: Option<String>,
+}
+
+/// Snapshot of a run's worktree, stored as a commit under
+/// `refs/townui/checkpoints/<run>/<seq>` so the crew branch is never touched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunCheckpoint {
+    /// 1-based, increasing within the run.
+    pub seq: u32,
+    /// Snapshot commit; its parent is the worktree `HEAD` at the time.
+    pub commit: String,
+    pub tree: String,
+    /// Crew whose worktree was captured.
+    pub crew_id: String,
+    /// "interval", "manual", "exit" or "pre_restore".
+    pub reason: String,
+    pub created_at: String,
+    /// Changes against the run's base commit at the time.
+    pub diff_stat: Option<String>,
+}
+
+/// Snapshot taken when a worker is stopped; stored as the hook's `state_blob`
+/// so `resume_hook` can hand it to the next agent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerCheckpoint {
+    pub worker_id: String,
+    pub run_id: Option<String>,
+    pub task_id: Option<String>,
+    pub agent_type: String,
+    pub reason: String,
+    pub captured_at: String,
+    pub elapsed_seconds: Option<u64>,
+    pub startup_primed: bool,
+    /// Uncommitted changes in the worktree (`git diff --stat HEAD`).
+    pub diff_stat: Option<String>,
+    /// Last lines of agent output before the stop.
+    pub last_output: Vec<String>,
+    /// What the agent printed after Ctrl-C and its exit command, usually its
+    /// own account of where it left off. Added once the interrupt stage ends.
+    #[serde(default)]
+    pub interrupt_output: Vec<String>,
 }
 
 impl Worker {
@@ -97,6 +221,9 @@ impl Worker {
             startup_primed: false,
             task_label: None,
             crew_name: None,
+            session: None,
+            waiting_input: None,
+            liveness: LivenessState::Active,
         }
     }
 }
@@ -128,6 +255,30 @@ impl Run {
             model_tag: None,
             quality_signal: None,
             revision_count: 0,
+            input_tokens: None,
+            output_tokens: None,
+            total_tokens: None,
+            cost_usd: None,
+            cost_source: None,
+            attempt: 1,
+            previous_run_id: None,
+            retry_at: None,
+            checks: Vec::new(),
+            reviews: Vec::new(),
+            comparison_id: None,
+            base_commit: None,
+            end_commit: None,
+            replay_of: None,
+            checkpoints: Vec::new(),
+        }
+    }
+
+    /// All tokens the run consumed: input plus output, or the combined
+    /// figure when that is all the agent reported.
+    pub fn tokens_used(&self) -> u64 {
+        match (self.input_tokens, self.output_tokens) {
+            (None, None) => self.total_tokens.unwrap_or(0),
+            (input, output) => input.unwrap_or(0) + output.unwrap_or(0),
         }
     }
 }
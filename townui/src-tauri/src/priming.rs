@@ -0,0 +1,210 @@
+//! Startup priming: which profile applies to a worker, what it renders to
+//! and how each agent adapter can receive it.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::models::priming::{PrimingDelivery, PrimingProfile, PrimingTarget};
+use crate::prompt_context::PromptContext;
+use crate::state::AppState;
+
+/// Worktree file the launch-time deliveries read; excluded from git and
+/// removed when the worker exits.
+pub const PRIMING_FILE: &str = ".townui_priming.md";
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The priming channels an agent CLI offers besides its terminal.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AdapterPriming {
+    /// Flag taking text appended to the agent's system prompt.
+    pub system_prompt_flag: Option<&'static str>,
+    /// Flag taking a file the agent reads as context.
+    pub context_file_flag: Option<&'static str>,
+    /// Shown once the interactive UI accepts input.
+    pub ready_pattern: Option<&'static str>,
+}
+
+pub fn adapter_priming(agent_type: &str) -> AdapterPriming {
+    match agent_type.to_ascii_lowercase().as_str() {
+        "claude" => AdapterPriming {
+            system_prompt_flag: Some("--append-system-prompt"),
+            ready_pattern: Some(r"\? for shortcuts"),
+            ..Default::default()
+        },
+        "codex" => AdapterPriming {
+            ready_pattern: Some(r"\? for shortcuts"),
+            ..Default::default()
+        },
+        "gemini" => AdapterPriming {
+            ready_pattern: Some(r"(?i)type your message"),
+            ..Default::default()
+        },
+        "aider" => AdapterPriming {
+            context_file_flag: Some("--read"),
+            ready_pattern: Some(r"(?m)^> ?$"),
+            ..Default::default()
+        },
+        _ => AdapterPriming::default(),
+    }
+}
+
+/// The channel actually used for a requested delivery: the request if the
+/// adapter supports it, else the first supported one in the usual order.
+/// `reads_terminal` is false for one-shot runs that never read stdin.
+pub fn resolve_delivery(requested: PrimingDelivery, adapter: &AdapterPriming, reads_terminal: bool) -> PrimingDelivery {
+    let supported = |d: PrimingDelivery| match d {
+        PrimingDelivery::SystemPrompt => adapter.system_prompt_flag.is_some(),
+        PrimingDelivery::ContextFile => adapter.context_file_flag.is_some(),
+        PrimingDelivery::PtyInject => reads_terminal,
+        PrimingDelivery::Auto | PrimingDelivery::Off => false,
+    };
+    if requested == PrimingDelivery::Off {
+        return PrimingDelivery::Off;
+    }
+    if requested != PrimingDelivery::Auto && supported(requested) {
+        return requested;
+    }
+    [PrimingDelivery::SystemPrompt, PrimingDelivery::ContextFile, PrimingDelivery::PtyInject]
+        .into_iter()
+        .find(|d| supported(*d))
+        .unwrap_or(PrimingDelivery::Off)
+}
+
+pub fn priming_target(
+    state: &AppState,
+    crew_id: &str,
+    agent_type: &str,
+    actor_id: Option<&str>,
+) -> PrimingTarget {
+    let mut target = PrimingTarget {
+        agent_type: agent_type.to_string(),
+        ..Default::default()
+    };
+    {
+        let crews = state.crews.lock().unwrap();
+        if let Some(crew) = crews.iter().find(|c| c.id == crew_id) {
+            target.rig_id = crew.rig_id.clone();
+            target.crew_preset = crate::commands::crews::crew_preset_key(crew);
+        }
+    }
+    if let Some(actor_id) = actor_id {
+        let actors = state.actors.lock().unwrap();
+        target.role = actors.iter().find(|a| a.actor_id == actor_id).map(|a| a.role.clone());
+    }
+    target
+}
+
+/// Most specific enabled profile matching `target`.
+pub fn match_profile(state: &AppState, target: &PrimingTarget) -> Option<PrimingProfile> {
+    let settings = state.settings.lock().unwrap();
+    let mut best: Option<(usize, &PrimingProfile)> = None;
+    for profile in &settings.priming_profiles {
+        if let Some(score) = profile.specificity(target) {
+            if best.is_none_or(|(top, _)| score > top) {
+                best = Some((score, profile));
+            }
+        }
+    }
+    best.map(|(_, p)| p.clone())
+}
+
+/// How a spawn gets primed, decided before its command line is built.
+#[derive(Debug, Clone)]
+pub struct PrimingPlan {
+    pub delivery: PrimingDelivery,
+    pub profile: Option<PrimingProfile>,
+    pub ready_pattern: Option<String>,
+}
+
+/// `None` when startup priming is turned off.
+pub fn plan_priming(
+    state: &AppState,
+    crew_id: &str,
+    agent_type: &str,
+    actor_id: Option<&str>,
+    reads_terminal: bool,
+) -> Option<PrimingPlan> {
+    if !state.settings.lock().unwrap().startup_priming_enabled {
+        return None;
+    }
+    let profile = match_profile(state, &priming_target(state, crew_id, agent_type, actor_id));
+    let adapter = adapter_priming(agent_type);
+    let requested = profile.as_ref().map(|p| p.delivery).unwrap_or_default();
+    let ready_pattern = profile
+        .as_ref()
+        .and_then(|p| p.ready_pattern.clone())
+        .filter(|p| !p.trim().is_empty())
+        .or_else(|| adapter.ready_pattern.map(str::to_string));
+    Some(PrimingPlan {
+        delivery: resolve_delivery(requested, &adapter, reads_terminal),
+        profile,
+        ready_pattern,
+    })
+}
+
+/// Render the priming text: the profile's template, else the town's
+/// `priming_template`, else a short header. A template that fails to render
+/// is replaced by the header; the error comes back for the worker's log.
+pub fn render_priming(state: &AppState, profile: Option<&PrimingProfile>, ctx: &PromptContext) -> (String, Option<String>) {
+    let template = profile
+        .map(|p| p.template.clone())
+        .filter(|t| !t.trim().is_empty())
+        .or_else(|| state.settings.lock().unwrap().priming_template.clone());
+    let mut error = None;
+    if let Some(tpl) = template {
+        // The flat names predate the context object and keep older templates working.
+        let mut value = serde_json::to_value(ctx).unwrap_or_default();
+        if let Some(map) = value.as_object_mut() {
+            map.insert("rig_id".to_string(), ctx.rig.id.clone().into());
+            map.insert("worker_id".to_string(), ctx.worker.id.clone().into());
+            map.insert("crew_name".to_string(), ctx.crew.name.clone().into());
+            map.insert("task_title".to_string(), ctx.task.title.clone().into());
+        }
+        let fragments = crate::commands::templates::template_registry(
+            state,
+            Some(&ctx.rig.id),
+            Some(&ctx.crew.id).filter(|id| !id.is_empty()).map(|id| id.as_str()),
+        );
+        let name = profile.map(|p| p.name.as_str()).unwrap_or("priming_template");
+        match crate::templates::render_with_context(name, &tpl, &value, &fragments) {
+            Ok(text) => return (text, None),
+            Err(e) => error = Some(format!("[priming] {e}; using the default priming text")),
+        }
+    }
+    // Default priming: concise context injection
+    let mut parts = vec![
+        "# TownUI Context (do not reply to this message)".to_string(),
+        format!("rig: {}", ctx.rig.id),
+    ];
+    if !ctx.worker.id.is_empty() {
+        parts.push(format!("worker_id: {}", &ctx.worker.id[..8.min(ctx.worker.id.len())]));
+    }
+    if !ctx.crew.name.is_empty() { parts.push(format!("crew: {}", ctx.crew.name)); }
+    if !ctx.actor.name.is_empty() { parts.push(format!("actor: {} ({})", ctx.actor.name, ctx.actor.role)); }
+    if !ctx.task.title.is_empty() { parts.push(format!("task: {}", ctx.task.title)); }
+    parts.push("# End of context. Proceed with your task.".to_string());
+    (parts.join("\n"), error)
+}
+
+/// Wait until the worker's screen matches `pattern` or `timeout` passes.
+/// Returns whether the pattern was seen; an invalid pattern is an error.
+pub fn wait_for_ready(state: &AppState, worker_id: &str, pattern: &str, timeout: Duration) -> Result<bool, String> {
+    let regex = regex::Regex::new(pattern).map_err(|e| format!("Invalid ready pattern '{}': {}", pattern, e))?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        let text = {
+            let screens = state.worker_screens.lock().unwrap();
+            match screens.get(worker_id) {
+                Some(screen) => screen.text(),
+                None => return Ok(false),
+            }
+        };
+        if regex.is_match(&text) {
+            return Ok(true);
+        }
+        if Instant::now() >= deadline {
+            return Ok(false);
+        }
+        thread::sleep(READY_POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,348 @@
+//! The context object every prompt template and the startup priming text
+//! render from. Fields that do not apply are empty rather than missing, so
+//! templates can test them with `{% if %}` under strict undefined.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::models::handoff::HandoffStatus;
+use crate::models::worker::RunStatus;
+use crate::state::AppState;
+
+/// Log lines of the previous failed run shown as `previous_run.error_tail`.
+const ERROR_TAIL_LINES: usize = 30;
+/// Conventions files longer than this are cut.
+const CONVENTIONS_MAX_CHARS: usize = 20_000;
+/// Looked for in the worktree when the rig does not name a conventions file.
+const CONVENTIONS_CANDIDATES: [&str; 3] = [".townui/conventions.md", "CONVENTIONS.md", "AGENTS.md"];
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PromptContext {
+    pub task: TaskContext,
+    pub rig: RigContext,
+    pub crew: CrewContext,
+    pub repo: RepoContext,
+    pub agent: AgentContext,
+    pub worker: WorkerContext,
+    pub actor: ActorContext,
+    pub convoy: ConvoyContext,
+    pub hook: HookContext,
+    pub handoff: HandoffContext,
+    pub previous_run: PreviousRunContext,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TaskContext {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub acceptance_criteria: String,
+    pub tags: Vec<String>,
+    pub priority: String,
+    pub status: String,
+    pub dependencies: Vec<RelatedTask>,
+}
+
+/// A dependency or convoy sibling of the task.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RelatedTask {
+    pub id: String,
+    pub title: String,
+    pub status: String,
+    pub outcome: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RigContext {
+    pub id: String,
+    pub name: String,
+    /// Contents of the rig's conventions file.
+    pub conventions: String,
+    /// Path of that file, relative to the worktree.
+    pub conventions_file: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CrewContext {
+    pub id: String,
+    pub name: String,
+    pub branch: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RepoContext {
+    pub root: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AgentContext {
+    pub cli: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WorkerContext {
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ActorContext {
+    pub id: String,
+    pub name: String,
+    pub role: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConvoyContext {
+    pub id: String,
+    pub title: String,
+    /// Other work items of the convoy.
+    pub siblings: Vec<RelatedTask>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HookContext {
+    pub id: String,
+    pub state_blob: String,
+}
+
+/// The latest non-rejected handoff of the task.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HandoffContext {
+    pub from_actor_id: String,
+    pub summary: String,
+    pub blockers: Vec<String>,
+    pub next_steps: Vec<String>,
+}
+
+/// The run this one follows up on, or the task's latest failed run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PreviousRunContext {
+    pub id: String,
+    pub status: String,
+    pub exit_code: Option<i32>,
+    /// Last lines of its output.
+    pub error_tail: String,
+}
+
+/// What a context is built for; everything else is looked up from state.
+#[derive(Debug, Clone, Default)]
+pub struct ContextRequest<'a> {
+    pub rig_id: &'a str,
+    pub task_id: Option<&'a str>,
+    pub crew_id: Option<&'a str>,
+    pub agent_cli: &'a str,
+    pub worker_id: Option<&'a str>,
+    /// Defaults to the task owner, then the task hook's actor.
+    pub actor_id: Option<&'a str>,
+    /// Defaults to the task's latest failed run.
+    pub previous_run_id: Option<&'a str>,
+}
+
+fn label<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn related(task: &crate::models::task::Task) -> RelatedTask {
+    RelatedTask {
+        id: task.id.clone(),
+        title: task.title.clone(),
+        status: label(&task.status),
+        outcome: task.outcome.clone().unwrap_or_default(),
+    }
+}
+
+/// The rig's conventions file (`conventions_file` setting, else the first
+/// candidate that exists) read from `root`. Paths resolving outside `root`
+/// (absolute, `..`, or through a symlink) are not read.
+fn read_conventions(root: &str, configured: Option<&str>) -> (String, String) {
+    let candidates: Vec<&str> = match configured {
+        Some(file) => vec![file],
+        None => CONVENTIONS_CANDIDATES.to_vec(),
+    };
+    let Ok(root) = Path::new(root).canonicalize() else {
+        return (String::new(), String::new());
+    };
+    for file in candidates {
+        let Ok(path) = root.join(file).canonicalize() else {
+            continue;
+        };
+        if !path.starts_with(&root) {
+            continue;
+        }
+        if let Ok(text) = std::fs::read_to_string(&path) {
+            let text = if text.chars().count() > CONVENTIONS_MAX_CHARS {
+                let mut cut: String = text.chars().take(CONVENTIONS_MAX_CHARS).collect();
+                cut.push_str("\n[truncated]");
+                cut
+            } else {
+                text
+            };
+            return (text, file.to_string());
+        }
+    }
+    (String::new(), String::new())
+}
+
+pub fn build_prompt_context(state: &AppState, request: &ContextRequest) -> PromptContext {
+    let mut ctx = PromptContext {
+        agent: AgentContext { cli: request.agent_cli.to_string() },
+        worker: WorkerContext { id: request.worker_id.unwrap_or_default().to_string() },
+        ..Default::default()
+    };
+
+    let conventions_setting = {
+        let rigs = state.rigs.lock().unwrap();
+        rigs.iter().find(|r| r.id == request.rig_id).map(|rig| {
+            ctx.rig.id = rig.id.clone();
+            ctx.rig.name = rig.name.clone();
+            ctx.repo.root = rig.path.clone();
+            rig.settings.conventions_file.clone()
+        })
+    }
+    .flatten();
+
+    let crew_path = request.crew_id.and_then(|crew_id| {
+        let crews = state.crews.lock().unwrap();
+        crews.iter().find(|c| c.id == crew_id).map(|crew| {
+            ctx.crew = CrewContext {
+                id: crew.id.clone(),
+                name: crew.name.clone(),
+                branch: crew.branch.clone(),
+            };
+            crew.path.clone()
+        })
+    });
+    let (conventions, conventions_file) = read_conventions(
+        crew_path.as_deref().unwrap_or(&ctx.repo.root),
+        conventions_setting.as_deref().filter(|f| !f.trim().is_empty()),
+    );
+    ctx.rig.conventions = conventions;
+    ctx.rig.conventions_file = conventions_file;
+
+    let task = request.task_id.and_then(|task_id| {
+        let tasks = state.tasks.lock().unwrap();
+        let task = tasks.iter().find(|t| t.id == task_id)?.clone();
+        ctx.task = TaskContext {
+            id: task.id.clone(),
+            title: task.title.clone(),
+            description: task.description.clone(),
+            acceptance_criteria: task.acceptance_criteria.clone().unwrap_or_default(),
+            tags: task.tags.clone(),
+            priority: label(&task.priority),
+            status: label(&task.status),
+            dependencies: task
+                .dependencies
+                .iter()
+                .filter_map(|dep| tasks.iter().find(|t| t.id == *dep))
+                .map(related)
+                .collect(),
+        };
+        Some(task)
+    });
+
+    let mut hook_actor_id = None;
+    if let Some(ref task) = task {
+        if let Some(ref convoy_id) = task.convoy_id {
+            let convoy = {
+                let convoys = state.convoys.lock().unwrap();
+                convoys.iter().find(|c| c.convoy_id == *convoy_id).cloned()
+            };
+            if let Some(convoy) = convoy {
+                let tasks = state.tasks.lock().unwrap();
+                ctx.convoy = ConvoyContext {
+                    id: convoy.convoy_id.clone(),
+                    title: convoy.title.clone(),
+                    siblings: convoy
+                        .work_item_ids
+                        .iter()
+                        .filter(|id| **id != task.id)
+                        .filter_map(|id| tasks.iter().find(|t| t.id == *id))
+                        .map(related)
+                        .collect(),
+                };
+            }
+        }
+
+        {
+            let hooks = state.hooks.lock().unwrap();
+            let hook = hooks.iter().find(|h| match task.hook_id {
+                Some(ref hook_id) => h.hook_id == *hook_id,
+                None => h.current_work_id.as_deref() == Some(task.id.as_str()),
+            });
+            if let Some(hook) = hook {
+                ctx.hook = HookContext {
+                    id: hook.hook_id.clone(),
+                    state_blob: hook.state_blob.clone().unwrap_or_default(),
+                };
+                hook_actor_id = Some(hook.attached_actor_id.clone());
+            }
+        }
+
+        {
+            let handoffs = state.handoffs.lock().unwrap();
+            let latest = handoffs
+                .iter()
+                .filter(|h| h.work_item_id == task.id && h.status != HandoffStatus::Rejected)
+                .max_by(|a, b| a.created_at.cmp(&b.created_at));
+            if let Some(handoff) = latest {
+                ctx.handoff = HandoffContext {
+                    from_actor_id: handoff.from_actor_id.clone(),
+                    summary: handoff.context_summary.clone(),
+                    blockers: handoff.blockers.clone(),
+                    next_steps: handoff.next_steps.clone(),
+                };
+            }
+        }
+
+        let previous = {
+            let runs = state.runs.lock().unwrap();
+            match request.previous_run_id {
+                Some(run_id) => runs.iter().find(|r| r.id == run_id).cloned(),
+                // Only a failure that is the task's latest outcome; runs still
+                // queued or going (such as the one being started) don't count.
+                None => runs
+                    .iter()
+                    .rev()
+                    .find(|r| r.task_id == task.id && !matches!(r.status, RunStatus::Queued | RunStatus::Running))
+                    .filter(|r| r.status == RunStatus::Failed)
+                    .cloned(),
+            }
+        };
+        if let Some(run) = previous {
+            let log = if run.worker_id.is_empty() { Vec::new() } else { state.load_log(&run.worker_id) };
+            let tail: Vec<String> = log
+                .iter()
+                .skip(log.len().saturating_sub(ERROR_TAIL_LINES))
+                .map(|entry| entry.line.clone())
+                .collect();
+            ctx.previous_run = PreviousRunContext {
+                id: run.id,
+                status: label(&run.status),
+                exit_code: run.exit_code,
+                error_tail: tail.join("\n"),
+            };
+        }
+    }
+
+    let actor_id = request
+        .actor_id
+        .map(str::to_string)
+        .or_else(|| task.as_ref().and_then(|t| t.owner_actor_id.clone()))
+        .or(hook_actor_id);
+    if let Some(actor_id) = actor_id {
+        let actors = state.actors.lock().unwrap();
+        if let Some(actor) = actors.iter().find(|a| a.actor_id == actor_id) {
+            ctx.actor = ActorContext {
+                id: actor.actor_id.clone(),
+                name: actor.name.clone(),
+                role: actor.role.clone(),
+            };
+        }
+    }
+
+    ctx
+}
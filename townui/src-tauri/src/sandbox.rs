@@ -0,0 +1,384 @@
+#[cfg(target_os = "linux")]
+use std::path::Path;
+#[cfg(target_os = "linux")]
+use std::process::Command;
+
+use serde::Serialize;
+
+use crate::models::isolation::{ResourceLimits, SandboxKind, SandboxPolicy};
+
+/// First argument that turns the app binary into a Landlock launcher
+/// (`townui --townui-landlock-exec --ro <path>... --rw <path>... -- <command>...`).
+pub const LANDLOCK_EXEC_FLAG: &str = "--townui-landlock-exec";
+
+/// Host directories a sandboxed agent may read: binaries, libraries and
+/// system config. Home directories and other users' files are not among them.
+#[cfg(target_os = "linux")]
+const SYSTEM_READ_PATHS: &[&str] = &[
+    "/usr",
+    "/bin",
+    "/sbin",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/etc",
+    "/opt",
+    "/nix",
+    "/run/systemd/resolve",
+];
+
+/// Device nodes a sandboxed agent may open under Landlock.
+#[cfg(target_os = "linux")]
+const LANDLOCK_DEVICES: &[&str] = &[
+    "/dev/null",
+    "/dev/zero",
+    "/dev/full",
+    "/dev/random",
+    "/dev/urandom",
+    "/dev/tty",
+    "/dev/ptmx",
+    "/dev/pts",
+    "/dev/shm",
+];
+
+/// Which isolation mechanisms this machine supports.
+#[derive(Debug, Clone, Serialize)]
+pub struct SandboxCapabilities {
+    pub platform: String,
+    pub cgroup_v2: bool,
+    pub systemd_run: bool,
+    pub prlimit: bool,
+    pub bubblewrap: bool,
+    pub landlock: bool,
+}
+
+/// Argv prefix to place in front of the agent command, plus notes about
+/// anything that was requested but could not be applied.
+#[derive(Debug, Clone, Default)]
+pub struct LaunchWrapper {
+    pub prefix: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl LaunchWrapper {
+    /// Wrap a shell command line (PTY path) so it runs under the prefix.
+    pub fn wrap_shell_command(&self, command: &str) -> String {
+        if self.prefix.is_empty() {
+            return command.to_string();
+        }
+        let prefix: Vec<String> = self.prefix.iter().map(|a| shell_quote(a)).collect();
+        format!("{} sh -c {}", prefix.join(" "), shell_quote(command))
+    }
+}
+
+pub(crate) fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:%+@,".contains(c))
+    {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+#[cfg(target_os = "linux")]
+fn has_binary(name: &str) -> bool {
+    Command::new("which")
+        .arg(name)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+pub fn detect_capabilities() -> SandboxCapabilities {
+    #[cfg(target_os = "linux")]
+    {
+        let landlock = std::fs::read_to_string("/sys/kernel/security/lsm")
+            .map(|lsm| lsm.split(',').any(|m| m.trim() == "landlock"))
+            .unwrap_or(false);
+        SandboxCapabilities {
+            platform: "linux".to_string(),
+            cgroup_v2: Path::new("/sys/fs/cgroup/cgroup.controllers").exists(),
+            systemd_run: has_binary("systemd-run"),
+            prlimit: has_binary("prlimit"),
+            bubblewrap: has_binary("bwrap"),
+            landlock,
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        SandboxCapabilities {
+            platform: std::env::consts::OS.to_string(),
+            cgroup_v2: false,
+            systemd_run: false,
+            prlimit: false,
+            bubblewrap: false,
+            landlock: false,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// Build the launch prefix for a worker: cgroup scope → rlimits → filesystem sandbox.
+/// Unsupported pieces are skipped with a warning rather than failing the spawn.
+#[cfg(target_os = "linux")]
+pub fn build_launch_wrapper(
+    limits: &ResourceLimits,
+    sandbox: &SandboxPolicy,
+    worktree: &str,
+    rig_path: &str,
+) -> LaunchWrapper {
+    let mut wrapper = LaunchWrapper::default();
+    if limits.is_empty() && sandbox.kind == SandboxKind::None {
+        return wrapper;
+    }
+    let caps = detect_capabilities();
+
+    // ── cgroup v2 via a transient systemd scope ──
+    let wants_cgroup =
+        limits.memory_mb.is_some() || limits.cpu_percent.is_some() || limits.max_pids.is_some();
+    let use_cgroup = wants_cgroup && caps.cgroup_v2 && caps.systemd_run;
+    if use_cgroup {
+        let unit_id = uuid::Uuid::new_v4().to_string();
+        wrapper.prefix.extend(
+            [
+                "systemd-run",
+                "--user",
+                "--scope",
+                "--quiet",
+                "--collect",
+            ]
+            .map(String::from),
+        );
+        wrapper.prefix.push(format!("--unit=townui-worker-{}", &unit_id[..8]));
+        if let Some(mb) = limits.memory_mb {
+            wrapper.prefix.extend(["-p".to_string(), format!("MemoryMax={mb}M")]);
+        }
+        if let Some(pct) = limits.cpu_percent {
+            wrapper.prefix.extend(["-p".to_string(), format!("CPUQuota={pct}%")]);
+        }
+        if let Some(n) = limits.max_pids {
+            wrapper.prefix.extend(["-p".to_string(), format!("TasksMax={n}")]);
+        }
+        wrapper.prefix.push("--".to_string());
+    } else if wants_cgroup {
+        wrapper.warnings.push(
+            "cgroup v2 scope unavailable (needs cgroup v2 + systemd-run --user); falling back to rlimits".to_string(),
+        );
+        if limits.cpu_percent.is_some() {
+            wrapper
+                .warnings
+                .push("cpu_percent cannot be enforced without cgroups; ignored".to_string());
+        }
+    }
+
+    // ── rlimits via prlimit ──
+    let mut rlimits = Vec::new();
+    if let Some(secs) = limits.cpu_seconds {
+        rlimits.push(format!("--cpu={secs}"));
+    }
+    if let Some(n) = limits.max_open_files {
+        rlimits.push(format!("--nofile={n}"));
+    }
+    if !use_cgroup {
+        if let Some(mb) = limits.memory_mb {
+            rlimits.push(format!("--as={}", mb * 1024 * 1024));
+        }
+        if let Some(n) = limits.max_pids {
+            rlimits.push(format!("--nproc={n}"));
+        }
+    }
+    if !rlimits.is_empty() {
+        if caps.prlimit {
+            wrapper.prefix.push("prlimit".to_string());
+            wrapper.prefix.extend(rlimits);
+            wrapper.prefix.push("--".to_string());
+        } else {
+            wrapper
+                .warnings
+                .push("prlimit not found; rlimit-based limits ignored".to_string());
+        }
+    }
+
+    // ── filesystem sandbox ──
+    let git_dir = Path::new(rig_path).join(".git");
+    let git_dir = (sandbox.allow_git_writes && git_dir.is_dir())
+        .then(|| git_dir.to_string_lossy().to_string());
+    let existing = |paths: &[String]| -> Vec<String> {
+        paths
+            .iter()
+            .map(|p| expand_home(p))
+            .filter(|p| Path::new(p).exists())
+            .collect()
+    };
+    let writable = existing(&sandbox.writable_paths);
+    let readable = existing(&sandbox.readable_paths);
+    let system: Vec<String> = SYSTEM_READ_PATHS
+        .iter()
+        .filter(|p| Path::new(p).exists())
+        .map(|p| p.to_string())
+        .collect();
+
+    match sandbox.kind {
+        SandboxKind::None => {}
+        SandboxKind::Bubblewrap if !caps.bubblewrap => {
+            wrapper
+                .warnings
+                .push("bubblewrap (bwrap) not found; starting without a sandbox".to_string());
+        }
+        SandboxKind::Bubblewrap => {
+            let p = &mut wrapper.prefix;
+            p.extend(["bwrap", "--die-with-parent"].map(String::from));
+            if sandbox.read_only_root {
+                for path in &system {
+                    p.extend(["--ro-bind".to_string(), path.clone(), path.clone()]);
+                }
+                p.extend(["--tmpfs", "/tmp", "--tmpfs", "/var/tmp"].map(String::from));
+                // Home starts empty; the rig, worktree and allowlisted dirs are mounted into it.
+                if let Some(home) = dirs::home_dir() {
+                    p.extend(["--tmpfs".to_string(), home.to_string_lossy().to_string()]);
+                }
+                // A TMPDIR outside /tmp stays writable too.
+                let tmp = std::env::temp_dir().to_string_lossy().to_string();
+                if !tmp.starts_with("/tmp") && Path::new(&tmp).is_dir() {
+                    p.extend(["--bind".to_string(), tmp.clone(), tmp]);
+                }
+            } else {
+                p.extend(["--bind", "/", "/"].map(String::from));
+            }
+            p.extend(["--dev", "/dev", "--proc", "/proc"].map(String::from));
+            p.extend(["--ro-bind".to_string(), rig_path.to_string(), rig_path.to_string()]);
+            for path in &readable {
+                p.extend(["--ro-bind".to_string(), path.clone(), path.clone()]);
+            }
+            for path in writable.iter().chain(git_dir.iter()) {
+                p.extend(["--bind".to_string(), path.clone(), path.clone()]);
+            }
+            p.extend(["--bind".to_string(), worktree.to_string(), worktree.to_string()]);
+            if !sandbox.network {
+                p.push("--unshare-net".to_string());
+            }
+            p.push("--".to_string());
+        }
+        SandboxKind::Landlock if !caps.landlock => {
+            wrapper
+                .warnings
+                .push("Landlock is not enabled in this kernel; starting without a sandbox".to_string());
+        }
+        SandboxKind::Landlock => match std::env::current_exe() {
+            Ok(exe) => {
+                let p = &mut wrapper.prefix;
+                p.push(exe.to_string_lossy().to_string());
+                p.push(LANDLOCK_EXEC_FLAG.to_string());
+                let ro = ["/proc".to_string(), rig_path.to_string()];
+                for path in system.iter().chain(ro.iter()).chain(readable.iter()) {
+                    p.extend(["--ro".to_string(), path.clone()]);
+                }
+                let tmp = std::env::temp_dir().to_string_lossy().to_string();
+                let devices = LANDLOCK_DEVICES
+                    .iter()
+                    .filter(|d| Path::new(d).exists())
+                    .map(|d| d.to_string());
+                let rw: Vec<String> = [worktree.to_string(), tmp].into_iter().chain(devices).collect();
+                for path in rw.iter().chain(writable.iter()).chain(git_dir.iter()) {
+                    p.extend(["--rw".to_string(), path.clone()]);
+                }
+                p.push("--".to_string());
+                if !sandbox.network {
+                    wrapper
+                        .warnings
+                        .push("Landlock sandbox does not restrict network; use bubblewrap for that".to_string());
+                }
+            }
+            Err(e) => wrapper
+                .warnings
+                .push(format!("Cannot locate app binary for Landlock launcher: {e}")),
+        },
+    }
+
+    wrapper
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn build_launch_wrapper(
+    limits: &ResourceLimits,
+    sandbox: &SandboxPolicy,
+    _worktree: &str,
+    _rig_path: &str,
+) -> LaunchWrapper {
+    let mut wrapper = LaunchWrapper::default();
+    if !limits.is_empty() || sandbox.kind != SandboxKind::None {
+        wrapper.warnings.push(
+            "Resource limits and sandboxing are only supported on Linux; starting unconfined".to_string(),
+        );
+    }
+    wrapper
+}
+
+/// Entry point for `LANDLOCK_EXEC_FLAG`: restrict this process to reading the
+/// `--ro` paths and writing the `--rw` paths, then exec the command. Never returns.
+#[cfg(target_os = "linux")]
+pub fn landlock_exec_main() -> ! {
+    use std::os::unix::process::CommandExt;
+
+    let mut ro = Vec::new();
+    let mut rw = Vec::new();
+    let mut command = Vec::new();
+    let mut args = std::env::args().skip(2);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ro" => ro.extend(args.next()),
+            "--rw" => rw.extend(args.next()),
+            "--" => {
+                command = args.collect();
+                break;
+            }
+            other => {
+                eprintln!("[townui] landlock launcher: unexpected argument '{other}'");
+                std::process::exit(2);
+            }
+        }
+    }
+    if command.is_empty() {
+        eprintln!("[townui] landlock launcher: no command given");
+        std::process::exit(2);
+    }
+
+    if let Err(e) = restrict_landlock(&ro, &rw) {
+        eprintln!("[townui] landlock launcher: {e}");
+        std::process::exit(126);
+    }
+
+    let err = Command::new(&command[0]).args(&command[1..]).exec();
+    eprintln!("[townui] landlock launcher: failed to exec {}: {err}", command[0]);
+    std::process::exit(127);
+}
+
+#[cfg(target_os = "linux")]
+fn restrict_landlock(ro: &[String], rw: &[String]) -> Result<(), String> {
+    use landlock::{
+        path_beneath_rules, Access, AccessFs, Ruleset, RulesetAttr, RulesetCreatedAttr,
+        RulesetStatus, ABI,
+    };
+
+    let abi = ABI::V2;
+    let status = Ruleset::default()
+        .handle_access(AccessFs::from_all(abi))
+        .and_then(|r| r.create())
+        .and_then(|r| r.add_rules(path_beneath_rules(ro, AccessFs::from_read(abi))))
+        .and_then(|r| r.add_rules(path_beneath_rules(rw, AccessFs::from_all(abi))))
+        .and_then(|r| r.restrict_self())
+        .map_err(|e| e.to_string())?;
+    if status.ruleset == RulesetStatus::NotEnforced {
+        return Err("kernel does not enforce Landlock".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,531 @@
+//! Central admission for worker spawns. Every automated spawn (task runs,
+//! retries, hooks, propulsion, witness, best-of, replays, reviews, restarts)
+//! is submitted here with a priority, rig and agent. It starts at once when
+//! global, per-rig, per-agent and machine-load limits allow, and otherwise
+//! waits in a priority queue that is drained as workers exit, with ties
+//! between rigs broken by fair share. Manual spawns go through `submit_now`.
+
+use std::collections::{HashMap, HashSet};
+use std::thread;
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::models::audit::{AuditEvent, AuditEventType};
+use crate::models::scheduler::{QueuedSpawn, SchedulerStatus, SlotUsage, SpawnSource};
+use crate::models::task::TaskPriority;
+use crate::models::worker::{RunStatus, WorkerStatusEnum, WorkerType};
+use crate::state::AppState;
+
+/// Deferred spawn; errors are logged since nobody is waiting on the result.
+type SpawnJob = Box<dyn FnOnce(&AppHandle) + Send>;
+
+struct Pending {
+    info: QueuedSpawn,
+    job: SpawnJob,
+}
+
+/// `AppState.scheduler`: waiting spawns and admitted ones whose worker is not
+/// registered yet. In memory only; queued runs are re-submitted on startup.
+#[derive(Default)]
+pub struct SchedulerQueue {
+    pending: Vec<Pending>,
+    in_flight: Vec<QueuedSpawn>,
+}
+
+pub enum Admission<T> {
+    Started(T),
+    Queued(Box<QueuedSpawn>),
+}
+
+impl QueuedSpawn {
+    pub fn new(rig_id: &str, agent_type: &str, priority: TaskPriority, source: SpawnSource, label: impl Into<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            rig_id: rig_id.to_string(),
+            crew_id: None,
+            agent_type: agent_type.to_string(),
+            priority,
+            source,
+            polecat: false,
+            task_id: None,
+            run_id: None,
+            label: label.into(),
+            enqueued_at: chrono::Utc::now().to_rfc3339(),
+            waiting_on: None,
+        }
+    }
+}
+
+/// Priority of a task's spawns; Medium when the task is unknown.
+pub fn task_priority(state: &AppState, task_id: Option<&str>) -> TaskPriority {
+    let Some(task_id) = task_id else {
+        return TaskPriority::Medium;
+    };
+    let tasks = state.tasks.lock().unwrap();
+    tasks
+        .iter()
+        .find(|t| t.id == task_id)
+        .map(|t| t.priority.clone())
+        .unwrap_or(TaskPriority::Medium)
+}
+
+#[cfg(target_os = "linux")]
+fn load_average() -> Option<f64> {
+    std::fs::read_to_string("/proc/loadavg")
+        .ok()?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+#[cfg(target_os = "macos")]
+fn load_average() -> Option<f64> {
+    // `{ 1.23 1.10 1.05 }`
+    let out = std::process::Command::new("sysctl").args(["-n", "vm.loadavg"]).output().ok()?;
+    String::from_utf8_lossy(&out.stdout)
+        .split_whitespace()
+        .find_map(|p| p.parse().ok())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn load_average() -> Option<f64> {
+    None
+}
+
+/// 1-minute load average divided by the CPU count.
+pub fn load_per_cpu() -> Option<f64> {
+    let cpus = thread::available_parallelism().map(|n| n.get()).unwrap_or(1) as f64;
+    load_average().map(|load| load / cpus)
+}
+
+struct Limits {
+    global: Option<usize>,
+    per_agent: HashMap<String, usize>,
+    max_load: Option<f64>,
+    max_polecats: usize,
+    /// Rig id → (worker limit, fair-share weight).
+    rigs: HashMap<String, (Option<usize>, u32)>,
+}
+
+impl Limits {
+    fn load(state: &AppState) -> Self {
+        let (global, per_agent, max_load, max_polecats) = {
+            let settings = state.settings.lock().unwrap();
+            (
+                settings.max_concurrent_workers,
+                settings.max_workers_per_agent.clone(),
+                settings.max_load_per_cpu,
+                settings.max_polecats_per_rig,
+            )
+        };
+        let rigs = state
+            .rigs
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|r| {
+                (
+                    r.id.clone(),
+                    (r.settings.max_concurrent_workers, r.settings.scheduler_weight.unwrap_or(1).max(1)),
+                )
+            })
+            .collect();
+        Self {
+            global,
+            per_agent,
+            max_load,
+            max_polecats,
+            rigs,
+        }
+    }
+
+    fn rig_limit(&self, rig_id: &str) -> Option<usize> {
+        self.rigs.get(rig_id).and_then(|(limit, _)| *limit)
+    }
+
+    fn weight(&self, rig_id: &str) -> u32 {
+        self.rigs.get(rig_id).map(|(_, w)| *w).unwrap_or(1)
+    }
+}
+
+/// Running workers plus admitted spawns that have not registered a worker yet.
+#[derive(Default)]
+struct Usage {
+    total: usize,
+    by_rig: HashMap<String, usize>,
+    polecats_by_rig: HashMap<String, usize>,
+    by_agent: HashMap<String, usize>,
+    /// Crews targeted by admitted spawns; their lease is taken during the spawn.
+    crews: Vec<String>,
+}
+
+impl Usage {
+    fn collect(state: &AppState, in_flight: &[QueuedSpawn]) -> Self {
+        let mut usage = Self::default();
+        {
+            let workers = state.workers.lock().unwrap();
+            for w in workers.iter().filter(|w| w.status == WorkerStatusEnum::Running) {
+                usage.add(&w.rig_id, &w.agent_type, w.worker_type == WorkerType::Polecat);
+            }
+        }
+        for spawn in in_flight {
+            usage.add(&spawn.rig_id, &spawn.agent_type, spawn.polecat);
+            usage.crews.extend(spawn.crew_id.clone());
+        }
+        usage
+    }
+
+    fn add(&mut self, rig_id: &str, agent_type: &str, polecat: bool) {
+        self.total += 1;
+        *self.by_rig.entry(rig_id.to_string()).or_default() += 1;
+        *self.by_agent.entry(agent_type.to_string()).or_default() += 1;
+        if polecat {
+            *self.polecats_by_rig.entry(rig_id.to_string()).or_default() += 1;
+        }
+    }
+
+    fn rig(&self, rig_id: &str) -> usize {
+        self.by_rig.get(rig_id).copied().unwrap_or(0)
+    }
+}
+
+/// Crew leases, budget pauses and machine load as of one moment. Checking a
+/// lease may probe tmux or the process table, so this is gathered before the
+/// queue is locked and `blocker` only reads it.
+struct Snapshot {
+    /// Crew id → worker (or pending spawn) holding its lease.
+    crew_holders: HashMap<String, String>,
+    paused_rigs: HashSet<String>,
+    load: Option<f64>,
+}
+
+impl Snapshot {
+    fn gather(state: &AppState) -> Self {
+        let leased: Vec<String> = {
+            let crews = state.crews.lock().unwrap();
+            crews.iter().filter(|c| c.lease.is_some()).map(|c| c.id.clone()).collect()
+        };
+        let crew_holders = leased
+            .into_iter()
+            .filter_map(|id| crate::commands::crews::crew_lease_holder(state, &id).map(|holder| (id, holder)))
+            .collect();
+        let rig_ids: Vec<String> = state.rigs.lock().unwrap().iter().map(|r| r.id.clone()).collect();
+        let paused_rigs = rig_ids
+            .into_iter()
+            .filter(|id| crate::commands::budgets::rig_spawning_paused(state, id))
+            .collect();
+        Self {
+            crew_holders,
+            paused_rigs,
+            load: load_per_cpu(),
+        }
+    }
+}
+
+/// The first limit that keeps `spawn` from starting now.
+fn blocker(spawn: &QueuedSpawn, limits: &Limits, usage: &Usage, snapshot: &Snapshot) -> Option<String> {
+    if let Some(ref crew_id) = spawn.crew_id {
+        if usage.crews.iter().any(|c| c == crew_id) {
+            return Some("crew busy (spawn in progress)".to_string());
+        }
+        if let Some(holder) = snapshot.crew_holders.get(crew_id) {
+            return Some(format!("crew busy (worker {})", holder));
+        }
+    }
+    if let Some(max) = limits.global.filter(|max| usage.total >= *max) {
+        return Some(format!("global limit ({}/{})", usage.total, max));
+    }
+    if let Some(max) = limits.rig_limit(&spawn.rig_id).filter(|max| usage.rig(&spawn.rig_id) >= *max) {
+        return Some(format!("rig limit ({}/{})", usage.rig(&spawn.rig_id), max));
+    }
+    if spawn.polecat {
+        let running = usage.polecats_by_rig.get(&spawn.rig_id).copied().unwrap_or(0);
+        if running >= limits.max_polecats {
+            return Some(format!("polecat limit ({}/{})", running, limits.max_polecats));
+        }
+    }
+    if let Some(max) = limits.per_agent.get(&spawn.agent_type) {
+        let running = usage.by_agent.get(&spawn.agent_type).copied().unwrap_or(0);
+        if running >= *max {
+            return Some(format!("{} limit ({}/{})", spawn.agent_type, running, max));
+        }
+    }
+    if let (Some(max), Some(load)) = (limits.max_load, snapshot.load) {
+        if load > max {
+            return Some(format!("machine load ({:.2}/{:.2} per CPU)", load, max));
+        }
+    }
+    if snapshot.paused_rigs.contains(&spawn.rig_id) {
+        return Some("rig budget exhausted".to_string());
+    }
+    None
+}
+
+/// Admission order: priority, then the rig furthest below its fair share,
+/// then age.
+fn sort_pending(pending: &mut [Pending], limits: &Limits, usage: &Usage) {
+    let share = |rig_id: &str| usage.rig(rig_id) as f64 / limits.weight(rig_id) as f64;
+    pending.sort_by(|a, b| {
+        b.info
+            .priority
+            .cmp(&a.info.priority)
+            .then_with(|| share(&a.info.rig_id).total_cmp(&share(&b.info.rig_id)))
+            .then_with(|| a.info.enqueued_at.cmp(&b.info.enqueued_at))
+    });
+}
+
+/// Take the next admissible pending spawn and mark it in flight.
+fn take_next(state: &AppState, queue: &mut SchedulerQueue, snapshot: &Snapshot) -> Option<Pending> {
+    if queue.pending.is_empty() {
+        return None;
+    }
+    let limits = Limits::load(state);
+    let usage = Usage::collect(state, &queue.in_flight);
+    sort_pending(&mut queue.pending, &limits, &usage);
+    let index = queue
+        .pending
+        .iter()
+        .position(|p| blocker(&p.info, &limits, &usage, snapshot).is_none())?;
+    let next = queue.pending.remove(index);
+    queue.in_flight.push(next.info.clone());
+    Some(next)
+}
+
+fn release(state: &AppState, id: &str) {
+    state.scheduler.lock().unwrap().in_flight.retain(|s| s.id != id);
+}
+
+/// Whether a queued or admitted spawn targets the crew.
+pub(crate) fn has_spawns_for_crew(state: &AppState, crew_id: &str) -> bool {
+    let queue = state.scheduler.lock().unwrap();
+    queue.pending.iter().map(|p| &p.info).chain(&queue.in_flight).any(|s| s.crew_id.as_deref() == Some(crew_id))
+}
+
+/// Start every queued spawn that fits, each on its own thread (spawning may
+/// run pre_spawn hooks). Called when a worker exits and by the scheduler tick.
+pub fn dispatch(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    loop {
+        if state.scheduler.lock().unwrap().pending.is_empty() {
+            break;
+        }
+        let snapshot = Snapshot::gather(&state);
+        let next = {
+            let mut queue = state.scheduler.lock().unwrap();
+            take_next(&state, &mut queue, &snapshot)
+        };
+        let Some(Pending { info, job }) = next else {
+            break;
+        };
+        let app = app.clone();
+        let spawned = thread::Builder::new()
+            .name(format!("scheduler-{}", &info.id[..8]))
+            .spawn(move || {
+                job(&app);
+                let state = app.state::<AppState>();
+                release(&state, &info.id);
+                let _ = app.emit("data-changed", "");
+                dispatch(&app);
+            });
+        if spawned.is_err() {
+            break;
+        }
+    }
+}
+
+/// Start `job` now if a slot is free, otherwise queue it. Queued spawns ahead
+/// of it are admitted first, so a new request never jumps the line.
+pub fn submit<T: 'static>(
+    app: &AppHandle,
+    mut request: QueuedSpawn,
+    job: impl FnOnce(&AppHandle) -> Result<T, String> + Send + 'static,
+) -> Result<Admission<T>, String> {
+    dispatch(app);
+    let state = app.state::<AppState>();
+    let snapshot = Snapshot::gather(&state);
+    let waiting_on = {
+        let mut queue = state.scheduler.lock().unwrap();
+        let limits = Limits::load(&state);
+        let usage = Usage::collect(&state, &queue.in_flight);
+        let waiting_on = blocker(&request, &limits, &usage, &snapshot);
+        if waiting_on.is_none() {
+            queue.in_flight.push(request.clone());
+        }
+        waiting_on
+    };
+
+    let Some(waiting_on) = waiting_on else {
+        let result = job(app);
+        release(&state, &request.id);
+        if result.is_err() {
+            dispatch(app);
+        }
+        return result.map(Admission::Started);
+    };
+
+    request.waiting_on = Some(waiting_on);
+    let label = request.label.clone();
+    state.scheduler.lock().unwrap().pending.push(Pending {
+        info: request.clone(),
+        job: Box::new(move |app| {
+            if let Err(e) = job(app) {
+                eprintln!("[scheduler] queued spawn '{}' failed: {}", label, e);
+            }
+        }),
+    });
+    state.append_audit_event(&AuditEvent::new(
+        request.rig_id.clone(),
+        None,
+        request.task_id.clone(),
+        AuditEventType::SpawnQueued,
+        serde_json::json!({
+            "spawn_id": request.id,
+            "source": request.source,
+            "agent_type": request.agent_type,
+            "priority": request.priority,
+            "run_id": request.run_id,
+            "waiting_on": request.waiting_on,
+        })
+        .to_string(),
+    ));
+    let _ = app.emit("data-changed", "");
+    Ok(Admission::Queued(Box::new(request)))
+}
+
+/// Start a direct (user-initiated) spawn now or reject it when it would
+/// exceed a limit; those return a worker immediately and cannot wait in the
+/// queue. Holds its slot while `job` runs, like an admitted `submit`.
+pub fn submit_now<T>(
+    app: &AppHandle,
+    request: QueuedSpawn,
+    job: impl FnOnce(&AppHandle) -> Result<T, String>,
+) -> Result<T, String> {
+    let state = app.state::<AppState>();
+    let snapshot = Snapshot::gather(&state);
+    {
+        let mut queue = state.scheduler.lock().unwrap();
+        let limits = Limits::load(&state);
+        let usage = Usage::collect(&state, &queue.in_flight);
+        if let Some(reason) = blocker(&request, &limits, &usage, &snapshot) {
+            return Err(format!("No worker slot free: {}", reason));
+        }
+        queue.in_flight.push(request.clone());
+    }
+    let result = job(app);
+    release(&state, &request.id);
+    if result.is_err() {
+        dispatch(app);
+    }
+    result
+}
+
+/// Snapshot of the waiting spawns, unordered.
+pub fn queued(state: &AppState) -> Vec<QueuedSpawn> {
+    let queue = state.scheduler.lock().unwrap();
+    queue.pending.iter().map(|p| p.info.clone()).collect()
+}
+
+/// Drop a waiting spawn. A queued run is marked cancelled.
+pub fn cancel(state: &AppState, id: &str) -> Option<QueuedSpawn> {
+    let info = {
+        let mut queue = state.scheduler.lock().unwrap();
+        let index = queue.pending.iter().position(|p| p.info.id == id)?;
+        queue.pending.remove(index).info
+    };
+    if let Some(ref run_id) = info.run_id {
+        let mut runs = state.runs.lock().unwrap();
+        if let Some(run) = runs.iter_mut().find(|r| r.id == *run_id && r.status == RunStatus::Queued) {
+            run.status = RunStatus::Cancelled;
+            run.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        }
+        state.save_runs(&runs);
+    }
+    Some(info)
+}
+
+/// Cancel the queued spawn of `run_id`, if it is still waiting.
+pub fn cancel_run(state: &AppState, run_id: &str) -> Option<QueuedSpawn> {
+    let id = queued(state)
+        .into_iter()
+        .find(|q| q.run_id.as_deref() == Some(run_id))?
+        .id;
+    cancel(state, &id)
+}
+
+pub fn status(state: &AppState) -> SchedulerStatus {
+    let snapshot = Snapshot::gather(state);
+    let mut queue = state.scheduler.lock().unwrap();
+    let limits = Limits::load(state);
+    let usage = Usage::collect(state, &queue.in_flight);
+    sort_pending(&mut queue.pending, &limits, &usage);
+
+    let mut rig_ids: Vec<String> = limits.rigs.keys().cloned().collect();
+    rig_ids.sort();
+    let rigs = rig_ids
+        .into_iter()
+        .map(|rig_id| SlotUsage {
+            running: usage.rig(&rig_id),
+            limit: limits.rig_limit(&rig_id),
+            queued: queue.pending.iter().filter(|p| p.info.rig_id == rig_id).count(),
+            weight: limits.weight(&rig_id),
+            key: rig_id,
+        })
+        .collect();
+
+    let mut agent_keys: Vec<String> = usage
+        .by_agent
+        .keys()
+        .chain(limits.per_agent.keys())
+        .chain(queue.pending.iter().map(|p| &p.info.agent_type))
+        .cloned()
+        .collect();
+    agent_keys.sort();
+    agent_keys.dedup();
+    let agents = agent_keys
+        .into_iter()
+        .map(|agent| SlotUsage {
+            running: usage.by_agent.get(&agent).copied().unwrap_or(0),
+            limit: limits.per_agent.get(&agent).copied(),
+            queued: queue.pending.iter().filter(|p| p.info.agent_type == agent).count(),
+            weight: 1,
+            key: agent,
+        })
+        .collect();
+
+    let pending: Vec<QueuedSpawn> = queue
+        .pending
+        .iter()
+        .map(|p| QueuedSpawn {
+            waiting_on: blocker(&p.info, &limits, &usage, &snapshot),
+            ..p.info.clone()
+        })
+        .collect();
+
+    SchedulerStatus {
+        running: usage.total,
+        max_concurrent_workers: limits.global,
+        load_per_cpu: snapshot.load,
+        max_load_per_cpu: limits.max_load,
+        rigs,
+        agents,
+        queue: pending,
+    }
+}
+
+/// Background tick that admits queued spawns once limits, load or budgets allow,
+/// and keeps the crew leases of running workers from expiring.
+pub fn start_scheduler(app: AppHandle) {
+    let _ = thread::Builder::new()
+        .name("scheduler".to_string())
+        .spawn(move || loop {
+            thread::sleep(Duration::from_secs(5));
+            crate::commands::crews::renew_crew_leases(&app.state::<AppState>());
+            crate::commands::crews::clean_up_overflow_crews(&app);
+            let idle = app.state::<AppState>().scheduler.lock().unwrap().pending.is_empty();
+            if !idle {
+                dispatch(&app);
+            }
+        });
+}
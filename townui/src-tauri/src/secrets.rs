@@ -0,0 +1,356 @@
+//! Layered worker environments and encrypted secret values.
+//!
+//! Secret values are stored as AES-256-GCM ciphertext in `env_profiles.json`.
+//! The key lives in the OS keyring, or is derived with Argon2id from a
+//! passphrase on machines without one. Decrypted values only exist in memory:
+//! in the environment of spawned workers and in the redaction list that
+//! scrubs them from logs, recordings and audit payloads.
+
+use std::collections::HashMap;
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+
+use crate::models::env::{EnvProfile, EnvScope, ResolvedEnvVar, SecretKeySource, SecretsStatus};
+use crate::state::AppState;
+
+const KEYRING_SERVICE: &str = "townui";
+const KEYRING_USER: &str = "env-secrets";
+/// Plaintext of `SecretsConfig.check`.
+const CHECK_PLAINTEXT: &str = "townui-secrets";
+/// Shorter secrets are not redacted; replacing them would mangle ordinary output.
+const MIN_REDACT_LEN: usize = 4;
+const NONCE_LEN: usize = 12;
+const ENV_PROFILES_FILE: &str = "env_profiles.json";
+const SECRETS_CONFIG_FILE: &str = "secrets.json";
+
+pub fn encrypt(key: &[u8; 32], plaintext: &str) -> Result<String, String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|_| "Failed to encrypt secret".to_string())?;
+    let mut blob = nonce.to_vec();
+    blob.extend_from_slice(&ciphertext);
+    Ok(BASE64.encode(blob))
+}
+
+pub fn decrypt(key: &[u8; 32], encoded: &str) -> Result<String, String> {
+    let blob = BASE64.decode(encoded).map_err(|_| "Secret is not valid base64".to_string())?;
+    if blob.len() <= NONCE_LEN {
+        return Err("Secret ciphertext is truncated".to_string());
+    }
+    let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Secret cannot be decrypted with the current key".to_string())?;
+    String::from_utf8(plaintext).map_err(|_| "Secret is not valid UTF-8".to_string())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+fn keyring_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).map_err(|e| format!("OS keyring unavailable: {}", e))
+}
+
+/// Key from the OS keyring; creates one on first use when no secrets exist yet.
+fn load_keyring_key(first_use: bool) -> Result<[u8; 32], String> {
+    let entry = keyring_entry()?;
+    match entry.get_password() {
+        Ok(encoded) => {
+            let bytes = BASE64.decode(encoded).map_err(|_| "Keyring key is corrupt".to_string())?;
+            bytes.try_into().map_err(|_| "Keyring key has the wrong length".to_string())
+        }
+        Err(keyring::Error::NoEntry) if first_use => {
+            let key: [u8; 32] = random_bytes();
+            entry
+                .set_password(&BASE64.encode(key))
+                .map_err(|e| format!("Failed to store key in the OS keyring: {}", e))?;
+            Ok(key)
+        }
+        Err(keyring::Error::NoEntry) => {
+            Err("The secrets key is missing from the OS keyring; existing secrets cannot be decrypted".to_string())
+        }
+        Err(e) => Err(format!("OS keyring unavailable: {}", e)),
+    }
+}
+
+/// Make `key` current: verify it against (or record) the check value and
+/// refresh the redaction list.
+fn install_key(state: &AppState, key: [u8; 32]) -> Result<(), String> {
+    {
+        let mut config = state.secrets_config.lock().unwrap();
+        match config.check {
+            Some(ref check) => {
+                if decrypt(&key, check).as_deref() != Ok(CHECK_PLAINTEXT) {
+                    return Err("Wrong key for the stored secrets".to_string());
+                }
+            }
+            None => {
+                config.check = Some(encrypt(&key, CHECK_PLAINTEXT)?);
+                state.save_secrets_config(&config);
+            }
+        }
+    }
+    *state.secret_key.lock().unwrap() = Some(key);
+    *state.secret_key_error.lock().unwrap() = None;
+    refresh_redactions(state);
+    Ok(())
+}
+
+fn set_key_error(state: &AppState, error: String) {
+    *state.secret_key_error.lock().unwrap() = Some(error);
+}
+
+/// Load the key at startup: from the keyring, or from `TOWNUI_SECRETS_PASSPHRASE`
+/// for the passphrase source. Otherwise secrets stay locked until `unlock`.
+pub fn init(state: &AppState) {
+    let (source, first_use, salt) = {
+        let config = state.secrets_config.lock().unwrap();
+        (config.source.clone(), config.check.is_none(), config.salt.clone())
+    };
+    let result = match source {
+        SecretKeySource::Keyring => load_keyring_key(first_use),
+        SecretKeySource::Passphrase => match std::env::var("TOWNUI_SECRETS_PASSPHRASE") {
+            Ok(passphrase) => salt
+                .ok_or_else(|| "Passphrase salt is missing".to_string())
+                .and_then(|s| BASE64.decode(s).map_err(|_| "Passphrase salt is corrupt".to_string()))
+                .and_then(|s| derive_key(&passphrase, &s)),
+            Err(_) => Err("Secrets are locked; enter the passphrase to unlock them".to_string()),
+        },
+    };
+    if let Err(e) = result.and_then(|key| install_key(state, key)) {
+        set_key_error(state, e);
+    }
+}
+
+/// Unlock passphrase-protected secrets for this session.
+pub fn unlock(state: &AppState, passphrase: &str) -> Result<(), String> {
+    let salt = {
+        let config = state.secrets_config.lock().unwrap();
+        if config.source != SecretKeySource::Passphrase {
+            return Err("Secrets are not passphrase-protected".to_string());
+        }
+        config.salt.clone().ok_or_else(|| "Passphrase salt is missing".to_string())?
+    };
+    let salt = BASE64.decode(salt).map_err(|_| "Passphrase salt is corrupt".to_string())?;
+    install_key(state, derive_key(passphrase, &salt)?)
+}
+
+/// Switch the key source (passphrase when `Some`, keyring when `None`) and
+/// re-encrypt every secret under the new key. Everything is re-encrypted in
+/// memory and written to staged files first, so a secret that fails to
+/// decrypt or a failed write leaves the old key and files untouched. Only
+/// then is a keyring key replaced and the files renamed into place.
+pub fn change_key_source(state: &AppState, passphrase: Option<&str>) -> Result<(), String> {
+    let old_key = *state.secret_key.lock().unwrap();
+    if passphrase.is_some_and(|p| p.len() < 8) {
+        return Err("Passphrase must be at least 8 characters".to_string());
+    }
+
+    // Held throughout so no secret is added under the old key meanwhile.
+    let mut profiles = state.env_profiles.lock().unwrap();
+    let has_secrets = profiles.iter().flat_map(|p| p.vars.iter()).any(|v| v.secret);
+    if has_secrets && old_key.is_none() {
+        return Err("Unlock the existing secrets before changing how they are protected".to_string());
+    }
+
+    let (new_key, source, salt) = match passphrase {
+        Some(p) => {
+            let salt: [u8; 16] = random_bytes();
+            (derive_key(p, &salt)?, SecretKeySource::Passphrase, Some(BASE64.encode(salt)))
+        }
+        None => (random_bytes::<32>(), SecretKeySource::Keyring, None),
+    };
+
+    let mut reencrypted = profiles.clone();
+    if let Some(old_key) = old_key {
+        for var in reencrypted.iter_mut().flat_map(|p| p.vars.iter_mut()).filter(|v| v.secret) {
+            let plain = decrypt(&old_key, &var.value).map_err(|e| format!("{}: {}", var.key, e))?;
+            var.value = encrypt(&new_key, &plain)?;
+        }
+    }
+    let mut config = state.secrets_config.lock().unwrap();
+    let mut new_config = config.clone();
+    new_config.source = source.clone();
+    new_config.salt = salt;
+    new_config.check = Some(encrypt(&new_key, CHECK_PLAINTEXT)?);
+
+    let staged = state
+        .stage_json(&reencrypted, ENV_PROFILES_FILE)
+        .and_then(|_| state.stage_json(&new_config, SECRETS_CONFIG_FILE))
+        .and_then(|_| match source {
+            SecretKeySource::Keyring => keyring_entry()?
+                .set_password(&BASE64.encode(new_key))
+                .map_err(|e| format!("Failed to store key in the OS keyring: {}", e)),
+            SecretKeySource::Passphrase => Ok(()),
+        });
+    if let Err(e) = staged {
+        state.discard_staged(ENV_PROFILES_FILE);
+        state.discard_staged(SECRETS_CONFIG_FILE);
+        return Err(e);
+    }
+    // Only renames remain; the staged files already match the new key.
+    if let Err(e) = state.commit_staged(ENV_PROFILES_FILE) {
+        // The old files are still in place; give the keyring back their key.
+        let replaced_keyring_key = source == SecretKeySource::Keyring && config.source == SecretKeySource::Keyring;
+        if let Some(old_key) = old_key.filter(|_| replaced_keyring_key) {
+            let _ = keyring_entry().and_then(|entry| {
+                entry.set_password(&BASE64.encode(old_key)).map_err(|e| e.to_string())
+            });
+        }
+        state.discard_staged(SECRETS_CONFIG_FILE);
+        return Err(e);
+    }
+    state.commit_staged(SECRETS_CONFIG_FILE)?;
+    *profiles = reencrypted;
+    *config = new_config;
+    drop(config);
+    *state.secret_key.lock().unwrap() = Some(new_key);
+    drop(profiles);
+    install_key(state, new_key)
+}
+
+pub fn secret_count(state: &AppState) -> usize {
+    let profiles = state.env_profiles.lock().unwrap();
+    profiles.iter().flat_map(|p| p.vars.iter()).filter(|v| v.secret).count()
+}
+
+pub fn status(state: &AppState) -> SecretsStatus {
+    SecretsStatus {
+        source: state.secrets_config.lock().unwrap().source.clone(),
+        unlocked: state.secret_key.lock().unwrap().is_some(),
+        secret_count: secret_count(state),
+        error: state.secret_key_error.lock().unwrap().clone(),
+    }
+}
+
+/// Encrypt `value` with the current key.
+pub fn seal(state: &AppState, value: &str) -> Result<String, String> {
+    let key = state.secret_key.lock().unwrap().ok_or_else(locked_error)?;
+    encrypt(&key, value)
+}
+
+fn locked_error() -> String {
+    "Secrets are locked; unlock them first".to_string()
+}
+
+/// Rebuild the redaction list from every decryptable secret, longest first so
+/// a secret containing another is replaced whole.
+pub fn refresh_redactions(state: &AppState) {
+    let Some(key) = *state.secret_key.lock().unwrap() else {
+        return;
+    };
+    let mut values: Vec<String> = {
+        let profiles = state.env_profiles.lock().unwrap();
+        profiles
+            .iter()
+            .flat_map(|p| p.vars.iter())
+            .filter(|v| v.secret)
+            .filter_map(|v| decrypt(&key, &v.value).ok())
+            .filter(|v| v.len() >= MIN_REDACT_LEN)
+            .collect()
+    };
+    values.sort_by_key(|v| std::cmp::Reverse(v.len()));
+    values.dedup();
+    *state.redactions.lock().unwrap() = values;
+}
+
+/// Profiles that apply to a worker, lowest precedence first.
+fn layers<'a>(
+    profiles: &'a [EnvProfile],
+    rig_id: &str,
+    crew_id: &str,
+    actor_id: Option<&str>,
+) -> Vec<&'a EnvProfile> {
+    let mut matching: Vec<&EnvProfile> = profiles
+        .iter()
+        .filter(|p| match p.scope {
+            EnvScope::Global => true,
+            EnvScope::Rig => p.scope_id.as_deref() == Some(rig_id),
+            EnvScope::Crew => p.scope_id.as_deref() == Some(crew_id),
+            EnvScope::Actor => actor_id.is_some() && p.scope_id.as_deref() == actor_id,
+        })
+        .collect();
+    matching.sort_by(|a, b| a.scope.partial_cmp(&b.scope).unwrap_or(std::cmp::Ordering::Equal));
+    matching
+}
+
+/// Effective environment for a worker: `AppSettings.env_vars`, then the
+/// global, rig, crew and actor profiles. Secrets are decrypted here, at spawn
+/// time only; a locked key is an error when a layer holds secrets.
+pub fn resolve_worker_env(
+    state: &AppState,
+    rig_id: &str,
+    crew_id: &str,
+    actor_id: Option<&str>,
+) -> Result<HashMap<String, String>, String> {
+    let mut env = state.settings.lock().unwrap().env_vars.clone();
+    let key = *state.secret_key.lock().unwrap();
+    let profiles = state.env_profiles.lock().unwrap();
+    for profile in layers(&profiles, rig_id, crew_id, actor_id) {
+        for var in &profile.vars {
+            let value = if var.secret {
+                let key = key.ok_or_else(|| format!("{} ({} is a secret)", locked_error(), var.key))?;
+                decrypt(&key, &var.value).map_err(|e| format!("{}: {}", var.key, e))?
+            } else {
+                var.value.clone()
+            };
+            env.insert(var.key.clone(), value);
+        }
+    }
+    Ok(env)
+}
+
+/// The same layering as `resolve_worker_env`, with secrets masked and the
+/// winning layer of each variable.
+pub fn preview_worker_env(
+    state: &AppState,
+    rig_id: &str,
+    crew_id: &str,
+    actor_id: Option<&str>,
+) -> Vec<ResolvedEnvVar> {
+    let mut resolved: Vec<ResolvedEnvVar> = state
+        .settings
+        .lock()
+        .unwrap()
+        .env_vars
+        .iter()
+        .map(|(k, v)| ResolvedEnvVar {
+            key: k.clone(),
+            value: v.clone(),
+            secret: false,
+            scope: None,
+        })
+        .collect();
+    let profiles = state.env_profiles.lock().unwrap();
+    for profile in layers(&profiles, rig_id, crew_id, actor_id) {
+        for var in &profile.vars {
+            resolved.retain(|r| r.key != var.key);
+            resolved.push(ResolvedEnvVar {
+                key: var.key.clone(),
+                value: if var.secret { String::new() } else { var.value.clone() },
+                secret: var.secret,
+                scope: Some(profile.scope),
+            });
+        }
+    }
+    resolved.sort_by(|a, b| a.key.cmp(&b.key));
+    resolved
+}
@@ -0,0 +1,225 @@
+//! tmux session host for worker PTYs.
+//!
+//! Each worker gets a detached session on a private tmux server
+//! (`tmux -L townui`). TownUI attaches a client inside its own PTY to read and
+//! write the session; when the app exits only the client dies, so the agent
+//! keeps running and is reattached on the next startup.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Serialize;
+
+use crate::sandbox::shell_quote;
+
+/// Private tmux server socket so user sessions are never touched.
+const TMUX_SOCKET: &str = "townui";
+pub const SESSION_PREFIX: &str = "townui-";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerSessionInfo {
+    pub session: String,
+    pub worker_id: Option<String>,
+    pub pane_pid: Option<u32>,
+    pub attached_clients: u32,
+}
+
+fn tmux() -> Command {
+    let mut cmd = Command::new("tmux");
+    cmd.args(["-L", TMUX_SOCKET]);
+    cmd
+}
+
+fn run_tmux(args: &[&str]) -> Result<String, String> {
+    let output = tmux()
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run tmux: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub fn tmux_available() -> bool {
+    !cfg!(target_os = "windows")
+        && Command::new("tmux")
+            .arg("-V")
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+}
+
+pub fn new_session_name() -> String {
+    let id = uuid::Uuid::new_v4().to_string();
+    format!("{}{}", SESSION_PREFIX, &id[..8])
+}
+
+/// File the session's wrapper writes the shell's exit code to.
+pub fn exit_code_path(sessions_dir: &Path, session: &str) -> PathBuf {
+    sessions_dir.join(format!("{}.exit", session))
+}
+
+/// Write `env` as `export` lines to an owner-only file next to `exit_file`.
+/// It may hold decrypted secrets, so it never goes on a command line.
+fn write_env_file(exit_file: &Path, env: &HashMap<String, String>) -> Result<PathBuf, String> {
+    use std::io::Write;
+    let path = exit_file.with_extension("env");
+    let valid_name = |k: &str| {
+        !k.is_empty()
+            && !k.starts_with(|c: char| c.is_ascii_digit())
+            && k.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    let mut lines: Vec<String> = env
+        .iter()
+        .filter(|(k, _)| valid_name(k))
+        .map(|(k, v)| format!("export {}={}\n", k, shell_quote(v)))
+        .collect();
+    lines.sort();
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&path)
+        .map_err(|e| format!("Failed to write session environment: {}", e))?;
+    file.write_all(lines.concat().as_bytes())
+        .map_err(|e| format!("Failed to write session environment: {}", e))?;
+    Ok(path)
+}
+
+/// Start a detached session running `shell` in `cwd` with `env` applied.
+/// The environment is sourced from a private file the pane deletes before
+/// starting the shell, keeping values out of tmux's argv and
+/// `#{pane_start_command}`. The pane records the shell's exit status in
+/// `exit_file` before closing.
+pub fn create_session(
+    session: &str,
+    cwd: &str,
+    shell: &str,
+    env: &HashMap<String, String>,
+    exit_file: &Path,
+    scrollback_lines: usize,
+    size: (u16, u16),
+) -> Result<(), String> {
+    let _ = std::fs::remove_file(exit_file);
+    let env_file = shell_quote(&write_env_file(exit_file, env)?.to_string_lossy());
+    let pane_command = format!(
+        ". {env}; rm -f {env}; {}; echo $? > {}",
+        shell_quote(shell),
+        shell_quote(&exit_file.to_string_lossy()),
+        env = env_file,
+    );
+    let history = scrollback_lines.to_string();
+    let (cols, rows) = (size.0.to_string(), size.1.to_string());
+    run_tmux(&[
+        "start-server",
+        ";",
+        "set-option",
+        "-g",
+        "history-limit",
+        &history,
+        ";",
+        "new-session",
+        "-d",
+        "-s",
+        session,
+        "-x",
+        &cols,
+        "-y",
+        &rows,
+        "-c",
+        cwd,
+        &pane_command,
+        ";",
+        "set-option",
+        "-t",
+        session,
+        "status",
+        "off",
+    ])
+    .map(|_| ())
+    .map_err(|e| {
+        let _ = std::fs::remove_file(exit_file.with_extension("env"));
+        format!("Failed to create tmux session {}: {}", session, e)
+    })
+}
+
+pub fn has_session(session: &str) -> bool {
+    tmux()
+        .args(["has-session", "-t", session])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// PID of the process running in the session's pane (the worker shell).
+pub fn pane_pid(session: &str) -> Option<u32> {
+    run_tmux(&["display-message", "-p", "-t", session, "#{pane_pid}"])
+        .ok()
+        .and_then(|out| out.trim().parse().ok())
+}
+
+/// Argv for a client attached to `session`, to be spawned inside an app-owned PTY.
+pub fn attach_argv(session: &str) -> Vec<String> {
+    ["tmux", "-L", TMUX_SOCKET, "attach-session", "-t", session]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Pane history (with escape sequences when `ansi`) for restoring a terminal view.
+pub fn capture_scrollback(session: &str, lines: usize, ansi: bool) -> Result<String, String> {
+    let start = format!("-{}", lines);
+    let mut args = vec!["capture-pane", "-p", "-J", "-S", start.as_str(), "-t", session];
+    if ansi {
+        args.push("-e");
+    }
+    run_tmux(&args)
+}
+
+/// Type `text` into the session's pane followed by Enter.
+pub fn send_line(session: &str, text: &str) -> Result<(), String> {
+    run_tmux(&["send-keys", "-t", session, "-l", text])?;
+    run_tmux(&["send-keys", "-t", session, "Enter"]).map(|_| ())
+}
+
+pub fn kill_session(session: &str) {
+    let _ = run_tmux(&["kill-session", "-t", session]);
+}
+
+/// Exit code recorded by a finished session, removing the marker file.
+pub fn take_exit_code(exit_file: &Path) -> Option<i32> {
+    let code = std::fs::read_to_string(exit_file)
+        .ok()
+        .and_then(|s| s.trim().parse().ok());
+    let _ = std::fs::remove_file(exit_file);
+    code
+}
+
+/// All live TownUI sessions on the private server.
+pub fn list_sessions() -> Vec<(String, Option<u32>, u32)> {
+    let Ok(out) = run_tmux(&[
+        "list-sessions",
+        "-F",
+        "#{session_name} #{pane_pid} #{session_attached}",
+    ]) else {
+        return Vec::new();
+    };
+    out.lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = parts.next()?;
+            if !name.starts_with(SESSION_PREFIX) {
+                return None;
+            }
+            let pid = parts.next().and_then(|p| p.parse().ok());
+            let attached = parts.next().and_then(|a| a.parse().ok()).unwrap_or(0);
+            Some((name.to_string(), pid, attached))
+        })
+        .collect()
+}
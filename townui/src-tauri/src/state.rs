@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 use std::fs;
-use std::io::Write;
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::time::Instant;
 use tokio::sync::oneshot;
 
 /// A thread-safe writer handle for sending input to a running worker's stdin/PTY.
@@ -11,13 +12,20 @@ pub type WorkerWriter = Box<dyn Write + Send>;
 /// A handle to the PTY master, kept alive so the PTY stays open and can be resized.
 pub type PtyMasterHandle = Box<dyn portable_pty::MasterPty + Send>;
 
+use crate::asciicast::CastWriter;
+use crate::usage::UsageReport;
+use crate::vt::Screen;
 use crate::models::actor::Actor;
 use crate::models::audit::AuditEvent;
+use crate::models::best_of::BestOfComparison;
+use crate::models::budget::BudgetUsage;
 use crate::models::convoy::Convoy;
 use crate::models::crew::Crew;
 use crate::models::dog::Dog;
+use crate::models::env::{EnvProfile, SecretsConfig};
 use crate::models::handoff::Handoff;
 use crate::models::hook::Hook;
+use crate::models::liveness::WorkerLiveness;
 use crate::models::rig::Rig;
 use crate::models::settings::AppSettings;
 use crate::models::task::Task;
@@ -32,6 +40,8 @@ pub struct SupervisorRuntimeState {
     pub last_compact_at: Option<String>,
     pub loop_interval_seconds: u64,
     pub auto_refinery_sync: bool,
+    /// Budget scopes that were over their limit at the last enforcement pass.
+    pub budget_pauses: Vec<BudgetUsage>,
 }
 
 impl Default for SupervisorRuntimeState {
@@ -43,6 +53,7 @@ impl Default for SupervisorRuntimeState {
             last_compact_at: None,
             loop_interval_seconds: 30,
             auto_refinery_sync: true,
+            budget_pauses: Vec::new(),
         }
     }
 }
@@ -101,8 +112,34 @@ pub struct AppState {
     pub actors: Mutex<Vec<Actor>>,
     pub workers: Mutex<Vec<Worker>>,
     pub runs: Mutex<Vec<Run>>,
+    pub best_of: Mutex<Vec<BestOfComparison>>,
+    /// Layered worker environments; secret values stay encrypted here.
+    pub env_profiles: Mutex<Vec<EnvProfile>>,
+    pub secrets_config: Mutex<SecretsConfig>,
+    /// Key for secret env values while unlocked; never persisted by the app.
+    pub secret_key: Mutex<Option<[u8; 32]>>,
+    /// Why the secret key is not loaded, for `get_secrets_status`.
+    pub secret_key_error: Mutex<Option<String>>,
+    /// Decrypted secret values scrubbed from logs, recordings and audit payloads.
+    pub redactions: Mutex<Vec<String>>,
+    /// Spawns waiting for a worker slot; see `scheduler`.
+    pub scheduler: Mutex<crate::scheduler::SchedulerQueue>,
     pub dogs: Mutex<Vec<Dog>>,
+    /// Tail cache of recent log entries per live worker; the full log is on disk.
+    /// An entry exists from spawn (or reattach) until `close_worker_log`.
     pub worker_logs: Mutex<HashMap<String, Vec<LogEntry>>>,
+    /// Open buffered append handles for `logs/<worker>.jsonl`.
+    pub worker_log_files: Mutex<HashMap<String, BufWriter<fs::File>>>,
+    /// Token/cost figures reported so far by each live worker, folded in as its log grows.
+    pub worker_usage: Mutex<HashMap<String, UsageReport>>,
+    /// Active asciicast recordings (`recordings/<worker>.cast`) for PTY workers.
+    pub worker_casts: Mutex<HashMap<String, CastWriter>>,
+    /// Emulated terminal screen per PTY worker.
+    pub worker_screens: Mutex<HashMap<String, Screen>>,
+    /// When each running worker last produced output.
+    pub worker_last_output: Mutex<HashMap<String, Instant>>,
+    /// Latest liveness sample and recovery-ladder progress per running worker.
+    pub worker_liveness: Mutex<HashMap<String, WorkerLiveness>>,
     pub worker_writers: Mutex<HashMap<String, WorkerWriter>>,
     pub worker_pty_masters: Mutex<HashMap<String, PtyMasterHandle>>,
     pub settings: Mutex<AppSettings>,
@@ -137,6 +174,8 @@ impl AppState {
         fs::create_dir_all(&town_dir).expect("Could not create .townui directory");
         fs::create_dir_all(town_dir.join("worktrees")).ok();
         fs::create_dir_all(town_dir.join("logs")).ok();
+        fs::create_dir_all(town_dir.join("sessions")).ok();
+        fs::create_dir_all(town_dir.join("recordings")).ok();
         fs::create_dir_all(town_dir.join("templates")).ok();
 
         let rigs: Vec<Rig> = Self::load_json_vec(&town_dir, "rigs.json");
@@ -148,8 +187,12 @@ impl AppState {
         let actors: Vec<Actor> = Self::load_json_vec(&town_dir, "actors.json");
         let workers: Vec<Worker> = Self::load_json_vec(&town_dir, "workers.json");
         let runs: Vec<Run> = Self::load_json_vec(&town_dir, "runs.json");
+        let best_of: Vec<BestOfComparison> = Self::load_json_vec(&town_dir, "best_of.json");
+        let env_profiles: Vec<EnvProfile> = Self::load_json_vec(&town_dir, "env_profiles.json");
+        let secrets_config: SecretsConfig = Self::load_json_obj(&town_dir, "secrets.json");
 
-        let settings: AppSettings = Self::load_json_obj(&town_dir, "settings.json");
+        let mut settings: AppSettings = Self::load_json_obj(&town_dir, "settings.json");
+        crate::models::prompt::upgrade_default_rules(&mut settings.prompt_rules);
         let workflow_templates: Vec<WorkflowTemplate> = Self::load_json_vec(&town_dir, "workflow_templates.json");
         let workflow_instances: Vec<WorkflowInstance> = Self::load_json_vec(&town_dir, "workflow_instances.json");
 
@@ -163,8 +206,21 @@ impl AppState {
             actors: Mutex::new(actors),
             workers: Mutex::new(workers),
             runs: Mutex::new(runs),
+            best_of: Mutex::new(best_of),
+            env_profiles: Mutex::new(env_profiles),
+            secrets_config: Mutex::new(secrets_config),
+            secret_key: Mutex::new(None),
+            secret_key_error: Mutex::new(None),
+            redactions: Mutex::new(Vec::new()),
+            scheduler: Mutex::new(Default::default()),
             dogs: Mutex::new(Vec::new()),
             worker_logs: Mutex::new(HashMap::new()),
+            worker_log_files: Mutex::new(HashMap::new()),
+            worker_usage: Mutex::new(HashMap::new()),
+            worker_casts: Mutex::new(HashMap::new()),
+            worker_screens: Mutex::new(HashMap::new()),
+            worker_last_output: Mutex::new(HashMap::new()),
+            worker_liveness: Mutex::new(HashMap::new()),
             worker_writers: Mutex::new(HashMap::new()),
             worker_pty_masters: Mutex::new(HashMap::new()),
             settings: Mutex::new(settings),
@@ -252,6 +308,72 @@ impl AppState {
         self.save_json(runs, "runs.json");
     }
 
+    pub fn save_best_of(&self, comparisons: &[BestOfComparison]) {
+        self.save_json(comparisons, "best_of.json");
+    }
+
+    pub fn save_env_profiles(&self, profiles: &[EnvProfile]) {
+        self.save_json(profiles, "env_profiles.json");
+    }
+
+    pub fn save_secrets_config(&self, config: &SecretsConfig) {
+        self.save_json(config, "secrets.json");
+    }
+
+    /// Write `data` beside `filename` without replacing it; `commit_staged`
+    /// moves it into place. For changes that must not land half-written.
+    pub fn stage_json<T: serde::Serialize + ?Sized>(&self, data: &T, filename: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(data).map_err(|e| format!("Failed to serialize {}: {}", filename, e))?;
+        fs::write(self.staged_path(filename), json).map_err(|e| format!("Failed to write {}: {}", filename, e))
+    }
+
+    pub fn commit_staged(&self, filename: &str) -> Result<(), String> {
+        fs::rename(self.staged_path(filename), self.town_dir.join(filename))
+            .map_err(|e| format!("Failed to replace {}: {}", filename, e))
+    }
+
+    pub fn discard_staged(&self, filename: &str) {
+        let _ = fs::remove_file(self.staged_path(filename));
+    }
+
+    fn staged_path(&self, filename: &str) -> PathBuf {
+        self.town_dir.join(format!("{}.tmp", filename))
+    }
+
+    /// Replace every known secret value in `text` with `[REDACTED]`.
+    pub fn redact(&self, text: &str) -> String {
+        let redactions = self.redactions.lock().unwrap_or_else(|e| e.into_inner());
+        redact_with(&redactions, text)
+    }
+
+    /// Redact a stream read in chunks. `carry` holds back a tail that may be
+    /// the start of a secret continuing in the next chunk; pass an empty
+    /// chunk at the end of the stream to flush it.
+    pub fn redact_stream(&self, carry: &mut String, chunk: &str) -> String {
+        let redactions = self.redactions.lock().unwrap_or_else(|e| e.into_inner());
+        redact_stream_with(&redactions, carry, chunk)
+    }
+
+    /// `payload_json` with secrets redacted in its string values, so a secret
+    /// escaped by the JSON encoding is still found.
+    fn redact_json(&self, payload_json: &str) -> String {
+        fn walk(value: &mut serde_json::Value, redact: &dyn Fn(&str) -> String) {
+            match value {
+                serde_json::Value::String(s) => *s = redact(s),
+                serde_json::Value::Array(items) => items.iter_mut().for_each(|v| walk(v, redact)),
+                serde_json::Value::Object(map) => map.values_mut().for_each(|v| walk(v, redact)),
+                _ => {}
+            }
+        }
+        match serde_json::from_str::<serde_json::Value>(payload_json) {
+            Ok(mut value) => {
+                walk(&mut value, &|s| self.redact(s));
+                value.to_string()
+            }
+            Err(_) => self.redact(payload_json),
+        }
+    }
+
     pub fn save_settings(&self, settings: &AppSettings) {
         self.save_json(settings, "settings.json");
     }
@@ -272,57 +394,277 @@ impl AppState {
         self.town_dir.join("logs")
     }
 
+    pub fn sessions_dir(&self) -> PathBuf {
+        self.town_dir.join("sessions")
+    }
+
+    pub fn recordings_dir(&self) -> PathBuf {
+        self.town_dir.join("recordings")
+    }
+
+    pub fn recording_path(&self, worker_id: &str) -> PathBuf {
+        self.recordings_dir().join(format!("{}.cast", worker_id))
+    }
+
+    pub fn patches_dir(&self) -> PathBuf {
+        self.town_dir.join("patches")
+    }
+
+    /// Full patch of a run, kept after its worktree is removed.
+    pub fn patch_path(&self, run_id: &str) -> PathBuf {
+        self.patches_dir().join(format!("{}.patch", run_id))
+    }
+
+    pub fn save_run_patch(&self, run_id: &str, patch: &str) {
+        let _ = fs::create_dir_all(self.patches_dir());
+        if let Err(e) = fs::write(self.patch_path(run_id), patch) {
+            eprintln!("Failed to write patch for run {}: {}", run_id, e);
+        }
+    }
+
+    pub fn load_run_patch(&self, run_id: &str) -> Option<String> {
+        fs::read_to_string(self.patch_path(run_id)).ok()
+    }
+
+    /// Delete the patches of runs not in `live_run_ids`. Returns how many went.
+    pub fn prune_run_patches(&self, live_run_ids: &std::collections::HashSet<String>) -> usize {
+        let Ok(entries) = fs::read_dir(self.patches_dir()) else {
+            return 0;
+        };
+        entries
+            .flatten()
+            .filter(|e| {
+                let path = e.path();
+                path.extension().is_some_and(|ext| ext == "patch")
+                    && path
+                        .file_stem()
+                        .and_then(|s| s.to_str())
+                        .is_some_and(|run_id| !live_run_ids.contains(run_id))
+            })
+            .filter(|e| fs::remove_file(e.path()).is_ok())
+            .count()
+    }
+
     pub fn templates_dir(&self) -> PathBuf {
         self.town_dir.join("templates")
     }
 
-    pub fn save_log(&self, worker_id: &str, entries: &[LogEntry]) {
-        let log_path = self.logs_dir().join(format!("{}.jsonl", worker_id));
-        let lines: Vec<String> = entries
-            .iter()
-            .map(|e| serde_json::to_string(e).unwrap_or_default())
-            .collect();
-        fs::write(log_path, lines.join("\n")).ok();
+    fn log_path(&self, worker_id: &str) -> PathBuf {
+        self.logs_dir().join(format!("{}.jsonl", worker_id))
+    }
+
+    fn open_log_writer(&self, worker_id: &str) -> Option<BufWriter<fs::File>> {
+        let path = self.log_path(worker_id);
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .read(true)
+            .open(&path)
+            .ok()?;
+        // Logs written whole by older versions have no trailing newline.
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        if len > 0 {
+            let mut last = [0u8; 1];
+            if file.seek(SeekFrom::End(-1)).is_ok()
+                && file.read_exact(&mut last).is_ok()
+                && last[0] != b'\n'
+            {
+                file.write_all(b"\n").ok();
+            }
+        }
+        Some(BufWriter::new(file))
     }
 
-    pub fn load_log(&self, worker_id: &str) -> Vec<LogEntry> {
-        let log_path = self.logs_dir().join(format!("{}.jsonl", worker_id));
-        if log_path.exists() {
-            let data = fs::read_to_string(&log_path).unwrap_or_default();
-            data.lines()
-                .filter_map(|l| serde_json::from_str(l).ok())
-                .collect()
-        } else {
-            Vec::new()
+    /// Flush buffered log lines for every worker to disk.
+    pub fn flush_worker_logs(&self) {
+        let mut files = self.worker_log_files.lock().unwrap_or_else(|e| e.into_inner());
+        for writer in files.values_mut() {
+            writer.flush().ok();
         }
     }
 
+    fn flush_worker_log(&self, worker_id: &str) {
+        let mut files = self.worker_log_files.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(writer) = files.get_mut(worker_id) {
+            writer.flush().ok();
+        }
+    }
+
+    /// Flush and release a worker's log file, tail cache and live usage once it has exited.
+    /// Later entries for the worker are appended straight to disk.
+    pub fn close_worker_log(&self, worker_id: &str) {
+        let mut files = self.worker_log_files.lock().unwrap_or_else(|e| e.into_inner());
+        self.worker_logs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(worker_id);
+        self.worker_usage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(worker_id);
+        if let Some(mut writer) = files.remove(worker_id) {
+            writer.flush().ok();
+        }
+    }
+
+    pub fn load_log(&self, worker_id: &str) -> Vec<LogEntry> {
+        self.load_log_page(worker_id, 0, None)
+    }
+
+    /// Read `limit` entries (all when `None`) starting at entry `offset` of the on-disk log.
+    pub fn load_log_page(&self, worker_id: &str, offset: usize, limit: Option<usize>) -> Vec<LogEntry> {
+        self.flush_worker_log(worker_id);
+        let Ok(file) = fs::File::open(self.log_path(worker_id)) else {
+            return Vec::new();
+        };
+        BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter(|l| !l.trim().is_empty())
+            .skip(offset)
+            .take(limit.unwrap_or(usize::MAX))
+            .filter_map(|l| serde_json::from_str(&l).ok())
+            .collect()
+    }
+
+    /// Number of entries in a worker's on-disk log.
+    pub fn count_log_entries(&self, worker_id: &str) -> usize {
+        self.flush_worker_log(worker_id);
+        fs::File::open(self.log_path(worker_id))
+            .map(|f| {
+                BufReader::new(f)
+                    .lines()
+                    .map_while(Result::ok)
+                    .filter(|l| !l.trim().is_empty())
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
     pub fn delete_log(&self, worker_id: &str) {
-        let log_path = self.logs_dir().join(format!("{}.jsonl", worker_id));
+        self.worker_log_files
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(worker_id);
+        self.worker_usage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(worker_id);
+        let log_path = self.log_path(worker_id);
         if log_path.exists() {
             fs::remove_file(log_path).ok();
         }
     }
 
-    pub fn append_worker_log(&self, worker_id: &str, entry: LogEntry) {
+    // ── Terminal recordings ──
+
+    /// Start (or resume) the asciicast recording for a PTY worker, if enabled.
+    pub fn start_recording(&self, worker_id: &str, cols: u16, rows: u16, title: &str) {
+        if !self.settings.lock().unwrap_or_else(|e| e.into_inner()).record_sessions {
+            return;
+        }
+        match CastWriter::open(&self.recording_path(worker_id), cols, rows, title) {
+            Ok(cast) => {
+                self.worker_casts
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(worker_id.to_string(), cast);
+            }
+            Err(e) => eprintln!("[recording] failed to open cast for {}: {}", worker_id, e),
+        }
+    }
+
+    pub fn record_output(&self, worker_id: &str, data: &str) {
+        let mut casts = self.worker_casts.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(cast) = casts.get_mut(worker_id) {
+            cast.output(data);
+        }
+    }
+
+    pub fn record_resize(&self, worker_id: &str, cols: u16, rows: u16) {
+        let mut casts = self.worker_casts.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(cast) = casts.get_mut(worker_id) {
+            cast.resize(cols, rows);
+        }
+    }
+
+    pub fn flush_recordings(&self) {
+        let mut casts = self.worker_casts.lock().unwrap_or_else(|e| e.into_inner());
+        for cast in casts.values_mut() {
+            cast.flush();
+        }
+    }
+
+    pub fn finish_recording(&self, worker_id: &str) {
+        let mut casts = self.worker_casts.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(mut cast) = casts.remove(worker_id) {
+            cast.flush();
+        }
+    }
+
+    /// Append a log entry to `logs/<worker>.jsonl` and, while the worker is live,
+    /// to the in-memory tail cache. Live workers keep a buffered handle (see
+    /// `flush_worker_logs`); entries after `close_worker_log` are written and
+    /// flushed at once. Returns the entry as stored, with secrets redacted.
+    pub fn append_worker_log(&self, worker_id: &str, mut entry: LogEntry) -> LogEntry {
+        entry.line = self.redact(&entry.line);
+        {
+            let mut files = self.worker_log_files.lock().unwrap_or_else(|e| e.into_inner());
+            let live = self
+                .worker_logs
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .contains_key(worker_id);
+            if live {
+                if !files.contains_key(worker_id) {
+                    if let Some(writer) = self.open_log_writer(worker_id) {
+                        files.insert(worker_id.to_string(), writer);
+                    }
+                }
+                self.worker_usage
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .entry(worker_id.to_string())
+                    .or_default()
+                    .observe(&entry.line);
+            }
+            if let Ok(line) = serde_json::to_string(&entry) {
+                match files.get_mut(worker_id) {
+                    Some(writer) => {
+                        writeln!(writer, "{}", line).ok();
+                    }
+                    None => {
+                        if let Some(mut writer) = self.open_log_writer(worker_id) {
+                            writeln!(writer, "{}", line).ok();
+                            writer.flush().ok();
+                        }
+                    }
+                }
+            }
+        }
+
         let mut logs = self.worker_logs.lock().unwrap_or_else(|e| e.into_inner());
-        let entries = logs.entry(worker_id.to_string()).or_insert_with(Vec::new);
-        entries.push(entry);
-        
-        // Ring buffer: prevent memory leak for long-running workers.
-        const MAX_LOG_ENTRIES: usize = 5000;
-        if entries.len() > MAX_LOG_ENTRIES {
-            // Drop oldest 500 entries at once to amortize O(N) drain cost
-            let drain_count = entries.len() - MAX_LOG_ENTRIES + 500;
-            entries.drain(0..drain_count);
+        if let Some(entries) = logs.get_mut(worker_id) {
+            entries.push(entry.clone());
+
+            // The full history lives on disk; memory only keeps a tail for live views.
+            const MAX_LOG_TAIL: usize = 2000;
+            if entries.len() > MAX_LOG_TAIL {
+                // Drop oldest 200 entries at once to amortize O(N) drain cost
+                let drain_count = entries.len() - MAX_LOG_TAIL + 200;
+                entries.drain(0..drain_count);
+            }
         }
+        entry
     }
 
     // ── Audit events (append-only) ──
 
     pub fn append_audit_event(&self, event: &AuditEvent) {
         let audit_path = self.town_dir.join("audit_events.jsonl");
-        if let Ok(json) = serde_json::to_string(event) {
+        let mut event = event.clone();
+        event.payload_json = self.redact_json(&event.payload_json);
+        if let Ok(json) = serde_json::to_string(&event) {
             if let Ok(mut file) = fs::OpenOptions::new()
                 .create(true)
                 .append(true)
@@ -363,3 +705,58 @@ impl AppState {
             .collect()
     }
 }
+
+fn redact_with(redactions: &[String], text: &str) -> String {
+    let mut out = text.to_string();
+    for secret in redactions {
+        if out.contains(secret.as_str()) {
+            out = out.replace(secret.as_str(), "[REDACTED]");
+        }
+    }
+    out
+}
+
+fn redact_stream_with(redactions: &[String], carry: &mut String, chunk: &str) -> String {
+    carry.push_str(chunk);
+    let text = redact_with(redactions, carry);
+    let held = if chunk.is_empty() {
+        0
+    } else {
+        (1..=text.len())
+            .rev()
+            .filter(|&n| text.is_char_boundary(text.len() - n))
+            .find(|&n| {
+                let tail = &text[text.len() - n..];
+                redactions.iter().any(|s| s.len() > n && s.starts_with(tail))
+            })
+            .unwrap_or(0)
+    };
+    let split = text.len() - held;
+    *carry = text[split..].to_string();
+    text[..split].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_every_occurrence() {
+        let secrets = vec!["sk-ant-api03-abc123".to_string()];
+        assert_eq!(
+            redact_with(&secrets, "export ANTHROPIC_API_KEY=sk-ant-api03-abc123 # sk-ant-api03-abc123"),
+            "export ANTHROPIC_API_KEY=[REDACTED] # [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn redacts_a_secret_split_across_chunks() {
+        let secrets = vec!["ghp_token1234".to_string()];
+        let mut carry = String::new();
+        let mut out = redact_stream_with(&secrets, &mut carry, "Authorization: token ghp_to");
+        out += &redact_stream_with(&secrets, &mut carry, "ken1234\r\n");
+        out += &redact_stream_with(&secrets, &mut carry, "");
+        assert_eq!(out, "Authorization: token [REDACTED]\r\n");
+        assert!(carry.is_empty());
+    }
+}
@@ -1,11 +1,38 @@
 use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::prompt_context::PromptContext;
+
+/// Where a template was found. Later sources override earlier ones by name:
+/// builtin < town (`~/.townui/templates`) < rig (`<repo>/.townui/templates`)
+/// < crew (`<worktree>/.townui/templates`).
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateSource {
+    #[default]
+    Builtin,
+    Town,
+    Rig,
+    Crew,
+}
 
+#[derive(Debug, Clone, Default)]
 pub struct Template {
     pub name: String,
     pub description: String,
+    /// Template body, without any front-matter.
     pub content: String,
     pub is_builtin: bool,
+    pub source: TemplateSource,
+    /// File the template was loaded from (custom templates only).
+    pub path: Option<String>,
+    /// Variables that must be set and non-empty to render (front-matter `required`).
+    pub required_vars: Vec<String>,
+    /// Agent to use when a run does not pick one (front-matter `agent`).
+    pub recommended_agent: Option<String>,
 }
 
 pub fn get_builtin_templates() -> Vec<Template> {
@@ -24,6 +51,7 @@ Description:
 Please implement this feature. Write clean, well-structured code that follows the existing codebase patterns.
 After implementing, briefly summarize what you changed."#.to_string(),
             is_builtin: true,
+            ..Default::default()
         },
         Template {
             name: "fix_bug".to_string(),
@@ -39,6 +67,7 @@ Description:
 Please investigate and fix this bug. Explain the root cause before applying the fix.
 Make sure the fix doesn't introduce regressions."#.to_string(),
             is_builtin: true,
+            ..Default::default()
         },
         Template {
             name: "write_tests".to_string(),
@@ -54,6 +83,7 @@ Description:
 Please write comprehensive tests. Cover edge cases, error conditions, and happy paths.
 Follow the existing test patterns in the codebase."#.to_string(),
             is_builtin: true,
+            ..Default::default()
         },
         Template {
             name: "refactor".to_string(),
@@ -69,6 +99,7 @@ Description:
 Please refactor the code as described. Ensure behavior is preserved — no functional changes unless explicitly requested.
 Keep the code clean and well-organized."#.to_string(),
             is_builtin: true,
+            ..Default::default()
         },
         // ── Bigtech-grade templates ──────────────────────────────────
         Template {
@@ -99,6 +130,7 @@ Check for:
 End with: LGTM / REQUEST_CHANGES / NEEDS_DISCUSSION
 Provide an overall summary of the review."#.to_string(),
             is_builtin: true,
+            ..Default::default()
         },
         Template {
             name: "security_audit".to_string(),
@@ -135,6 +167,7 @@ Perform a comprehensive security audit:
 For each finding: CVSS score estimate, exploitability, remediation steps.
 Output: Security report sorted by severity."#.to_string(),
             is_builtin: true,
+            ..Default::default()
         },
         Template {
             name: "architecture_review".to_string(),
@@ -169,6 +202,7 @@ Evaluate the architecture:
 For each concern, rate: ✅ Good / ⚠️ Needs Attention / ❌ Problematic
 Provide specific code references and improvement suggestions."#.to_string(),
             is_builtin: true,
+            ..Default::default()
         },
         Template {
             name: "api_design".to_string(),
@@ -196,6 +230,7 @@ Design the API following industry best practices:
 
 Output: OpenAPI 3.0 spec (YAML) with usage examples (curl commands)."#.to_string(),
             is_builtin: true,
+            ..Default::default()
         },
         Template {
             name: "performance_analysis".to_string(),
@@ -226,6 +261,7 @@ For each finding:
 
 Prioritize by impact-to-effort ratio."#.to_string(),
             is_builtin: true,
+            ..Default::default()
         },
         Template {
             name: "incident_investigation".to_string(),
@@ -255,6 +291,7 @@ Output:
 - Fix applied (code changes)
 - Prevention action items (3-5 items)"#.to_string(),
             is_builtin: true,
+            ..Default::default()
         },
         Template {
             name: "documentation".to_string(),
@@ -283,6 +320,7 @@ Guidelines:
 - Add a TL;DR at the top
 - Include a prerequisite section"#.to_string(),
             is_builtin: true,
+            ..Default::default()
         },
         Template {
             name: "test_strategy".to_string(),
@@ -323,6 +361,7 @@ For each test:
 
 Write the actual test code following existing test patterns."#.to_string(),
             is_builtin: true,
+            ..Default::default()
         },
         Template {
             name: "data_migration".to_string(),
@@ -368,6 +407,7 @@ Plan and execute the data migration:
 
 All scripts must be idempotent (safe to re-run)."#.to_string(),
             is_builtin: true,
+            ..Default::default()
         },
         Template {
             name: "tech_debt_analysis".to_string(),
@@ -417,120 +457,391 @@ Analyze technical debt systematically:
 For each item: Impact (1-5), Effort (S/M/L/XL), Priority score.
 Output: Prioritized backlog sorted by priority score."#.to_string(),
             is_builtin: true,
+            ..Default::default()
         },
     ]
 }
 
-pub fn render_template(
-    template_content: &str,
-    vars: &HashMap<String, String>,
-) -> String {
-    let mut result = template_content.to_string();
-    for (key, value) in vars {
-        result = result.replace(&format!("{{{{{}}}}}", key), value);
+/// Jinja environment used for every prompt: strict undefined (a typo'd or
+/// missing variable is an error, not an empty string), no HTML escaping, and
+/// the other templates of the registry available to `{% include %}` by name.
+fn environment(fragments: &[Template]) -> minijinja::Environment<'_> {
+    let mut env = minijinja::Environment::new();
+    env.set_undefined_behavior(minijinja::UndefinedBehavior::Strict);
+    env.set_auto_escape_callback(|_| minijinja::AutoEscape::None);
+    env.set_keep_trailing_newline(true);
+    env.add_filter("truncate", truncate_filter);
+    for fragment in fragments {
+        // A broken template only fails the prompts that include it.
+        let _ = env.add_template(&fragment.name, &fragment.content);
     }
-    result
+    env
 }
 
-pub fn render_builtin_template(
-    template_name: &str,
-    agent_cli: &str,
-    task_title: &str,
-    task_description: &str,
-    rig_name: &str,
-    crew_branch: &str,
-    repo_root: &str,
-) -> String {
-    let templates = get_builtin_templates();
-    let template = templates
-        .iter()
-        .find(|t| t.name == template_name)
-        .map(|t| t.content.clone())
-        .unwrap_or_else(|| format!("Task: {}\n\n{}", task_title, task_description));
+/// `{{ text | truncate(200) }}`: cut to `length` characters, ending with `end` ("...").
+fn truncate_filter(value: String, length: Option<usize>, end: Option<String>) -> String {
+    let length = length.unwrap_or(255);
+    if value.chars().count() <= length {
+        return value;
+    }
+    let end = end.unwrap_or_else(|| "...".to_string());
+    let keep = length.saturating_sub(end.chars().count());
+    let mut out: String = value.chars().take(keep).collect();
+    out.push_str(&end);
+    out
+}
+
+fn template_error(e: minijinja::Error) -> String {
+    let mut message = e.to_string();
+    let mut source = std::error::Error::source(&e);
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    message
+}
+
+/// Nest dotted variable names (`task.title`) into objects so templates can
+/// use them as attributes. A flat key loses to a nested one of the same name.
+pub fn vars_context(vars: &HashMap<String, String>) -> serde_json::Value {
+    let mut root = serde_json::Map::new();
+    let mut keys: Vec<&String> = vars.keys().collect();
+    keys.sort_by_key(|k| std::cmp::Reverse(k.matches('.').count()));
+    for key in keys {
+        let parts: Vec<&str> = key.split('.').collect();
+        let mut map = &mut root;
+        for part in &parts[..parts.len() - 1] {
+            let entry = map
+                .entry(part.to_string())
+                .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+            if !entry.is_object() {
+                *entry = serde_json::Value::Object(serde_json::Map::new());
+            }
+            map = entry.as_object_mut().unwrap();
+        }
+        map.entry(parts[parts.len() - 1].to_string())
+            .or_insert_with(|| serde_json::Value::String(vars[key].clone()));
+    }
+    serde_json::Value::Object(root)
+}
+
+/// Value at a dotted path of a context, if set.
+fn lookup<'a>(ctx: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split('.').try_fold(ctx, |value, part| value.get(part))
+}
+
+/// Render `template_content` against `ctx`. `fragments` are the templates
+/// it may include.
+pub fn render_with_context(
+    name: &str,
+    template_content: &str,
+    ctx: &serde_json::Value,
+    fragments: &[Template],
+) -> Result<String, String> {
+    let env = environment(fragments);
+    let template = env
+        .template_from_named_str(name, template_content)
+        .map_err(|e| format!("Template '{}' has a syntax error: {}", name, template_error(e)))?;
+    template
+        .render(ctx)
+        .map_err(|e| format!("Template '{}' failed to render: {}", name, template_error(e)))
+}
 
-    let mut vars = HashMap::new();
-    vars.insert("task.title".to_string(), task_title.to_string());
-    vars.insert("task.description".to_string(), task_description.to_string());
-    vars.insert("rig.name".to_string(), rig_name.to_string());
-    vars.insert("crew.branch".to_string(), crew_branch.to_string());
-    vars.insert("repo.root".to_string(), repo_root.to_string());
-    vars.insert("agent.cli".to_string(), agent_cli.to_string());
+pub fn render_template(
+    template_content: &str,
+    vars: &HashMap<String, String>,
+) -> Result<String, String> {
+    render_with_context("template", template_content, &vars_context(vars), &[])
+}
 
-    let rendered = render_template(&template, &vars);
+/// Render a task prompt from a resolved template. Fails when a variable the
+/// template's front-matter requires is empty or the template uses one the
+/// context does not have.
+pub fn render_task_template(
+    template: &Template,
+    fragments: &[Template],
+    ctx: &PromptContext,
+) -> Result<String, String> {
+    let ctx_value = serde_json::to_value(ctx).map_err(|e| e.to_string())?;
+    let missing = missing_required_vars(template, &ctx_value);
+    if !missing.is_empty() {
+        return Err(format!(
+            "Template '{}' requires variables: {}",
+            template.name,
+            missing.join(", ")
+        ));
+    }
+    let rendered = render_with_context(&template.name, &template.content, &ctx_value, fragments)?;
 
-    let cli = agent_cli.trim();
+    let cli = ctx.agent.cli.trim();
     if cli.is_empty() {
-        rendered
+        Ok(rendered)
     } else {
         let hint = match cli.to_ascii_lowercase().as_str() {
             "codex" => "Runtime CLI: codex. Prefer concise, deterministic execution and explicit final output.",
             "claude" => "Runtime CLI: claude. Prefer clear stepwise reasoning and implementation summary.",
             _ => "Runtime CLI: use the selected default agent and adapt commands/flow accordingly.",
         };
-        format!("{}\n{}\n\n{}", hint, format!("Selected CLI: {}", cli), rendered)
+        Ok(format!("{}\n{}\n\n{}", hint, format!("Selected CLI: {}", cli), rendered))
     }
 }
 
-/// Extract all `{{var}}` variable names from a template string.
-pub fn extract_variables(template_content: &str) -> Vec<String> {
-    let mut vars = Vec::new();
-    let bytes = template_content.as_bytes();
-    let len = bytes.len();
-    let mut i = 0;
-
-    while i + 3 < len {
-        if bytes[i] == b'{' && bytes[i + 1] == b'{' {
-            // Found opening {{
-            let start = i + 2;
-            if let Some(end_offset) = template_content[start..].find("}}") {
-                let var_name = template_content[start..start + end_offset].trim();
-                if !var_name.is_empty() && !vars.contains(&var_name.to_string()) {
-                    vars.push(var_name.to_string());
-                }
-                i = start + end_offset + 2;
-            } else {
-                i += 2;
-            }
-        } else {
-            i += 1;
-        }
-    }
+/// Front-matter `required` variables that are unset or blank in `ctx`.
+pub fn missing_required_vars(template: &Template, ctx: &serde_json::Value) -> Vec<String> {
+    template
+        .required_vars
+        .iter()
+        .filter(|v| match lookup(ctx, v) {
+            None | Some(serde_json::Value::Null) => true,
+            Some(serde_json::Value::String(s)) => s.trim().is_empty(),
+            Some(serde_json::Value::Array(items)) => items.is_empty(),
+            Some(_) => false,
+        })
+        .cloned()
+        .collect()
+}
 
-    vars
+/// Variables a template reads from its context, as dotted paths
+/// (`task.title`, `task.dependencies`), found by static analysis. Loop
+/// variables, `{% set %}` names and macro arguments are not included.
+pub fn extract_variables(template_content: &str) -> Result<Vec<String>, String> {
+    let env = environment(&[]);
+    let template = env
+        .template_from_str(template_content)
+        .map_err(|e| format!("Template syntax error: {}", template_error(e)))?;
+    let globals: Vec<&str> = env.globals().map(|(name, _)| name).collect();
+    let mut vars: Vec<String> = template
+        .undeclared_variables(true)
+        .into_iter()
+        .filter(|v| !globals.contains(&v.split('.').next().unwrap_or_default()))
+        .collect();
+    vars.sort();
+    Ok(vars)
 }
 
-/// Validate that all variables in the template are provided in the vars map.
-/// Returns a list of missing variable names.
+/// Validate that all variables the template reads are provided in the vars
+/// map. Returns a list of missing variable names, or the syntax error.
 pub fn validate_variables(
     template_content: &str,
     vars: &HashMap<String, String>,
-) -> Vec<String> {
-    extract_variables(template_content)
+) -> Result<Vec<String>, String> {
+    let ctx = vars_context(vars);
+    Ok(extract_variables(template_content)?
         .into_iter()
-        .filter(|v| !vars.contains_key(v))
-        .collect()
+        .filter(|v| lookup(&ctx, v).is_none())
+        .collect())
 }
 
-pub fn load_custom_templates(templates_dir: &std::path::Path) -> Vec<Template> {
+/// Split an optional `---` front-matter block off a template file. Recognised
+/// keys: `description`, `required` (a `[a, b]` / comma list or `- item`
+/// lines) and `agent` (alias `recommended_agent`); others are ignored.
+fn parse_front_matter(raw: &str) -> (Option<String>, Vec<String>, Option<String>, String) {
+    let text = raw.strip_prefix('\u{feff}').unwrap_or(raw);
+    let Some(rest) = text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n")) else {
+        return (None, Vec::new(), None, raw.to_string());
+    };
+    let Some(end) = rest.find("\n---") else {
+        return (None, Vec::new(), None, raw.to_string());
+    };
+    let header = &rest[..end];
+    let body = rest[end + 4..].trim_start_matches(['\r', '\n']).to_string();
+
+    let unquote = |v: &str| v.trim().trim_matches(|c| c == '"' || c == '\'').to_string();
+    let mut description = None;
+    let mut required = Vec::new();
+    let mut agent = None;
+    let mut in_required = false;
+    for line in header.lines() {
+        let trimmed = line.trim();
+        if in_required {
+            if let Some(item) = trimmed.strip_prefix("- ") {
+                required.push(unquote(item));
+                continue;
+            }
+            in_required = false;
+        }
+        let Some((key, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "description" => description = Some(unquote(value)).filter(|d| !d.is_empty()),
+            "agent" | "recommended_agent" => agent = Some(unquote(value)).filter(|a| !a.is_empty()),
+            "required" | "required_vars" => {
+                if value.is_empty() {
+                    in_required = true;
+                } else {
+                    required.extend(
+                        value
+                            .trim_start_matches('[')
+                            .trim_end_matches(']')
+                            .split(',')
+                            .map(unquote)
+                            .filter(|v| !v.is_empty()),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+    (description, required, agent, body)
+}
+
+/// Load the `.txt` / `.md` templates of one directory, named by file stem.
+pub fn load_templates_from_dir(dir: &Path, source: TemplateSource) -> Vec<Template> {
     let mut templates = Vec::new();
-    if let Ok(entries) = fs::read_dir(templates_dir) {
+    if let Ok(entries) = fs::read_dir(dir) {
         for entry in entries.flatten() {
             let path = entry.path();
             if path.extension().map(|e| e == "txt" || e == "md").unwrap_or(false) {
-                if let Ok(content) = fs::read_to_string(&path) {
+                if let Ok(raw) = fs::read_to_string(&path) {
                     let name = path
                         .file_stem()
                         .map(|s| s.to_string_lossy().to_string())
                         .unwrap_or_default();
+                    let (description, required_vars, recommended_agent, content) = parse_front_matter(&raw);
                     templates.push(Template {
-                        name: name.clone(),
-                        description: format!("Custom template: {}", name),
+                        description: description.unwrap_or_else(|| format!("Custom template: {}", name)),
+                        name,
                         content,
                         is_builtin: false,
+                        source,
+                        path: Some(path.to_string_lossy().to_string()),
+                        required_vars,
+                        recommended_agent,
                     });
                 }
             }
         }
     }
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
     templates
 }
+
+/// Template directory inside a repo or worktree.
+pub fn repo_templates_dir(root: &str) -> PathBuf {
+    Path::new(root).join(".townui").join("templates")
+}
+
+/// Every template visible from a rig/crew, one per name, the most specific
+/// source winning. Builtins keep their order; custom names follow by name.
+pub fn resolve_templates(town_dir: &Path, rig_path: Option<&str>, crew_path: Option<&str>) -> Vec<Template> {
+    let mut layers = vec![load_templates_from_dir(town_dir, TemplateSource::Town)];
+    if let Some(rig_path) = rig_path {
+        layers.push(load_templates_from_dir(&repo_templates_dir(rig_path), TemplateSource::Rig));
+    }
+    if let Some(crew_path) = crew_path.filter(|c| Some(*c) != rig_path) {
+        layers.push(load_templates_from_dir(&repo_templates_dir(crew_path), TemplateSource::Crew));
+    }
+
+    let mut templates = get_builtin_templates();
+    for template in layers.into_iter().flatten() {
+        match templates.iter_mut().find(|t| t.name == template.name) {
+            Some(existing) => *existing = template,
+            None => templates.push(template),
+        }
+    }
+    templates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_front_matter_from_the_body() {
+        let raw = "---\ndescription: \"Fix a failing test\"\nrequired:\n  - task.title\n  - rig.name\nrecommended_agent: claude\n---\n\nFix {{ task.title }}.\n";
+        let (description, required, agent, body) = parse_front_matter(raw);
+        assert_eq!(description.as_deref(), Some("Fix a failing test"));
+        assert_eq!(required, vec!["task.title", "rig.name"]);
+        assert_eq!(agent.as_deref(), Some("claude"));
+        assert_eq!(body, "Fix {{ task.title }}.\n");
+    }
+
+    #[test]
+    fn inline_required_list_and_no_front_matter() {
+        let (_, required, _, _) = parse_front_matter("---\r\nrequired: [task.title, 'crew.branch']\r\n---\r\nbody");
+        assert_eq!(required, vec!["task.title", "crew.branch"]);
+
+        let raw = "Plain template without a header";
+        assert_eq!(parse_front_matter(raw), (None, Vec::new(), None, raw.to_string()));
+    }
+
+    fn fragment(name: &str, content: &str) -> Template {
+        Template {
+            name: name.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn undefined_variable_is_an_error() {
+        let ctx = serde_json::json!({ "task": { "title": "Fix login" } });
+        let err = render_with_context("t", "{{ task.titel }}", &ctx, &[]).unwrap_err();
+        assert!(err.starts_with("Template 't' failed to render"), "{}", err);
+        assert!(render_with_context("t", "{{ rig.name }}", &ctx, &[]).is_err());
+    }
+
+    #[test]
+    fn conditionals_and_loops_over_dependencies() {
+        let template = "{% if task.dependencies %}Depends on:\n{% for d in task.dependencies %}- {{ d.title }} ({{ d.status }})\n{% endfor %}{% else %}No dependencies.\n{% endif %}";
+        let ctx = serde_json::json!({ "task": { "dependencies": [
+            { "title": "Add schema", "status": "done" },
+            { "title": "Seed data", "status": "todo" },
+        ] } });
+        assert_eq!(
+            render_with_context("t", template, &ctx, &[]).unwrap(),
+            "Depends on:\n- Add schema (done)\n- Seed data (todo)\n"
+        );
+        let empty = serde_json::json!({ "task": { "dependencies": [] } });
+        assert_eq!(render_with_context("t", template, &empty, &[]).unwrap(), "No dependencies.\n");
+    }
+
+    #[test]
+    fn includes_a_registry_fragment() {
+        let fragments = [fragment("house-rules", "Run the tests before {{ task.title | lower }}.")];
+        let ctx = serde_json::json!({ "task": { "title": "Committing" } });
+        let rendered = render_with_context("t", "Rules: {% include 'house-rules' %}", &ctx, &fragments).unwrap();
+        assert_eq!(rendered, "Rules: Run the tests before committing.");
+        assert!(render_with_context("t", "{% include 'missing' %}", &ctx, &fragments).is_err());
+    }
+
+    #[test]
+    fn truncate_counts_characters_not_bytes() {
+        assert_eq!(truncate_filter("héllo wörld".to_string(), Some(8), None), "héllo...");
+        assert_eq!(truncate_filter("日本語のテキスト".to_string(), Some(4), Some("…".to_string())), "日本語…");
+        assert_eq!(truncate_filter("短い".to_string(), Some(4), None), "短い");
+        let ctx = serde_json::json!({ "text": "ÄÖÜäöüß" });
+        assert_eq!(render_with_context("t", "{{ text | truncate(5) }}", &ctx, &[]).unwrap(), "ÄÖ...");
+    }
+
+    #[test]
+    fn extracted_variables_are_dotted_paths_without_locals() {
+        let template = "{% set heading = task.title | upper %}{{ heading }} on {{ crew.branch }}\n{% for d in task.dependencies %}{{ d.title }} {{ loop.index }}{% endfor %}";
+        assert_eq!(
+            extract_variables(template).unwrap(),
+            vec!["crew.branch", "task.dependencies", "task.title"]
+        );
+
+        let vars = HashMap::from([("task.title".to_string(), "Fix login".to_string())]);
+        assert_eq!(
+            validate_variables(template, &vars).unwrap(),
+            vec!["crew.branch", "task.dependencies"]
+        );
+        assert!(extract_variables("{% if %}").is_err());
+    }
+
+    #[test]
+    fn legacy_flat_variables_still_render() {
+        let vars = HashMap::from([
+            ("task.title".to_string(), "Fix login".to_string()),
+            ("task.description".to_string(), "Session expires early".to_string()),
+            ("branch".to_string(), "crew/alice".to_string()),
+        ]);
+        assert_eq!(
+            render_template("{{task.title}}: {{task.description}} [{{branch}}]", &vars).unwrap(),
+            "Fix login: Session expires early [crew/alice]"
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use crate::models::settings::ModelPrice;
+use crate::models::worker::{LogEntry, Run};
+
+/// Token/cost figures recovered from an agent's output.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageReport {
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    /// Only set when the agent reports a combined figure (e.g. codex "tokens used: N").
+    pub total_tokens: Option<u64>,
+    pub cost_usd: Option<f64>,
+}
+
+impl UsageReport {
+    pub fn is_empty(&self) -> bool {
+        self.input_tokens.is_none()
+            && self.output_tokens.is_none()
+            && self.total_tokens.is_none()
+            && self.cost_usd.is_none()
+    }
+
+    /// Fold one line of agent output into the report; a figure it reports
+    /// replaces the earlier one.
+    ///
+    /// Recognised shapes:
+    /// - Claude `--output-format json`: `"input_tokens": N`, `"output_tokens": N`, `"total_cost_usd": X`
+    /// - Claude `/cost`: `Total cost: $X`
+    /// - Aider: `Tokens: 12k sent, 1.2k received. Cost: $0.02 message, $0.15 session.`
+    /// - Codex: `tokens used: 12,345`
+    /// - Generic: `input tokens: N`, `output tokens: N`, `prompt tokens`, `completion tokens`
+    pub fn observe(&mut self, line: &str) {
+        let lower = line.to_ascii_lowercase();
+        if !lower.contains("token") && !lower.contains("cost") {
+            return;
+        }
+
+        // Aider: "Tokens: 12k sent, 1.2k received. Cost: $0.02 message, $0.15 session."
+        if lower.contains(" sent") && lower.contains(" received") {
+            if let Some(n) = number_before(line, " sent").and_then(|s| parse_count(&s)) {
+                self.input_tokens = Some(n);
+            }
+            if let Some(n) = number_before(line, " received").and_then(|s| parse_count(&s)) {
+                self.output_tokens = Some(n);
+            }
+            if let Some(c) = number_before(line, " session").and_then(|s| {
+                s.trim_start_matches('$').replace(',', "").parse::<f64>().ok()
+            }) {
+                self.cost_usd = Some(c);
+            }
+            return;
+        }
+
+        for key in ["\"input_tokens\"", "input tokens", "input_tokens", "prompt tokens", "prompt_tokens"] {
+            if let Some(n) = number_after(line, key).and_then(|s| parse_count(&s)) {
+                self.input_tokens = Some(n);
+                break;
+            }
+        }
+        for key in ["\"output_tokens\"", "output tokens", "output_tokens", "completion tokens", "completion_tokens"] {
+            if let Some(n) = number_after(line, key).and_then(|s| parse_count(&s)) {
+                self.output_tokens = Some(n);
+                break;
+            }
+        }
+        if let Some(n) = number_after(line, "tokens used").and_then(|s| parse_count(&s)) {
+            self.total_tokens = Some(n);
+        }
+        for key in ["\"total_cost_usd\"", "total_cost_usd", "total cost", "cost_usd"] {
+            if let Some(c) = number_after(line, key).and_then(|s| s.replace(',', "").parse::<f64>().ok()) {
+                self.cost_usd = Some(c);
+                break;
+            }
+        }
+    }
+}
+
+/// Parse a human-style count such as `1,234`, `12k`, `1.2M`.
+fn parse_count(raw: &str) -> Option<u64> {
+    let cleaned: String = raw.trim().chars().filter(|c| *c != ',' && *c != '_').collect();
+    let lower = cleaned.to_ascii_lowercase();
+    let (num, mult) = if let Some(n) = lower.strip_suffix('k') {
+        (n, 1_000.0)
+    } else if let Some(n) = lower.strip_suffix('m') {
+        (n, 1_000_000.0)
+    } else {
+        (lower.as_str(), 1.0)
+    };
+    let value: f64 = num.parse().ok()?;
+    if value < 0.0 {
+        return None;
+    }
+    Some((value * mult).round() as u64)
+}
+
+/// Take the leading number-ish token (digits, `.`, `,`, optional k/M suffix) from `s`.
+fn leading_number(s: &str) -> Option<&str> {
+    let s = s.trim_start();
+    let mut end = 0;
+    for (i, c) in s.char_indices() {
+        if c.is_ascii_digit() || c == '.' || c == ',' || c == '_' {
+            end = i + c.len_utf8();
+        } else if (c == 'k' || c == 'K' || c == 'm' || c == 'M') && end > 0 {
+            end = i + c.len_utf8();
+            break;
+        } else {
+            break;
+        }
+    }
+    if end == 0 {
+        None
+    } else {
+        Some(&s[..end])
+    }
+}
+
+/// Find `key` in `line` (case-insensitive) and parse the number that follows it,
+/// skipping separators such as `:`, `=`, `"` and `$`.
+fn number_after(line: &str, key: &str) -> Option<String> {
+    let lower = line.to_ascii_lowercase();
+    let idx = lower.find(key)?;
+    let rest = line[idx + key.len()..].trim_start_matches(|c: char| {
+        c == ':' || c == '=' || c == '"' || c == '$' || c == ' ' || c == '\t'
+    });
+    leading_number(rest).map(|s| s.to_string())
+}
+
+/// Parse the number that precedes `suffix` (e.g. `12k sent`).
+fn number_before(line: &str, suffix: &str) -> Option<String> {
+    let lower = line.to_ascii_lowercase();
+    let idx = lower.find(suffix)?;
+    let head = line[..idx].trim_end();
+    let start = head
+        .rfind(|c: char| c.is_whitespace() || c == ':' || c == '(')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    Some(head[start..].to_string())
+}
+
+/// Scan agent output for token/cost reports. Later reports win, since most agents
+/// print cumulative session totals.
+pub fn parse_usage_from_lines<'a, I>(lines: I) -> UsageReport
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut report = UsageReport::default();
+    for line in lines {
+        report.observe(line);
+    }
+    report
+}
+
+pub fn parse_usage_from_logs(entries: &[LogEntry]) -> UsageReport {
+    parse_usage_from_lines(entries.iter().map(|e| e.line.as_str()))
+}
+
+/// Look up the price entry for a run: exact `model_tag` first, then `agent_type`.
+pub fn resolve_price<'a>(
+    prices: &'a HashMap<String, ModelPrice>,
+    model_tag: Option<&str>,
+    agent_type: &str,
+) -> Option<&'a ModelPrice> {
+    model_tag
+        .and_then(|tag| prices.get(tag))
+        .or_else(|| prices.get(agent_type))
+}
+
+/// Estimate USD cost from token counts. A combined total without an
+/// input/output split is not priced, so it leaves the cost unknown.
+pub fn estimate_cost(
+    price: &ModelPrice,
+    input_tokens: Option<u64>,
+    output_tokens: Option<u64>,
+) -> Option<f64> {
+    if input_tokens.is_none() && output_tokens.is_none() {
+        return None;
+    }
+    let input = input_tokens.unwrap_or(0) as f64 * price.input_per_mtok / 1_000_000.0;
+    let output = output_tokens.unwrap_or(0) as f64 * price.output_per_mtok / 1_000_000.0;
+    Some(input + output)
+}
+
+/// Re-estimate a run's cost from its token counts, unless the figure was reported or entered manually.
+pub fn reprice_run(run: &mut Run, prices: &HashMap<String, ModelPrice>) {
+    if matches!(run.cost_source.as_deref(), Some("reported") | Some("manual")) {
+        return;
+    }
+    let estimated = resolve_price(prices, run.model_tag.as_deref(), &run.agent_type)
+        .and_then(|p| estimate_cost(p, run.input_tokens, run.output_tokens));
+    run.cost_source = estimated.map(|_| "estimated".to_string());
+    run.cost_usd = estimated;
+}
+
+/// Merge parsed usage into a run and fill in an estimated cost when none was reported.
+pub fn apply_usage(run: &mut Run, usage: &UsageReport, prices: &HashMap<String, ModelPrice>) {
+    if usage.input_tokens.is_some() || usage.output_tokens.is_some() {
+        run.input_tokens = usage.input_tokens.or(run.input_tokens);
+        run.output_tokens = usage.output_tokens.or(run.output_tokens);
+        run.total_tokens = None;
+    } else if let Some(total) = usage.total_tokens {
+        run.total_tokens = Some(total);
+    }
+
+    if let Some(cost) = usage.cost_usd {
+        run.cost_usd = Some(cost);
+        run.cost_source = Some("reported".to_string());
+    } else {
+        reprice_run(run, prices);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_claude_json_result() {
+        let line = r#"{"type":"result","subtype":"success","is_error":false,"duration_ms":48211,"num_turns":9,"total_cost_usd":0.1843,"usage":{"input_tokens":1520,"cache_creation_input_tokens":10894,"cache_read_input_tokens":98713,"output_tokens":2231}}"#;
+        let report = parse_usage_from_lines([line]);
+        assert_eq!(report.input_tokens, Some(1520));
+        assert_eq!(report.output_tokens, Some(2231));
+        assert_eq!(report.cost_usd, Some(0.1843));
+    }
+
+    #[test]
+    fn later_aider_reports_win() {
+        let report = parse_usage_from_lines([
+            "Tokens: 4.1k sent, 312 received. Cost: $0.0071 message, $0.0071 session.",
+            "Tokens: 12k sent, 1.2k received. Cost: $0.02 message, $0.15 session.",
+        ]);
+        assert_eq!(report.input_tokens, Some(12_000));
+        assert_eq!(report.output_tokens, Some(1_200));
+        assert_eq!(report.cost_usd, Some(0.15));
+    }
+
+    #[test]
+    fn codex_total_is_kept_apart_and_not_priced() {
+        let report = parse_usage_from_lines(["[2025-09-01T10:12:44] tokens used: 12,345"]);
+        assert_eq!(report.total_tokens, Some(12_345));
+        assert_eq!(report.input_tokens, None);
+
+        let mut run = Run::new(
+            "task".into(),
+            "worker".into(),
+            "crew".into(),
+            "rig".into(),
+            "codex".into(),
+            "default".into(),
+            String::new(),
+        );
+        let prices = HashMap::from([(
+            "codex".to_string(),
+            ModelPrice { input_per_mtok: 1.25, output_per_mtok: 10.0 },
+        )]);
+        apply_usage(&mut run, &report, &prices);
+        assert_eq!(run.total_tokens, Some(12_345));
+        assert_eq!(run.input_tokens, None);
+        assert_eq!(run.cost_usd, None);
+        assert_eq!(run.tokens_used(), 12_345);
+    }
+}
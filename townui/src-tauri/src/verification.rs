@@ -0,0 +1,154 @@
+//! Runs a rig's verification pipeline (build / lint / test commands) in a
+//! crew worktree and collects structured outcomes. `run_shell` is shared with
+//! lifecycle hooks.
+
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::models::verification::{CheckOutcome, VerificationCheck};
+
+/// Lines of combined output kept per check.
+const OUTPUT_TAIL_LINES: usize = 60;
+
+fn shell_command(command: &str) -> Command {
+    #[cfg(target_os = "windows")]
+    {
+        let mut c = Command::new("cmd");
+        c.args(["/C", command]);
+        c
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        use std::os::unix::process::CommandExt;
+        let mut c = Command::new("sh");
+        c.args(["-lc", command]);
+        // Own process group so a timeout can kill the whole tree.
+        c.process_group(0);
+        c
+    }
+}
+
+pub fn tail_lines(text: &str, lines: usize) -> String {
+    let all: Vec<&str> = text.lines().collect();
+    all[all.len().saturating_sub(lines)..].join("\n")
+}
+
+/// Outcome of one shell command run to completion or timeout.
+pub struct ShellResult {
+    pub passed: bool,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+    /// Combined stdout and stderr.
+    pub output: String,
+}
+
+/// Run `command` through the shell in `cwd` with extra `env`, killing it once
+/// `timeout` elapses.
+pub fn run_shell(cwd: &str, command: &str, env: &[(String, String)], timeout: Duration) -> ShellResult {
+    let started = Instant::now();
+    let result = |passed: bool, exit_code: Option<i32>, timed_out: bool, output: String| ShellResult {
+        passed,
+        exit_code,
+        timed_out,
+        duration_ms: u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+        output,
+    };
+
+    let mut child = match shell_command(command)
+        .current_dir(cwd)
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return result(false, None, false, format!("Failed to run command: {e}")),
+    };
+
+    // Drain both pipes concurrently so a chatty command cannot block on a full pipe.
+    let output = Arc::new(Mutex::new(String::new()));
+    let readers: Vec<_> = [
+        child.stdout.take().map(|s| Box::new(s) as Box<dyn Read + Send>),
+        child.stderr.take().map(|s| Box::new(s) as Box<dyn Read + Send>),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|mut pipe| {
+        let output = Arc::clone(&output);
+        thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            while let Ok(n) = pipe.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+                output.lock().unwrap().push_str(&String::from_utf8_lossy(&buf[..n]));
+            }
+        })
+    })
+    .collect();
+
+    let mut timed_out = false;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if started.elapsed() >= timeout => {
+                timed_out = true;
+                crate::commands::workers::kill_process_tree(child.id());
+                let _ = child.kill();
+                break child.wait().ok();
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(100)),
+            Err(_) => break None,
+        }
+    };
+    for reader in readers {
+        let _ = reader.join();
+    }
+
+    let mut text = output.lock().unwrap().clone();
+    if timed_out {
+        text.push_str(&format!("\n[timed out after {}s]", timeout.as_secs()));
+    }
+    let exit_code = status.and_then(|s| s.code());
+    let passed = !timed_out && status.map(|s| s.success()).unwrap_or(false);
+    result(passed, exit_code, timed_out, text)
+}
+
+/// Run one check, killing it once `timeout` elapses.
+pub fn run_check(cwd: &str, check: &VerificationCheck, timeout: Duration) -> CheckOutcome {
+    let result = run_shell(cwd, &check.command, &[], timeout);
+    CheckOutcome {
+        name: check.name.clone(),
+        command: check.command.clone(),
+        required: check.required,
+        passed: result.passed,
+        exit_code: result.exit_code,
+        timed_out: result.timed_out,
+        duration_ms: result.duration_ms,
+        output_tail: tail_lines(&result.output, OUTPUT_TAIL_LINES),
+    }
+}
+
+/// Run checks in order. Later checks still run after a failure so the
+/// feedback covers everything that is broken.
+pub fn run_checks(
+    cwd: &str,
+    checks: &[VerificationCheck],
+    default_timeout_seconds: u64,
+    mut on_result: impl FnMut(&CheckOutcome),
+) -> Vec<CheckOutcome> {
+    checks
+        .iter()
+        .map(|check| {
+            let timeout = Duration::from_secs(check.timeout_seconds.unwrap_or(default_timeout_seconds));
+            let result = run_check(cwd, check, timeout);
+            on_result(&result);
+            result
+        })
+        .collect()
+}
@@ -0,0 +1,685 @@
+//! Minimal VT100/xterm screen emulator for PTY workers.
+//!
+//! Fed with the same output the UI terminal receives, it keeps the current
+//! grid, cursor and scrollback so a late viewer (or the supervisor) can see
+//! exactly what the agent is showing. It covers the sequences agent TUIs rely
+//! on: cursor movement, erase, scroll regions, insert/delete, SGR colours,
+//! the alternate screen, OSC titles and double-width characters. Everything
+//! else is parsed and ignored.
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+use unicode_width::UnicodeWidthChar;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Color {
+    Indexed { index: u8 },
+    Rgb { r: u8, g: u8, b: u8 },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Attrs {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub inverse: bool,
+}
+
+/// Right half of a double-width character; it renders as nothing.
+const WIDE_SPACER: char = '\0';
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cell {
+    ch: char,
+    attrs: Attrs,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            attrs: Attrs::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Cursor {
+    row: usize,
+    col: usize,
+    /// Set after writing the last column; the next printable wraps first.
+    pending_wrap: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ParseState {
+    Ground,
+    Escape,
+    /// `ESC (`, `ESC #` etc.: swallow one more character.
+    EscapeArg,
+    Csi,
+    Osc,
+    /// DCS / SOS / PM / APC payloads, ignored until ST.
+    Str,
+    /// Saw `ESC` inside an OSC or string; `\` terminates it.
+    StrEscape,
+}
+
+/// One run of identically styled text within a screen line.
+#[derive(Debug, Clone, Serialize)]
+pub struct StyledRun {
+    pub text: String,
+    #[serde(flatten)]
+    pub attrs: Attrs,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScreenSnapshot {
+    pub rows: usize,
+    pub cols: usize,
+    pub cursor_row: usize,
+    pub cursor_col: usize,
+    pub cursor_visible: bool,
+    pub alternate_screen: bool,
+    pub title: Option<String>,
+    /// Visible grid as plain text, one entry per row (trailing spaces trimmed).
+    pub lines: Vec<String>,
+    /// Visible grid as styled runs, one entry per row.
+    pub styled_lines: Vec<Vec<StyledRun>>,
+    /// Lines scrolled off the top of the primary screen, oldest first.
+    pub scrollback: Vec<String>,
+}
+
+pub struct Screen {
+    rows: usize,
+    cols: usize,
+    grid: Vec<Vec<Cell>>,
+    /// Primary grid and cursor while the alternate screen is active.
+    saved_primary: Option<(Vec<Vec<Cell>>, Cursor)>,
+    cursor: Cursor,
+    saved_cursor: Cursor,
+    pen: Attrs,
+    scroll_top: usize,
+    scroll_bottom: usize,
+    cursor_visible: bool,
+    autowrap: bool,
+    title: Option<String>,
+    scrollback: VecDeque<String>,
+    scrollback_limit: usize,
+    state: ParseState,
+    params: String,
+    private: bool,
+    osc: String,
+}
+
+const MAX_OSC_LEN: usize = 4096;
+
+fn blank_line(cols: usize) -> Vec<Cell> {
+    vec![Cell::default(); cols]
+}
+
+fn line_text(line: &[Cell]) -> String {
+    let text: String = line.iter().map(|c| c.ch).filter(|c| *c != WIDE_SPACER).collect();
+    text.trim_end().to_string()
+}
+
+impl Screen {
+    pub fn new(rows: u16, cols: u16, scrollback_limit: usize) -> Self {
+        let (rows, cols) = (rows.max(1) as usize, cols.max(1) as usize);
+        Self {
+            rows,
+            cols,
+            grid: vec![blank_line(cols); rows],
+            saved_primary: None,
+            cursor: Cursor::default(),
+            saved_cursor: Cursor::default(),
+            pen: Attrs::default(),
+            scroll_top: 0,
+            scroll_bottom: rows - 1,
+            cursor_visible: true,
+            autowrap: true,
+            title: None,
+            scrollback: VecDeque::new(),
+            scrollback_limit,
+            state: ParseState::Ground,
+            params: String::new(),
+            private: false,
+            osc: String::new(),
+        }
+    }
+
+    pub fn process(&mut self, data: &str) {
+        for ch in data.chars() {
+            self.advance(ch);
+        }
+    }
+
+    /// Visible screen as plain text (rows joined by newlines, trailing blanks trimmed).
+    pub fn text(&self) -> String {
+        let lines: Vec<String> = self.grid.iter().map(|l| line_text(l)).collect();
+        lines.join("\n").trim_end().to_string()
+    }
+
+    pub fn snapshot(&self, include_scrollback: bool) -> ScreenSnapshot {
+        ScreenSnapshot {
+            rows: self.rows,
+            cols: self.cols,
+            cursor_row: self.cursor.row,
+            cursor_col: self.cursor.col,
+            cursor_visible: self.cursor_visible,
+            alternate_screen: self.saved_primary.is_some(),
+            title: self.title.clone(),
+            lines: self.grid.iter().map(|l| line_text(l)).collect(),
+            styled_lines: self.grid.iter().map(|l| styled_runs(l)).collect(),
+            scrollback: if include_scrollback {
+                self.scrollback.iter().cloned().collect()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    pub fn resize(&mut self, rows: u16, cols: u16) {
+        let (rows, cols) = (rows.max(1) as usize, cols.max(1) as usize);
+        if rows == self.rows && cols == self.cols {
+            return;
+        }
+        // Keep the cursor line on screen when shrinking by pushing top lines out.
+        let overflow = (self.cursor.row + 1).saturating_sub(rows);
+        for _ in 0..overflow {
+            let line = self.grid.remove(0);
+            if self.saved_primary.is_none() {
+                self.push_scrollback(&line);
+            }
+        }
+        self.cursor.row -= overflow;
+        self.grid.resize(rows, blank_line(cols));
+        for line in self.grid.iter_mut() {
+            line.resize(cols, Cell::default());
+        }
+        if let Some((primary, cursor)) = self.saved_primary.as_mut() {
+            primary.resize(rows, blank_line(cols));
+            for line in primary.iter_mut() {
+                line.resize(cols, Cell::default());
+            }
+            cursor.row = cursor.row.min(rows - 1);
+            cursor.col = cursor.col.min(cols - 1);
+        }
+        self.saved_cursor.row = self.saved_cursor.row.saturating_sub(overflow).min(rows - 1);
+        self.saved_cursor.col = self.saved_cursor.col.min(cols - 1);
+        self.rows = rows;
+        self.cols = cols;
+        self.scroll_top = 0;
+        self.scroll_bottom = rows - 1;
+        self.cursor.row = self.cursor.row.min(rows - 1);
+        self.cursor.col = self.cursor.col.min(cols - 1);
+        self.cursor.pending_wrap = false;
+    }
+
+    // ── Parser ──
+
+    fn advance(&mut self, ch: char) {
+        match self.state {
+            ParseState::Ground => self.ground(ch),
+            ParseState::Escape => self.escape(ch),
+            ParseState::EscapeArg => self.state = ParseState::Ground,
+            ParseState::Csi => self.csi(ch),
+            ParseState::Osc | ParseState::Str => match ch {
+                '\x07' => self.end_string(),
+                '\x1b' => self.state = ParseState::StrEscape,
+                _ if self.state == ParseState::Osc && self.osc.len() < MAX_OSC_LEN => self.osc.push(ch),
+                _ => {}
+            },
+            ParseState::StrEscape => {
+                if ch == '\\' {
+                    self.end_string();
+                } else {
+                    self.osc.clear();
+                    self.state = ParseState::Escape;
+                    self.escape(ch);
+                }
+            }
+        }
+    }
+
+    fn ground(&mut self, ch: char) {
+        match ch {
+            '\x1b' => self.state = ParseState::Escape,
+            '\r' => {
+                self.cursor.col = 0;
+                self.cursor.pending_wrap = false;
+            }
+            '\n' | '\x0b' | '\x0c' => self.linefeed(),
+            '\x08' => {
+                self.cursor.col = self.cursor.col.saturating_sub(1);
+                self.cursor.pending_wrap = false;
+            }
+            '\t' => {
+                self.cursor.col = ((self.cursor.col / 8) + 1) * 8;
+                self.cursor.col = self.cursor.col.min(self.cols - 1);
+                self.cursor.pending_wrap = false;
+            }
+            c if (c as u32) < 0x20 || c == '\x7f' => {}
+            c => self.put_char(c),
+        }
+    }
+
+    fn escape(&mut self, ch: char) {
+        self.state = ParseState::Ground;
+        match ch {
+            '[' => {
+                self.params.clear();
+                self.private = false;
+                self.state = ParseState::Csi;
+            }
+            ']' => {
+                self.osc.clear();
+                self.state = ParseState::Osc;
+            }
+            'P' | 'X' | '^' | '_' => self.state = ParseState::Str,
+            '(' | ')' | '*' | '+' | '#' | '%' => self.state = ParseState::EscapeArg,
+            '7' => self.saved_cursor = self.cursor,
+            '8' => self.restore_cursor(),
+            'D' => self.linefeed(),
+            'E' => {
+                self.cursor.col = 0;
+                self.linefeed();
+            }
+            'M' => self.reverse_index(),
+            'c' => {
+                let limit = self.scrollback_limit;
+                let scrollback = std::mem::take(&mut self.scrollback);
+                *self = Screen::new(self.rows as u16, self.cols as u16, limit);
+                self.scrollback = scrollback;
+            }
+            _ => {}
+        }
+    }
+
+    fn end_string(&mut self) {
+        if self.state == ParseState::Osc || !self.osc.is_empty() {
+            let osc = std::mem::take(&mut self.osc);
+            if let Some((code, text)) = osc.split_once(';') {
+                if code == "0" || code == "2" {
+                    self.title = Some(text.to_string());
+                }
+            }
+        }
+        self.state = ParseState::Ground;
+    }
+
+    fn csi(&mut self, ch: char) {
+        match ch {
+            '0'..='9' | ';' | ':' => self.params.push(ch),
+            '?' | '>' | '=' | '<' => self.private = true,
+            ' '..='/' => {}
+            '\x1b' => self.state = ParseState::Escape,
+            '@'..='~' => {
+                self.state = ParseState::Ground;
+                self.dispatch_csi(ch);
+            }
+            _ => {}
+        }
+    }
+
+    fn param_list(&self) -> Vec<usize> {
+        self.params
+            .split([';', ':'])
+            .map(|p| p.parse().unwrap_or(0))
+            .collect()
+    }
+
+    fn dispatch_csi(&mut self, final_char: char) {
+        let params = self.param_list();
+        // Count-style parameter: missing or zero means 1.
+        let n = |i: usize| params.get(i).copied().filter(|v| *v > 0).unwrap_or(1);
+        let raw = |i: usize| params.get(i).copied().unwrap_or(0);
+
+        if self.private {
+            match final_char {
+                'h' => params.iter().for_each(|m| self.set_private_mode(*m, true)),
+                'l' => params.iter().for_each(|m| self.set_private_mode(*m, false)),
+                _ => {}
+            }
+            return;
+        }
+
+        self.cursor.pending_wrap = false;
+        let (row, col) = (self.cursor.row, self.cursor.col);
+        match final_char {
+            '@' => {
+                let line = &mut self.grid[row];
+                for _ in 0..n(0).min(self.cols - col) {
+                    line.insert(col, Cell::default());
+                    line.pop();
+                }
+            }
+            'A' => self.cursor.row = row.saturating_sub(n(0)).max(self.top_bound(row)),
+            'B' | 'e' => self.cursor.row = (row + n(0)).min(self.bottom_bound(row)),
+            'C' | 'a' => self.cursor.col = (col + n(0)).min(self.cols - 1),
+            'D' => self.cursor.col = col.saturating_sub(n(0)),
+            'E' => {
+                self.cursor.row = (row + n(0)).min(self.bottom_bound(row));
+                self.cursor.col = 0;
+            }
+            'F' => {
+                self.cursor.row = row.saturating_sub(n(0)).max(self.top_bound(row));
+                self.cursor.col = 0;
+            }
+            'G' | '`' => self.cursor.col = (n(0) - 1).min(self.cols - 1),
+            'H' | 'f' => {
+                self.cursor.row = (n(0) - 1).min(self.rows - 1);
+                self.cursor.col = (n(1) - 1).min(self.cols - 1);
+            }
+            'd' => self.cursor.row = (n(0) - 1).min(self.rows - 1),
+            'J' => self.erase_display(raw(0)),
+            'K' => self.erase_line(raw(0)),
+            'L' if (self.scroll_top..=self.scroll_bottom).contains(&row) => {
+                for _ in 0..n(0).min(self.scroll_bottom - row + 1) {
+                    self.grid.remove(self.scroll_bottom);
+                    self.grid.insert(row, blank_line(self.cols));
+                }
+            }
+            'M' if (self.scroll_top..=self.scroll_bottom).contains(&row) => {
+                for _ in 0..n(0).min(self.scroll_bottom - row + 1) {
+                    self.grid.remove(row);
+                    self.grid.insert(self.scroll_bottom, blank_line(self.cols));
+                }
+            }
+            'P' => {
+                let line = &mut self.grid[row];
+                for _ in 0..n(0).min(self.cols - col) {
+                    line.remove(col);
+                    line.push(Cell::default());
+                }
+            }
+            'X' => {
+                let end = (col + n(0)).min(self.cols);
+                self.grid[row][col..end].fill(Cell::default());
+            }
+            'S' => self.scroll_up(n(0)),
+            'T' => self.scroll_down(n(0)),
+            'm' => self.set_graphics(&params),
+            'r' => {
+                let top = n(0) - 1;
+                let bottom = params.get(1).copied().filter(|v| *v > 0).unwrap_or(self.rows) - 1;
+                if top < bottom && bottom < self.rows {
+                    self.scroll_top = top;
+                    self.scroll_bottom = bottom;
+                    self.cursor.row = 0;
+                    self.cursor.col = 0;
+                }
+            }
+            's' => self.saved_cursor = self.cursor,
+            'u' => self.restore_cursor(),
+            _ => {}
+        }
+    }
+
+    fn set_private_mode(&mut self, mode: usize, on: bool) {
+        match mode {
+            7 => self.autowrap = on,
+            25 => self.cursor_visible = on,
+            47 | 1047 | 1049 => {
+                if on && self.saved_primary.is_none() {
+                    let primary = std::mem::replace(&mut self.grid, vec![blank_line(self.cols); self.rows]);
+                    self.saved_primary = Some((primary, self.cursor));
+                } else if !on {
+                    if let Some((primary, cursor)) = self.saved_primary.take() {
+                        self.grid = primary;
+                        if mode == 1049 {
+                            self.cursor = cursor;
+                            self.clamp_cursor();
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn set_graphics(&mut self, params: &[usize]) {
+        let mut i = 0;
+        while i < params.len() {
+            match params[i] {
+                0 => self.pen = Attrs::default(),
+                1 => self.pen.bold = true,
+                3 => self.pen.italic = true,
+                4 => self.pen.underline = true,
+                7 => self.pen.inverse = true,
+                22 => self.pen.bold = false,
+                23 => self.pen.italic = false,
+                24 => self.pen.underline = false,
+                27 => self.pen.inverse = false,
+                v @ 30..=37 => self.pen.fg = Some(Color::Indexed { index: (v - 30) as u8 }),
+                v @ 40..=47 => self.pen.bg = Some(Color::Indexed { index: (v - 40) as u8 }),
+                v @ 90..=97 => self.pen.fg = Some(Color::Indexed { index: (v - 90 + 8) as u8 }),
+                v @ 100..=107 => self.pen.bg = Some(Color::Indexed { index: (v - 100 + 8) as u8 }),
+                39 => self.pen.fg = None,
+                49 => self.pen.bg = None,
+                v @ (38 | 48) => {
+                    let color = match params.get(i + 1) {
+                        Some(5) => {
+                            let c = params.get(i + 2).map(|n| Color::Indexed { index: *n as u8 });
+                            i += 2;
+                            c
+                        }
+                        Some(2) => {
+                            let c = (params.len() > i + 4).then(|| Color::Rgb {
+                                r: params[i + 2] as u8,
+                                g: params[i + 3] as u8,
+                                b: params[i + 4] as u8,
+                            });
+                            i += 4;
+                            c
+                        }
+                        _ => None,
+                    };
+                    if v == 38 {
+                        self.pen.fg = color;
+                    } else {
+                        self.pen.bg = color;
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    // ── Screen operations ──
+
+    fn restore_cursor(&mut self) {
+        self.cursor = self.saved_cursor;
+        self.clamp_cursor();
+    }
+
+    fn clamp_cursor(&mut self) {
+        self.cursor.row = self.cursor.row.min(self.rows - 1);
+        self.cursor.col = self.cursor.col.min(self.cols - 1);
+    }
+
+    fn top_bound(&self, row: usize) -> usize {
+        if row >= self.scroll_top { self.scroll_top } else { 0 }
+    }
+
+    fn bottom_bound(&self, row: usize) -> usize {
+        if row <= self.scroll_bottom { self.scroll_bottom } else { self.rows - 1 }
+    }
+
+    fn put_char(&mut self, ch: char) {
+        let width = ch.width().unwrap_or(0);
+        // Combining marks and other zero-width characters do not take a cell.
+        if width == 0 {
+            return;
+        }
+        let width = width.min(self.cols);
+        if self.cursor.pending_wrap || self.cursor.col + width > self.cols {
+            if self.autowrap {
+                self.cursor.col = 0;
+                self.linefeed();
+            } else {
+                self.cursor.col = self.cols - width;
+            }
+            self.cursor.pending_wrap = false;
+        }
+        let (row, col) = (self.cursor.row, self.cursor.col);
+        // Overwriting half of a wide character blanks its other half.
+        if self.grid[row][col].ch == WIDE_SPACER && col > 0 {
+            self.grid[row][col - 1] = Cell::default();
+        }
+        if col + width < self.cols && self.grid[row][col + width].ch == WIDE_SPACER {
+            self.grid[row][col + width] = Cell::default();
+        }
+        self.grid[row][col] = Cell { ch, attrs: self.pen };
+        if width == 2 {
+            self.grid[row][col + 1] = Cell { ch: WIDE_SPACER, attrs: self.pen };
+        }
+        if col + width >= self.cols {
+            self.cursor.col = self.cols - 1;
+            self.cursor.pending_wrap = true;
+        } else {
+            self.cursor.col += width;
+        }
+    }
+
+    fn linefeed(&mut self) {
+        self.cursor.pending_wrap = false;
+        if self.cursor.row == self.scroll_bottom {
+            self.scroll_up(1);
+        } else if self.cursor.row + 1 < self.rows {
+            self.cursor.row += 1;
+        }
+    }
+
+    fn reverse_index(&mut self) {
+        self.cursor.pending_wrap = false;
+        if self.cursor.row == self.scroll_top {
+            self.scroll_down(1);
+        } else {
+            self.cursor.row = self.cursor.row.saturating_sub(1);
+        }
+    }
+
+    fn push_scrollback(&mut self, line: &[Cell]) {
+        if self.scrollback_limit == 0 {
+            return;
+        }
+        self.scrollback.push_back(line_text(line));
+        while self.scrollback.len() > self.scrollback_limit {
+            self.scrollback.pop_front();
+        }
+    }
+
+    fn scroll_up(&mut self, n: usize) {
+        let region = self.scroll_bottom - self.scroll_top + 1;
+        for _ in 0..n.min(region) {
+            let line = self.grid.remove(self.scroll_top);
+            if self.scroll_top == 0 && self.saved_primary.is_none() {
+                self.push_scrollback(&line);
+            }
+            self.grid.insert(self.scroll_bottom, blank_line(self.cols));
+        }
+    }
+
+    fn scroll_down(&mut self, n: usize) {
+        let region = self.scroll_bottom - self.scroll_top + 1;
+        for _ in 0..n.min(region) {
+            self.grid.remove(self.scroll_bottom);
+            self.grid.insert(self.scroll_top, blank_line(self.cols));
+        }
+    }
+
+    fn erase_display(&mut self, mode: usize) {
+        let (row, col) = (self.cursor.row, self.cursor.col);
+        match mode {
+            0 => {
+                self.grid[row][col..].fill(Cell::default());
+                for line in self.grid[row + 1..].iter_mut() {
+                    line.fill(Cell::default());
+                }
+            }
+            1 => {
+                self.grid[row][..=col].fill(Cell::default());
+                for line in self.grid[..row].iter_mut() {
+                    line.fill(Cell::default());
+                }
+            }
+            2 | 3 => {
+                for line in self.grid.iter_mut() {
+                    line.fill(Cell::default());
+                }
+                if mode == 3 {
+                    self.scrollback.clear();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn erase_line(&mut self, mode: usize) {
+        let (row, col) = (self.cursor.row, self.cursor.col);
+        match mode {
+            0 => self.grid[row][col..].fill(Cell::default()),
+            1 => self.grid[row][..=col].fill(Cell::default()),
+            2 => self.grid[row].fill(Cell::default()),
+            _ => {}
+        }
+    }
+}
+
+fn styled_runs(line: &[Cell]) -> Vec<StyledRun> {
+    let mut runs: Vec<StyledRun> = Vec::new();
+    for cell in line.iter().filter(|c| c.ch != WIDE_SPACER) {
+        match runs.last_mut() {
+            Some(run) if run.attrs == cell.attrs => run.text.push(cell.ch),
+            _ => runs.push(StyledRun {
+                text: cell.ch.to_string(),
+                attrs: cell.attrs,
+            }),
+        }
+    }
+    // Drop trailing unstyled blanks so empty rows serialize compactly.
+    if let Some(last) = runs.last_mut() {
+        if last.attrs == Attrs::default() {
+            let trimmed = last.text.trim_end().len();
+            last.text.truncate(trimmed);
+            if last.text.is_empty() {
+                runs.pop();
+            }
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_after_shrink_stays_on_screen() {
+        let mut screen = Screen::new(40, 80, 100);
+        screen.process("\x1b[31;10H\x1b7");
+        screen.resize(24, 80);
+        screen.process("\x1b8x");
+        assert!(screen.cursor.row < 24);
+        screen.process("\x1b[31;10H\x1b[s");
+        screen.resize(24, 5);
+        screen.process("\x1b[uy");
+        assert!(screen.cursor.row < 24 && screen.cursor.col < 5);
+    }
+
+    #[test]
+    fn wide_characters_take_two_cells() {
+        let mut screen = Screen::new(2, 4, 0);
+        screen.process("a漢b");
+        assert_eq!(screen.text(), "a漢b");
+        assert_eq!(screen.cursor.col, 3);
+        screen.process("字");
+        assert_eq!(screen.snapshot(false).lines, vec!["a漢b", "字"]);
+    }
+}
@@ -5,7 +5,7 @@ import { t } from "../lib/i18n";
 interface CrewCreateDialogProps {
   branches: string[];
   existingCrewNames: string[];
-  onCreated: (name: string, baseBranch: string, pushToRemote: boolean) => Promise<void>;
+  onCreated: (name: string, baseBranch: string, pushToRemote: boolean, preset?: string) => Promise<void>;
   onClose: () => void;
 }
 
@@ -53,7 +53,7 @@ export default function CrewCreateDialog({
         setProgress(
           `Creating ${preset.name}... (${completed + 1}/${selected.length})`,
         );
-        await onCreated(preset.name, baseBranch, pushToRemote);
+        await onCreated(preset.name, baseBranch, pushToRemote, preset.key);
         completed++;
       }
       onClose();
@@ -230,8 +230,8 @@ export default function RigDetails({
         <CrewCreateDialog
           branches={branches}
           existingCrewNames={crews.map((c) => c.name)}
-          onCreated={async (name, baseBranch, pushToRemote) => {
-            await addCrew(name, baseBranch, pushToRemote);
+          onCreated={async (name, baseBranch, pushToRemote, preset) => {
+            await addCrew(name, baseBranch, pushToRemote, preset);
           }}
           onClose={() => setShowCrewCreate(false)}
         />
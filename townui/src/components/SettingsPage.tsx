@@ -2,6 +2,8 @@ import { useState, useEffect } from "react";
 import {
   AiInboxStatus,
   AppSettings,
+  PrimingDelivery,
+  PrimingProfile,
   getAiInboxStatus,
   getSeedInfo,
   seedWorkflowTemplates,
@@ -110,6 +112,45 @@ export default function SettingsPage({
     });
   };
 
+  const primingProfiles = current.priming_profiles ?? [];
+
+  const updatePrimingProfile = (id: string, patch: Partial<PrimingProfile>) => {
+    setDraft({
+      ...current,
+      priming_profiles: primingProfiles.map((p) =>
+        p.id === id ? { ...p, ...patch } : p,
+      ),
+    });
+  };
+
+  const removePrimingProfile = (id: string) => {
+    setDraft({
+      ...current,
+      priming_profiles: primingProfiles.filter((p) => p.id !== id),
+    });
+  };
+
+  const addPrimingProfile = () => {
+    setDraft({
+      ...current,
+      priming_profiles: [
+        ...primingProfiles,
+        {
+          id: crypto.randomUUID(),
+          name: `Profile ${primingProfiles.length + 1}`,
+          role: null,
+          crew_preset: null,
+          rig_id: null,
+          agent_type: null,
+          template: "",
+          delivery: "auto",
+          ready_pattern: null,
+          enabled: true,
+        },
+      ],
+    });
+  };
+
   const handleSave = async () => {
     if (!current) return;
     setError(null);
@@ -907,7 +948,7 @@ export default function SettingsPage({
             {/* Priming delay */}
             <div className="space-y-1">
               <label className="text-xs text-town-text-muted">
-                Priming delay (ms after spawn)
+                Priming delay (ms after spawn, agents without a ready prompt)
               </label>
               <input
                 type="number"
@@ -925,6 +966,27 @@ export default function SettingsPage({
               />
             </div>
 
+            {/* Ready pattern timeout */}
+            <div className="space-y-1">
+              <label className="text-xs text-town-text-muted">
+                Wait for agent ready prompt (ms, then inject anyway)
+              </label>
+              <input
+                type="number"
+                min={0}
+                max={120000}
+                step={1000}
+                value={current.priming_ready_timeout_ms ?? 20000}
+                onChange={(e) =>
+                  setDraft({
+                    ...current,
+                    priming_ready_timeout_ms: parseInt(e.target.value, 10) || 20000,
+                  })
+                }
+                className="input-base w-32"
+              />
+            </div>
+
             {/* Priming template */}
             <div className="space-y-1">
               <label className="text-xs text-town-text-muted">
@@ -947,6 +1009,145 @@ export default function SettingsPage({
                 crew.name{"}}"}, {"{{"}task.title{"}}"}
               </p>
             </div>
+
+            {/* Priming profiles */}
+            <div className="space-y-2">
+              <div className="flex items-center justify-between">
+                <label className="text-xs text-town-text-muted">
+                  Profiles (blank keys match any; the most specific wins)
+                </label>
+                <button
+                  onClick={addPrimingProfile}
+                  className="btn-ghost text-xs flex items-center gap-1.5"
+                >
+                  <svg
+                    width="12"
+                    height="12"
+                    viewBox="0 0 24 24"
+                    fill="none"
+                    stroke="currentColor"
+                    strokeWidth="2.5"
+                  >
+                    <line x1="12" y1="5" x2="12" y2="19" />
+                    <line x1="5" y1="12" x2="19" y2="12" />
+                  </svg>
+                  Add Profile
+                </button>
+              </div>
+              {primingProfiles.length === 0 ? (
+                <p className="text-[11px] text-town-text-faint">
+                  No profiles; every worker gets the template above.
+                </p>
+              ) : (
+                primingProfiles.map((profile) => (
+                  <div
+                    key={profile.id}
+                    className="group rounded-md border border-town-border p-3 space-y-2"
+                  >
+                    <div className="flex items-center gap-2">
+                      <input
+                        type="checkbox"
+                        checked={profile.enabled}
+                        onChange={(e) =>
+                          updatePrimingProfile(profile.id, {
+                            enabled: e.target.checked,
+                          })
+                        }
+                        title="Enabled"
+                      />
+                      <input
+                        type="text"
+                        value={profile.name}
+                        onChange={(e) =>
+                          updatePrimingProfile(profile.id, {
+                            name: e.target.value,
+                          })
+                        }
+                        className="flex-1 input-base text-xs"
+                        placeholder="name"
+                      />
+                      <select
+                        value={profile.delivery}
+                        onChange={(e) =>
+                          updatePrimingProfile(profile.id, {
+                            delivery: e.target.value as PrimingDelivery,
+                          })
+                        }
+                        className="select-base w-36 text-xs"
+                      >
+                        <option value="auto">Auto</option>
+                        <option value="system_prompt">System prompt</option>
+                        <option value="context_file">Context file</option>
+                        <option value="pty_inject">Terminal</option>
+                        <option value="off">Off</option>
+                      </select>
+                      <button
+                        onClick={() => removePrimingProfile(profile.id)}
+                        className="p-1.5 rounded-md text-town-text-faint hover:text-town-danger hover:bg-town-danger/10 transition-all duration-200 opacity-0 group-hover:opacity-100"
+                        title="Remove profile"
+                      >
+                        <svg
+                          width="14"
+                          height="14"
+                          viewBox="0 0 24 24"
+                          fill="none"
+                          stroke="currentColor"
+                          strokeWidth="2"
+                        >
+                          <polyline points="3 6 5 6 21 6" />
+                          <path d="M19 6v14a2 2 0 0 1-2 2H7a2 2 0 0 1-2-2V6m3 0V4a2 2 0 0 1 2-2h4a2 2 0 0 1 2 2v2" />
+                        </svg>
+                      </button>
+                    </div>
+                    <div className="grid grid-cols-4 gap-2">
+                      {(
+                        [
+                          ["role", "role"],
+                          ["crew_preset", "crew preset"],
+                          ["rig_id", "rig id"],
+                          ["agent_type", "agent"],
+                        ] as const
+                      ).map(([field, placeholder]) => (
+                        <input
+                          key={field}
+                          type="text"
+                          value={profile[field] ?? ""}
+                          onChange={(e) =>
+                            updatePrimingProfile(profile.id, {
+                              [field]: e.target.value.trim() || null,
+                            } as Partial<PrimingProfile>)
+                          }
+                          className="input-base font-mono text-xs"
+                          placeholder={placeholder}
+                        />
+                      ))}
+                    </div>
+                    <textarea
+                      rows={3}
+                      value={profile.template}
+                      placeholder="Template (blank uses the one above)"
+                      onChange={(e) =>
+                        updatePrimingProfile(profile.id, {
+                          template: e.target.value,
+                        })
+                      }
+                      className="input-base w-full resize-none font-mono text-[11px]"
+                    />
+                    <input
+                      type="text"
+                      value={profile.ready_pattern ?? ""}
+                      onChange={(e) =>
+                        updatePrimingProfile(profile.id, {
+                          ready_pattern: e.target.value || null,
+                        })
+                      }
+                      className="input-base w-full font-mono text-xs"
+                      placeholder="Ready pattern regex (blank for the agent's default)"
+                    />
+                  </div>
+                ))
+              )}
+            </div>
           </section>
 
           {/* ── Propulsion & Witness ───────────────────────────── */}
@@ -46,9 +46,6 @@ export default function TaskExecuteDialog({
       })
       .catch(() => {});
 
-    listTemplates()
-      .then(setTemplates)
-      .catch(() => {});
     listCrews(task.rig_id)
       .then((data) => {
         setCrews(data);
@@ -59,6 +56,13 @@ export default function TaskExecuteDialog({
       .catch(() => {});
   }, [task.rig_id]);
 
+  // Rig and crew templates can override builtins of the same name.
+  useEffect(() => {
+    listTemplates(task.rig_id, crewId || undefined)
+      .then(setTemplates)
+      .catch(() => {});
+  }, [task.rig_id, crewId]);
+
   const handleExecute = async () => {
     if (!crewId) return;
     setExecuting(true);
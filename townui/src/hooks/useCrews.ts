@@ -40,11 +40,11 @@ export function useCrews(rigId: string | null) {
     };
   }, [refresh]);
 
-  const addCrew = useCallback(async (name: string, baseBranch: string, pushToRemote: boolean = false) => {
+  const addCrew = useCallback(async (name: string, baseBranch: string, pushToRemote: boolean = false, preset?: string) => {
     if (!rigId) return;
     try {
       setError(null);
-      const crew = await createCrew(rigId, name, baseBranch, pushToRemote);
+      const crew = await createCrew(rigId, name, baseBranch, pushToRemote, preset);
       setCrews((prev) => [...prev, crew]);
       return crew;
     } catch (e) {
@@ -11,6 +11,100 @@ export interface RigInfo {
   git_branch: string | null;
   git_status: string | null;
   is_git_repo: boolean;
+  settings: RigSettings;
+}
+
+export interface RigSettings {
+  budget_usd: number | null;
+  daily_budget_usd: number | null;
+  max_run_seconds: number | null;
+  max_run_tokens: number | null;
+  limits: ResourceLimits;
+  sandbox: SandboxPolicy;
+  retry_policy: RetryPolicy | null;
+  verification: VerificationCheck[];
+  review: ReviewPolicy | null;
+  lifecycle_hooks: LifecycleHook[];
+  max_concurrent_workers: number | null;
+  scheduler_weight: number | null;
+  busy_crew_policy: BusyCrewPolicy;
+  checkpoint_interval_minutes: number | null;
+  conventions_file: string | null;
+}
+
+export type BusyCrewPolicy = "queue" | "idle_crew" | "polecat";
+
+export type LifecycleEvent = "pre_spawn" | "post_exit" | "pre_commit" | "post_merge" | "on_task_done";
+
+export interface LifecycleHook {
+  event: LifecycleEvent;
+  command: string;
+  timeout_seconds: number | null;
+  required: boolean;
+}
+
+export type ReviewMode = "rules" | "agent";
+export type ReviewVerdict = "pending" | "pass" | "fail" | "inconclusive";
+
+export interface ReviewPolicy {
+  mode: ReviewMode;
+  max_revisions: number;
+  reviewer_agent: string | null;
+  forbidden_patterns: string[];
+}
+
+export interface ReviewRecord {
+  mode: ReviewMode;
+  verdict: ReviewVerdict;
+  feedback: string;
+  reviewer_worker_id: string | null;
+  started_at: string;
+  finished_at: string | null;
+}
+
+export interface VerificationCheck {
+  name: string;
+  command: string;
+  timeout_seconds: number | null;
+  required: boolean;
+}
+
+export interface CheckOutcome {
+  name: string;
+  command: string;
+  required: boolean;
+  passed: boolean;
+  exit_code: number | null;
+  timed_out: boolean;
+  duration_ms: number;
+  output_tail: string;
+}
+
+export interface RetryPolicy {
+  max_attempts: number;
+  backoff_seconds: number;
+  backoff_multiplier: number;
+  max_backoff_seconds: number;
+  fallback_agents: string[];
+}
+
+export interface ResourceLimits {
+  memory_mb: number | null;
+  cpu_percent: number | null;
+  max_pids: number | null;
+  cpu_seconds: number | null;
+  max_open_files: number | null;
+}
+
+export type SandboxKind = "none" | "bubblewrap" | "landlock";
+
+export interface SandboxPolicy {
+  kind: SandboxKind;
+  read_only_root: boolean;
+  allow_git_writes: boolean;
+  network: boolean;
+  writable_paths: string[];
+  readable_paths: string[];
 }
 
 export interface TerminalCommandResult {
@@ -37,6 +131,13 @@ export async function deleteRig(id: string): Promise<void> {
   return invoke<void>("delete_rig", { id });
 }
 
+export async function updateRigSettings(
+  id: string,
+  settings: RigSettings,
+): Promise<RigInfo> {
+  return invoke<RigInfo>("update_rig_settings", { id, settings });
+}
+
 // ── Crew types ──
 
 export interface CrewInfo {
@@ -50,6 +151,16 @@ export interface CrewInfo {
   git_branch: string | null;
   git_status: string | null;
   changed_files: number;
+  limits: ResourceLimits;
+  sandbox: SandboxPolicy | null;
+  lease: CrewLease | null;
+  preset: string | null;
+}
+
+export interface CrewLease {
+  worker_id: string;
+  acquired_at: string;
+  expires_at: string;
 }
 
 export async function listCrews(rigId: string): Promise<CrewInfo[]> {
@@ -61,12 +172,14 @@ export async function createCrew(
   name: string,
   baseBranch: string,
   pushToRemote: boolean = false,
+  preset?: string,
 ): Promise<CrewInfo> {
   return invoke<CrewInfo>("create_crew", {
     rigId,
     name,
     baseBranch,
     pushToRemote,
+    preset: preset ?? null,
   });
 }
 
@@ -78,6 +191,14 @@ export async function deleteCrew(id: string): Promise<void> {
   return invoke<void>("delete_crew", { id });
 }
 
+export async function updateCrewIsolation(
+  id: string,
+  limits: ResourceLimits,
+  sandbox: SandboxPolicy | null,
+): Promise<CrewInfo> {
+  return invoke<CrewInfo>("update_crew_isolation", { id, limits, sandbox });
+}
+
 export async function listBranches(rigId: string): Promise<string[]> {
   return invoke<string[]>("list_branches", { rigId });
 }
@@ -128,6 +249,7 @@ export interface TaskItem {
   completed_at: string | null;
   created_at: string;
   updated_at: string;
+  retry_policy: RetryPolicy | null;
 }
 
 export interface TaskUpdate {
@@ -144,6 +266,7 @@ export interface TaskUpdate {
   hook_id?: string | null;
   blocked_reason?: string | null;
   outcome?: string | null;
+  retry_policy?: RetryPolicy | null;
 }
 
 export async function listTasks(rigId: string): Promise<TaskItem[]> {
@@ -383,6 +506,7 @@ export interface ConvoyInfo {
   owner_actor_id: string | null;
   merge_strategy: MergeStrategy;
   land_notes: string | null;
+  budget_usd: number | null;
 }
 
 export async function listConvoys(): Promise<ConvoyInfo[]> {
@@ -415,6 +539,13 @@ export async function updateConvoyStatus(
   return invoke<ConvoyInfo>("update_convoy_status", { convoyId, status });
 }
 
+export async function setConvoyBudget(
+  convoyId: string,
+  budgetUsd: number | null,
+): Promise<ConvoyInfo> {
+  return invoke<ConvoyInfo>("set_convoy_budget", { convoyId, budgetUsd });
+}
+
 // ── Actor types ──
 
 export interface ActorInfo {
@@ -494,11 +625,14 @@ export interface WorkerInfo {
   startup_primed: boolean;
   task_label: string | null;
   crew_name: string | null;
+  session: string | null;
+  waiting_input: PromptMatch | null;
+  liveness: LivenessState;
 }
 
 export interface LogEntry {
   timestamp: string;
-  stream: "stdout" | "stderr";
+  stream: "stdout" | "stderr" | "system" | "hook";
   line: string;
 }
 
@@ -530,8 +664,20 @@ export async function listWorkers(rigId: string): Promise<WorkerInfo[]> {
   return invoke<WorkerInfo[]>("list_workers", { rigId });
 }
 
-export async function getWorkerLogs(id: string): Promise<LogEntry[]> {
-  return invoke<LogEntry[]>("get_worker_logs", { id });
+export async function getWorkerLogs(
+  id: string,
+  offset?: number,
+  limit?: number,
+): Promise<LogEntry[]> {
+  return invoke<LogEntry[]>("get_worker_logs", {
+    id,
+    offset: offset ?? null,
+    limit: limit ?? null,
+  });
+}
+
+export async function countWorkerLogs(id: string): Promise<number> {
+  return invoke<number>("count_worker_logs", { id });
 }
 
 export async function writeToWorker(id: string, input: string): Promise<void> {
@@ -544,9 +690,101 @@ export async function resizeWorkerPty(
 ): Promise<void> {
   return invoke("resize_worker_pty", { id, rows, cols });
 }
+
+export interface WorkerSessionInfo {
+  session: string;
+  worker_id: string | null;
+  pane_pid: number | null;
+  attached_clients: number;
+}
+
+export async function getWorkerScrollback(id: string): Promise<string> {
+  return invoke<string>("get_worker_scrollback", { id });
+}
+
+export async function listWorkerSessions(): Promise<WorkerSessionInfo[]> {
+  return invoke<WorkerSessionInfo[]>("list_worker_sessions");
+}
+
+export type TerminalColor =
+  | { kind: "indexed"; index: number }
+  | { kind: "rgb"; r: number; g: number; b: number };
+
+export interface StyledRun {
+  text: string;
+  fg: TerminalColor | null;
+  bg: TerminalColor | null;
+  bold: boolean;
+  italic: boolean;
+  underline: boolean;
+  inverse: boolean;
+}
+
+export interface ScreenSnapshot {
+  rows: number;
+  cols: number;
+  cursor_row: number;
+  cursor_col: number;
+  cursor_visible: boolean;
+  alternate_screen: boolean;
+  title: string | null;
+  lines: string[];
+  styled_lines: StyledRun[][];
+  scrollback: string[];
+}
+
+export async function getWorkerScreen(
+  id: string,
+  includeScrollback?: boolean,
+): Promise<ScreenSnapshot> {
+  return invoke<ScreenSnapshot>("get_worker_screen", {
+    id,
+    includeScrollback: includeScrollback ?? null,
+  });
+}
+
+// ── Recordings (asciicast v2) ──
+
+export interface RecordingInfo {
+  worker_id: string;
+  path: string;
+  size_bytes: number;
+  modified_at: string | null;
+  active: boolean;
+}
+
+export interface RecordingChunk {
+  data: string;
+  next_offset: number;
+  total_bytes: number;
+  active: boolean;
+}
+
+export async function listRecordings(): Promise<RecordingInfo[]> {
+  return invoke<RecordingInfo[]>("list_recordings");
+}
+
+export async function readWorkerRecording(
+  id: string,
+  offset?: number,
+  maxBytes?: number,
+): Promise<RecordingChunk> {
+  return invoke<RecordingChunk>("read_worker_recording", {
+    id,
+    offset: offset ?? null,
+    maxBytes: maxBytes ?? null,
+  });
+}
+
+export async function exportWorkerRecording(
+  id: string,
+  destPath: string,
+): Promise<string> {
+  return invoke<string>("export_worker_recording", { id, destPath });
+}
 // ── Run types ──
 
-export type RunStatus = "running" | "completed" | "failed" | "cancelled";
+export type RunStatus = "queued" | "running" | "completed" | "failed" | "cancelled";
 
 export interface RunInfo {
   id: string;
@@ -566,6 +804,39 @@ export interface RunInfo {
   model_tag: string | null;
   quality_signal: number | null;
   revision_count: number;
+  // Cost accounting
+  input_tokens: number | null;
+  output_tokens: number | null;
+  total_tokens: number | null;
+  cost_usd: number | null;
+  cost_source: "reported" | "estimated" | "manual" | null;
+  // Retries
+  attempt: number;
+  previous_run_id: string | null;
+  retry_at: string | null;
+  // Verification
+  checks: CheckOutcome[];
+  // Review loop
+  reviews: ReviewRecord[];
+  // Best-of-N
+  comparison_id: string | null;
+  // Diff range
+  base_commit: string | null;
+  end_commit: string | null;
+  // Replays
+  replay_of: string | null;
+  // Checkpoints
+  checkpoints: RunCheckpoint[];
+}
+
+export interface RunCheckpoint {
+  seq: number;
+  commit: string;
+  tree: string;
+  crew_id: string;
+  reason: "interval" | "manual" | "exit" | "pre_restore";
+  created_at: string;
+  diff_stat: string | null;
 }
 
 export interface ModelStats {
@@ -577,6 +848,12 @@ export interface ModelStats {
   avg_duration_secs: number | null;
   avg_quality_signal: number | null;
   avg_revision_count: number;
+  total_input_tokens: number;
+  total_output_tokens: number;
+  total_combined_tokens: number;
+  total_cost_usd: number;
+  avg_cost_usd: number | null;
+  cost_per_completed: number | null;
 }
 
 export async function listRuns(rigId: string): Promise<RunInfo[]> {
@@ -591,24 +868,199 @@ export async function getRunLogs(id: string): Promise<LogEntry[]> {
   return invoke<LogEntry[]>("get_run_logs", { id });
 }
 
+// ── Run diffs ──
+
+export type FileChangeKind = "added" | "modified" | "deleted" | "renamed";
+
+export interface DiffLine {
+  kind: "context" | "add" | "remove";
+  content: string;
+  old_line: number | null;
+  new_line: number | null;
+}
+
+export interface DiffHunk {
+  header: string;
+  old_start: number;
+  old_lines: number;
+  new_start: number;
+  new_lines: number;
+  lines: DiffLine[];
+}
+
+export interface FileDiff {
+  path: string;
+  old_path: string | null;
+  change: FileChangeKind;
+  binary: boolean;
+  additions: number;
+  deletions: number;
+  hunks: DiffHunk[];
+}
+
+export interface RunDiff {
+  run_id: string;
+  base_commit: string | null;
+  end_commit: string | null;
+  live: boolean;
+  additions: number;
+  deletions: number;
+  files: FileDiff[];
+}
+
+export async function getRunDiff(runId: string): Promise<RunDiff> {
+  return invoke<RunDiff>("get_run_diff", { runId });
+}
+
+export async function getRunPatch(runId: string): Promise<string> {
+  return invoke<string>("get_run_patch", { runId });
+}
+
+export async function retryRun(runId: string, agentType?: string): Promise<RunInfo> {
+  return invoke<RunInfo>("retry_run", { runId, agentType: agentType ?? null });
+}
+
+export async function cancelRetry(runId: string): Promise<RunInfo> {
+  return invoke<RunInfo>("cancel_retry", { runId });
+}
+
+export async function reviewRun(runId: string): Promise<RunInfo> {
+  return invoke<RunInfo>("review_run", { runId });
+}
+
+export async function getRunAttempts(runId: string): Promise<RunInfo[]> {
+  return invoke<RunInfo[]>("get_run_attempts", { runId });
+}
+
+// ── Replays ──
+
+export interface ReplayOverrides {
+  agent_type?: string | null;
+  model_tag?: string | null;
+  template_name?: string | null;
+  new_polecat?: boolean;
+  crew_id?: string | null;
+}
+
+export async function replayRun(runId: string, overrides?: ReplayOverrides): Promise<RunInfo> {
+  return invoke<RunInfo>("replay_run", { runId, overrides: overrides ?? null });
+}
+
+export async function listRunReplays(runId: string): Promise<RunInfo[]> {
+  return invoke<RunInfo[]>("list_run_replays", { runId });
+}
+
+// ── Best-of-N ──
+
+export type BestOfStatus = "open" | "promoted" | "discarded";
+
+export interface BestOfEntry {
+  run_id: string;
+  worker_id: string;
+  crew_id: string;
+  branch: string;
+  agent_type: string;
+  model_tag: string | null;
+}
+
+export interface BestOfComparison {
+  id: string;
+  task_id: string;
+  rig_id: string;
+  template_name: string;
+  entries: BestOfEntry[];
+  status: BestOfStatus;
+  winner_run_id: string | null;
+  promoted_crew_id: string | null;
+  created_at: string;
+  resolved_at: string | null;
+}
+
+export interface BestOfCandidate {
+  run_id: string;
+  worker_id: string;
+  branch: string;
+  agent_type: string;
+  model_tag: string | null;
+  status: RunStatus;
+  diff_stats: string | null;
+  files_changed: number;
+  insertions: number;
+  deletions: number;
+  checks_passed: boolean | null;
+  failed_checks: string[];
+  quality_signal: number | null;
+  cost_usd: number | null;
+  input_tokens: number | null;
+  output_tokens: number | null;
+  total_tokens: number | null;
+  duration_secs: number | null;
+}
+
+export interface BestOfReport {
+  comparison: BestOfComparison;
+  candidates: BestOfCandidate[];
+  all_finished: boolean;
+  recommended_run_id: string | null;
+}
+
+/** `agents` entries are `"agent"` or `"agent@model_tag"`, used round-robin. */
+export async function executeTaskBestOf(
+  taskId: string,
+  agents: string[],
+  n?: number,
+  templateName?: string
+): Promise<BestOfComparison> {
+  return invoke<BestOfComparison>("execute_task_best_of", {
+    taskId,
+    agents,
+    n: n ?? null,
+    templateName: templateName ?? null,
+  });
+}
+
+export async function listBestOf(rigId: string): Promise<BestOfComparison[]> {
+  return invoke<BestOfComparison[]>("list_best_of", { rigId });
+}
+
+export async function getBestOfReport(id: string): Promise<BestOfReport> {
+  return invoke<BestOfReport>("get_best_of_report", { id });
+}
+
+export async function promoteBestOf(id: string, runId: string, crewId?: string): Promise<BestOfComparison> {
+  return invoke<BestOfComparison>("promote_best_of", { id, runId, crewId: crewId ?? null });
+}
+
+export async function discardBestOf(id: string): Promise<BestOfComparison> {
+  return invoke<BestOfComparison>("discard_best_of", { id });
+}
+
 // ── Template types ──
 
+export type TemplateSource = "builtin" | "town" | "rig" | "crew";
+
 export interface TemplateInfo {
   name: string;
   description: string;
   content: string;
   is_builtin: boolean;
+  source: TemplateSource;
+  path: string | null;
+  required_vars: string[];
+  recommended_agent: string | null;
 }
 
-export async function listTemplates(): Promise<TemplateInfo[]> {
-  return invoke<TemplateInfo[]>("list_templates");
+export async function listTemplates(rigId?: string, crewId?: string): Promise<TemplateInfo[]> {
+  return invoke<TemplateInfo[]>("list_templates", { rigId: rigId ?? null, crewId: crewId ?? null });
 }
 
 export async function renderTemplate(
   name: string,
   vars: Record<string, string>,
+  rigId?: string,
+  crewId?: string,
 ): Promise<string> {
-  return invoke<string>("render_template", { name, vars });
+  return invoke<string>("render_template", { name, vars, rigId: rigId ?? null, crewId: crewId ?? null });
 }
 
 // ── Settings types ──
@@ -623,12 +1075,289 @@ export interface AppSettings {
   startup_priming_enabled: boolean;
   priming_template: string | null;
   priming_delay_ms: number;
+  priming_profiles: PrimingProfile[];
+  priming_ready_timeout_ms: number;
   // Propulsion & Witness
   propulsion_enabled: boolean;
   propulsion_interval_seconds: number;
   witness_auto_spawn: boolean;
   max_polecats_per_rig: number;
   polecat_nudge_after_seconds: number;
+  // Scheduler
+  max_concurrent_workers: number | null;
+  max_workers_per_agent: Record<string, number>;
+  max_load_per_cpu: number | null;
+  // Cost accounting
+  model_prices: Record<string, ModelPrice>;
+  // Budgets
+  daily_budget_usd: number | null;
+  max_run_seconds: number | null;
+  max_run_tokens: number | null;
+  // Graceful stop
+  stop_interrupt_grace_ms: number;
+  stop_term_grace_ms: number;
+  checkpoint_log_lines: number;
+  // Session host
+  session_backend: SessionBackend;
+  session_scrollback_lines: number;
+  record_sessions: boolean;
+  // Interactive prompts
+  prompt_rules: PromptRule[];
+  prompt_settle_ms: number;
+  // Liveness
+  liveness_idle_seconds: number;
+  liveness_hung_seconds: number;
+  // Retries
+  retry_policy: RetryPolicy;
+  // Verification
+  verification_timeout_seconds: number;
+  // Lifecycle hooks
+  lifecycle_hooks: LifecycleHook[];
+  lifecycle_hook_timeout_seconds: number;
+  // Checkpoints
+  checkpoint_interval_minutes: number | null;
+  max_checkpoints_per_run: number;
+  keep_checkpoints_on_success: boolean;
+}
+
+export type PrimingDelivery = "auto" | "system_prompt" | "context_file" | "pty_inject" | "off";
+
+export interface PrimingProfile {
+  id: string;
+  name: string;
+  role: string | null;
+  crew_preset: string | null;
+  rig_id: string | null;
+  agent_type: string | null;
+  template: string;
+  delivery: PrimingDelivery;
+  ready_pattern: string | null;
+  enabled: boolean;
+}
+
+export type PromptScope = "screen" | "output";
+export type PromptAction = "auto_answer" | "escalate";
+
+export interface PromptRule {
+  id: string;
+  name: string;
+  agent_types: string[];
+  pattern: string;
+  scope: PromptScope;
+  action: PromptAction;
+  response: string | null;
+  enabled: boolean;
+}
+
+export interface PromptMatch {
+  rule_id: string;
+  rule_name: string;
+  action: PromptAction;
+  matched_text: string;
+  detected_at: string;
+}
+
+export interface PromptRuleTestResult {
+  rule_id: string;
+  matched_text: string | null;
+  error: string | null;
+}
+
+export async function testPromptRules(
+  text: string,
+  agentType?: string,
+  rules?: PromptRule[],
+): Promise<PromptRuleTestResult[]> {
+  return invoke<PromptRuleTestResult[]>("test_prompt_rules", {
+    text,
+    agentType: agentType ?? null,
+    rules: rules ?? null,
+  });
+}
+
+export type SessionBackend = "pty" | "tmux";
+
+// ── Env profiles & secrets ──
+
+export type EnvScope = "global" | "rig" | "crew" | "actor";
+
+export interface EnvVar {
+  key: string;
+  /** Empty for secrets; their values never leave the backend. */
+  value: string;
+  secret: boolean;
+}
+
+export interface EnvProfile {
+  scope: EnvScope;
+  scope_id: string | null;
+  vars: EnvVar[];
+  updated_at: string;
+}
+
+export type SecretKeySource = "keyring" | "passphrase";
+
+export interface SecretsStatus {
+  source: SecretKeySource;
+  unlocked: boolean;
+  secret_count: number;
+  error: string | null;
+}
+
+export interface ResolvedEnvVar {
+  key: string;
+  value: string;
+  secret: boolean;
+  /** null for the legacy settings env vars. */
+  scope: EnvScope | null;
+}
+
+export async function listEnvProfiles(): Promise<EnvProfile[]> {
+  return invoke<EnvProfile[]>("list_env_profiles");
+}
+
+export async function setEnvVar(
+  scope: EnvScope,
+  scopeId: string | null,
+  key: string,
+  value: string,
+  secret: boolean,
+): Promise<void> {
+  return invoke("set_env_var", { scope, scopeId, key, value, secret });
+}
+
+export async function removeEnvVar(scope: EnvScope, scopeId: string | null, key: string): Promise<void> {
+  return invoke("remove_env_var", { scope, scopeId, key });
+}
+
+export async function getSecretsStatus(): Promise<SecretsStatus> {
+  return invoke<SecretsStatus>("get_secrets_status");
+}
+
+export async function unlockSecrets(passphrase: string): Promise<SecretsStatus> {
+  return invoke<SecretsStatus>("unlock_secrets", { passphrase });
+}
+
+export async function setSecretsPassphrase(passphrase?: string): Promise<SecretsStatus> {
+  return invoke<SecretsStatus>("set_secrets_passphrase", { passphrase: passphrase ?? null });
+}
+
+export async function previewWorkerEnv(crewId: string, actorId?: string): Promise<ResolvedEnvVar[]> {
+  return invoke<ResolvedEnvVar[]>("preview_worker_env", { crewId, actorId: actorId ?? null });
+}
+
+// ── Scheduler ──
+
+export type SpawnSource =
+  | "manual"
+  | "task"
+  | "retry"
+  | "hook"
+  | "propulsion"
+  | "witness"
+  | "best_of"
+  | "replay"
+  | "review"
+  | "restart";
+
+export interface QueuedSpawn {
+  id: string;
+  rig_id: string;
+  crew_id: string | null;
+  agent_type: string;
+  priority: TaskPriority;
+  source: SpawnSource;
+  polecat: boolean;
+  task_id: string | null;
+  run_id: string | null;
+  label: string;
+  enqueued_at: string;
+  waiting_on: string | null;
+}
+
+export interface SlotUsage {
+  key: string;
+  running: number;
+  limit: number | null;
+  queued: number;
+  weight: number;
+}
+
+export interface SchedulerStatus {
+  running: number;
+  max_concurrent_workers: number | null;
+  load_per_cpu: number | null;
+  max_load_per_cpu: number | null;
+  rigs: SlotUsage[];
+  agents: SlotUsage[];
+  queue: QueuedSpawn[];
+}
+
+export async function getSchedulerStatus(): Promise<SchedulerStatus> {
+  return invoke<SchedulerStatus>("get_scheduler_status");
+}
+
+export async function cancelQueuedSpawn(id: string): Promise<QueuedSpawn> {
+  return invoke<QueuedSpawn>("cancel_queued_spawn", { id });
+}
+
+// ── Run checkpoints ──
+
+export async function createRunCheckpoint(runId: string): Promise<RunCheckpoint | null> {
+  return invoke<RunCheckpoint | null>("create_run_checkpoint", { runId });
+}
+
+export async function restoreRunCheckpoint(runId: string, seq: number, crewId?: string): Promise<Run> {
+  return invoke<Run>("restore_run_checkpoint", { runId, seq, crewId: crewId ?? null });
+}
+
+export async function discardRunCheckpoints(runId: string): Promise<number> {
+  return invoke<number>("discard_run_checkpoints", { runId });
+}
+
+// ── Liveness ──
+
+export type LivenessState = "active" | "idle" | "waiting_input" | "hung";
+export type RecoveryStep = "nudge" | "reprime" | "restart" | "escalate";
+
+export interface WorkerLiveness {
+  worker_id: string;
+  state: LivenessState;
+  last_output_at: string | null;
+  last_file_change_at: string | null;
+  cpu_seconds: number | null;
+  cpu_delta_seconds: number | null;
+  quiet_seconds: number;
+  recovery_step: RecoveryStep | null;
+  recovery_step_at: string | null;
+  checked_at: string;
+}
+
+export async function getWorkerLiveness(id: string): Promise<WorkerLiveness> {
+  return invoke<WorkerLiveness>("get_worker_liveness", { id });
+}
+
+export async function listWorkerLiveness(rigId?: string): Promise<WorkerLiveness[]> {
+  return invoke<WorkerLiveness[]>("list_worker_liveness", { rigId: rigId ?? null });
+}
+
+export interface WorkerCheckpoint {
+  worker_id: string;
+  run_id: string | null;
+  task_id: string | null;
+  agent_type: string;
+  reason: string;
+  captured_at: string;
+  elapsed_seconds: number | null;
+  startup_primed: boolean;
+  diff_stat: string | null;
+  last_output: string[];
+  interrupt_output: string[];
+}
+
+export interface ModelPrice {
+  input_per_mtok: number;
+  output_per_mtok: number;
 }
 
 export async function getSettings(): Promise<AppSettings> {
@@ -643,6 +1372,19 @@ export async function validateCliPath(path: string): Promise<string> {
   return invoke<string>("validate_cli_path", { path });
 }
 
+export interface SandboxCapabilities {
+  platform: string;
+  cgroup_v2: boolean;
+  systemd_run: boolean;
+  prlimit: boolean;
+  bubblewrap: boolean;
+  landlock: boolean;
+}
+
+export async function getSandboxCapabilities(): Promise<SandboxCapabilities> {
+  return invoke<SandboxCapabilities>("get_sandbox_capabilities");
+}
+
 // ── AI Inbox Bridge ──
 
 export interface AiInboxStatus {
@@ -720,7 +1462,31 @@ export type AuditEventType =
   | "queue_reconciled"
   | "state_compacted"
   | "refinery_synced"
-  | "refinery_sync_failed";
+  | "refinery_sync_failed"
+  | "budget_exceeded"
+  | "budget_cleared"
+  | "run_limit_exceeded"
+  | "worker_reattached"
+  | "worker_session_lost"
+  | "prompt_auto_answered"
+  | "prompt_escalated"
+  | "prompt_resolved"
+  | "liveness_recovery"
+  | "run_retry_scheduled"
+  | "run_retried"
+  | "run_retry_exhausted"
+  | "run_reviewed"
+  | "run_revision_started"
+  | "best_of_started"
+  | "best_of_promoted"
+  | "best_of_discarded"
+  | "run_replayed"
+  | "lifecycle_hook_failed"
+  | "env_var_changed"
+  | "secrets_key_changed"
+  | "spawn_queued"
+  | "spawn_cancelled"
+  | "checkpoint_restored";
 
 export interface AuditEvent {
   event_id: string;
@@ -752,6 +1518,7 @@ export interface StuckTaskInfo {
   title: string;
   minutes_stuck: number;
   assigned_worker_id: string | null;
+  reason: string;
 }
 
 export interface HealthMetrics {
@@ -1402,6 +2169,95 @@ export async function listRunStats(rigId?: string): Promise<ModelStats[]> {
   return invoke<ModelStats[]>("list_run_stats", { rigId: rigId ?? null });
 }
 
+// ── Cost accounting ──
+
+export async function recordRunUsage(
+  runId: string,
+  inputTokens?: number,
+  outputTokens?: number,
+  costUsd?: number,
+): Promise<RunInfo> {
+  return invoke<RunInfo>("record_run_usage", {
+    runId,
+    inputTokens: inputTokens ?? null,
+    outputTokens: outputTokens ?? null,
+    costUsd: costUsd ?? null,
+  });
+}
+
+export type CostGroupBy =
+  | "rig"
+  | "task"
+  | "actor"
+  | "convoy"
+  | "agent"
+  | "day"
+  | "week"
+  | "month";
+
+export interface CostBucket {
+  key: string;
+  label: string;
+  runs_total: number;
+  runs_completed: number;
+  runs_failed: number;
+  runs_with_cost: number;
+  input_tokens: number;
+  output_tokens: number;
+  combined_tokens: number;
+  cost_usd: number;
+  cost_per_completed: number | null;
+}
+
+export interface CostReport {
+  generated_at: string;
+  group_by: CostGroupBy;
+  rig_scope: string | null;
+  since: string | null;
+  until: string | null;
+  total_input_tokens: number;
+  total_output_tokens: number;
+  total_combined_tokens: number;
+  total_cost_usd: number;
+  buckets: CostBucket[];
+}
+
+export type BudgetScope = "town_daily" | "rig" | "rig_daily" | "convoy";
+
+export interface BudgetUsage {
+  scope: BudgetScope;
+  scope_id: string;
+  rig_id: string | null;
+  label: string;
+  limit_usd: number;
+  spent_usd: number;
+  exceeded: boolean;
+}
+
+export interface BudgetStatus {
+  checked_at: string;
+  usages: BudgetUsage[];
+  paused: BudgetUsage[];
+}
+
+export async function getBudgetStatus(): Promise<BudgetStatus> {
+  return invoke<BudgetStatus>("get_budget_status");
+}
+
+export async function getCostReport(
+  groupBy: CostGroupBy,
+  rigId?: string,
+  since?: string,
+  until?: string,
+): Promise<CostReport> {
+  return invoke<CostReport>("get_cost_report", {
+    groupBy,
+    rigId: rigId ?? null,
+    since: since ?? null,
+    until: until ?? null,
+  });
+}
+
 // ── Dog Pool ──
 
 export type DogRole =